@@ -0,0 +1,281 @@
+use sorcerers::card::{Edition, catalog::CatalogEntry};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+/// The printed cards of one set, loaded from `sets/<edition>.txt`.
+pub struct SetList {
+    pub edition: Edition,
+    pub cards: Vec<String>,
+}
+
+impl SetList {
+    /// Load every `*.txt` set list in `dir`, ordered by file name.
+    pub fn load_all(dir: &Path) -> anyhow::Result<Vec<SetList>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                let edition = edition_from_set_name(stem).ok_or(anyhow::anyhow!(
+                    "{} does not name a known edition",
+                    path.display()
+                ))?;
+                Ok(SetList {
+                    edition,
+                    cards: parse_set_list(&std::fs::read_to_string(path)?),
+                })
+            })
+            .collect()
+    }
+}
+
+fn edition_from_set_name(name: &str) -> Option<Edition> {
+    match name {
+        "alpha" => Some(Edition::Alpha),
+        "beta" => Some(Edition::Beta),
+        "arthurian_legends" => Some(Edition::ArthurianLegends),
+        "dragonlord" => Some(Edition::Dragonlord),
+        "gothic" => Some(Edition::Gothic),
+        _ => None,
+    }
+}
+
+/// One card name per line. Blank lines and lines starting with `#` are ignored.
+fn parse_set_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// A TODO comment left in a card's source file.
+pub struct Todo {
+    pub card: String,
+    pub location: String,
+    pub text: String,
+}
+
+/// Find TODO comments in the card sources under `dir`. Files are matched to cards through their
+/// `const NAME` declaration.
+pub fn find_todos(dir: &Path) -> anyhow::Result<Vec<Todo>> {
+    let mut todos = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path.extension().is_none_or(|ext| ext != "rs") {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&path)?;
+            let Some(card) = card_name(&source) else {
+                continue;
+            };
+            for (idx, line) in source.lines().enumerate() {
+                if let Some(pos) = line.find("TODO") {
+                    todos.push(Todo {
+                        card: card.clone(),
+                        location: format!("{}:{}", path.display(), idx + 1),
+                        text: line[pos..].trim().to_string(),
+                    });
+                }
+            }
+        }
+    }
+    todos.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(todos)
+}
+
+fn card_name(source: &str) -> Option<String> {
+    let line = source
+        .lines()
+        .find(|line| line.contains("const NAME: &'static str = \""))?;
+    let start = line.find('"')? + 1;
+    let end = line.rfind('"')?;
+    Some(line[start..end].to_string())
+}
+
+/// How much of a printed set is implemented.
+pub struct Coverage<'a> {
+    pub edition: &'a Edition,
+    pub printed: usize,
+    /// Printed cards that are not implemented at all.
+    pub missing: Vec<&'a str>,
+    /// Printed cards that are implemented, but with a different edition.
+    pub wrong_edition: Vec<(&'a str, &'a Edition)>,
+    /// Cards implemented for this edition that are not in the set list.
+    pub unlisted: Vec<&'a str>,
+    pub todos: Vec<&'a Todo>,
+}
+
+impl<'a> Coverage<'a> {
+    pub fn new(set: &'a SetList, catalog: &'a [CatalogEntry], todos: &'a [Todo]) -> Self {
+        let implemented: HashMap<&str, &Edition> = catalog
+            .iter()
+            .map(|entry| (entry.name.as_str(), &entry.edition))
+            .collect();
+        let listed: HashSet<&str> = set.cards.iter().map(String::as_str).collect();
+
+        let mut missing = vec![];
+        let mut wrong_edition = vec![];
+        for name in &set.cards {
+            match implemented.get(name.as_str()) {
+                None => missing.push(name.as_str()),
+                Some(edition) if *edition != &set.edition => {
+                    wrong_edition.push((name.as_str(), *edition))
+                }
+                Some(_) => {}
+            }
+        }
+        let unlisted: Vec<&str> = catalog
+            .iter()
+            .filter(|entry| entry.edition == set.edition && !listed.contains(entry.name.as_str()))
+            .map(|entry| entry.name.as_str())
+            .collect();
+        let todos = todos
+            .iter()
+            .filter(|todo| {
+                listed.contains(todo.card.as_str()) || unlisted.contains(&todo.card.as_str())
+            })
+            .collect();
+
+        Self {
+            edition: &set.edition,
+            printed: set.cards.len(),
+            missing,
+            wrong_edition,
+            unlisted,
+            todos,
+        }
+    }
+}
+
+impl std::fmt::Display for Coverage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let implemented = self.printed - self.missing.len();
+        writeln!(
+            f,
+            "{:?}: {implemented}/{} printed cards implemented",
+            self.edition, self.printed
+        )?;
+
+        if !self.missing.is_empty() {
+            writeln!(f, "  Missing ({}):", self.missing.len())?;
+            for name in &self.missing {
+                writeln!(f, "    {name}")?;
+            }
+        }
+        if !self.wrong_edition.is_empty() {
+            writeln!(f, "  Implemented with another edition:")?;
+            for (name, edition) in &self.wrong_edition {
+                writeln!(f, "    {name} ({edition:?})")?;
+            }
+        }
+        if !self.unlisted.is_empty() {
+            writeln!(f, "  Not in the set list:")?;
+            for name in &self.unlisted {
+                writeln!(f, "    {name}")?;
+            }
+        }
+        if !self.todos.is_empty() {
+            writeln!(f, "  Flagged by TODOs ({}):", self.todos.len())?;
+            for todo in &self.todos {
+                writeln!(f, "    {} - {} ({})", todo.card, todo.text, todo.location)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorcerers::card::{AridDesert, KiteArcher, Teleport, catalog::catalog};
+
+    fn entries(names: &[&str]) -> Vec<CatalogEntry> {
+        catalog()
+            .into_iter()
+            .filter(|entry| names.contains(&entry.name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn set_lists_skip_blank_lines_and_comments() {
+        let cards = parse_set_list("# Beta\n\nKite Archer\n  Arid Desert  \n# Teleport\n");
+        assert_eq!(cards, vec!["Kite Archer", "Arid Desert"]);
+    }
+
+    #[test]
+    fn coverage_reports_missing_unlisted_and_misfiled_cards() {
+        let mut catalog = entries(&[KiteArcher::NAME, AridDesert::NAME, Teleport::NAME]);
+        let teleport = catalog
+            .iter_mut()
+            .find(|entry| entry.name == Teleport::NAME)
+            .unwrap();
+        teleport.edition = Edition::Alpha;
+        let set = SetList {
+            edition: Edition::Beta,
+            cards: vec![
+                KiteArcher::NAME.to_string(),
+                Teleport::NAME.to_string(),
+                "Unprinted Wonder".to_string(),
+            ],
+        };
+
+        let coverage = Coverage::new(&set, &catalog, &[]);
+        assert_eq!(coverage.printed, 3);
+        assert_eq!(coverage.missing, vec!["Unprinted Wonder"]);
+        assert!(matches!(
+            coverage.wrong_edition.as_slice(),
+            [(name, Edition::Alpha)] if *name == Teleport::NAME
+        ));
+        assert_eq!(coverage.unlisted, vec![AridDesert::NAME]);
+        assert!(
+            coverage
+                .to_string()
+                .starts_with("Beta: 2/3 printed cards implemented")
+        );
+    }
+
+    #[test]
+    fn todos_are_matched_to_cards_by_name() {
+        let dir = std::env::temp_dir().join(format!("catalog-todos-{}", std::process::id()));
+        let nested = dir.join("beta");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            nested.join("kite_archer.rs"),
+            "impl KiteArcher {\n    pub const NAME: &'static str = \"Kite Archer\";\n}\n\n// TODO: Ranged strikes\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("mod.rs"), "// TODO: not a card\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "TODO: not source\n").unwrap();
+
+        let todos = find_todos(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].card, KiteArcher::NAME);
+        assert_eq!(todos[0].text, "TODO: Ranged strikes");
+        assert!(todos[0].location.ends_with("kite_archer.rs:5"));
+
+        let catalog = entries(&[KiteArcher::NAME]);
+        let set = SetList {
+            edition: Edition::Beta,
+            cards: vec![KiteArcher::NAME.to_string()],
+        };
+        assert_eq!(Coverage::new(&set, &catalog, &todos).todos.len(), 1);
+    }
+}
@@ -0,0 +1,96 @@
+use sorcerers::card::catalog::CatalogEntry;
+
+const HEADER: [&str; 18] = [
+    "name",
+    "edition",
+    "type",
+    "rarity",
+    "mana_cost",
+    "fire_cost",
+    "air_cost",
+    "earth_cost",
+    "water_cost",
+    "provided_fire",
+    "provided_air",
+    "provided_earth",
+    "provided_water",
+    "power",
+    "toughness",
+    "abilities",
+    "types",
+    "description",
+];
+
+/// Render the catalog as CSV, one card per row. List columns are separated with `;`.
+pub fn to_csv(entries: &[CatalogEntry]) -> String {
+    let mut csv = HEADER.join(",");
+    csv.push('\n');
+    for entry in entries {
+        let optional = |value: Option<u16>| value.map(|v| v.to_string()).unwrap_or_default();
+        let row = [
+            entry.name.clone(),
+            format!("{:?}", entry.edition),
+            entry.card_type.to_string(),
+            entry.rarity.to_string(),
+            entry
+                .mana_cost
+                .map(|mana| mana.to_string())
+                .unwrap_or("X".to_string()),
+            entry.threshold_cost.fire.to_string(),
+            entry.threshold_cost.air.to_string(),
+            entry.threshold_cost.earth.to_string(),
+            entry.threshold_cost.water.to_string(),
+            entry.provided_thresholds.fire.to_string(),
+            entry.provided_thresholds.air.to_string(),
+            entry.provided_thresholds.earth.to_string(),
+            entry.provided_thresholds.water.to_string(),
+            optional(entry.power),
+            optional(entry.toughness),
+            entry
+                .abilities
+                .iter()
+                .map(|ability| format!("{ability:?}"))
+                .collect::<Vec<_>>()
+                .join(";"),
+            entry.types.join(";"),
+            entry.description.clone(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| escape(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorcerers::card::{AridDesert, catalog::catalog};
+
+    #[test]
+    fn fields_with_commas_and_quotes_are_quoted() {
+        let mut entry = catalog()
+            .into_iter()
+            .find(|entry| entry.name == AridDesert::NAME)
+            .unwrap();
+        entry.name = "Sand, Sun and Wind".to_string();
+        entry.description = "Say \"dry\".".to_string();
+
+        let csv = to_csv(&[entry]);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap().split(',').count(), HEADER.len());
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("\"Sand, Sun and Wind\",Beta,"));
+        assert!(row.ends_with(",\"Say \"\"dry\"\".\""));
+        assert!(row.contains(",0,0,0,0,1,0,0,0,"));
+        assert_eq!(lines.next(), None);
+    }
+}
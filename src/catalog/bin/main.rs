@@ -0,0 +1,67 @@
+//! Card database tooling.
+//!
+//! ```text
+//! catalog export [--out DIR]                 write DIR/cards.json and DIR/cards.csv
+//! catalog coverage [--sets DIR] [--cards DIR]
+//! ```
+//!
+//! `coverage` compares the implemented cards against the set lists in `sets/` (one printed card
+//! name per line, named after the edition, e.g. `sets/beta.txt`) and reports which printed cards
+//! are still missing, which are implemented under another edition, and which have TODOs left in
+//! their source under `src/lib/card/`.
+mod coverage;
+mod export;
+
+use crate::coverage::{Coverage, SetList, find_todos};
+use sorcerers::card::catalog::catalog;
+use std::path::PathBuf;
+
+const USAGE: &str =
+    "usage: catalog export [--out DIR] | catalog coverage [--sets DIR] [--cards DIR]";
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().ok_or(anyhow::anyhow!(USAGE))?;
+
+    let mut out = PathBuf::from("catalog");
+    let mut sets = PathBuf::from("sets");
+    let mut cards = PathBuf::from("src/lib/card");
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .map(PathBuf::from)
+            .ok_or(anyhow::anyhow!("{arg} requires a value"))?;
+        match arg.as_str() {
+            "--out" => out = value,
+            "--sets" => sets = value,
+            "--cards" => cards = value,
+            _ => return Err(anyhow::anyhow!("unknown argument {arg}\n{USAGE}")),
+        }
+    }
+
+    let entries = catalog();
+    match command.as_str() {
+        "export" => {
+            std::fs::create_dir_all(&out)?;
+            let json_path = out.join("cards.json");
+            let csv_path = out.join("cards.csv");
+            std::fs::write(&json_path, serde_json::to_string_pretty(&entries)?)?;
+            std::fs::write(&csv_path, export::to_csv(&entries))?;
+            println!(
+                "Exported {} cards to {} and {}",
+                entries.len(),
+                json_path.display(),
+                csv_path.display()
+            );
+        }
+        "coverage" => {
+            let todos = find_todos(&cards)?;
+            for set in SetList::load_all(&sets)? {
+                print!("{}", Coverage::new(&set, &entries, &todos));
+            }
+        }
+        _ => return Err(anyhow::anyhow!("unknown command {command}\n{USAGE}")),
+    }
+
+    Ok(())
+}
@@ -21,6 +21,8 @@ pub struct SorcerersApp {
     scene: Scene,
     _runtime: Runtime,
     rx: mpsc::UnboundedReceiver<ServerMessage>,
+    /// Shown over every scene while the server is draining for maintenance.
+    maintenance_notice: Option<String>,
 }
 
 impl SorcerersApp {
@@ -49,6 +51,7 @@ impl SorcerersApp {
             scene,
             _runtime: rt,
             rx,
+            maintenance_notice: None,
         })
     }
 
@@ -208,6 +211,9 @@ impl eframe::App for SorcerersApp {
 
         // Drain incoming server messages
         while let Ok(msg) = self.rx.try_recv() {
+            if let ServerMessage::MaintenanceNotice { message } = &msg {
+                self.maintenance_notice = message.clone();
+            }
             if let Some(new_scene) = self.scene.process_message(&msg) {
                 self.scene = new_scene;
             }
@@ -233,5 +239,24 @@ impl eframe::App for SorcerersApp {
                     self.scene = new_scene;
                 }
             });
+        if let Some(notice) = &self.maintenance_notice {
+            egui::Area::new(egui::Id::new("maintenance_notice"))
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 12.0))
+                .order(egui::Order::Foreground)
+                .show(ui.ctx(), |ui| {
+                    egui::Frame::NONE
+                        .fill(theme::PANEL_BG)
+                        .stroke(egui::Stroke::new(1.0, theme::PANEL_BORDER))
+                        .inner_margin(egui::Margin::symmetric(16, 10))
+                        .show(ui, |ui| {
+                            ui.set_max_width(560.0);
+                            ui.label(
+                                egui::RichText::new(notice)
+                                    .color(theme::TEXT_BRIGHT)
+                                    .size(15.0),
+                            );
+                        });
+                });
+        }
     }
 }
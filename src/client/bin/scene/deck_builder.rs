@@ -1,11 +1,20 @@
-use crate::{element_icon, render, scene::Scene, texture_cache::TextureCache, theme};
+use crate::{
+    element_icon, render,
+    scene::{Scene, menu::LimitedEvents},
+    texture_cache::TextureCache,
+    theme,
+};
 use egui::{
     Color32, Context, CornerRadius, Frame, Rect, ScrollArea, Sense, Stroke, StrokeKind, Ui, pos2,
     vec2,
 };
 use sorcerers::collection::CollectedCard;
+use sorcerers::deck::analytics::{DeckStats, OPENING_SITES, OPENING_SPELLS, sites_seen_by_turn};
 use sorcerers::deck::precon::PreconDeck;
+use sorcerers::deck::text::{ImportDiagnostic, parse_deck_text};
 use sorcerers::game::PlayerId;
+use sorcerers::networking::message::{ClientMessage, ServerMessage};
+use sorcerers::sealed::{SEALED_MIN_SITES, SEALED_MIN_SPELLS, SealedPool};
 use sorcerers::{
     card::{ALL_CARDS, CardType, Edition, Rarity},
     game::Element,
@@ -45,8 +54,15 @@ pub struct DeckBuilder {
     player_name: String,
     prev_available_decks: Vec<PreconDeck>,
     prev_saved_decks: Vec<sorcerers::deck::DeckList>,
+    prev_collection: Vec<CollectedCard>,
+    prev_limited: LimitedEvents,
+    /// The sealed or drafted pool the deck is built from, instead of the permanent collection.
+    limited_pool: Option<SealedPool>,
     collection_entries: Vec<CollectedCard>,
     collection: HashMap<String, u8>,
+    /// Crafting dust, once the server has sent it. Never loaded for limited pools.
+    crafting_dust: Option<u32>,
+    crafting_feedback: Option<String>,
 
     all_cards: Vec<CardEntry>,
     avatars: Vec<CardEntry>,
@@ -67,6 +83,11 @@ pub struct DeckBuilder {
     // Validation / save feedback
     save_error: Option<String>,
 
+    /// Text pasted into the import window, while it is open.
+    import_text: Option<String>,
+    import_diagnostics: Vec<ImportDiagnostic>,
+    show_stats: bool,
+
     // Card preview: (entry, row_rect center-right position)
     hovered_card: Option<(CardEntry, egui::Pos2)>,
 }
@@ -84,6 +105,36 @@ impl DeckBuilder {
         })
     }
 
+    fn set_collection(&mut self, collection: Vec<CollectedCard>) {
+        self.collection = collection_totals(&collection);
+        self.prev_collection = collection.clone();
+        self.collection_entries = collection;
+    }
+
+    pub fn process_message(&mut self, message: &ServerMessage) -> Option<Scene> {
+        match message {
+            ServerMessage::CraftingUpdated { collection, dust } if self.limited_pool.is_none() => {
+                self.set_collection(collection.clone());
+                self.crafting_dust = Some(*dust);
+                self.crafting_feedback = None;
+            }
+            ServerMessage::CraftingRejected { message } => {
+                self.crafting_feedback = Some(message.clone());
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Minimum (sites, spells) the deck needs before it can be saved.
+    fn required_counts(&self) -> (u32, u32) {
+        if self.limited_pool.is_some() {
+            (SEALED_MIN_SITES as u32, SEALED_MIN_SPELLS as u32)
+        } else {
+            (30, 60)
+        }
+    }
+
     pub fn from_menu(
         client: networking::client::Client,
         player_id: Option<PlayerId>,
@@ -91,11 +142,51 @@ impl DeckBuilder {
         prev_available_decks: Vec<PreconDeck>,
         prev_saved_decks: Vec<sorcerers::deck::DeckList>,
         collection: Vec<CollectedCard>,
+        limited: LimitedEvents,
+    ) -> Self {
+        client.send(ClientMessage::LoadCrafting).ok();
+        Self::build(
+            client,
+            player_id,
+            player_name,
+            prev_available_decks,
+            prev_saved_decks,
+            collection,
+            limited,
+            None,
+        )
+    }
+
+    /// Open the deck builder over a sealed or drafted pool, with limited deck size and copy
+    /// rules.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_limited_pool(
+        client: networking::client::Client,
+        player_id: Option<PlayerId>,
+        player_name: String,
+        prev_available_decks: Vec<PreconDeck>,
+        prev_saved_decks: Vec<sorcerers::deck::DeckList>,
+        collection: Vec<CollectedCard>,
+        limited: LimitedEvents,
+        pool: SealedPool,
     ) -> Self {
-        Self::build(client, player_id, player_name, prev_available_decks, prev_saved_decks, collection, None)
+        let mut builder = Self::build(
+            client,
+            player_id,
+            player_name,
+            prev_available_decks,
+            prev_saved_decks,
+            pool.collection(),
+            limited,
+            None,
+        );
+        builder.prev_collection = collection;
+        builder.limited_pool = Some(pool);
+        builder
     }
 
     /// Open the deck builder pre-populated with an existing saved deck for editing.
+    #[allow(clippy::too_many_arguments)]
     pub fn from_deck_list(
         client: networking::client::Client,
         player_id: Option<PlayerId>,
@@ -103,8 +194,10 @@ impl DeckBuilder {
         prev_available_decks: Vec<PreconDeck>,
         prev_saved_decks: Vec<sorcerers::deck::DeckList>,
         collection: Vec<CollectedCard>,
+        limited: LimitedEvents,
         deck: sorcerers::deck::DeckList,
     ) -> Self {
+        client.send(ClientMessage::LoadCrafting).ok();
         Self::build(
             client,
             player_id,
@@ -112,10 +205,12 @@ impl DeckBuilder {
             prev_available_decks,
             prev_saved_decks,
             collection,
+            limited,
             Some(deck),
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build(
         client: networking::client::Client,
         player_id: Option<PlayerId>,
@@ -123,14 +218,12 @@ impl DeckBuilder {
         prev_available_decks: Vec<PreconDeck>,
         prev_saved_decks: Vec<sorcerers::deck::DeckList>,
         collection: Vec<CollectedCard>,
+        prev_limited: LimitedEvents,
         existing: Option<sorcerers::deck::DeckList>,
     ) -> Self {
+        let prev_collection = collection.clone();
         let collection_entries = collection;
-        let collection: HashMap<String, u8> = collection_entries.iter().fold(HashMap::new(), |mut cards, card| {
-            let count = cards.entry(card.name.clone()).or_insert(0u8);
-            *count = count.saturating_add(card.count);
-            cards
-        });
+        let collection = collection_totals(&collection_entries);
         let dummy_id = uuid::Uuid::nil();
         let mut all_cards: Vec<CardEntry> = Vec::new();
         let mut avatars: Vec<CardEntry> = Vec::new();
@@ -193,8 +286,13 @@ impl DeckBuilder {
             player_name,
             prev_available_decks,
             prev_saved_decks,
+            prev_collection,
+            prev_limited,
+            limited_pool: None,
             collection_entries,
             collection,
+            crafting_dust: None,
+            crafting_feedback: None,
             all_cards,
             avatars,
             deck_spells,
@@ -207,6 +305,9 @@ impl DeckBuilder {
             elem_filter: ElemFilter::All,
             type_filter: TypeFilter::All,
             save_error: None,
+            import_text: None,
+            import_diagnostics: Vec::new(),
+            show_stats: false,
             hovered_card: None,
         }
     }
@@ -259,7 +360,11 @@ impl DeckBuilder {
                 ui.painter().text(
                     pos2(header_rect.center().x, header_rect.center().y + 11.0),
                     egui::Align2::CENTER_CENTER,
-                    "Build from your collection",
+                    if self.limited_pool.is_some() {
+                        "Build from your limited pool"
+                    } else {
+                        "Build from your collection"
+                    },
                     egui::FontId::proportional(12.0),
                     TEXT_DIM,
                 );
@@ -271,10 +376,11 @@ impl DeckBuilder {
                 );
                 let spell_count_total: u32 = self.deck_spells.values().map(|&c| c as u32).sum();
                 let site_count_total: u32 = self.deck_sites.values().map(|&c| c as u32).sum();
+                let (required_sites, required_spells) = self.required_counts();
                 let can_use = self.selected_avatar.is_some()
                     && !self.deck_name.trim().is_empty()
-                    && spell_count_total >= 60
-                    && site_count_total >= 30;
+                    && spell_count_total >= required_spells
+                    && site_count_total >= required_sites;
                 let use_resp = ui.allocate_rect(use_rect, Sense::click());
                 let use_bg = if !can_use {
                     Color32::from_rgb(30, 35, 55)
@@ -304,6 +410,35 @@ impl DeckBuilder {
                     }
                 }
 
+                // Deck list copy and import, left of the save button
+                let list_rect = Rect::from_min_size(
+                    header_rect.right_top() + vec2(-460.0, 8.0),
+                    vec2(330.0, 32.0),
+                );
+                ui.scope_builder(egui::UiBuilder::new().max_rect(list_rect), |ui| {
+                    ui.horizontal_centered(|ui| {
+                        if ui
+                            .button("📋 Copy list")
+                            .on_hover_text("Copy the deck as a plain-text list")
+                            .clicked()
+                        {
+                            ui.ctx().copy_text(self.deck_list().to_text());
+                        }
+                        if ui
+                            .button("Curiosa")
+                            .on_hover_text("Copy the deck in the curiosa.io format")
+                            .clicked()
+                        {
+                            ui.ctx().copy_text(self.deck_list().to_curiosa_text());
+                        }
+                        if ui.button("📥 Import").clicked() {
+                            self.import_text = Some(String::new());
+                            self.import_diagnostics.clear();
+                        }
+                        ui.toggle_value(&mut self.show_stats, "📊 Stats");
+                    });
+                });
+
                 // Show save error or requirement hint below the header
                 let hint = if let Some(ref err) = self.save_error.clone() {
                     Some((format!("⚠ {err}"), ERROR))
@@ -318,14 +453,8 @@ impl DeckBuilder {
                     if self.deck_name.trim().is_empty() {
                         missing.push("deck name");
                     }
-                    if site_count_total < 30 {
-                        // already shown in the panel, just summarise
-                    }
-                    if spell_count_total < 60 {
-                        // already shown in the panel
-                    }
-                    let needs_sites = 30u32.saturating_sub(site_count_total);
-                    let needs_spells = 60u32.saturating_sub(spell_count_total);
+                    let needs_sites = required_sites.saturating_sub(site_count_total);
+                    let needs_spells = required_spells.saturating_sub(spell_count_total);
                     let mut parts: Vec<String> = missing.iter().map(|s| s.to_string()).collect();
                     if needs_sites > 0 {
                         parts.push(format!("{needs_sites} more site(s)"));
@@ -390,11 +519,193 @@ impl DeckBuilder {
                 {
                     Self::draw_card_preview(ui.ctx(), entry, anchor, screen);
                 }
+
+                self.render_import_window(ui.ctx());
+                self.render_stats_window(ui.ctx());
             });
 
         next_scene
     }
 
+    /// Mana curve, element requirements against atlas affinity, type and rarity split, and the
+    /// odds of finding each element's sites.
+    fn render_stats_window(&mut self, ctx: &Context) {
+        if !self.show_stats {
+            return;
+        }
+        let stats = DeckStats::from_deck(&self.deck_list());
+        egui::Window::new("Deck stats")
+            .open(&mut self.show_stats)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(440.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("Mana curve").color(TEXT_BRIGHT).strong());
+                let max_cost = stats.mana_curve.keys().max().copied().unwrap_or(0).max(7);
+                let tallest = stats.mana_curve.values().max().copied().unwrap_or(0).max(1);
+                let (curve_rect, _) = ui.allocate_exact_size(vec2(400.0, 90.0), Sense::hover());
+                let slots = u32::from(max_cost) + 2;
+                let slot_w = curve_rect.width() / slots as f32;
+                for slot in 0..slots {
+                    let (label, count) = if slot as u8 > max_cost {
+                        ("X".to_string(), stats.variable_cost)
+                    } else {
+                        let cost = slot as u8;
+                        (
+                            cost.to_string(),
+                            stats.mana_curve.get(&cost).copied().unwrap_or(0),
+                        )
+                    };
+                    let x = curve_rect.min.x + slot as f32 * slot_w;
+                    let bar_h = (curve_rect.height() - 28.0) * count as f32 / tallest as f32;
+                    let bar = Rect::from_min_max(
+                        pos2(x + 3.0, curve_rect.max.y - 14.0 - bar_h),
+                        pos2(x + slot_w - 3.0, curve_rect.max.y - 14.0),
+                    );
+                    ui.painter()
+                        .rect_filled(bar, CornerRadius::same(2), Color32::from_rgb(120, 160, 255));
+                    if count > 0 {
+                        ui.painter().text(
+                            bar.center_top() - vec2(0.0, 2.0),
+                            egui::Align2::CENTER_BOTTOM,
+                            count.to_string(),
+                            egui::FontId::proportional(11.0),
+                            TEXT_BRIGHT,
+                        );
+                    }
+                    ui.painter().text(
+                        pos2(bar.center().x, curve_rect.max.y),
+                        egui::Align2::CENTER_BOTTOM,
+                        label,
+                        egui::FontId::proportional(11.0),
+                        TEXT_DIM,
+                    );
+                }
+
+                ui.add_space(8.0);
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Thresholds and odds by turn (opening hand {OPENING_SITES} sites + {OPENING_SPELLS} spells, one site drawn per turn)"
+                    ))
+                    .color(TEXT_BRIGHT)
+                    .strong(),
+                );
+                const TURNS: [usize; 4] = [1, 3, 5, 7];
+                egui::Grid::new("deck_stats_elements")
+                    .striped(true)
+                    .spacing(vec2(12.0, 4.0))
+                    .show(ui, |ui| {
+                        ui.label("Element");
+                        ui.label("Spells");
+                        ui.label("Needs");
+                        ui.label("Sites");
+                        ui.label("Affinity");
+                        for turn in TURNS {
+                            ui.label(format!("T{turn}"))
+                                .on_hover_text(format!(
+                                    "Odds of meeting the threshold with {} sites seen",
+                                    sites_seen_by_turn(turn)
+                                ));
+                        }
+                        ui.end_row();
+                        for (element, element_stats) in &stats.elements {
+                            ui.label(format!("{element:?}"));
+                            ui.label(element_stats.spells.to_string());
+                            ui.label(element_stats.required.to_string());
+                            ui.label(element_stats.sites.to_string());
+                            let short = element_stats.affinity < u32::from(element_stats.required);
+                            ui.label(
+                                egui::RichText::new(element_stats.affinity.to_string())
+                                    .color(if short { ERROR } else { TEXT_BRIGHT }),
+                            );
+                            for turn in TURNS {
+                                let odds = stats.threshold_odds(
+                                    element,
+                                    element_stats.required.max(1),
+                                    turn,
+                                );
+                                ui.label(format!("{:.0}%", odds * 100.0));
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(8.0);
+                ui.columns(2, |columns| {
+                    columns[0].label(egui::RichText::new("Card types").color(TEXT_BRIGHT).strong());
+                    for (card_type, count) in &stats.card_types {
+                        columns[0].label(format!("{card_type}: {count}"));
+                    }
+                    columns[1].label(egui::RichText::new("Rarity").color(TEXT_BRIGHT).strong());
+                    for (rarity, count) in &stats.rarities {
+                        columns[1].label(format!("{rarity:?}: {count}"));
+                    }
+                });
+            });
+    }
+
+    /// Window for pasting a plain-text or curiosa.io deck list, with per-line import problems.
+    fn render_import_window(&mut self, ctx: &Context) {
+        let Some(mut text) = self.import_text.take() else {
+            return;
+        };
+        let mut open = true;
+        let mut import = false;
+        egui::Window::new("Import deck list")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(420.0)
+            .anchor(egui::Align2::CENTER_CENTER, vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Paste a deck list: \"Avatar: Sorcerer\", then one card per line such as \"3 Arid Desert\".",
+                    )
+                    .color(TEXT_DIM)
+                    .size(12.0),
+                );
+                ui.add_space(6.0);
+                ScrollArea::vertical()
+                    .id_salt("import_text")
+                    .max_height(260.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut text)
+                                .desired_rows(12)
+                                .desired_width(f32::INFINITY)
+                                .font(egui::TextStyle::Monospace),
+                        );
+                    });
+                for diagnostic in &self.import_diagnostics {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Line {}: {}",
+                            diagnostic.line, diagnostic.message
+                        ))
+                        .color(ERROR)
+                        .size(12.0),
+                    );
+                }
+                ui.add_space(6.0);
+                import = ui
+                    .add_enabled(!text.trim().is_empty(), egui::Button::new("Import"))
+                    .clicked();
+            });
+
+        if import {
+            let imported = parse_deck_text(self.deck_name.trim(), &text);
+            self.apply_import(imported.deck);
+            self.import_diagnostics = imported.diagnostics;
+            // Keep the window open when there is something to fix.
+            if !self.import_diagnostics.is_empty() {
+                self.import_text = Some(text);
+            }
+        } else if open {
+            self.import_text = Some(text);
+        }
+    }
+
     fn render_left_panel(&mut self, ui: &mut Ui, ctx: &Context, rect: Rect) {
         let pad = 8.0;
 
@@ -868,8 +1179,10 @@ impl DeckBuilder {
                     let minus_rect = Rect::from_min_size(pos2(controls_left, controls_y), vec2(btn_w, btn_h));
                     let count_rect = Rect::from_min_size(pos2(controls_left + 25.0, controls_y), vec2(34.0, btn_h));
                     let plus_rect = Rect::from_min_size(pos2(controls_left + 60.0, controls_y), vec2(btn_w, btn_h));
-                    let can_add = current_in_deck < printing_owned_count
-                        && total_in_deck < entry.max_copies();
+                    // Beta cards the player is missing can still be added, and crafted later.
+                    let craftable = self.limited_pool.is_none() && entry.edition == Edition::Beta;
+                    let can_add = (current_in_deck < printing_owned_count || craftable)
+                        && (self.limited_pool.is_some() || total_in_deck < entry.max_copies());
 
                     // `allocate_rect` moves the parent layout cursor. Since these rects
                     // live inside the row already allocated above, use `interact` so they
@@ -1119,9 +1432,10 @@ impl DeckBuilder {
             TEXT_BRIGHT,
         );
         y += 22.0;
+        let (required_sites, required_spells) = self.required_counts();
         for (label, count, required, color) in [
-            ("Atlas", site_count, 30, Color32::from_rgb(100, 200, 100)),
-            ("Spellbook", spell_count, 60, Color32::from_rgb(120, 160, 255)),
+            ("Atlas", site_count, required_sites, Color32::from_rgb(100, 200, 100)),
+            ("Spellbook", spell_count, required_spells, Color32::from_rgb(120, 160, 255)),
         ] {
             let label_pos = pos2(inner.min.x, y);
             ui.painter().text(
@@ -1172,9 +1486,9 @@ impl DeckBuilder {
                     );
                     ui.add_space(4.0);
                     ui.label(
-                        egui::RichText::new(
-                            "Use the + controls in the collection to add cards. Start with 30 sites and 60 spells.",
-                        )
+                        egui::RichText::new(format!(
+                            "Use the + controls in the collection to add cards. Start with {required_sites} sites and {required_spells} spells.",
+                        ))
                         .color(TEXT_DIM)
                         .size(13.0),
                     );
@@ -1276,6 +1590,59 @@ impl DeckBuilder {
                     self.deck_spells.remove(&rm);
                 }
             });
+
+        if self.limited_pool.is_none() {
+            let strip = Rect::from_min_max(
+                pos2(inner.min.x, rect.max.y - pad - 26.0),
+                pos2(inner.max.x, rect.max.y - pad),
+            );
+            self.render_crafting_strip(ui, strip);
+        }
+    }
+
+    /// Dust balance plus buttons to dust extra copies and craft the cards this deck is missing.
+    fn render_crafting_strip(&mut self, ui: &mut Ui, strip: Rect) {
+        let Some(dust) = self.crafting_dust else {
+            return;
+        };
+        let missing = sorcerers::crafting::missing_cards(&self.deck_list(), &self.collection_entries);
+        let mut strip_ui = ui.new_child(egui::UiBuilder::new().max_rect(strip));
+        strip_ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!("✦ {dust} dust"))
+                    .color(Color32::from_rgb(255, 215, 120))
+                    .size(13.0),
+            );
+            if ui
+                .small_button("Dust extras")
+                .on_hover_text("Disenchant copies beyond the deck limit that no saved deck uses")
+                .clicked()
+            {
+                self.client.send(ClientMessage::DustExtraCopies).ok();
+            }
+            if !missing.is_empty() {
+                match sorcerers::crafting::crafting_cost(&missing) {
+                    Ok(cost) => {
+                        let count: u32 = missing.iter().map(|card| card.count as u32).sum();
+                        let button = ui.add_enabled(
+                            cost <= dust,
+                            egui::Button::new(format!("Craft {count} missing ({cost} dust)")).small(),
+                        );
+                        if button.clicked() {
+                            self.client
+                                .send(ClientMessage::CraftCards { cards: missing.clone() })
+                                .ok();
+                        }
+                    }
+                    Err(e) => {
+                        ui.label(egui::RichText::new(e).color(TEXT_DIM).size(12.0));
+                    }
+                }
+            }
+            if let Some(feedback) = &self.crafting_feedback {
+                ui.label(egui::RichText::new(feedback).color(TEXT_DIM).size(12.0));
+            }
+        });
     }
 
     /// Draw a large card preview floating near `anchor`, flipping left/up to stay on screen.
@@ -1352,3 +1719,12 @@ impl DeckBuilder {
             });
     }
 }
+
+/// Total copies owned of each card, foil or not.
+fn collection_totals(collection: &[CollectedCard]) -> HashMap<String, u8> {
+    collection.iter().fold(HashMap::new(), |mut cards, card| {
+        let count = cards.entry(card.name.clone()).or_insert(0u8);
+        *count = count.saturating_add(card.count);
+        cards
+    })
+}
@@ -4,51 +4,77 @@ use super::*;
 
 impl DeckBuilder {
     pub(super) fn back_to_menu(&self) -> Scene {
-        Scene::Menu(crate::scene::menu::Menu::restore(
+        let mut menu = crate::scene::menu::Menu::restore(
             self.client.clone(),
             self.player_id,
             self.player_name.clone(),
             self.prev_available_decks.clone(),
             self.prev_saved_decks.clone(),
-            self.collection_entries.clone(),
-        ))
+            self.prev_collection.clone(),
+        );
+        menu.set_limited_events(self.prev_limited.clone());
+        Scene::Menu(menu)
     }
 
-    pub(super) fn try_save_deck(&mut self) -> Result<Scene, String> {
-        let avatar = self.selected_avatar.clone().unwrap_or_default();
-        let name = self.deck_name.trim().to_string();
+    pub(super) fn deck_list(&self) -> DeckList {
+        let cards = |cards: &HashMap<(String, bool), u8>| {
+            cards
+                .iter()
+                .map(|((card_name, is_foil), &count)| CardNameWithCount {
+                    count,
+                    name: card_name.clone(),
+                    is_foil: *is_foil,
+                })
+                .collect()
+        };
+        DeckList {
+            name: self.deck_name.trim().to_string(),
+            avatar: self.selected_avatar.clone().unwrap_or_default(),
+            spells: cards(&self.deck_spells),
+            sites: cards(&self.deck_sites),
+        }
+    }
 
-        let spells = self
-            .deck_spells
-            .iter()
-            .map(|((card_name, is_foil), &count)| CardNameWithCount {
-                count,
-                name: card_name.clone(),
-                is_foil: *is_foil,
-            })
-            .collect();
-        let sites = self
-            .deck_sites
-            .iter()
-            .map(|((card_name, is_foil), &count)| CardNameWithCount {
-                count,
-                name: card_name.clone(),
-                is_foil: *is_foil,
+    /// Replace the deck contents with an imported list. The deck name is kept.
+    pub(super) fn apply_import(&mut self, deck: DeckList) {
+        let cards = |cards: Vec<CardNameWithCount>| {
+            cards.into_iter().fold(HashMap::new(), |mut counts, card| {
+                *counts.entry((card.name, card.is_foil)).or_insert(0u8) += card.count;
+                counts
             })
-            .collect();
-
-        let deck_list = DeckList {
-            name,
-            avatar,
-            spells,
-            sites,
         };
+        self.deck_sites = cards(deck.sites);
+        self.deck_spells = cards(deck.spells);
+        if !deck.avatar.is_empty() {
+            self.selected_avatar = Some(deck.avatar);
+        }
+        self.save_error = None;
+    }
+
+    pub(super) fn try_save_deck(&mut self) -> Result<Scene, String> {
+        let deck_list = self.deck_list();
 
-        deck_list.validate()?;
+        match &self.limited_pool {
+            Some(pool) => pool.validate(&deck_list)?,
+            None => {
+                deck_list.validate()?;
+                let missing = sorcerers::crafting::missing_cards(&deck_list, &self.collection_entries);
+                if !missing.is_empty() {
+                    return Err(format!(
+                        "You are missing {} card(s). Craft them before saving.",
+                        missing.iter().map(|card| card.count as u32).sum::<u32>()
+                    ));
+                }
+            }
+        }
         deck_list
             .save()
             .map_err(|e| format!("Failed to save: {e}"))?;
 
+        // Limited decks are played straight from the menu, so it needs to list the new deck.
+        self.prev_saved_decks
+            .retain(|saved| saved.name != deck_list.name);
+        self.prev_saved_decks.push(deck_list);
         Ok(self.back_to_menu())
     }
 }
@@ -96,12 +96,7 @@ pub struct CardEntry {
 
 impl CardEntry {
     pub fn max_copies(&self) -> u8 {
-        match self.rarity {
-            Rarity::Ordinary => 4,
-            Rarity::Exceptional => 3,
-            Rarity::Elite => 2,
-            Rarity::Unique => 1,
-        }
+        sorcerers::deck::copy_limit(&self.rarity)
     }
 
     pub(super) fn as_card_data(&self) -> CardData {
@@ -23,9 +23,10 @@ use kira::{AudioManager, DefaultBackend, sound::static_sound::StaticSoundData};
 use sorcerers::{
     card::{CardData, CardType, Region},
     game::{CardId, Direction, PlayerId, Resources},
+    moderation::ReportReason,
     networking::{
         self,
-        message::{ClientMessage, EffectDebugData, OngoingEffectData, ServerMessage},
+        message::{ClientMessage, DebugData, OngoingEffectData, ServerMessage},
     },
     zone::{Location, Zone},
 };
@@ -34,6 +35,7 @@ use std::collections::HashMap;
 const MATCH_STAGE_BACKGROUND: &[u8] = include_bytes!("../../../../assets/images/menu/match_stage_v1.png");
 
 mod messages;
+mod sandbox;
 mod ui;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -173,8 +175,8 @@ pub struct GameData {
     pub last_clicked_card_id: Option<CardId>,
     pub last_clicked_card_time: Option<f64>,
     pub pending_projectiles: Vec<PendingProjectileAnimation>,
-    pub stepped_effects: bool,
-    pub effect_queue: Vec<EffectDebugData>,
+    /// Engine internals, only synced by servers running in dev mode.
+    pub debug: Option<DebugData>,
     pub show_debug_effects: bool,
 }
 
@@ -201,8 +203,7 @@ impl GameData {
             last_clicked_card_id: None,
             last_clicked_card_time: None,
             pending_projectiles: Vec::new(),
-            stepped_effects: false,
-            effect_queue: Vec::new(),
+            debug: None,
             show_debug_effects: false,
         }
     }
@@ -250,6 +251,25 @@ pub struct Game {
     match_stage_background: Option<TextureHandle>,
     game_over_started_at: Option<f64>,
     match_reward: Option<(u32, u32, bool)>,
+    /// Quests the game finished, with the reward points they paid out.
+    completed_quests: Option<(Vec<String>, u32)>,
+    unlocked_achievements: Vec<sorcerers::achievement::UnlockedAchievement>,
+    /// Set for goldfish games, which can be reset and redrawn from the game screen.
+    goldfish: bool,
+    /// Set for sandbox games, which edit the board through the sandbox window.
+    sandbox: Option<sandbox::SandboxEditor>,
+    opponent_moderation: OpponentModeration,
+}
+
+/// The report and block controls on the results screen of a match against another player.
+#[derive(Debug, Default)]
+struct OpponentModeration {
+    reason: ReportReason,
+    details: String,
+    reported: bool,
+    blocked: bool,
+    request_pending: bool,
+    error: Option<String>,
 }
 
 enum GameOverlay {
@@ -314,7 +334,7 @@ impl GameComponents {
     }
 
     fn update(&mut self, data: &mut GameData, ctx: &Context) {
-        if ctx.input(|i| i.key_pressed(egui::Key::F3)) {
+        if data.debug.is_some() && ctx.input(|i| i.key_pressed(egui::Key::F3)) {
             data.show_debug_effects = !data.show_debug_effects;
         }
 
@@ -428,9 +448,41 @@ impl Game {
             match_stage_background: None,
             game_over_started_at: None,
             match_reward: None,
+            completed_quests: None,
+            unlocked_achievements: Vec::new(),
+            goldfish: false,
+            sandbox: None,
+            opponent_moderation: OpponentModeration::default(),
         }
     }
 
+    pub(crate) fn set_goldfish(&mut self, goldfish: bool) {
+        self.goldfish = goldfish;
+    }
+
+    /// Start over after the server reset a goldfish game.
+    fn restart_goldfish(&mut self, player1: &PlayerId, player2: &PlayerId, cards: &[CardData]) {
+        let opponent_id = if player1 == &self.player_id {
+            *player2
+        } else {
+            *player1
+        };
+        self.components = GameComponents::new(
+            &self.game_id,
+            &self.player_id,
+            &opponent_id,
+            player1 == &self.player_id,
+            self.client.clone(),
+        );
+        self.data = GameData::new(&self.player_id, cards.to_vec());
+        self.current_player = uuid::Uuid::nil();
+        self.overlay = None;
+        self.selected_value = None;
+        self.card_toast.clear();
+        self.prompt_stack_pos = None;
+        self.controlled_hand_opened_for = None;
+    }
+
     fn return_to_menu(&mut self) -> Option<Scene> {
         self.data.status = Status::Idle;
         self.return_menu.take().map(Scene::Menu)
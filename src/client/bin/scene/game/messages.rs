@@ -25,13 +25,29 @@ impl Game {
         if matches!(self.data.status, Status::GameOver { .. })
             && !matches!(
                 message,
-                ServerMessage::GameOver { .. } | ServerMessage::MatchRewards { .. }
+                ServerMessage::GameOver { .. }
+                    | ServerMessage::MatchRewards { .. }
+                    | ServerMessage::QuestsCompleted { .. }
+                    | ServerMessage::AchievementsUnlocked { .. }
+                    | ServerMessage::OpponentReported { .. }
+                    | ServerMessage::OpponentBlocked { .. }
+                    | ServerMessage::ModerationRejected { .. }
+                    | ServerMessage::BlockedPlayers { .. }
             )
         {
             return None;
         }
 
         match message {
+            ServerMessage::GameStarted {
+                player1,
+                player2,
+                cards,
+                ..
+            } if self.goldfish => {
+                self.restart_goldfish(player1, player2, cards);
+                None
+            }
             ServerMessage::MulligansEnded => {
                 self.data.status = Status::Idle;
                 None
@@ -65,6 +81,12 @@ impl Game {
                     });
                 None
             }
+            ServerMessage::GameAborted { game_id, message } if *game_id == self.game_id => {
+                self.data.status = Status::GameAborted {
+                    reason: message.clone(),
+                };
+                None
+            }
             ServerMessage::PlayerDisconnected { player_id, .. } => {
                 self.data.status = Status::GameAborted {
                     reason: format!("Player {} disconnected.", player_id),
@@ -102,6 +124,44 @@ impl Game {
                 }
                 None
             }
+            ServerMessage::QuestsCompleted {
+                completed,
+                points_earned,
+                reward_points,
+            } => {
+                self.completed_quests = Some((completed.clone(), *points_earned));
+                if let Some(menu) = &mut self.return_menu {
+                    menu.set_reward_points(*reward_points);
+                }
+                None
+            }
+            ServerMessage::AchievementsUnlocked { achievements } => {
+                self.unlocked_achievements = achievements.clone();
+                None
+            }
+            ServerMessage::OpponentReported { game_id } if *game_id == self.game_id => {
+                self.opponent_moderation.reported = true;
+                self.opponent_moderation.request_pending = false;
+                self.opponent_moderation.error = None;
+                None
+            }
+            ServerMessage::OpponentBlocked { game_id } if *game_id == self.game_id => {
+                self.opponent_moderation.blocked = true;
+                self.opponent_moderation.request_pending = false;
+                self.opponent_moderation.error = None;
+                None
+            }
+            ServerMessage::ModerationRejected { game_id, message } if *game_id == self.game_id => {
+                self.opponent_moderation.request_pending = false;
+                self.opponent_moderation.error = Some(message.clone());
+                None
+            }
+            ServerMessage::BlockedPlayers { usernames } => {
+                if let Some(menu) = &mut self.return_menu {
+                    menu.set_blocked_players(usernames.clone());
+                }
+                None
+            }
             ServerMessage::Resume { .. } => {
                 self.data.status = Status::Idle;
                 None
@@ -352,8 +412,7 @@ impl Game {
                 turn_player,
                 resources,
                 health,
-                stepped_effects,
-                effect_queue,
+                debug,
                 ..
             } => {
                 let turn_started = self.data.turn_player != uuid::Uuid::nil()
@@ -372,8 +431,7 @@ impl Game {
                 self.data.avatar_health = health.clone();
                 self.data.ongoing_effects = None;
                 self.data.highlighted_ongoing_effect = None;
-                self.data.stepped_effects = *stepped_effects;
-                self.data.effect_queue = effect_queue.clone();
+                self.data.debug = debug.clone();
                 if turn_started
                     && let Ok(sound_data) = StaticSoundData::from_file("assets/sounds/turn_start.wav")
                 {
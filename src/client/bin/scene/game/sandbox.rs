@@ -0,0 +1,283 @@
+use super::*;
+use sorcerers::{
+    card::{CARD_CONSTRUCTORS, CardStatus},
+    sandbox::SandboxCommand,
+};
+
+/// Spawn results shown at once; narrow the search to see the rest.
+const MAX_SEARCH_RESULTS: usize = 40;
+
+const SPAWN_ZONES: [&str; 5] = ["Hand", "Spellbook", "Atlasbook", "Cemetery", "Realm"];
+
+const REGIONS: [Region; 4] = [
+    Region::Surface,
+    Region::Underground,
+    Region::Underwater,
+    Region::Void,
+];
+
+const STATUSES: [CardStatus; 3] = [
+    CardStatus::Disabled,
+    CardStatus::Silenced,
+    CardStatus::SummoningSickness,
+];
+
+/// The card palette and board editor of a sandbox game.
+pub(crate) struct SandboxEditor {
+    opponent_id: PlayerId,
+    search: String,
+    /// Whether spawned cards go to the opponent's seat rather than the player's.
+    for_opponent: bool,
+    /// Index into [`SPAWN_ZONES`].
+    zone: usize,
+    square: u8,
+    region: Region,
+    open: bool,
+}
+
+impl SandboxEditor {
+    pub(crate) fn new(opponent_id: PlayerId) -> Self {
+        Self {
+            opponent_id,
+            search: String::new(),
+            for_opponent: false,
+            zone: 0,
+            square: 8,
+            region: Region::Surface,
+            open: true,
+        }
+    }
+
+    fn target_zone(&self) -> Zone {
+        match self.zone {
+            0 => Zone::Hand,
+            1 => Zone::Spellbook,
+            2 => Zone::Atlasbook,
+            3 => Zone::Cemetery,
+            _ => Zone::Location(Location::Square(self.square, self.region.clone())),
+        }
+    }
+}
+
+impl Game {
+    pub(crate) fn set_sandbox(&mut self, opponent_id: Option<PlayerId>) {
+        self.sandbox = opponent_id.map(SandboxEditor::new);
+    }
+
+    pub(super) fn render_sandbox_editor(&mut self, ui: &mut Ui) {
+        let Some(mut editor) = self.sandbox.take() else {
+            return;
+        };
+
+        let sr = screen_rect().unwrap_or(Rect::ZERO);
+        let mut commands = Vec::new();
+        let mut open = editor.open;
+        egui::Window::new("Sandbox")
+            .open(&mut open)
+            .default_pos(pos2(sr.min.x + 20.0, 80.0))
+            .default_size(vec2(320.0, 520.0))
+            .movable(true)
+            .resizable(true)
+            .show(ui.ctx(), |ui| {
+                self.render_spawn_palette(ui, &mut editor, &mut commands);
+                ui.separator();
+                self.render_mana_editor(ui, &editor, &mut commands);
+                ui.separator();
+                self.render_card_editor(ui, &editor, &mut commands);
+            });
+        // Closing the window only hides it; the button in the corner brings it back.
+        editor.open = open;
+
+        if !editor.open {
+            egui::Area::new(egui::Id::new("sandbox_btn"))
+                .fixed_pos(pos2(sr.min.x + 20.0, sr.min.y + 12.0))
+                .show(ui.ctx(), |ui| {
+                    if ui.button("🧪 Sandbox").clicked() {
+                        editor.open = true;
+                    }
+                });
+        }
+
+        for command in commands {
+            self.client
+                .send(ClientMessage::Sandbox {
+                    game_id: self.game_id,
+                    player_id: self.data.player_id,
+                    command,
+                })
+                .ok();
+        }
+        self.sandbox = Some(editor);
+    }
+
+    fn render_spawn_palette(
+        &self,
+        ui: &mut Ui,
+        editor: &mut SandboxEditor,
+        commands: &mut Vec<SandboxCommand>,
+    ) {
+        ui.heading("Spawn a card");
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut editor.for_opponent, false, "Yours");
+            ui.radio_value(&mut editor.for_opponent, true, "Opponent's");
+        });
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("sandbox_zone")
+                .selected_text(SPAWN_ZONES[editor.zone])
+                .show_ui(ui, |ui| {
+                    for (idx, name) in SPAWN_ZONES.iter().enumerate() {
+                        ui.selectable_value(&mut editor.zone, idx, *name);
+                    }
+                });
+            if editor.zone == SPAWN_ZONES.len() - 1 {
+                ui.add(
+                    egui::DragValue::new(&mut editor.square)
+                        .range(1..=20)
+                        .prefix("Square "),
+                );
+                egui::ComboBox::from_id_salt("sandbox_region")
+                    .selected_text(editor.region.to_string())
+                    .show_ui(ui, |ui| {
+                        for region in REGIONS {
+                            let label = region.to_string();
+                            ui.selectable_value(&mut editor.region, region, label);
+                        }
+                    });
+            }
+        });
+        ui.add(egui::TextEdit::singleline(&mut editor.search).hint_text("Search cards..."));
+
+        let search = editor.search.to_lowercase();
+        let mut names: Vec<&str> = CARD_CONSTRUCTORS
+            .keys()
+            .copied()
+            .filter(|name| name.to_lowercase().contains(&search))
+            .collect();
+        names.sort_unstable();
+        let owner_id = if editor.for_opponent {
+            editor.opponent_id
+        } else {
+            self.data.player_id
+        };
+        egui::ScrollArea::vertical()
+            .id_salt("sandbox_palette")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for name in names.iter().take(MAX_SEARCH_RESULTS) {
+                    if ui.selectable_label(false, *name).clicked() {
+                        commands.push(SandboxCommand::SpawnCard {
+                            name: name.to_string(),
+                            owner_id,
+                            zone: editor.target_zone(),
+                        });
+                    }
+                }
+                if names.len() > MAX_SEARCH_RESULTS {
+                    ui.label(
+                        RichText::new(format!("{} more...", names.len() - MAX_SEARCH_RESULTS))
+                            .color(theme::TURN_WAITING),
+                    );
+                }
+            });
+    }
+
+    fn render_mana_editor(
+        &self,
+        ui: &mut Ui,
+        editor: &SandboxEditor,
+        commands: &mut Vec<SandboxCommand>,
+    ) {
+        ui.horizontal(|ui| {
+            for (label, player_id) in [
+                ("Your mana", self.data.player_id),
+                ("Opponent's mana", editor.opponent_id),
+            ] {
+                let mut mana = self
+                    .data
+                    .resources
+                    .get(&player_id)
+                    .map(|resources| resources.mana)
+                    .unwrap_or_default();
+                ui.label(label);
+                if ui
+                    .add(egui::DragValue::new(&mut mana).range(0..=20))
+                    .changed()
+                {
+                    commands.push(SandboxCommand::SetMana { player_id, mana });
+                }
+            }
+        });
+    }
+
+    /// Every card outside the decks, with its damage, tapped state and statuses.
+    fn render_card_editor(
+        &self,
+        ui: &mut Ui,
+        editor: &SandboxEditor,
+        commands: &mut Vec<SandboxCommand>,
+    ) {
+        ui.heading("Cards");
+        egui::ScrollArea::vertical()
+            .id_salt("sandbox_cards")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let cards = self
+                    .data
+                    .cards
+                    .iter()
+                    .filter(|card| !matches!(card.zone, Zone::Spellbook | Zone::Atlasbook));
+                for card in cards {
+                    let owner = if card.owner_id == self.data.player_id {
+                        "yours"
+                    } else {
+                        "opponent's"
+                    };
+                    egui::CollapsingHeader::new(format!("{} ({owner}, {})", card.name, card.zone))
+                        .id_salt(card.id)
+                        .show(ui, |ui| {
+                            let card_id = card.id;
+                            ui.horizontal(|ui| {
+                                let mut damage = card.damage_taken;
+                                ui.label("Damage");
+                                if ui
+                                    .add(egui::DragValue::new(&mut damage).range(0..=99))
+                                    .changed()
+                                {
+                                    commands.push(SandboxCommand::SetDamage { card_id, damage });
+                                }
+                                let mut tapped = card.tapped;
+                                if ui.checkbox(&mut tapped, "Tapped").changed() {
+                                    commands.push(SandboxCommand::SetTapped { card_id, tapped });
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                for status in STATUSES {
+                                    let mut enabled = card.statuses.contains(&status);
+                                    if ui.checkbox(&mut enabled, format!("{status:?}")).changed() {
+                                        commands.push(SandboxCommand::SetStatus {
+                                            card_id,
+                                            status,
+                                            enabled,
+                                        });
+                                    }
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                let target = editor.target_zone();
+                                if ui.button(format!("Move to {target}")).clicked() {
+                                    commands.push(SandboxCommand::MoveCard {
+                                        card_id,
+                                        zone: target,
+                                    });
+                                }
+                                if card.card_type != CardType::Avatar
+                                    && ui.button("Remove").clicked()
+                                {
+                                    commands.push(SandboxCommand::RemoveCard { card_id });
+                                }
+                            });
+                        });
+                }
+            });
+    }
+}
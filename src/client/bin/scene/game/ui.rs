@@ -96,13 +96,20 @@ impl Game {
         );
 
         self.render_controls_button(ui, sr);
+        // Only offer a reset while no prompt is open: the server reads the next message from the
+        // player as the prompt's answer.
+        if self.goldfish && matches!(self.data.status, Status::Idle | Status::Mulligan) {
+            self.render_goldfish_controls(ui, sr);
+        }
+        self.render_sandbox_editor(ui);
 
-        #[cfg(debug_assertions)]
-        self.render_debug_effects_button(ui, sr);
+        // The server only sends debug data for games running in dev mode.
+        if self.data.debug.is_some() {
+            self.render_debug_effects_button(ui, sr);
 
-        #[cfg(debug_assertions)]
-        if self.data.show_debug_effects {
-            self.render_debug_effects_panel(ui);
+            if self.data.show_debug_effects {
+                self.render_debug_effects_panel(ui);
+            }
         }
 
         if is_in_turn && is_idle {
@@ -272,6 +279,10 @@ impl Game {
     }
 
     fn render_debug_effects_panel(&mut self, ui: &mut Ui) {
+        let Some(debug) = self.data.debug.clone() else {
+            return;
+        };
+
         let sr = screen_rect().unwrap_or(Rect::ZERO);
         egui::Window::new("Effect Debugger")
             .default_pos(pos2(sr.max.x - 320.0, 80.0))
@@ -280,10 +291,8 @@ impl Game {
             .resizable(true)
             .show(ui.ctx(), |ui| {
                 ui.horizontal(|ui| {
-                    if ui
-                        .checkbox(&mut self.data.stepped_effects, "Stepped Mode")
-                        .changed()
-                    {
+                    let mut stepped_effects = debug.stepped_effects;
+                    if ui.checkbox(&mut stepped_effects, "Stepped Mode").changed() {
                         self.client
                             .send(ClientMessage::ToggleSteppedEffects {
                                 player_id: self.data.player_id,
@@ -292,7 +301,7 @@ impl Game {
                             .ok();
                     }
 
-                    if self.data.stepped_effects && ui.button("Step Next").clicked() {
+                    if debug.stepped_effects && ui.button("Step Next").clicked() {
                         self.client
                             .send(ClientMessage::StepNextEffect {
                                 player_id: self.data.player_id,
@@ -303,23 +312,97 @@ impl Game {
                 });
 
                 ui.separator();
-                ui.label(format!("Queue Size: {}", self.data.effect_queue.len()));
                 egui::ScrollArea::vertical()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        for (i, effect) in self.data.effect_queue.iter().enumerate().rev() {
-                            egui::CollapsingHeader::new(format!("{}: {}", i, effect.name))
-                                .id_salt(format!("effect_{}", i))
-                                .show(ui, |ui| {
-                                    ui.add(egui::Label::new(
-                                        egui::RichText::new(&effect.description).monospace(),
-                                    ));
-                                });
-                        }
+                        egui::CollapsingHeader::new(format!(
+                            "Effect Queue ({})",
+                            debug.effect_queue.len()
+                        ))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (i, effect) in debug.effect_queue.iter().enumerate().rev() {
+                                egui::CollapsingHeader::new(format!("{}: {}", i, effect.name))
+                                    .id_salt(format!("effect_{}", i))
+                                    .show(ui, |ui| {
+                                        ui.add(egui::Label::new(
+                                            egui::RichText::new(&effect.description).monospace(),
+                                        ));
+                                    });
+                            }
+                        });
+
+                        egui::CollapsingHeader::new(format!(
+                            "Ongoing Effects ({})",
+                            debug.ongoing_effects.len()
+                        ))
+                        .show(ui, |ui| {
+                            for effect in &debug.ongoing_effects {
+                                let source = effect.source_name.as_deref().unwrap_or("Unknown");
+                                ui.label(format!(
+                                    "#{} {}: {}",
+                                    effect.timestamp, source, effect.description
+                                ));
+                            }
+                        });
+
+                        egui::CollapsingHeader::new(format!("Hooks ({})", debug.hooks.len()))
+                            .show(ui, |ui| {
+                                for (i, hook) in debug.hooks.iter().enumerate() {
+                                    egui::CollapsingHeader::new(format!(
+                                        "{} ({})",
+                                        hook.card_name, hook.timing
+                                    ))
+                                    .id_salt(format!("hook_{}", i))
+                                    .show(ui, |ui| {
+                                        ui.add(egui::Label::new(
+                                            egui::RichText::new(&hook.trigger).monospace(),
+                                        ));
+                                    });
+                                }
+                            });
                     });
             });
     }
 
+    /// Reset and redraw buttons for goldfish games, left of the turn buttons.
+    fn render_goldfish_controls(&mut self, ui: &mut Ui, sr: Rect) {
+        let btn_size = vec2(116.0, 30.0);
+        let btn_pos = pos2(sr.max.x - 2.0 * btn_size.x - 236.0, sr.min.y + 12.0);
+        let mut message = None;
+        egui::Area::new(egui::Id::new("goldfish_controls"))
+            .fixed_pos(btn_pos)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let reset = ui
+                        .add(egui::Button::new("↺ Reset game").min_size(btn_size))
+                        .on_hover_text("Start over with a freshly shuffled deck");
+                    if reset.clicked() {
+                        message = Some(ClientMessage::ResetGoldfish {
+                            game_id: self.game_id,
+                            player_id: self.data.player_id,
+                        });
+                    }
+                    if self.data.status != Status::Mulligan {
+                        return;
+                    }
+                    let redraw = ui
+                        .add(egui::Button::new("🂠 Redraw hand").min_size(btn_size))
+                        .on_hover_text("Shuffle your hand back and draw a new opening hand");
+                    if redraw.clicked() {
+                        message = Some(ClientMessage::RedrawOpeners {
+                            game_id: self.game_id,
+                            player_id: self.data.player_id,
+                        });
+                    }
+                });
+            });
+        if let Some(message) = message {
+            self.play_button_click();
+            self.client.send(message).ok();
+        }
+    }
+
     fn render_controls_button(&mut self, ui: &mut Ui, sr: Rect) {
         let icon_size = vec2(24.0, 24.0);
         let icon_pos = pos2(sr.center().x + 142.0, sr.min.y + 15.0);
@@ -832,6 +915,40 @@ impl Game {
                                         .color(Color32::from_rgb(255, 200, 60)),
                                 );
                             }
+                            if let Some((completed, points_earned)) = &self.completed_quests {
+                                ui.add_space(8.0);
+                                for quest in completed {
+                                    ui.label(
+                                        RichText::new(format!("Quest complete: {quest}"))
+                                            .size(14.0)
+                                            .color(theme::TEXT_BRIGHT),
+                                    );
+                                }
+                                ui.label(
+                                    RichText::new(format!("✦ +{points_earned} quest reward points"))
+                                        .size(14.0)
+                                        .strong()
+                                        .color(Color32::from_rgb(255, 200, 60)),
+                                );
+                            }
+                            for achievement in &self.unlocked_achievements {
+                                ui.add_space(8.0);
+                                ui.label(
+                                    RichText::new(format!("🏆 {}", achievement.name))
+                                        .size(15.0)
+                                        .strong()
+                                        .color(Color32::from_rgb(255, 200, 60)),
+                                );
+                                ui.label(
+                                    RichText::new(&achievement.description)
+                                        .size(13.0)
+                                        .color(theme::TURN_WAITING),
+                                );
+                            }
+                            if !self.goldfish && self.sandbox.is_none() {
+                                ui.add_space(16.0);
+                                self.render_opponent_moderation(ui);
+                            }
                             ui.add_space(22.0);
                             if ui
                                 .add(
@@ -857,4 +974,82 @@ impl Game {
 
         None
     }
+
+    /// Let the player report or block the opponent they just played.
+    fn render_opponent_moderation(&mut self, ui: &mut Ui) {
+        let game_id = self.game_id;
+        let moderation = &mut self.opponent_moderation;
+        let enabled = !moderation.request_pending;
+        let mut request = None;
+        egui::CollapsingHeader::new(
+            RichText::new("Report or block your opponent")
+                .size(13.0)
+                .color(theme::TURN_WAITING),
+        )
+        .id_salt("opponent_moderation")
+        .show(ui, |ui| {
+            if moderation.reported {
+                ui.label(
+                    RichText::new("Thanks. Your report was sent along with this match.")
+                        .size(13.0)
+                        .color(theme::TEXT_BRIGHT),
+                );
+            } else {
+                egui::ComboBox::from_id_salt("report_reason")
+                    .selected_text(moderation.reason.label())
+                    .show_ui(ui, |ui| {
+                        for reason in ReportReason::ALL {
+                            ui.selectable_value(&mut moderation.reason, reason, reason.label());
+                        }
+                    });
+                ui.add(
+                    egui::TextEdit::multiline(&mut moderation.details)
+                        .hint_text("What happened? (optional)")
+                        .char_limit(sorcerers::moderation::MAX_REPORT_DETAILS)
+                        .desired_rows(2),
+                );
+                if ui
+                    .add_enabled(enabled, egui::Button::new("Report"))
+                    .clicked()
+                {
+                    request = Some(ClientMessage::ReportOpponent {
+                        game_id,
+                        reason: moderation.reason,
+                        details: moderation.details.trim().to_string(),
+                    });
+                }
+            }
+            ui.add_space(6.0);
+            if moderation.blocked {
+                ui.label(
+                    RichText::new("Blocked. You won't be matched with this player again.")
+                        .size(13.0)
+                        .color(theme::TEXT_BRIGHT),
+                );
+            } else if ui
+                .add_enabled(enabled, egui::Button::new("Block"))
+                .clicked()
+            {
+                request = Some(ClientMessage::BlockOpponent { game_id });
+            }
+            if let Some(error) = &moderation.error {
+                ui.label(
+                    RichText::new(error)
+                        .size(13.0)
+                        .color(Color32::from_rgb(255, 195, 192)),
+                );
+            }
+        });
+
+        if let Some(request) = request {
+            self.play_button_click();
+            let moderation = &mut self.opponent_moderation;
+            if self.client.send(request).is_ok() {
+                moderation.request_pending = true;
+                moderation.error = None;
+            } else {
+                moderation.error = Some("Unable to reach the server.".to_string());
+            }
+        }
+    }
 }
@@ -8,24 +8,148 @@ use kira::{
 use sorcerers::booster::{BoosterCard, BoosterPack, UnopenedBoosterPack};
 use sorcerers::card::{CardData, Region, from_name};
 use sorcerers::collection::CollectedCard;
-use sorcerers::deck::DeckList;
 use sorcerers::deck::precon::PreconDeck;
+use sorcerers::deck::{CardNameWithCount, DeckList};
+use sorcerers::draft::{DRAFT_MAX_SEATS, DRAFT_MIN_SEATS};
 use sorcerers::game::PlayerId;
 use sorcerers::networking::message::ServerMessage;
 use sorcerers::networking::{
     self,
     message::{ClientMessage, DeckChoice},
 };
+use sorcerers::quest::{QuestCadence, QuestProgress};
+use sorcerers::sealed::SealedPool;
+use sorcerers::trade::{TradeOffer, TradeSide};
 
 const MENU_BG: Color32 = Color32::from_rgb(8, 8, 14);
 const MENU_BORDER: Color32 = theme::PANEL_BORDER;
 const MENU_TEXT: Color32 = Color32::from_rgb(235, 236, 225);
 const MENU_TEXT_MUTED: Color32 = Color32::from_rgb(171, 179, 168);
 const MENU_GOLD: Color32 = Color32::from_rgb(255, 200, 60);
-const BETA_BOOSTER_COST: u32 = 30;
+/// What a Beta booster costs until the server says otherwise.
+const DEFAULT_BETA_BOOSTER_COST: u32 = 30;
 const MENU_BACKGROUND: &[u8] =
     include_bytes!("../../../../assets/images/menu/enchanted_table_v1.png");
 
+/// Sealed and draft events the player is taking part in. They live on the server, so the menu
+/// carries them through the deck builder and games rather than asking for them again.
+#[derive(Debug, Clone, Default)]
+pub struct LimitedEvents {
+    pub sealed_pool: Option<SealedPool>,
+    pub draft: Option<DraftProgress>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftBracketView {
+    round: u8,
+    matches: Vec<(String, String)>,
+    bye: Option<String>,
+    champion: Option<String>,
+}
+
+/// The player's view of the draft pod they sit in.
+#[derive(Debug, Clone)]
+pub struct DraftProgress {
+    pod_id: uuid::Uuid,
+    seats: u8,
+    players: Vec<String>,
+    round: u8,
+    pick: u8,
+    /// The pack to pick from next. Empty while waiting for a neighbour to pass one.
+    pack: Vec<BoosterCard>,
+    pick_deadline: Option<std::time::Instant>,
+    picks: Vec<BoosterCard>,
+    /// Set once drafting is over and the picks can be built into a deck.
+    pool: Option<SealedPool>,
+    bracket: Option<DraftBracketView>,
+}
+
+impl DraftProgress {
+    fn new(pod_id: uuid::Uuid) -> Self {
+        Self {
+            pod_id,
+            seats: 0,
+            players: vec![],
+            round: 0,
+            pick: 0,
+            pack: vec![],
+            pick_deadline: None,
+            picks: vec![],
+            pool: None,
+            bracket: None,
+        }
+    }
+}
+
+/// Terms being put together for a new trade offer or a counter-offer.
+#[derive(Debug, Clone, Default)]
+struct TradeComposer {
+    /// The trade being countered, if any.
+    trade_id: Option<uuid::Uuid>,
+    recipient: String,
+    offered: TradeSide,
+    requested: TradeSide,
+    /// Index into the collection of the card to add to `offered`.
+    offer_card: Option<usize>,
+    request_name: String,
+    request_foil: bool,
+}
+
+/// Fields of the account screen, which are cleared once the server accepts a change.
+#[derive(Debug, Clone, Default)]
+struct AccountForm {
+    username: String,
+    current_password: String,
+    new_password: String,
+    new_email: String,
+    block_username: String,
+    delete_password: String,
+    confirm_delete: bool,
+    request_pending: bool,
+    feedback: Option<String>,
+    error: Option<String>,
+}
+
+impl TradeComposer {
+    fn counter(trade: &TradeOffer) -> Self {
+        Self {
+            trade_id: Some(trade.id),
+            recipient: trade.partner.clone(),
+            offered: trade.yours.clone(),
+            requested: trade.theirs.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+fn add_trade_card(side: &mut TradeSide, name: &str, is_foil: bool) {
+    if let Some(card) = side
+        .cards
+        .iter_mut()
+        .find(|card| card.name == name && card.is_foil == is_foil)
+    {
+        card.count = card.count.saturating_add(1);
+    } else {
+        side.cards.push(CardNameWithCount {
+            name: name.to_string(),
+            count: 1,
+            is_foil,
+        });
+    }
+}
+
+fn describe_trade_side(side: &TradeSide) -> String {
+    let mut parts: Vec<String> = side.cards.iter().map(ToString::to_string).collect();
+    if side.reward_points > 0 {
+        parts.push(format!("{} points", side.reward_points));
+    }
+    if parts.is_empty() {
+        "nothing".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
 pub struct Menu {
     client: networking::client::Client,
     player_id: Option<PlayerId>,
@@ -35,6 +159,10 @@ pub struct Menu {
     selected_saved_deck: Option<usize>,
     deck_error: Option<String>,
     looking_for_match: bool,
+    /// Set once a goldfish game was requested, so the game that starts next offers its controls.
+    starting_goldfish: bool,
+    /// Set once a sandbox game was requested, so the game that starts next opens its editor.
+    starting_sandbox: bool,
     player_name: String,
     username: String,
     email: String,
@@ -42,16 +170,38 @@ pub struct Menu {
     confirmation_code: String,
     registering: bool,
     awaiting_email_confirmation: bool,
+    /// Set while the login card shows the forgotten password form.
+    resetting_password: bool,
+    /// Set once a reset code was requested, so the form asks for the code and a new password.
+    password_reset_requested: bool,
+    new_password: String,
     auth_requested: bool,
     auth_error: Option<String>,
     booster_reward: Option<String>,
     reward_points: u32,
+    /// Reward points a Beta booster costs on this server.
+    beta_booster_cost: u32,
     show_rewards: bool,
     reward_redemption_requested: bool,
     reward_feedback: Option<String>,
     unopened_booster_packs: Vec<UnopenedBoosterPack>,
     opened_booster_pack: Option<BoosterPack>,
     show_packs: bool,
+    limited: LimitedEvents,
+    show_draft: bool,
+    draft_seats: u8,
+    draft_error: Option<String>,
+    show_trades: bool,
+    trades: Vec<TradeOffer>,
+    trade_composer: TradeComposer,
+    trade_unlock_decks: bool,
+    trade_feedback: Option<String>,
+    show_quests: bool,
+    quests: Vec<QuestProgress>,
+    show_account: bool,
+    account: AccountForm,
+    /// Players this player has blocked from being matched with them.
+    blocked_players: Vec<String>,
     selecting_starter_deck: bool,
     starter_decks: Vec<PreconDeck>,
     connect_requested: bool,
@@ -82,6 +232,18 @@ impl Menu {
         self.reward_points = reward_points;
     }
 
+    pub(crate) fn set_beta_booster_cost(&mut self, beta_booster_cost: u32) {
+        self.beta_booster_cost = beta_booster_cost;
+    }
+
+    pub(crate) fn set_blocked_players(&mut self, blocked_players: Vec<String>) {
+        self.blocked_players = blocked_players;
+    }
+
+    pub(crate) fn set_limited_events(&mut self, limited: LimitedEvents) {
+        self.limited = limited;
+    }
+
     pub fn new(client: networking::client::Client) -> Self {
         Self {
             client,
@@ -92,6 +254,8 @@ impl Menu {
             selected_saved_deck: None,
             deck_error: None,
             looking_for_match: false,
+            starting_goldfish: false,
+            starting_sandbox: false,
             player_name: String::new(),
             username: String::new(),
             email: String::new(),
@@ -99,16 +263,34 @@ impl Menu {
             confirmation_code: String::new(),
             registering: false,
             awaiting_email_confirmation: false,
+            resetting_password: false,
+            password_reset_requested: false,
+            new_password: String::new(),
             auth_requested: false,
             auth_error: None,
             booster_reward: None,
             reward_points: 0,
+            beta_booster_cost: DEFAULT_BETA_BOOSTER_COST,
             show_rewards: false,
             reward_redemption_requested: false,
             reward_feedback: None,
             unopened_booster_packs: vec![],
             opened_booster_pack: None,
             show_packs: false,
+            limited: LimitedEvents::default(),
+            show_draft: false,
+            draft_seats: DRAFT_MAX_SEATS as u8,
+            draft_error: None,
+            show_trades: false,
+            trades: vec![],
+            trade_composer: TradeComposer::default(),
+            trade_unlock_decks: false,
+            trade_feedback: None,
+            show_quests: false,
+            quests: vec![],
+            show_account: false,
+            account: AccountForm::default(),
+            blocked_players: vec![],
             selecting_starter_deck: false,
             starter_decks: vec![],
             connect_requested: false,
@@ -137,6 +319,8 @@ impl Menu {
             selected_saved_deck: None,
             deck_error: None,
             looking_for_match: false,
+            starting_goldfish: false,
+            starting_sandbox: false,
             player_name,
             username: String::new(),
             email: String::new(),
@@ -144,16 +328,34 @@ impl Menu {
             confirmation_code: String::new(),
             registering: false,
             awaiting_email_confirmation: false,
+            resetting_password: false,
+            password_reset_requested: false,
+            new_password: String::new(),
             auth_requested: false,
             auth_error: None,
             booster_reward: None,
             reward_points: 0,
+            beta_booster_cost: DEFAULT_BETA_BOOSTER_COST,
             show_rewards: false,
             reward_redemption_requested: false,
             reward_feedback: None,
             unopened_booster_packs: vec![],
             opened_booster_pack: None,
             show_packs: false,
+            limited: LimitedEvents::default(),
+            show_draft: false,
+            draft_seats: DRAFT_MAX_SEATS as u8,
+            draft_error: None,
+            show_trades: false,
+            trades: vec![],
+            trade_composer: TradeComposer::default(),
+            trade_unlock_decks: false,
+            trade_feedback: None,
+            show_quests: false,
+            quests: vec![],
+            show_account: false,
+            account: AccountForm::default(),
+            blocked_players: vec![],
             selecting_starter_deck: false,
             starter_decks: vec![],
             connect_requested: false,
@@ -255,6 +457,10 @@ impl Menu {
             self.render_email_confirmation_card(ui);
             return;
         }
+        if self.resetting_password {
+            self.render_password_reset_card(ui);
+            return;
+        }
         let title = if self.registering {
             "Create your player account"
         } else {
@@ -388,6 +594,183 @@ impl Menu {
                     self.awaiting_email_confirmation = false;
                     self.auth_error = None;
                 }
+                if !self.registering {
+                    ui.add_space(8.0);
+                    if ui
+                        .link(
+                            egui::RichText::new("Forgot your password?")
+                                .color(Color32::from_rgb(122, 194, 245)),
+                        )
+                        .clicked()
+                    {
+                        self.resetting_password = true;
+                        self.password_reset_requested = false;
+                        self.auth_error = None;
+                    }
+                }
+            });
+    }
+
+    fn render_password_reset_card(&mut self, ui: &mut Ui) {
+        egui::Frame::new()
+            .fill(Color32::from_rgba_premultiplied(7, 11, 11, 176))
+            .stroke(egui::Stroke::NONE)
+            .corner_radius(8.0)
+            .inner_margin(egui::Margin::same(24))
+            .show(ui, |ui| {
+                ui.set_width(360.0);
+                ui.label(
+                    egui::RichText::new("Reset your password")
+                        .color(MENU_TEXT)
+                        .size(24.0)
+                        .strong(),
+                );
+                ui.add_space(7.0);
+                let supporting_copy = if self.password_reset_requested {
+                    format!(
+                        "If {} belongs to an account, we sent it a six-digit code. It expires after 15 minutes.",
+                        self.email
+                    )
+                } else {
+                    "Enter the email address you log in with and we'll send you a reset code."
+                        .to_string()
+                };
+                ui.label(
+                    egui::RichText::new(supporting_copy)
+                        .color(MENU_TEXT_MUTED)
+                        .size(14.0),
+                );
+                ui.add_space(24.0);
+
+                if self.password_reset_requested {
+                    ui.label(
+                        egui::RichText::new("Reset code")
+                            .color(MENU_TEXT)
+                            .size(14.0)
+                            .strong(),
+                    );
+                    ui.add_space(6.0);
+                    Self::render_auth_input(ui, &mut self.confirmation_code, "000000", false);
+                    ui.add_space(16.0);
+                    ui.label(
+                        egui::RichText::new("New password")
+                            .color(MENU_TEXT)
+                            .size(14.0)
+                            .strong(),
+                    );
+                    ui.add_space(6.0);
+                    Self::render_auth_input(
+                        ui,
+                        &mut self.new_password,
+                        "Choose a new password",
+                        true,
+                    );
+                } else {
+                    ui.label(
+                        egui::RichText::new("Email address")
+                            .color(MENU_TEXT)
+                            .size(14.0)
+                            .strong(),
+                    );
+                    ui.add_space(6.0);
+                    Self::render_auth_input(ui, &mut self.email, "you@example.com", false);
+                }
+
+                if let Some(error) = &self.auth_error {
+                    ui.add_space(12.0);
+                    ui.label(
+                        egui::RichText::new(error)
+                            .color(Color32::from_rgb(255, 195, 192))
+                            .size(14.0),
+                    );
+                }
+
+                ui.add_space(22.0);
+                let (can_submit, submit_label) = if self.password_reset_requested {
+                    (
+                        self.confirmation_code.trim().len() == 6 && !self.new_password.is_empty(),
+                        "Reset password",
+                    )
+                } else {
+                    (!self.email.trim().is_empty(), "Send reset code")
+                };
+                let submit = ui.add_enabled(
+                    !self.auth_requested && can_submit,
+                    egui::Button::new(
+                        egui::RichText::new(if self.auth_requested {
+                            "Connecting…"
+                        } else {
+                            submit_label
+                        })
+                        .size(17.0),
+                    )
+                    .min_size(vec2(360.0, theme::BUTTON_HEIGHT)),
+                );
+                if submit.clicked() {
+                    let message = if self.password_reset_requested {
+                        ClientMessage::ResetPassword {
+                            email: self.email.clone(),
+                            code: self.confirmation_code.trim().to_string(),
+                            new_password: self.new_password.clone(),
+                        }
+                    } else {
+                        ClientMessage::RequestPasswordReset {
+                            email: self.email.trim().to_string(),
+                        }
+                    };
+                    if self.client.send(message).is_ok() {
+                        self.auth_requested = true;
+                        self.auth_error = None;
+                    } else {
+                        self.auth_error = Some(
+                            "Unable to reach the server. Check your connection and try again."
+                                .to_string(),
+                        );
+                    }
+                }
+
+                if self.password_reset_requested {
+                    ui.add_space(12.0);
+                    if ui
+                        .link(
+                            egui::RichText::new("Send a new code")
+                                .color(Color32::from_rgb(122, 194, 245)),
+                        )
+                        .clicked()
+                    {
+                        self.password_reset_requested = false;
+                        self.confirmation_code.clear();
+                        self.auth_error = None;
+                    }
+                } else if !self.email.trim().is_empty() {
+                    // Codes can also come from an operator, who sends them without a request.
+                    ui.add_space(12.0);
+                    if ui
+                        .link(
+                            egui::RichText::new("I already have a code")
+                                .color(Color32::from_rgb(122, 194, 245)),
+                        )
+                        .clicked()
+                    {
+                        self.email = self.email.trim().to_string();
+                        self.password_reset_requested = true;
+                        self.auth_error = None;
+                    }
+                }
+                ui.add_space(12.0);
+                if ui
+                    .link(
+                        egui::RichText::new("Back to log in")
+                            .color(Color32::from_rgb(122, 194, 245)),
+                    )
+                    .clicked()
+                {
+                    self.resetting_password = false;
+                    self.password_reset_requested = false;
+                    self.confirmation_code.clear();
+                    self.new_password.clear();
+                    self.auth_error = None;
+                }
             });
     }
 
@@ -544,6 +927,121 @@ impl Menu {
         }
     }
 
+    /// The deck to practice with: the precon `deck_list` was copied from, or the list itself.
+    fn practice_deck(&mut self, deck_list: DeckList) -> Option<DeckChoice> {
+        let starter_deck = self
+            .available_decks
+            .iter()
+            .find(|deck| deck_list.name == format!("{} Precon", deck.name()))
+            .cloned();
+        let deck = match starter_deck {
+            Some(starter_deck) => DeckChoice::Precon(starter_deck),
+            None => match deck_list.validate() {
+                Ok(()) => DeckChoice::Custom(deck_list),
+                Err(msg) => {
+                    self.deck_error = Some(msg);
+                    return None;
+                }
+            },
+        };
+        self.deck_error = None;
+        Some(deck)
+    }
+
+    /// Try `deck_list` alone against an inert dummy avatar.
+    fn goldfish_deck(&mut self, deck_list: DeckList) {
+        let Some(deck) = self.practice_deck(deck_list) else {
+            return;
+        };
+        self.client
+            .send(ClientMessage::StartGoldfish {
+                player_name: self.player_name.clone(),
+                player_id: self.player_id.expect("player id should be set"),
+                deck,
+            })
+            .ok();
+        self.starting_goldfish = true;
+    }
+
+    /// Open a sandbox where the player controls both seats, each playing `deck_list`.
+    fn sandbox_deck(&mut self, deck_list: DeckList) {
+        let Some(deck) = self.practice_deck(deck_list) else {
+            return;
+        };
+        self.client
+            .send(ClientMessage::StartSandbox {
+                player_name: self.player_name.clone(),
+                player_id: self.player_id.expect("player id should be set"),
+                deck,
+            })
+            .ok();
+        self.starting_sandbox = true;
+    }
+
+    fn play_sealed_deck(&mut self, deck_list: DeckList) {
+        let Some(pool) = &self.limited.sealed_pool else {
+            self.deck_error = Some("Open a sealed pool before queueing for sealed.".to_string());
+            return;
+        };
+
+        match pool.validate(&deck_list) {
+            Ok(()) => {
+                self.deck_error = None;
+                self.client
+                    .send(ClientMessage::JoinSealedQueue {
+                        player_name: self.player_name.clone(),
+                        player_id: self.player_id.expect("player id should be set"),
+                        deck: deck_list,
+                    })
+                    .ok();
+                self.looking_for_match = true;
+            }
+            Err(msg) => {
+                self.deck_error = Some(msg);
+            }
+        }
+    }
+
+    fn draft_mut(&mut self, pod_id: uuid::Uuid) -> &mut DraftProgress {
+        let draft = self
+            .limited
+            .draft
+            .get_or_insert_with(|| DraftProgress::new(pod_id));
+        if draft.pod_id != pod_id {
+            *draft = DraftProgress::new(pod_id);
+        }
+        draft
+    }
+
+    fn play_draft_match(&mut self, pod_id: uuid::Uuid, pool: &SealedPool) {
+        let Some(deck_list) = self
+            .selected_saved_deck
+            .and_then(|index| self.saved_decks.get(index))
+            .cloned()
+        else {
+            self.draft_error = Some("Build and select a deck from your draft pool.".to_string());
+            return;
+        };
+
+        match pool.validate(&deck_list) {
+            Ok(()) => {
+                self.draft_error = None;
+                self.client
+                    .send(ClientMessage::QueueDraftMatch {
+                        player_id: self.player_id.expect("player id should be set"),
+                        pod_id,
+                        deck: deck_list,
+                    })
+                    .ok();
+                self.show_draft = false;
+                self.looking_for_match = true;
+            }
+            Err(msg) => {
+                self.draft_error = Some(msg);
+            }
+        }
+    }
+
     fn foil_cards_in_deck(&self, deck: &DeckList) -> u32 {
         deck.sites
             .iter()
@@ -706,15 +1204,18 @@ impl Menu {
                                     );
                                     ui.add_space(16.0);
                                     ui.label(
-                                        egui::RichText::new(format!("{BETA_BOOSTER_COST} points"))
-                                            .color(MENU_GOLD)
-                                            .size(17.0)
-                                            .strong(),
+                                        egui::RichText::new(format!(
+                                            "{} points",
+                                            self.beta_booster_cost
+                                        ))
+                                        .color(MENU_GOLD)
+                                        .size(17.0)
+                                        .strong(),
                                     );
                                     ui.add_space(8.0);
 
                                     let progress = (self.reward_points as f32
-                                        / BETA_BOOSTER_COST as f32)
+                                        / self.beta_booster_cost as f32)
                                         .min(1.0);
                                     let progress_width = ui.available_width().min(360.0);
                                     let (progress_rect, _) = ui.allocate_exact_size(
@@ -743,19 +1244,19 @@ impl Menu {
                                         progress_rect.center(),
                                         egui::Align2::CENTER_CENTER,
                                         format!(
-                                            "{} / {BETA_BOOSTER_COST} points",
-                                            self.reward_points
+                                            "{} / {} points",
+                                            self.reward_points, self.beta_booster_cost
                                         ),
                                         egui::FontId::proportional(14.0),
                                         MENU_TEXT,
                                     );
                                     ui.add_space(10.0);
 
-                                    if self.reward_points < BETA_BOOSTER_COST {
+                                    if self.reward_points < self.beta_booster_cost {
                                         ui.label(
                                             egui::RichText::new(format!(
                                                 "{} more points to claim this pack",
-                                                BETA_BOOSTER_COST - self.reward_points
+                                                self.beta_booster_cost - self.reward_points
                                             ))
                                             .color(MENU_TEXT_MUTED)
                                             .size(14.0),
@@ -771,7 +1272,7 @@ impl Menu {
                                     ui.add_space(12.0);
 
                                     let claim = ui.add_enabled(
-                                        self.reward_points >= BETA_BOOSTER_COST
+                                        self.reward_points >= self.beta_booster_cost
                                             && !self.reward_redemption_requested,
                                         egui::Button::new(
                                             egui::RichText::new(
@@ -926,35 +1427,134 @@ impl Menu {
                                     self.available_decks.clone(),
                                     self.saved_decks.clone(),
                                     self.collection.clone(),
+                                    self.limited.clone(),
                                 ),
                             ));
                         }
-                        if !self.unopened_booster_packs.is_empty() {
-                            ui.add_space(18.0);
-                            let packs = ui.add(
-                                egui::Label::new(
-                                    egui::RichText::new(format!(
-                                        "✦ {} unopened pack{}",
-                                        self.unopened_booster_packs.len(),
-                                        if self.unopened_booster_packs.len() == 1 {
-                                            ""
-                                        } else {
-                                            "s"
-                                        }
-                                    ))
-                                    .size(14.0)
-                                    .color(MENU_GOLD),
-                                )
-                                .sense(egui::Sense::click()),
-                            );
-                            if packs.clicked() {
-                                self.show_packs = true;
-                            }
-                        }
-                    });
-                });
-                ui.add_space(10.0);
-
+                        ui.add_space(18.0);
+                        let new_pool = ui.add(
+                            egui::Label::new(
+                                egui::RichText::new("New Sealed Pool")
+                                    .size(15.0)
+                                    .color(Color32::from_rgb(142, 203, 240)),
+                            )
+                            .sense(egui::Sense::click()),
+                        );
+                        if new_pool.clicked() {
+                            self.client.send(ClientMessage::StartSealedEvent).ok();
+                        }
+                        ui.add_space(18.0);
+                        let draft = ui.add(
+                            egui::Label::new(
+                                egui::RichText::new("Draft")
+                                    .size(15.0)
+                                    .color(Color32::from_rgb(142, 203, 240)),
+                            )
+                            .sense(egui::Sense::click()),
+                        );
+                        if draft.clicked() {
+                            self.show_draft = true;
+                        }
+                        ui.add_space(18.0);
+                        let awaiting = self
+                            .trades
+                            .iter()
+                            .filter(|trade| trade.partner_confirmed && !trade.you_confirmed)
+                            .count();
+                        let trades = ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(if awaiting == 0 {
+                                    "Trades".to_string()
+                                } else {
+                                    format!("Trades ({awaiting})")
+                                })
+                                .size(15.0)
+                                .color(Color32::from_rgb(142, 203, 240)),
+                            )
+                            .sense(egui::Sense::click()),
+                        );
+                        if trades.clicked() {
+                            self.show_trades = true;
+                            self.trade_feedback = None;
+                            self.client.send(ClientMessage::LoadTrades).ok();
+                        }
+                        ui.add_space(18.0);
+                        let quests = ui.add(
+                            egui::Label::new(
+                                egui::RichText::new("Quests")
+                                    .size(15.0)
+                                    .color(Color32::from_rgb(142, 203, 240)),
+                            )
+                            .sense(egui::Sense::click()),
+                        );
+                        if quests.clicked() {
+                            self.show_quests = true;
+                            self.client.send(ClientMessage::LoadQuests).ok();
+                        }
+                        ui.add_space(18.0);
+                        let account = ui.add(
+                            egui::Label::new(
+                                egui::RichText::new("Account")
+                                    .size(15.0)
+                                    .color(Color32::from_rgb(142, 203, 240)),
+                            )
+                            .sense(egui::Sense::click()),
+                        );
+                        if account.clicked() {
+                            self.show_account = true;
+                            self.account = AccountForm::default();
+                        }
+                        if let Some(pool) = &self.limited.sealed_pool {
+                            ui.add_space(18.0);
+                            let sealed = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new("Sealed Builder")
+                                        .size(15.0)
+                                        .color(Color32::from_rgb(142, 203, 240)),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+                            if sealed.clicked() {
+                                *next_scene = Some(Scene::DeckBuilder(
+                                    crate::scene::deck_builder::DeckBuilder::from_limited_pool(
+                                        self.client.clone(),
+                                        self.player_id,
+                                        self.player_name.clone(),
+                                        self.available_decks.clone(),
+                                        self.saved_decks.clone(),
+                                        self.collection.clone(),
+                                        self.limited.clone(),
+                                        pool.clone(),
+                                    ),
+                                ));
+                            }
+                        }
+                        if !self.unopened_booster_packs.is_empty() {
+                            ui.add_space(18.0);
+                            let packs = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!(
+                                        "✦ {} unopened pack{}",
+                                        self.unopened_booster_packs.len(),
+                                        if self.unopened_booster_packs.len() == 1 {
+                                            ""
+                                        } else {
+                                            "s"
+                                        }
+                                    ))
+                                    .size(14.0)
+                                    .color(MENU_GOLD),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+                            if packs.clicked() {
+                                self.show_packs = true;
+                            }
+                        }
+                    });
+                });
+                ui.add_space(10.0);
+
                 let saved = self.saved_decks.clone();
                 if saved.is_empty() {
                     egui::Frame::new()
@@ -1016,10 +1616,61 @@ impl Menu {
                         .min_size(vec2(250.0, theme::BUTTON_HEIGHT)),
                     );
                     if play.clicked()
-                        && let Some(deck) = selected_deck
+                        && let Some(deck) = selected_deck.clone()
                     {
                         self.play_custom_deck(deck);
                     }
+                    ui.add_space(8.0);
+                    let goldfish = ui
+                        .add_enabled(
+                            selected_deck.is_some(),
+                            egui::Button::new(
+                                egui::RichText::new("🐟 Goldfish")
+                                    .size(16.0)
+                                    .color(Color32::WHITE),
+                            )
+                            .min_size(vec2(140.0, theme::BUTTON_HEIGHT)),
+                        )
+                        .on_hover_text("Try the deck alone against a dummy avatar");
+                    if goldfish.clicked()
+                        && let Some(deck) = selected_deck.clone()
+                    {
+                        self.goldfish_deck(deck);
+                    }
+                    ui.add_space(8.0);
+                    let sandbox = ui
+                        .add_enabled(
+                            selected_deck.is_some(),
+                            egui::Button::new(
+                                egui::RichText::new("🧪 Sandbox")
+                                    .size(16.0)
+                                    .color(Color32::WHITE),
+                            )
+                            .min_size(vec2(140.0, theme::BUTTON_HEIGHT)),
+                        )
+                        .on_hover_text("Play both seats and edit the board freely");
+                    if sandbox.clicked()
+                        && let Some(deck) = selected_deck.clone()
+                    {
+                        self.sandbox_deck(deck);
+                    }
+                    if self.limited.sealed_pool.is_some() {
+                        ui.add_space(8.0);
+                        let play_sealed = ui.add_enabled(
+                            selected_deck.is_some(),
+                            egui::Button::new(
+                                egui::RichText::new("▶ Play sealed")
+                                    .size(16.0)
+                                    .color(Color32::WHITE),
+                            )
+                            .min_size(vec2(160.0, theme::BUTTON_HEIGHT)),
+                        );
+                        if play_sealed.clicked()
+                            && let Some(deck) = selected_deck
+                        {
+                            self.play_sealed_deck(deck);
+                        }
+                    }
                 });
             });
     }
@@ -1106,6 +1757,7 @@ impl Menu {
                                 self.available_decks.clone(),
                                 self.saved_decks.clone(),
                                 self.collection.clone(),
+                                self.limited.clone(),
                                 deck_list,
                             ),
                         ));
@@ -1427,151 +2079,1196 @@ impl Menu {
         });
     }
 
-    fn card_preview_data(name: &str) -> CardData {
-        let card = from_name(name, &uuid::Uuid::nil());
-        let base = card.get_base();
-        CardData {
-            id: uuid::Uuid::nil(),
-            name: card.get_name().to_string(),
-            owner_id: PlayerId::nil(),
-            controller_id: PlayerId::nil(),
-            zone_sequence: 0,
-            tapped: false,
-            edition: base.edition.clone(),
-            zone: base.zone.clone(),
-            region: Region::Surface,
-            card_type: card.get_card_type(),
-            abilities: vec![],
-            statuses: vec![],
-            damage_taken: 0,
-            bearer: None,
-            rarity: base.rarity.clone(),
-            power: card
-                .get_unit_base()
-                .map(|unit| unit.power)
-                .unwrap_or_default(),
-            has_attachments: false,
-            image_path: card.get_image_path(),
-            is_token: base.is_token,
-        }
-    }
+    fn render_draft(&mut self, ui: &mut Ui, next_scene: &mut Option<Scene>) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.0);
+            ui.label(
+                egui::RichText::new("Booster Draft")
+                    .color(MENU_GOLD)
+                    .font(theme::display_bold_font(38.0)),
+            );
+            ui.add_space(4.0);
 
-    pub fn process_message(&mut self, msg: &ServerMessage) -> Option<Scene> {
-        match msg {
-            ServerMessage::ConnectResponse {
-                player_id,
-                available_decks,
-            } => {
-                self.available_decks = available_decks.clone();
-                self.player_id = Some(*player_id);
-                self.connect_requested = false;
-                None
-            }
-            ServerMessage::AuthenticationSuccess {
-                player_id,
-                username,
-                available_decks,
-                saved_decks,
-                collection,
-                unopened_booster_packs,
-                reward_points,
-            } => {
-                self.available_decks = available_decks.clone();
-                self.saved_decks = saved_decks.clone();
-                self.collection = collection.clone();
-                self.player_id = Some(*player_id);
-                self.player_name = username.clone();
-                self.password.clear();
-                self.confirmation_code.clear();
-                self.auth_requested = false;
-                self.auth_error = None;
-                self.awaiting_email_confirmation = false;
-                self.unopened_booster_packs = unopened_booster_packs.clone();
-                self.reward_points = *reward_points;
-                self.booster_reward = (!unopened_booster_packs.is_empty()).then(|| {
-                    format!(
-                        "Weekly reward: {} unopened Beta booster packs.",
-                        unopened_booster_packs.len()
-                    )
-                });
-                self.selecting_starter_deck = false;
-                None
-            }
-            ServerMessage::AuthenticationFailure { message } => {
-                self.auth_requested = false;
-                self.auth_error = Some(message.clone());
-                None
-            }
-            ServerMessage::EmailConfirmationRequired {
-                email,
-                delivery_failed,
-            } => {
-                self.email = email.clone();
-                self.awaiting_email_confirmation = true;
-                self.auth_requested = false;
-                self.auth_error = delivery_failed.then(|| {
-                    "We could not send a code. Check your email details and try resending."
-                        .to_string()
-                });
-                None
-            }
-            ServerMessage::StarterDeckSelection {
-                username,
-                available_decks,
-            } => {
-                self.player_name = username.clone();
-                self.password.clear();
-                self.auth_requested = false;
-                self.selecting_starter_deck = true;
-                self.starter_decks = available_decks.clone();
-                None
-            }
-            ServerMessage::BoosterPackOpened { pack_id, pack } => {
-                self.unopened_booster_packs
-                    .retain(|unopened| unopened.id != *pack_id);
-                for booster_card in &pack.cards {
-                    if let Some(card) = self.collection.iter_mut().find(|card| {
-                        card.name == booster_card.name && card.is_foil == booster_card.is_foil
-                    }) {
-                        card.count = card.count.saturating_add(1);
-                    } else {
-                        self.collection.push(CollectedCard {
-                            name: booster_card.name.clone(),
-                            count: 1,
-                            is_foil: booster_card.is_foil,
-                        });
+            match self.limited.draft.clone() {
+                None => self.render_draft_signup(ui),
+                Some(draft) if draft.pool.is_some() => {
+                    self.render_draft_bracket(ui, &draft, next_scene)
+                }
+                Some(draft) if !draft.pack.is_empty() => self.render_draft_pack(ui, &draft),
+                Some(draft) if draft.round == 0 => {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Waiting for players · {}/{} seats taken",
+                            draft.players.len(),
+                            draft.seats
+                        ))
+                        .color(MENU_TEXT)
+                        .size(16.0),
+                    );
+                    ui.add_space(8.0);
+                    for name in &draft.players {
+                        ui.label(egui::RichText::new(name).color(MENU_TEXT_MUTED).size(14.0));
                     }
+                    ui.add_space(8.0);
+                    ui.label(
+                        egui::RichText::new("Empty seats are filled by bots shortly.")
+                            .color(MENU_TEXT_MUTED)
+                            .size(13.0),
+                    );
+                }
+                Some(draft) => {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Waiting for the next pack… {} cards drafted",
+                            draft.picks.len()
+                        ))
+                        .color(MENU_TEXT)
+                        .size(16.0),
+                    );
                 }
-                self.opened_booster_pack = Some(pack.clone());
-                None
             }
-            ServerMessage::BoosterRedeemed {
-                reward_points,
-                pack,
-            } => {
-                self.reward_points = *reward_points;
-                self.unopened_booster_packs.push(pack.clone());
-                self.reward_redemption_requested = false;
-                self.reward_feedback = Some("Beta Booster added to your packs.".to_string());
-                None
+
+            if let Some(err) = &self.draft_error {
+                ui.add_space(10.0);
+                ui.label(
+                    egui::RichText::new(format!("⚠ {err}"))
+                        .color(Color32::from_rgb(220, 80, 60))
+                        .size(14.0),
+                );
             }
-            ServerMessage::RewardRedemptionFailed { message } => {
-                self.reward_redemption_requested = false;
-                self.reward_feedback = Some(message.clone());
-                None
+            ui.add_space(18.0);
+            if ui.button("Back").clicked() {
+                self.show_draft = false;
             }
-            ServerMessage::GameStarted {
-                player1,
-                player2,
-                game_id,
-                cards,
-            } => {
-                let player_id = self.player_id?;
-                let opponent_id = if player1 == &player_id {
-                    *player2
-                } else {
-                    *player1
-                };
+        });
+    }
+
+    fn render_draft_signup(&mut self, ui: &mut Ui) {
+        ui.label(
+            egui::RichText::new(
+                "Open Beta boosters with other players, pick one card and pass the rest. \
+                 Empty seats are filled by bots.",
+            )
+            .color(MENU_TEXT_MUTED)
+            .size(15.0),
+        );
+        ui.add_space(16.0);
+        ui.add(
+            egui::Slider::new(
+                &mut self.draft_seats,
+                DRAFT_MIN_SEATS as u8..=DRAFT_MAX_SEATS as u8,
+            )
+            .text("seats"),
+        );
+        ui.add_space(12.0);
+        let join = ui.add(
+            egui::Button::new(
+                egui::RichText::new("Join a pod")
+                    .size(16.0)
+                    .color(Color32::WHITE),
+            )
+            .min_size(vec2(180.0, theme::BUTTON_HEIGHT)),
+        );
+        if join.clicked() {
+            self.draft_error = None;
+            self.client
+                .send(ClientMessage::JoinDraft {
+                    player_name: self.player_name.clone(),
+                    player_id: self.player_id.expect("player id should be set"),
+                    seats: self.draft_seats,
+                })
+                .ok();
+        }
+    }
+
+    fn render_draft_pack(&mut self, ui: &mut Ui, draft: &DraftProgress) {
+        let seconds_left = draft
+            .pick_deadline
+            .map(|deadline| {
+                deadline
+                    .saturating_duration_since(std::time::Instant::now())
+                    .as_secs()
+            })
+            .unwrap_or_default();
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_secs(1));
+        ui.label(
+            egui::RichText::new(format!(
+                "Pack {} · Pick {} · {seconds_left}s left · {} cards drafted",
+                draft.round,
+                draft.pick,
+                draft.picks.len()
+            ))
+            .color(MENU_TEXT)
+            .size(16.0),
+        );
+        ui.add_space(12.0);
+
+        let mut picked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("draft_pack")
+            .max_height(ui.available_height() - 80.0)
+            .show(ui, |ui| {
+                egui::Grid::new("draft_pack_grid")
+                    .num_columns(5)
+                    .spacing(vec2(12.0, 12.0))
+                    .show(ui, |ui| {
+                        for (index, card) in draft.pack.iter().enumerate() {
+                            let rect = ui
+                                .scope(|ui| Self::render_reward_card(ui, card, vec2(170.0, 230.0)))
+                                .response
+                                .rect;
+                            let response = ui.interact(
+                                rect,
+                                ui.id().with(("draft_pick", index)),
+                                egui::Sense::click(),
+                            );
+                            if response.clicked() {
+                                picked = Some(index);
+                            }
+                            if index % 5 == 4 {
+                                ui.end_row();
+                            }
+                        }
+                    });
+            });
+
+        if let Some(index) = picked {
+            self.client
+                .send(ClientMessage::PickDraftCard {
+                    player_id: self.player_id.expect("player id should be set"),
+                    pod_id: draft.pod_id,
+                    index,
+                })
+                .ok();
+            if let Some(draft) = &mut self.limited.draft {
+                draft.pack.clear();
+                draft.pick_deadline = None;
+            }
+        }
+    }
+
+    fn render_draft_bracket(
+        &mut self,
+        ui: &mut Ui,
+        draft: &DraftProgress,
+        next_scene: &mut Option<Scene>,
+    ) {
+        let Some(pool) = &draft.pool else {
+            return;
+        };
+        ui.label(
+            egui::RichText::new(format!("Drafting complete · {} cards", pool.cards.len()))
+                .color(MENU_TEXT)
+                .size(16.0),
+        );
+        ui.add_space(12.0);
+        if ui
+            .add(
+                egui::Button::new(egui::RichText::new("Build draft deck").size(16.0))
+                    .min_size(vec2(200.0, theme::BUTTON_HEIGHT)),
+            )
+            .clicked()
+        {
+            *next_scene = Some(Scene::DeckBuilder(
+                crate::scene::deck_builder::DeckBuilder::from_limited_pool(
+                    self.client.clone(),
+                    self.player_id,
+                    self.player_name.clone(),
+                    self.available_decks.clone(),
+                    self.saved_decks.clone(),
+                    self.collection.clone(),
+                    self.limited.clone(),
+                    pool.clone(),
+                ),
+            ));
+        }
+
+        let Some(bracket) = &draft.bracket else {
+            return;
+        };
+        ui.add_space(18.0);
+        if let Some(champion) = &bracket.champion {
+            ui.label(
+                egui::RichText::new(format!("🏆 {champion} wins the pod!"))
+                    .color(MENU_GOLD)
+                    .size(20.0)
+                    .strong(),
+            );
+        } else {
+            ui.label(
+                egui::RichText::new(format!("Bracket · round {}", bracket.round))
+                    .color(MENU_TEXT)
+                    .size(18.0)
+                    .strong(),
+            );
+            for (player1, player2) in &bracket.matches {
+                ui.label(
+                    egui::RichText::new(format!("{player1} vs {player2}"))
+                        .color(MENU_TEXT_MUTED)
+                        .size(14.0),
+                );
+            }
+            if let Some(bye) = &bracket.bye {
+                ui.label(
+                    egui::RichText::new(format!("{bye} advances with a bye"))
+                        .color(MENU_TEXT_MUTED)
+                        .size(14.0),
+                );
+            }
+            ui.add_space(12.0);
+            let deck_name = self
+                .selected_saved_deck
+                .and_then(|index| self.saved_decks.get(index))
+                .map(|deck| deck.name.clone());
+            let play_label = deck_name
+                .map(|name| format!("▶ Play bracket match with {name}"))
+                .unwrap_or_else(|| "▶ Play bracket match".to_string());
+            if ui
+                .add(
+                    egui::Button::new(
+                        egui::RichText::new(play_label)
+                            .size(16.0)
+                            .color(Color32::WHITE),
+                    )
+                    .min_size(vec2(250.0, theme::BUTTON_HEIGHT)),
+                )
+                .clicked()
+            {
+                self.play_draft_match(draft.pod_id, pool);
+            }
+        }
+
+        ui.add_space(12.0);
+        if ui.button("Leave draft").clicked() {
+            self.client
+                .send(ClientMessage::LeaveDraft {
+                    player_id: self.player_id.expect("player id should be set"),
+                })
+                .ok();
+            self.limited.draft = None;
+        }
+    }
+
+    fn render_trades(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.0);
+            ui.label(
+                egui::RichText::new("Trades")
+                    .color(MENU_GOLD)
+                    .font(theme::display_bold_font(38.0)),
+            );
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new("Cards change hands once both players confirm the same terms.")
+                    .color(MENU_TEXT_MUTED)
+                    .size(15.0),
+            );
+            ui.add_space(12.0);
+            if let Some(feedback) = &self.trade_feedback {
+                ui.label(egui::RichText::new(feedback).color(MENU_GOLD).size(14.0));
+                ui.add_space(8.0);
+            }
+            ui.checkbox(
+                &mut self.trade_unlock_decks,
+                "Allow trading cards used by my saved decks",
+            );
+            ui.add_space(12.0);
+
+            let content_w = ui.available_width().min(860.0);
+            egui::ScrollArea::vertical()
+                .id_salt("trades")
+                .max_height(ui.available_height() - 60.0)
+                .show(ui, |ui| {
+                    ui.set_width(content_w);
+                    self.render_trade_inbox(ui);
+                    ui.add_space(18.0);
+                    self.render_trade_composer(ui);
+                });
+
+            ui.add_space(18.0);
+            if ui.button("Back").clicked() {
+                self.show_trades = false;
+            }
+        });
+    }
+
+    fn render_quests(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.0);
+            ui.label(
+                egui::RichText::new("Quests")
+                    .color(MENU_GOLD)
+                    .font(theme::display_bold_font(38.0)),
+            );
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new("Finish games to make progress. Rewards are paid in reward points.")
+                    .color(MENU_TEXT_MUTED)
+                    .size(15.0),
+            );
+            ui.add_space(16.0);
+
+            let content_w = ui.available_width().min(620.0);
+            egui::ScrollArea::vertical()
+                .id_salt("quests")
+                .max_height(ui.available_height() - 60.0)
+                .show(ui, |ui| {
+                    ui.set_width(content_w);
+                    if self.quests.is_empty() {
+                        ui.label(
+                            egui::RichText::new("Loading quests…")
+                                .color(MENU_TEXT_MUTED)
+                                .size(14.0),
+                        );
+                    }
+                    for cadence in [QuestCadence::Daily, QuestCadence::Weekly] {
+                        let quests: Vec<_> = self
+                            .quests
+                            .iter()
+                            .filter(|quest| quest.cadence == cadence)
+                            .collect();
+                        let Some(first) = quests.first() else {
+                            continue;
+                        };
+                        let heading = match cadence {
+                            QuestCadence::Daily => "Daily",
+                            QuestCadence::Weekly => "Weekly",
+                        };
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{heading} · ends {}",
+                                first.ends_on.format("%b %-d")
+                            ))
+                            .color(MENU_TEXT)
+                            .size(17.0)
+                            .strong(),
+                        );
+                        ui.add_space(6.0);
+                        for quest in quests {
+                            egui::Frame::new()
+                                .fill(theme::PANEL_BG)
+                                .stroke(egui::Stroke::new(1.0, MENU_BORDER))
+                                .corner_radius(6.0)
+                                .inner_margin(egui::Margin::same(12))
+                                .show(ui, |ui| {
+                                    ui.set_width(ui.available_width());
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            egui::RichText::new(&quest.description)
+                                                .color(MENU_TEXT)
+                                                .size(15.0),
+                                        );
+                                        ui.with_layout(
+                                            egui::Layout::right_to_left(egui::Align::Center),
+                                            |ui| {
+                                                ui.label(
+                                                    egui::RichText::new(format!(
+                                                        "✦ {}",
+                                                        quest.reward_points
+                                                    ))
+                                                    .color(MENU_GOLD)
+                                                    .size(14.0),
+                                                );
+                                            },
+                                        );
+                                    });
+                                    ui.add_space(4.0);
+                                    let (text, fraction) = if quest.completed {
+                                        ("Complete".to_string(), 1.0)
+                                    } else {
+                                        (
+                                            format!("{}/{}", quest.progress, quest.target),
+                                            quest.progress as f32 / quest.target.max(1) as f32,
+                                        )
+                                    };
+                                    ui.add(egui::ProgressBar::new(fraction).text(text));
+                                });
+                            ui.add_space(6.0);
+                        }
+                        ui.add_space(12.0);
+                    }
+                });
+
+            ui.add_space(18.0);
+            if ui.button("Back").clicked() {
+                self.show_quests = false;
+            }
+        });
+    }
+
+    fn render_account(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.0);
+            ui.label(
+                egui::RichText::new("Account")
+                    .color(MENU_GOLD)
+                    .font(theme::display_bold_font(38.0)),
+            );
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new(format!("Signed in as {}", self.player_name))
+                    .color(MENU_TEXT_MUTED)
+                    .size(15.0),
+            );
+            ui.add_space(12.0);
+            if let Some(feedback) = &self.account.feedback {
+                ui.label(egui::RichText::new(feedback).color(MENU_GOLD).size(14.0));
+                ui.add_space(8.0);
+            }
+            if let Some(error) = &self.account.error {
+                ui.label(
+                    egui::RichText::new(error)
+                        .color(Color32::from_rgb(255, 195, 192))
+                        .size(14.0),
+                );
+                ui.add_space(8.0);
+            }
+
+            egui::ScrollArea::vertical()
+                .id_salt("account")
+                .max_height(ui.available_height() - 60.0)
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width().min(400.0));
+                    let enabled = !self.account.request_pending;
+
+                    if let Some(message) = Self::render_account_section(ui, "Username", |ui| {
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.username,
+                            "New username",
+                            false,
+                        );
+                        ui.add_space(10.0);
+                        ui.add_enabled(
+                            enabled && !self.account.username.trim().is_empty(),
+                            egui::Button::new("Change username"),
+                        )
+                        .clicked()
+                        .then(|| ClientMessage::ChangeUsername {
+                            username: self.account.username.trim().to_string(),
+                        })
+                    }) {
+                        self.send_account_request(message);
+                    }
+
+                    if let Some(message) = Self::render_account_section(ui, "Password", |ui| {
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.current_password,
+                            "Current password",
+                            true,
+                        );
+                        ui.add_space(8.0);
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.new_password,
+                            "New password",
+                            true,
+                        );
+                        ui.add_space(10.0);
+                        ui.add_enabled(
+                            enabled
+                                && !self.account.current_password.is_empty()
+                                && !self.account.new_password.is_empty(),
+                            egui::Button::new("Change password"),
+                        )
+                        .clicked()
+                        .then(|| ClientMessage::ChangePassword {
+                            current_password: self.account.current_password.clone(),
+                            new_password: self.account.new_password.clone(),
+                        })
+                    }) {
+                        self.send_account_request(message);
+                    }
+
+                    if let Some(message) = Self::render_account_section(ui, "Email address", |ui| {
+                        ui.label(
+                            egui::RichText::new(
+                                "You'll need to confirm the new address the next time you log in.",
+                            )
+                            .color(MENU_TEXT_MUTED)
+                            .size(13.0),
+                        );
+                        ui.add_space(8.0);
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.new_email,
+                            "new@example.com",
+                            false,
+                        );
+                        ui.add_space(8.0);
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.current_password,
+                            "Current password",
+                            true,
+                        );
+                        ui.add_space(10.0);
+                        ui.add_enabled(
+                            enabled
+                                && !self.account.new_email.trim().is_empty()
+                                && !self.account.current_password.is_empty(),
+                            egui::Button::new("Change email"),
+                        )
+                        .clicked()
+                        .then(|| ClientMessage::ChangeEmail {
+                            password: self.account.current_password.clone(),
+                            new_email: self.account.new_email.trim().to_string(),
+                        })
+                    }) {
+                        self.send_account_request(message);
+                    }
+
+                    if let Some(message) = Self::render_account_section(ui, "Blocked players", |ui| {
+                        ui.label(
+                            egui::RichText::new(
+                                "You won't be matched with players you block, or with players who block you.",
+                            )
+                            .color(MENU_TEXT_MUTED)
+                            .size(13.0),
+                        );
+                        ui.add_space(8.0);
+                        let mut request = None;
+                        for username in &self.blocked_players {
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(username).color(MENU_TEXT).size(14.0));
+                                if ui
+                                    .add_enabled(enabled, egui::Button::new("Unblock"))
+                                    .clicked()
+                                {
+                                    request = Some(ClientMessage::UnblockPlayer {
+                                        username: username.clone(),
+                                    });
+                                }
+                            });
+                        }
+                        ui.add_space(8.0);
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.block_username,
+                            "Username",
+                            false,
+                        );
+                        ui.add_space(10.0);
+                        if ui
+                            .add_enabled(
+                                enabled && !self.account.block_username.trim().is_empty(),
+                                egui::Button::new("Block player"),
+                            )
+                            .clicked()
+                        {
+                            request = Some(ClientMessage::BlockPlayer {
+                                username: self.account.block_username.trim().to_string(),
+                            });
+                        }
+                        request
+                    }) {
+                        self.send_account_request(message);
+                    }
+
+                    if let Some(message) = Self::render_account_section(ui, "Delete account", |ui| {
+                        ui.label(
+                            egui::RichText::new(
+                                "This permanently removes your collection, decks, trades and rewards.",
+                            )
+                            .color(MENU_TEXT_MUTED)
+                            .size(13.0),
+                        );
+                        ui.add_space(8.0);
+                        Self::render_auth_input(
+                            ui,
+                            &mut self.account.delete_password,
+                            "Current password",
+                            true,
+                        );
+                        ui.add_space(8.0);
+                        ui.checkbox(
+                            &mut self.account.confirm_delete,
+                            "I understand this cannot be undone",
+                        );
+                        ui.add_space(10.0);
+                        ui.add_enabled(
+                            enabled
+                                && self.account.confirm_delete
+                                && !self.account.delete_password.is_empty(),
+                            egui::Button::new(
+                                egui::RichText::new("Delete account")
+                                    .color(Color32::from_rgb(255, 195, 192)),
+                            ),
+                        )
+                        .clicked()
+                        .then(|| ClientMessage::DeleteAccount {
+                            password: self.account.delete_password.clone(),
+                        })
+                    }) {
+                        self.send_account_request(message);
+                    }
+                });
+
+            ui.add_space(18.0);
+            if ui.button("Back").clicked() {
+                self.show_account = false;
+            }
+        });
+    }
+
+    /// A titled panel on the account screen. Returns whatever request its contents produced.
+    fn render_account_section(
+        ui: &mut Ui,
+        title: &str,
+        add_contents: impl FnOnce(&mut Ui) -> Option<ClientMessage>,
+    ) -> Option<ClientMessage> {
+        let request = egui::Frame::new()
+            .fill(theme::PANEL_BG)
+            .stroke(egui::Stroke::new(1.0, MENU_BORDER))
+            .corner_radius(6.0)
+            .inner_margin(egui::Margin::same(14))
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(
+                    egui::RichText::new(title)
+                        .color(MENU_TEXT)
+                        .size(16.0)
+                        .strong(),
+                );
+                ui.add_space(8.0);
+                add_contents(ui)
+            })
+            .inner;
+        ui.add_space(12.0);
+        request
+    }
+
+    fn send_account_request(&mut self, message: ClientMessage) {
+        self.account.feedback = None;
+        if self.client.send(message).is_ok() {
+            self.account.request_pending = true;
+            self.account.error = None;
+        } else {
+            self.account.error = Some(
+                "Unable to reach the server. Check your connection and try again.".to_string(),
+            );
+        }
+    }
+
+    fn render_trade_inbox(&mut self, ui: &mut Ui) {
+        if self.trades.is_empty() {
+            ui.label(
+                egui::RichText::new("No open trades.")
+                    .color(MENU_TEXT_MUTED)
+                    .size(14.0),
+            );
+            return;
+        }
+
+        for trade in self.trades.clone() {
+            egui::Frame::new()
+                .fill(theme::PANEL_BG)
+                .stroke(egui::Stroke::new(1.0, MENU_BORDER))
+                .corner_radius(6.0)
+                .inner_margin(egui::Margin::same(12))
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    let heading = if trade.incoming {
+                        format!("Offer from {}", trade.partner)
+                    } else {
+                        format!("Offer to {}", trade.partner)
+                    };
+                    ui.label(
+                        egui::RichText::new(heading)
+                            .color(MENU_TEXT)
+                            .size(16.0)
+                            .strong(),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "You give: {}",
+                            describe_trade_side(&trade.yours)
+                        ))
+                        .color(MENU_TEXT_MUTED)
+                        .size(14.0),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "You get: {}",
+                            describe_trade_side(&trade.theirs)
+                        ))
+                        .color(MENU_TEXT_MUTED)
+                        .size(14.0),
+                    );
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        if trade.you_confirmed {
+                            ui.label(
+                                egui::RichText::new(format!("Waiting for {}", trade.partner))
+                                    .color(MENU_TEXT_MUTED)
+                                    .size(14.0),
+                            );
+                        } else if ui.button("Confirm").clicked() {
+                            self.trade_feedback = None;
+                            self.client
+                                .send(ClientMessage::ConfirmTrade {
+                                    trade_id: trade.id,
+                                    unlock_decks: self.trade_unlock_decks,
+                                })
+                                .ok();
+                        }
+                        if ui.button("Counter").clicked() {
+                            self.trade_composer = TradeComposer::counter(&trade);
+                        }
+                        let decline = if trade.incoming {
+                            "Decline"
+                        } else {
+                            "Withdraw"
+                        };
+                        if ui.button(decline).clicked() {
+                            self.trade_feedback = None;
+                            self.client
+                                .send(ClientMessage::DeclineTrade { trade_id: trade.id })
+                                .ok();
+                        }
+                    });
+                });
+            ui.add_space(8.0);
+        }
+    }
+
+    fn render_trade_composer(&mut self, ui: &mut Ui) {
+        let composer = &mut self.trade_composer;
+        ui.label(
+            egui::RichText::new(if composer.trade_id.is_some() {
+                format!("Counter-offer to {}", composer.recipient)
+            } else {
+                "New offer".to_string()
+            })
+            .color(MENU_TEXT)
+            .size(18.0)
+            .strong(),
+        );
+        ui.add_space(6.0);
+        if composer.trade_id.is_none() {
+            ui.horizontal(|ui| {
+                ui.label("Player");
+                ui.add(
+                    egui::TextEdit::singleline(&mut composer.recipient)
+                        .hint_text("username")
+                        .desired_width(220.0),
+                );
+            });
+        }
+
+        ui.add_space(8.0);
+        ui.label(egui::RichText::new("You give").color(MENU_TEXT).size(15.0));
+        ui.horizontal(|ui| {
+            let selected = composer
+                .offer_card
+                .and_then(|index| self.collection.get(index))
+                .map(|card| format!("{}{}", card.name, if card.is_foil { " (foil)" } else { "" }))
+                .unwrap_or_else(|| "Choose a card".to_string());
+            egui::ComboBox::from_id_salt("trade_offer_card")
+                .selected_text(selected)
+                .width(260.0)
+                .show_ui(ui, |ui| {
+                    for (index, card) in self.collection.iter().enumerate() {
+                        let label = format!(
+                            "{}{} ×{}",
+                            card.name,
+                            if card.is_foil { " (foil)" } else { "" },
+                            card.count
+                        );
+                        ui.selectable_value(&mut composer.offer_card, Some(index), label);
+                    }
+                });
+            if ui.button("Add").clicked()
+                && let Some(card) = composer
+                    .offer_card
+                    .and_then(|index| self.collection.get(index))
+            {
+                add_trade_card(&mut composer.offered, &card.name, card.is_foil);
+            }
+            ui.add(
+                egui::DragValue::new(&mut composer.offered.reward_points)
+                    .range(0..=self.reward_points)
+                    .suffix(" points"),
+            );
+        });
+        Self::render_trade_side_cards(ui, &mut composer.offered, "trade_offered");
+
+        ui.add_space(8.0);
+        ui.label(
+            egui::RichText::new("You ask for")
+                .color(MENU_TEXT)
+                .size(15.0),
+        );
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut composer.request_name)
+                    .hint_text("card name")
+                    .desired_width(220.0),
+            );
+            ui.checkbox(&mut composer.request_foil, "Foil");
+            if ui.button("Add").clicked() && !composer.request_name.trim().is_empty() {
+                add_trade_card(
+                    &mut composer.requested,
+                    composer.request_name.trim(),
+                    composer.request_foil,
+                );
+                composer.request_name.clear();
+            }
+            ui.add(
+                egui::DragValue::new(&mut composer.requested.reward_points)
+                    .range(0..=10_000)
+                    .suffix(" points"),
+            );
+        });
+        Self::render_trade_side_cards(ui, &mut composer.requested, "trade_requested");
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            let send_label = if composer.trade_id.is_some() {
+                "Send counter-offer"
+            } else {
+                "Send offer"
+            };
+            if ui.button(send_label).clicked() {
+                let message = match composer.trade_id {
+                    Some(trade_id) => ClientMessage::CounterTrade {
+                        trade_id,
+                        offered: composer.offered.clone(),
+                        requested: composer.requested.clone(),
+                        unlock_decks: self.trade_unlock_decks,
+                    },
+                    None => ClientMessage::ProposeTrade {
+                        recipient: composer.recipient.trim().to_string(),
+                        offered: composer.offered.clone(),
+                        requested: composer.requested.clone(),
+                        unlock_decks: self.trade_unlock_decks,
+                    },
+                };
+                self.trade_feedback = None;
+                if self.client.send(message).is_ok() {
+                    *composer = TradeComposer::default();
+                }
+            }
+            if ui.button("Clear").clicked() {
+                *composer = TradeComposer::default();
+            }
+        });
+    }
+
+    fn render_trade_side_cards(ui: &mut Ui, side: &mut TradeSide, id_salt: &str) {
+        let mut removed = None;
+        for (index, card) in side.cards.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.push_id((id_salt, index), |ui| {
+                    ui.add(egui::DragValue::new(&mut card.count).range(1..=u8::MAX));
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{}{}",
+                            card.name,
+                            if card.is_foil { " (foil)" } else { "" }
+                        ))
+                        .color(MENU_TEXT_MUTED),
+                    );
+                    if ui.small_button("✕").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            });
+        }
+        if let Some(index) = removed {
+            side.cards.remove(index);
+        }
+    }
+
+    fn card_preview_data(name: &str) -> CardData {
+        let card = from_name(name, &uuid::Uuid::nil());
+        let base = card.get_base();
+        CardData {
+            id: uuid::Uuid::nil(),
+            name: card.get_name().to_string(),
+            owner_id: PlayerId::nil(),
+            controller_id: PlayerId::nil(),
+            zone_sequence: 0,
+            tapped: false,
+            edition: base.edition.clone(),
+            zone: base.zone.clone(),
+            region: Region::Surface,
+            card_type: card.get_card_type(),
+            abilities: vec![],
+            statuses: vec![],
+            damage_taken: 0,
+            bearer: None,
+            rarity: base.rarity.clone(),
+            power: card
+                .get_unit_base()
+                .map(|unit| unit.power)
+                .unwrap_or_default(),
+            has_attachments: false,
+            image_path: card.get_image_path(),
+            is_token: base.is_token,
+        }
+    }
+
+    pub fn process_message(&mut self, msg: &ServerMessage) -> Option<Scene> {
+        match msg {
+            ServerMessage::ConnectResponse {
+                player_id,
+                available_decks,
+            } => {
+                self.available_decks = available_decks.clone();
+                self.player_id = Some(*player_id);
+                self.connect_requested = false;
+                None
+            }
+            ServerMessage::AuthenticationSuccess {
+                player_id,
+                username,
+                available_decks,
+                saved_decks,
+                collection,
+                unopened_booster_packs,
+                reward_points,
+                beta_booster_cost,
+                sealed_pool,
+            } => {
+                self.available_decks = available_decks.clone();
+                self.saved_decks = saved_decks.clone();
+                self.collection = collection.clone();
+                self.player_id = Some(*player_id);
+                self.player_name = username.clone();
+                self.password.clear();
+                self.confirmation_code.clear();
+                self.auth_requested = false;
+                self.auth_error = None;
+                self.awaiting_email_confirmation = false;
+                self.resetting_password = false;
+                self.password_reset_requested = false;
+                self.new_password.clear();
+                self.unopened_booster_packs = unopened_booster_packs.clone();
+                self.reward_points = *reward_points;
+                self.beta_booster_cost = *beta_booster_cost;
+                self.limited.sealed_pool = sealed_pool.clone();
+                self.booster_reward = (!unopened_booster_packs.is_empty()).then(|| {
+                    format!(
+                        "Weekly reward: {} unopened Beta booster packs.",
+                        unopened_booster_packs.len()
+                    )
+                });
+                self.selecting_starter_deck = false;
+                None
+            }
+            ServerMessage::AuthenticationFailure { message } => {
+                self.auth_requested = false;
+                self.auth_error = Some(message.clone());
+                None
+            }
+            ServerMessage::EmailConfirmationRequired {
+                email,
+                delivery_failed,
+            } => {
+                self.email = email.clone();
+                self.awaiting_email_confirmation = true;
+                self.auth_requested = false;
+                self.auth_error = delivery_failed.then(|| {
+                    "We could not send a code. Check your email details and try resending."
+                        .to_string()
+                });
+                None
+            }
+            ServerMessage::PasswordResetRequested { email } => {
+                self.email = email.clone();
+                self.password_reset_requested = true;
+                self.auth_requested = false;
+                self.auth_error = None;
+                None
+            }
+            ServerMessage::AccountUpdated { username, message } => {
+                if let Some(username) = username {
+                    self.player_name = username.clone();
+                }
+                self.account = AccountForm {
+                    feedback: Some(message.clone()),
+                    ..AccountForm::default()
+                };
+                None
+            }
+            ServerMessage::AccountUpdateFailed { message } => {
+                self.account.request_pending = false;
+                self.account.feedback = None;
+                self.account.error = Some(message.clone());
+                None
+            }
+            ServerMessage::AccountDeleted => Some(Scene::Menu(Menu::new(self.client.clone()))),
+            ServerMessage::StarterDeckSelection {
+                username,
+                available_decks,
+            } => {
+                self.player_name = username.clone();
+                self.password.clear();
+                self.auth_requested = false;
+                self.selecting_starter_deck = true;
+                self.starter_decks = available_decks.clone();
+                None
+            }
+            ServerMessage::BoosterPackOpened { pack_id, pack } => {
+                self.unopened_booster_packs
+                    .retain(|unopened| unopened.id != *pack_id);
+                for booster_card in &pack.cards {
+                    if let Some(card) = self.collection.iter_mut().find(|card| {
+                        card.name == booster_card.name && card.is_foil == booster_card.is_foil
+                    }) {
+                        card.count = card.count.saturating_add(1);
+                    } else {
+                        self.collection.push(CollectedCard {
+                            name: booster_card.name.clone(),
+                            count: 1,
+                            is_foil: booster_card.is_foil,
+                        });
+                    }
+                }
+                self.opened_booster_pack = Some(pack.clone());
+                None
+            }
+            ServerMessage::BoosterRedeemed {
+                reward_points,
+                pack,
+            } => {
+                self.reward_points = *reward_points;
+                self.unopened_booster_packs.push(pack.clone());
+                self.reward_redemption_requested = false;
+                self.reward_feedback = Some("Beta Booster added to your packs.".to_string());
+                None
+            }
+            ServerMessage::RewardRedemptionFailed { message } => {
+                self.reward_redemption_requested = false;
+                self.reward_feedback = Some(message.clone());
+                None
+            }
+            ServerMessage::SealedPoolOpened { pool } => {
+                self.limited.sealed_pool = Some(pool.clone());
+                self.deck_error = None;
+                None
+            }
+            ServerMessage::SealedDeckRejected { message } => {
+                self.looking_for_match = false;
+                self.deck_error = Some(message.clone());
+                None
+            }
+            ServerMessage::DraftLobby {
+                pod_id,
+                seats,
+                players,
+            } => {
+                let draft = self.draft_mut(*pod_id);
+                draft.seats = *seats;
+                draft.players = players.clone();
+                self.draft_error = None;
+                None
+            }
+            ServerMessage::DraftPack {
+                pod_id,
+                round,
+                pick,
+                pack,
+                seconds_left,
+            } => {
+                let draft = self.draft_mut(*pod_id);
+                draft.round = *round;
+                draft.pick = *pick;
+                draft.pack = pack.clone();
+                draft.pick_deadline =
+                    Some(std::time::Instant::now() + std::time::Duration::from_secs(*seconds_left));
+                None
+            }
+            ServerMessage::DraftPicked { pod_id, card } => {
+                let draft = self.draft_mut(*pod_id);
+                draft.picks.push(card.clone());
+                draft.pack.clear();
+                draft.pick_deadline = None;
+                None
+            }
+            ServerMessage::DraftComplete { pod_id, pool } => {
+                let draft = self.draft_mut(*pod_id);
+                draft.pool = Some(pool.clone());
+                draft.pack.clear();
+                draft.pick_deadline = None;
+                None
+            }
+            ServerMessage::DraftBracketUpdated {
+                pod_id,
+                round,
+                matches,
+                bye,
+                champion,
+            } => {
+                self.draft_mut(*pod_id).bracket = Some(DraftBracketView {
+                    round: *round,
+                    matches: matches.clone(),
+                    bye: bye.clone(),
+                    champion: champion.clone(),
+                });
+                None
+            }
+            ServerMessage::DraftRejected { message } => {
+                self.looking_for_match = false;
+                self.draft_error = Some(message.clone());
+                None
+            }
+            // A match that could not start hands both players back to the lobby.
+            ServerMessage::GameAborted { message, .. } => {
+                self.looking_for_match = false;
+                self.deck_error = Some(message.clone());
+                None
+            }
+            // The notice is shown over every scene; the server took us out of its queues.
+            ServerMessage::MaintenanceNotice { message: Some(_) } => {
+                self.looking_for_match = false;
+                None
+            }
+            ServerMessage::TradeOffers { trades } => {
+                self.trades = trades.clone();
+                None
+            }
+            ServerMessage::BlockedPlayers { usernames } => {
+                self.blocked_players = usernames.clone();
+                None
+            }
+            ServerMessage::TradeCompleted {
+                collection,
+                reward_points,
+            } => {
+                self.collection = collection.clone();
+                self.reward_points = *reward_points;
+                self.trade_feedback =
+                    Some("Trade complete. Your collection was updated.".to_string());
+                None
+            }
+            ServerMessage::TradeRejected { message } => {
+                self.trade_feedback = Some(message.clone());
+                None
+            }
+            ServerMessage::CraftingUpdated { collection, .. } => {
+                self.collection = collection.clone();
+                None
+            }
+            ServerMessage::Quests { quests } => {
+                self.quests = quests.clone();
+                None
+            }
+            ServerMessage::GameStarted {
+                player1,
+                player2,
+                game_id,
+                cards,
+            } => {
+                let player_id = self.player_id?;
+                let opponent_id = if player1 == &player_id {
+                    *player2
+                } else {
+                    *player1
+                };
 
                 let mut manager =
                     AudioManager::<DefaultBackend>::new(AudioManagerSettings::default()).ok()?;
@@ -1579,7 +3276,18 @@ impl Menu {
                     manager.play(sound_data).ok();
                 }
 
-                Some(Scene::Game(Game::new(
+                let mut return_menu = Menu::restore(
+                    self.client.clone(),
+                    self.player_id,
+                    self.player_name.clone(),
+                    self.available_decks.clone(),
+                    self.saved_decks.clone(),
+                    self.collection.clone(),
+                );
+                return_menu.set_limited_events(self.limited.clone());
+                return_menu.set_beta_booster_cost(self.beta_booster_cost);
+
+                let mut game = Game::new(
                     *game_id,
                     player_id,
                     opponent_id,
@@ -1587,16 +3295,12 @@ impl Menu {
                     cards.clone(),
                     self.client.clone(),
                     manager,
-                    Menu::restore(
-                        self.client.clone(),
-                        self.player_id,
-                        self.player_name.clone(),
-                        self.available_decks.clone(),
-                        self.saved_decks.clone(),
-                        self.collection.clone(),
-                    ),
+                    return_menu,
                     self.reward_points,
-                )))
+                );
+                game.set_goldfish(std::mem::take(&mut self.starting_goldfish));
+                game.set_sandbox(std::mem::take(&mut self.starting_sandbox).then_some(opponent_id));
+                Some(Scene::Game(game))
             }
             _ => None,
         }
@@ -1640,6 +3344,22 @@ impl Menu {
                     self.render_packs(ui);
                     return;
                 }
+                if self.show_draft {
+                    self.render_draft(ui, &mut next_scene);
+                    return;
+                }
+                if self.show_trades {
+                    self.render_trades(ui);
+                    return;
+                }
+                if self.show_quests {
+                    self.render_quests(ui);
+                    return;
+                }
+                if self.show_account {
+                    self.render_account(ui);
+                    return;
+                }
                 if self.show_rewards {
                     self.render_rewards_screen(ui);
                     return;
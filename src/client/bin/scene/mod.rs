@@ -37,7 +37,7 @@ impl Scene {
         match self {
             Scene::Menu(menu) => menu.process_message(message),
             Scene::Game(game) => game.process_message(message),
-            Scene::DeckBuilder(_) => None,
+            Scene::DeckBuilder(db) => db.process_message(message),
         }
     }
 }
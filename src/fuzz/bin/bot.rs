@@ -0,0 +1,414 @@
+use async_channel::{Receiver, Sender};
+use rand::{Rng, SeedableRng, distr::uniform::SampleUniform, rngs::StdRng, seq::IndexedRandom};
+use sorcerers::{
+    effect::Effect,
+    game::{CardId, PlayerId},
+    networking::message::{ClientMessage, ServerMessage},
+    state::State,
+    zone::Zone,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::RangeInclusive,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// Number of cards a bot clicks in a turn before it is forced to end it.
+const MAX_ACTIONS_PER_TURN: usize = 8;
+
+/// Number of resolved effects kept in a failure report.
+const RECENT_EFFECTS: usize = 30;
+
+/// Random legal-move bot shared by both players. It picks top-level actions for the player whose
+/// turn it is and answers every prompt the engine sends, recording each decision in a
+/// human-readable replay.
+pub struct Bot {
+    game_id: uuid::Uuid,
+    rng: StdRng,
+    /// Number of replay steps after which the bot stops deciding at random and only passes:
+    /// it ends every turn and takes the first option of every prompt.
+    decision_limit: Option<usize>,
+    card_names: HashMap<CardId, String>,
+    player_names: HashMap<PlayerId, String>,
+    actions_this_turn: usize,
+    pub replay: Vec<String>,
+    /// Names of the last effects taken off the queue, the newest last.
+    pub recent_effects: VecDeque<String>,
+    /// Set when the bot could not answer a prompt and aborted it.
+    pub abort_reason: Option<String>,
+}
+
+pub type SharedBot = Arc<Mutex<Bot>>;
+
+/// Lock the bot, ignoring poisoning so the replay survives a panic in the game task.
+pub fn lock(bot: &SharedBot) -> MutexGuard<'_, Bot> {
+    bot.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Bot {
+    pub fn new(game_id: uuid::Uuid, seed: u64, decision_limit: Option<usize>) -> Self {
+        Self {
+            game_id,
+            rng: StdRng::seed_from_u64(seed),
+            decision_limit,
+            card_names: HashMap::new(),
+            player_names: HashMap::new(),
+            actions_this_turn: 0,
+            replay: vec![],
+            recent_effects: VecDeque::new(),
+            abort_reason: None,
+        }
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Refresh the names used to describe cards and players in the replay.
+    pub fn observe(&mut self, state: &State) {
+        self.player_names = state
+            .players
+            .iter()
+            .map(|player| (player.id, player.name.clone()))
+            .collect();
+        for card in state.all_cards() {
+            self.card_names
+                .insert(*card.get_id(), card.get_name().to_string());
+        }
+    }
+
+    fn is_passing(&self) -> bool {
+        self.decision_limit
+            .is_some_and(|limit| self.replay.len() >= limit)
+    }
+
+    /// A random option, or the first one once the bot is passing.
+    fn pick<'a, T>(&mut self, options: &'a [T]) -> Option<&'a T> {
+        if self.is_passing() {
+            options.first()
+        } else {
+            options.choose(&mut self.rng)
+        }
+    }
+
+    /// A random value in `range`, or its start once the bot is passing.
+    fn pick_in<T: SampleUniform + PartialOrd + Copy>(&mut self, range: RangeInclusive<T>) -> T {
+        if self.is_passing() {
+            *range.start()
+        } else {
+            self.rng.random_range(range)
+        }
+    }
+
+    pub fn record(&mut self, player_id: &PlayerId, step: impl AsRef<str>) {
+        let player = self.player_name(player_id);
+        self.replay.push(format!("{player}: {}", step.as_ref()));
+    }
+
+    /// Remember that `effect` is about to be resolved, so a panic while resolving it can be
+    /// traced back to it.
+    pub fn resolving(&mut self, effect: &Effect) {
+        if self.recent_effects.len() == RECENT_EFFECTS {
+            self.recent_effects.pop_front();
+        }
+        self.recent_effects.push_back(effect.name().to_string());
+    }
+
+    /// Pick the next top-level action for the player whose turn it is: click a playable card in
+    /// hand, click a card in play that has an activatable ability, or end the turn.
+    pub fn next_action(&mut self, state: &State) -> ClientMessage {
+        let game_id = self.game_id;
+        let player_id = state.current_turn_controller();
+
+        let mut candidates: Vec<(String, String, CardId)> = state
+            .all_cards()
+            .filter(|card| match card.get_zone() {
+                Zone::Hand => {
+                    card.get_owner_id() == &player_id
+                        && !card.is_site()
+                        && card.is_playable(state, &player_id).unwrap_or_default()
+                }
+                zone if zone.is_in_play() => {
+                    card.get_controller_id(state) == player_id
+                        && !card
+                            .get_activated_abilities(state)
+                            .unwrap_or_default()
+                            .is_empty()
+                }
+                _ => false,
+            })
+            .map(|card| {
+                (
+                    card.get_name().to_string(),
+                    format!("{:?}", card.get_zone()),
+                    *card.get_id(),
+                )
+            })
+            .collect();
+        // Card ids are random, so order candidates by name and zone to keep games reproducible
+        // from their seed.
+        candidates.sort();
+
+        let end_turn = candidates.is_empty()
+            || self.actions_this_turn >= MAX_ACTIONS_PER_TURN
+            || self.is_passing()
+            || self.rng.random_ratio(1, 6);
+        if end_turn {
+            self.actions_this_turn = 0;
+            self.record(&player_id, "ends the turn");
+            return ClientMessage::EndTurn { game_id, player_id };
+        }
+
+        let (name, zone, card_id) = candidates
+            .choose(&mut self.rng)
+            .cloned()
+            .expect("candidates to not be empty");
+        self.actions_this_turn += 1;
+        self.record(&player_id, format!("clicks {name} in {zone}"));
+        ClientMessage::ClickCard {
+            game_id,
+            player_id,
+            card_id,
+        }
+    }
+
+    /// Answer a prompt with a random legal choice. Returns `None` if the message is not a
+    /// prompt, and an error if the prompt offers nothing to choose from.
+    fn answer(&mut self, prompt: &ServerMessage) -> Option<Result<ClientMessage, String>> {
+        let game_id = self.game_id;
+        let player_id = prompt.player_id();
+        let answer = match prompt {
+            ServerMessage::PickCard {
+                prompt,
+                pickable_cards,
+                ..
+            } => {
+                let mut options = pickable_cards.clone();
+                options.sort_by_key(|id| self.card_name(id));
+                self.pick(&options)
+                    .map(|card_id| {
+                        (
+                            format!("picks {} for \"{prompt}\"", self.card_name(card_id)),
+                            ClientMessage::PickCard {
+                                game_id,
+                                player_id,
+                                card_id: *card_id,
+                            },
+                        )
+                    })
+                    .ok_or(format!("PickCard \"{prompt}\" offered no pickable cards"))
+            }
+            ServerMessage::PickCards { prompt, cards, .. } => {
+                let mut options = cards.clone();
+                options.sort_by_key(|id| self.card_name(id));
+                let amount = self.pick_in(0..=options.len());
+                let card_ids: Vec<CardId> = options
+                    .choose_multiple(&mut self.rng, amount)
+                    .copied()
+                    .collect();
+                let names: Vec<String> = card_ids.iter().map(|id| self.card_name(id)).collect();
+                Ok((
+                    format!("picks {names:?} for \"{prompt}\""),
+                    ClientMessage::PickCards {
+                        game_id,
+                        player_id,
+                        card_ids,
+                    },
+                ))
+            }
+            ServerMessage::PickLocation {
+                prompt, locations, ..
+            } => self
+                .pick(locations)
+                .map(|location| {
+                    (
+                        format!("picks {location:?} for \"{prompt}\""),
+                        ClientMessage::PickLocation {
+                            game_id,
+                            player_id,
+                            location: location.clone(),
+                        },
+                    )
+                })
+                .ok_or(format!("PickLocation \"{prompt}\" offered no locations")),
+            ServerMessage::PickAction {
+                prompt, actions, ..
+            } => {
+                if actions.is_empty() {
+                    Err(format!("PickAction \"{prompt}\" offered no actions"))
+                } else {
+                    let action_idx = self.pick_in(0..=actions.len() - 1);
+                    Ok((
+                        format!("picks action \"{}\" for \"{prompt}\"", actions[action_idx]),
+                        ClientMessage::PickAction {
+                            game_id,
+                            player_id,
+                            action_idx,
+                        },
+                    ))
+                }
+            }
+            ServerMessage::PickDirection {
+                prompt, directions, ..
+            } => self
+                .pick(directions)
+                .map(|direction| {
+                    (
+                        format!("picks {direction:?} for \"{prompt}\""),
+                        ClientMessage::PickDirection {
+                            game_id,
+                            player_id,
+                            direction: direction.clone(),
+                        },
+                    )
+                })
+                .ok_or(format!("PickDirection \"{prompt}\" offered no directions")),
+            ServerMessage::PickAmount {
+                prompt,
+                min_amount,
+                max_amount,
+                ..
+            } => {
+                if min_amount > max_amount {
+                    Err(format!(
+                        "PickAmount \"{prompt}\" offered an empty range {min_amount}..={max_amount}"
+                    ))
+                } else {
+                    let amount = self.pick_in(*min_amount..=*max_amount);
+                    Ok((
+                        format!("picks {amount} for \"{prompt}\""),
+                        ClientMessage::PickAmount {
+                            game_id,
+                            player_id,
+                            amount,
+                        },
+                    ))
+                }
+            }
+            ServerMessage::PickPath { prompt, paths, .. } => self
+                .pick(paths)
+                .map(|path| {
+                    (
+                        format!("picks path {path:?} for \"{prompt}\""),
+                        ClientMessage::PickPath {
+                            game_id,
+                            player_id,
+                            path: path.clone(),
+                        },
+                    )
+                })
+                .ok_or(format!("PickPath \"{prompt}\" offered no paths")),
+            ServerMessage::PickLocationGroup { prompt, groups, .. } => {
+                if groups.is_empty() {
+                    Err(format!("PickLocationGroup \"{prompt}\" offered no groups"))
+                } else {
+                    let group_idx = self.pick_in(0..=groups.len() - 1);
+                    Ok((
+                        format!("picks group {:?} for \"{prompt}\"", groups[group_idx]),
+                        ClientMessage::PickLocationGroup {
+                            game_id,
+                            player_id,
+                            group_idx,
+                        },
+                    ))
+                }
+            }
+            ServerMessage::RevealCards {
+                prompt,
+                action: Some(action),
+                ..
+            } => {
+                let take_action = !self.is_passing() && self.rng.random_bool(0.5);
+                Ok((
+                    format!("answers {take_action} to \"{action}\" for \"{prompt}\""),
+                    ClientMessage::ResolveAction {
+                        game_id,
+                        player_id,
+                        take_action,
+                    },
+                ))
+            }
+            ServerMessage::DistributeDamage {
+                attacker,
+                defenders,
+                damage,
+                ..
+            } => {
+                let mut options = defenders.clone();
+                options.sort_by_key(|id| self.card_name(id));
+                self.pick(&options)
+                    .map(|defender_id| {
+                        (
+                            format!(
+                                "assigns {damage} damage from {} to {}",
+                                self.card_name(attacker),
+                                self.card_name(defender_id)
+                            ),
+                            ClientMessage::ResolveCombat {
+                                game_id,
+                                player_id,
+                                damage_assignment: HashMap::from([(*defender_id, *damage)]),
+                            },
+                        )
+                    })
+                    .ok_or("DistributeDamage offered no defenders".to_string())
+            }
+            _ => return None,
+        };
+
+        Some(answer.map(|(step, reply)| {
+            self.record(&player_id, step);
+            reply
+        }))
+    }
+
+    fn card_name(&self, card_id: &CardId) -> String {
+        self.card_names
+            .get(card_id)
+            .cloned()
+            .unwrap_or_else(|| card_id.to_string())
+    }
+
+    fn player_name(&self, player_id: &PlayerId) -> String {
+        self.player_names
+            .get(player_id)
+            .cloned()
+            .unwrap_or_else(|| player_id.to_string())
+    }
+}
+
+/// Answer prompts sent over the game's server channel until the game is dropped.
+pub async fn respond(
+    bot: SharedBot,
+    server_rx: Receiver<ServerMessage>,
+    client_tx: Sender<ClientMessage>,
+) {
+    while let Ok(message) = server_rx.recv().await {
+        let game_id = lock(&bot).game_id;
+        if let ServerMessage::ForceSync { cards, .. } = &message {
+            let mut bot = lock(&bot);
+            for card in cards {
+                bot.card_names.insert(card.id, card.name.clone());
+            }
+            continue;
+        }
+
+        let answer = lock(&bot).answer(&message);
+        let reply = match answer {
+            None => continue,
+            Some(Ok(reply)) => reply,
+            Some(Err(reason)) => {
+                lock(&bot).abort_reason = Some(reason);
+                // Abort the pending prompt so the game returns instead of waiting for an answer
+                // that will never come.
+                ClientMessage::PlayerDisconnected {
+                    game_id,
+                    player_id: message.player_id(),
+                }
+            }
+        };
+        if client_tx.send(reply).await.is_err() {
+            break;
+        }
+    }
+}
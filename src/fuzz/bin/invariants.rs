@@ -0,0 +1,165 @@
+use sorcerers::{
+    card::{Ability, Region},
+    game::CardId,
+    state::State,
+};
+use std::collections::HashSet;
+
+/// Engine invariants that must hold for every game, checked while the fuzzer resolves effects.
+pub struct Invariants {
+    card_count: usize,
+}
+
+impl Invariants {
+    /// Record the number of non-token cards at the start of the game.
+    pub fn capture(state: &State) -> Self {
+        Self {
+            card_count: non_token_cards(state).len(),
+        }
+    }
+
+    /// Checks that must hold after every resolved effect.
+    pub fn check_after_effect(&self, state: &State) -> Result<(), String> {
+        let card_count = non_token_cards(state).len();
+        if card_count != self.card_count {
+            return Err(format!(
+                "non-token card count changed from {} to {}",
+                self.card_count, card_count
+            ));
+        }
+
+        for player in &state.players {
+            let mana = state.player_mana.get(&player.id).copied().unwrap_or(0);
+            // Mana is stored as a u8 and adjusted through an i8, so a negative balance wraps to
+            // a value above i8::MAX.
+            if mana > i8::MAX as u8 {
+                return Err(format!(
+                    "{} has negative mana (stored as {mana})",
+                    player.name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that only hold once the effect queue has been drained, as the engine queues the
+    /// effects that fix these situations rather than applying them immediately.
+    pub fn check_settled(&self, state: &State) -> Result<(), String> {
+        // Avatars may stand on empty squares, so only minions are checked.
+        for card in state.cards_in_play() {
+            if !state.is_minion_card(card.get_id()) {
+                continue;
+            }
+
+            let in_void = card
+                .get_location()
+                .occupied_regions(state)
+                .contains(&Region::Void);
+            if in_void && !card.has_ability(state, &Ability::Voidwalk) {
+                return Err(format!(
+                    "{} is in the void at {:?} without Voidwalk",
+                    card.get_name(),
+                    card.get_zone()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn non_token_cards(state: &State) -> HashSet<CardId> {
+    state
+        .all_cards()
+        .chain(state.removed_cards())
+        .filter(|card| !card.is_token())
+        .map(|card| *card.get_id())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorcerers::{
+        card::KiteArcher,
+        deck::precon::ALL_PRECONS,
+        query::QueryCache,
+        scenario::{Scenario, ScenarioCard, ScenarioPlayer},
+        state::{Player, PlayerWithDeck},
+        zone::{Location, Zone},
+    };
+
+    fn new_state() -> State {
+        QueryCache::init();
+        let players = ALL_PRECONS[..2]
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, build))| {
+                let player_id = uuid::Uuid::new_v4();
+                let (deck, cards) = build(&player_id);
+                PlayerWithDeck {
+                    player: Player {
+                        id: player_id,
+                        name: format!("Player {}", idx + 1),
+                    },
+                    deck,
+                    cards,
+                }
+            })
+            .collect();
+        let (server_tx, _) = async_channel::unbounded();
+        let (_, client_rx) = async_channel::unbounded();
+        State::new(uuid::Uuid::new_v4(), players, server_tx, client_rx)
+    }
+
+    #[test]
+    fn lost_cards_break_card_conservation() {
+        let mut state = new_state();
+        let invariants = Invariants::capture(&state);
+        assert!(invariants.check_after_effect(&state).is_ok());
+
+        let card_id = *state.all_cards().next().unwrap().get_id();
+        state.remove_card(&card_id);
+        let violation = invariants.check_after_effect(&state).unwrap_err();
+        assert!(violation.contains("non-token card count changed"));
+    }
+
+    #[test]
+    fn wrapped_mana_breaks_the_mana_bound() {
+        let mut state = new_state();
+        let invariants = Invariants::capture(&state);
+        let player_id = state.players[0].id;
+
+        state.player_mana.insert(player_id, i8::MAX as u8);
+        assert!(invariants.check_after_effect(&state).is_ok());
+        state.player_mana.insert(player_id, u8::MAX);
+        let violation = invariants.check_after_effect(&state).unwrap_err();
+        assert!(violation.contains("negative mana"));
+    }
+
+    #[test]
+    fn minions_in_the_void_need_voidwalk() {
+        let mut state = new_state();
+        let invariants = Invariants::capture(&state);
+        assert!(invariants.check_settled(&state).is_ok());
+
+        Scenario {
+            name: "Void".to_string(),
+            players: vec![ScenarioPlayer {
+                cards: vec![ScenarioCard {
+                    name: KiteArcher::NAME.to_string(),
+                    zone: Zone::Location(Location::Square(8, Region::Surface)),
+                    damage: 0,
+                    tapped: false,
+                    statuses: vec![],
+                }],
+                ..Default::default()
+            }],
+        }
+        .apply(&mut state)
+        .unwrap();
+        let violation = invariants.check_settled(&state).unwrap_err();
+        assert!(violation.contains("without Voidwalk"));
+    }
+}
@@ -0,0 +1,155 @@
+//! Headless self-play fuzzer for the game engine.
+//!
+//! Pairs random legal-move bots using random precons and the deck lists saved in `decks/`, plays
+//! seeded games and checks engine invariants after every resolved effect. Any panic, engine
+//! error, invariant violation or stalled game is written to the output directory together with
+//! its seed and the replay of every decision the bots made.
+//!
+//! ```text
+//! fuzz [--games N] [--seed N] [--max-turns N] [--decision-limit N] [--out DIR]
+//! ```
+//!
+//! Bot decisions and deck order are derived from the seed, so running a failing seed again with
+//! `--games 1` usually reproduces it. The engine itself still draws on unseeded randomness (card
+//! ids, effects that shuffle or pick at random), so a replay is best effort.
+//!
+//! Failures are shrunk before they are saved: the seed is played again with the bots passing
+//! after a shorter and shorter prefix of their decisions, and the shortest run that fails the
+//! same way is reported with its `decision_limit`. Pass that limit with `--decision-limit` to
+//! reproduce the shrunk game. Stalled games are not shrunk.
+//!
+//! A stack overflow aborts the whole process. In that case the seed of the crashing game is left
+//! in `<out>/in-progress`.
+mod bot;
+mod invariants;
+mod runner;
+
+use crate::runner::{FuzzGame, Outcome};
+use sorcerers::{
+    deck::{DeckList, precon::ALL_PRECONS},
+    networking::message::DeckChoice,
+    query::QueryCache,
+};
+use std::path::PathBuf;
+
+struct Args {
+    games: u64,
+    seed: u64,
+    max_turns: usize,
+    decision_limit: Option<usize>,
+    out: PathBuf,
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut parsed = Args {
+            games: 1000,
+            seed: 0,
+            max_turns: 60,
+            decision_limit: None,
+            out: PathBuf::from("fuzz-failures"),
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(anyhow::anyhow!("{arg} requires a value"));
+            match arg.as_str() {
+                "--games" => parsed.games = value()?.parse()?,
+                "--seed" => parsed.seed = value()?.parse()?,
+                "--max-turns" => parsed.max_turns = value()?.parse()?,
+                "--decision-limit" => parsed.decision_limit = Some(value()?.parse()?),
+                "--out" => parsed.out = PathBuf::from(value()?),
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "unknown argument {arg}\nusage: fuzz [--games N] [--seed N] [--max-turns N] [--decision-limit N] [--out DIR]"
+                    ));
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Precons plus every valid saved deck list, ordered by name so a seed always picks the same
+/// decks.
+fn deck_pool() -> Vec<DeckChoice> {
+    let mut decks: Vec<DeckChoice> = ALL_PRECONS
+        .iter()
+        .map(|(precon, _)| DeckChoice::Precon((*precon).clone()))
+        .chain(
+            DeckList::load_all()
+                .into_iter()
+                .filter(|deck| deck.validate().is_ok())
+                .map(DeckChoice::Custom),
+        )
+        .collect();
+    decks.sort_by_key(|deck| deck.name());
+    decks
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse()?;
+    QueryCache::init();
+    runner::capture_panics();
+
+    let decks = deck_pool();
+    println!(
+        "Fuzzing {} games from seed {} with {} decks",
+        args.games,
+        args.seed,
+        decks.len()
+    );
+
+    // A single-threaded runtime keeps the interleaving of the game and the bots deterministic.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let mut wins = 0;
+    let mut unfinished = 0;
+    let mut failures = 0;
+    std::fs::create_dir_all(&args.out)?;
+    // A stack overflow aborts the process before a report can be written, so keep the seed of
+    // the game in progress on disk.
+    let in_progress = args.out.join("in-progress");
+    for seed in args.seed..args.seed + args.games {
+        std::fs::write(&in_progress, seed.to_string())?;
+        let report = runtime.block_on(FuzzGame::run(
+            seed,
+            &decks,
+            args.max_turns,
+            args.decision_limit,
+        ));
+        match report.outcome {
+            Outcome::Won { .. } => wins += 1,
+            Outcome::TurnLimit => unfinished += 1,
+            Outcome::Failed { .. } => {
+                failures += 1;
+                let report = runtime.block_on(FuzzGame::shrink(report, &decks, args.max_turns));
+                let Outcome::Failed { kind, message } = &report.outcome else {
+                    unreachable!("shrinking keeps the failure");
+                };
+                let path = args.out.join(format!("{seed}.json"));
+                std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+                println!(
+                    "seed {seed}: {kind:?} with {}: {message} ({})",
+                    report.decks.join(" vs "),
+                    path.display()
+                );
+            }
+        }
+    }
+
+    std::fs::remove_file(&in_progress)?;
+
+    println!(
+        "{} games: {wins} won, {unfinished} reached the turn limit, {failures} failed",
+        args.games
+    );
+    if failures > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,360 @@
+use crate::{
+    bot::{Bot, SharedBot, lock, respond},
+    invariants::Invariants,
+};
+use rand::seq::{IndexedRandom, SliceRandom};
+use serde::Serialize;
+use sorcerers::{
+    effect::EffectEngine,
+    game::{CardId, Game},
+    networking::message::{ClientMessage, DeckChoice},
+    state::{Player, PlayerWithDeck, State},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Wall-clock budget for a single game before it is reported as stalled, e.g. because the engine
+/// is waiting for an answer the bots never send.
+const GAME_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Number of effects a single action may resolve before the queue is considered to loop forever.
+const MAX_EFFECTS_PER_ACTION: usize = 10_000;
+
+/// Message of the last panic, captured by the hook installed in [`capture_panics`].
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Replace the default panic hook so panics inside games are recorded in the failure report
+/// instead of being printed for every game.
+pub fn capture_panics() {
+    std::panic::set_hook(Box::new(|info| {
+        let mut last_panic = LAST_PANIC
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *last_panic = Some(info.to_string());
+    }));
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum FailureKind {
+    Panic,
+    Error,
+    InvariantViolation,
+    /// The engine sent a prompt with nothing to choose from.
+    UnanswerablePrompt,
+    Stalled,
+}
+
+#[derive(Debug, Serialize)]
+pub enum Outcome {
+    Won { winner: String, turns: usize },
+    TurnLimit,
+    Failed { kind: FailureKind, message: String },
+}
+
+/// Result of a fuzzed game. Failing games are saved as JSON.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub seed: u64,
+    pub decks: Vec<String>,
+    pub outcome: Outcome,
+    /// Every top-level action and prompt answer, in order.
+    pub replay: Vec<String>,
+    /// Replay steps after which the bots stopped deciding at random and only passed, if any.
+    pub decision_limit: Option<usize>,
+    /// Length of the replay the failure was first found with, when a shorter one was found.
+    pub shrunk_from: Option<usize>,
+    /// The last effects taken off the queue, the newest last.
+    pub recent_effects: Vec<String>,
+}
+
+#[derive(Debug)]
+struct InvariantViolation(String);
+
+impl std::fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for InvariantViolation {}
+
+pub struct FuzzGame {
+    game: Game,
+    bot: SharedBot,
+    invariants: Invariants,
+}
+
+impl Report {
+    fn failure_kind(&self) -> Option<&FailureKind> {
+        match &self.outcome {
+            Outcome::Failed { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+impl FuzzGame {
+    /// Play one game between two bots with decks picked from `decks` by `seed`. With a
+    /// `decision_limit`, the bots only pass once the replay has that many steps.
+    pub async fn run(
+        seed: u64,
+        decks: &[DeckChoice],
+        max_turns: usize,
+        decision_limit: Option<usize>,
+    ) -> Report {
+        let game_id = uuid::Uuid::new_v4();
+        let bot = Arc::new(Mutex::new(Bot::new(game_id, seed, decision_limit)));
+        let picked: Vec<DeckChoice> = {
+            let mut bot = lock(&bot);
+            (0..2)
+                .filter_map(|_| decks.choose(bot.rng()).cloned())
+                .collect()
+        };
+        let deck_names = picked.iter().map(|deck| deck.name()).collect();
+
+        let handle = tokio::spawn(Self::play(game_id, picked, max_turns, Arc::clone(&bot)));
+        let abort_handle = handle.abort_handle();
+        let outcome = match tokio::time::timeout(GAME_TIMEOUT, handle).await {
+            Ok(Ok(Ok(outcome))) => outcome,
+            Ok(Ok(Err(err))) => {
+                let abort_reason = lock(&bot).abort_reason.take();
+                if let Some(reason) = abort_reason {
+                    Outcome::Failed {
+                        kind: FailureKind::UnanswerablePrompt,
+                        message: reason,
+                    }
+                } else if let Some(violation) = err.downcast_ref::<InvariantViolation>() {
+                    Outcome::Failed {
+                        kind: FailureKind::InvariantViolation,
+                        message: violation.to_string(),
+                    }
+                } else {
+                    Outcome::Failed {
+                        kind: FailureKind::Error,
+                        message: format!("{err:?}"),
+                    }
+                }
+            }
+            Ok(Err(err)) if err.is_panic() => {
+                let message = LAST_PANIC
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .take()
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                Outcome::Failed {
+                    kind: FailureKind::Panic,
+                    message,
+                }
+            }
+            Ok(Err(err)) => Outcome::Failed {
+                kind: FailureKind::Error,
+                message: err.to_string(),
+            },
+            Err(_) => {
+                abort_handle.abort();
+                Outcome::Failed {
+                    kind: FailureKind::Stalled,
+                    message: format!("game did not finish within {GAME_TIMEOUT:?}"),
+                }
+            }
+        };
+
+        let bot = lock(&bot);
+        Report {
+            seed,
+            decks: deck_names,
+            outcome,
+            replay: bot.replay.clone(),
+            decision_limit,
+            shrunk_from: None,
+            recent_effects: bot.recent_effects.iter().cloned().collect(),
+        }
+    }
+
+    /// Re-run a failed game with ever shorter prefixes of random decisions and return the
+    /// report of the shortest prefix that still fails the same way. The engine's own randomness
+    /// can hide a failure on a re-run, so the result is not guaranteed to be minimal.
+    pub async fn shrink(report: Report, decks: &[DeckChoice], max_turns: usize) -> Report {
+        // Every re-run of a stalled game would wait for the full timeout.
+        if matches!(report.failure_kind(), None | Some(FailureKind::Stalled)) {
+            return report;
+        }
+
+        let original_len = report.replay.len();
+        let mut shortest = report;
+        let (mut low, mut high) = (0, original_len);
+        while low < high {
+            let limit = low + (high - low) / 2;
+            let candidate = Self::run(shortest.seed, decks, max_turns, Some(limit)).await;
+            if candidate.failure_kind() == shortest.failure_kind() {
+                high = limit;
+                shortest = candidate;
+            } else {
+                low = limit + 1;
+            }
+        }
+
+        if shortest.decision_limit.is_some() {
+            shortest.shrunk_from = Some(original_len);
+        }
+        shortest
+    }
+
+    async fn play(
+        game_id: uuid::Uuid,
+        decks: Vec<DeckChoice>,
+        max_turns: usize,
+        bot: SharedBot,
+    ) -> anyhow::Result<Outcome> {
+        let players: Vec<PlayerWithDeck> = decks
+            .iter()
+            .enumerate()
+            .map(|(idx, choice)| {
+                let player_id = uuid::Uuid::new_v4();
+                let (mut deck, cards) = choice.build(&player_id);
+                // Decks are shuffled with an unseeded rng when built, so reshuffle them from the
+                // seed to draw the same cards on every run.
+                let names: HashMap<CardId, &str> = cards
+                    .iter()
+                    .map(|card| (*card.get_id(), card.get_name()))
+                    .collect();
+                let mut bot = lock(&bot);
+                for pile in [&mut deck.sites, &mut deck.spells] {
+                    pile.sort_by_key(|id| names.get(id).copied());
+                    pile.shuffle(bot.rng());
+                }
+                drop(bot);
+
+                PlayerWithDeck {
+                    player: Player {
+                        id: player_id,
+                        name: format!("Player {} ({})", idx + 1, choice.name()),
+                    },
+                    deck,
+                    cards,
+                }
+            })
+            .collect();
+        let player_ids: Vec<_> = players.iter().map(|p| p.player.id).collect();
+
+        let (server_tx, server_rx) = async_channel::unbounded();
+        let (client_tx, client_rx) = async_channel::unbounded();
+        let mut state = State::new(game_id, players, server_tx, client_rx);
+        // Resolve effects one at a time from here so invariants can be checked between them.
+        state.stepped_effects = true;
+        tokio::spawn(respond(Arc::clone(&bot), server_rx, client_tx));
+
+        let mut fuzz = FuzzGame {
+            invariants: Invariants::capture(&state),
+            game: Game::from_state(state),
+            bot,
+        };
+
+        lock(&fuzz.bot).observe(&fuzz.game.state);
+        let setup = [fuzz.game.place_avatars(), fuzz.game.draw_initial_six()].concat();
+        fuzz.game.state.queue(setup);
+        fuzz.settle().await?;
+
+        for player_id in player_ids {
+            lock(&fuzz.bot).record(&player_id, "keeps the opening hand");
+            fuzz.send(ClientMessage::PickCards {
+                game_id,
+                player_id,
+                card_ids: vec![],
+            })
+            .await?;
+        }
+
+        while fuzz.game.state.turns < max_turns {
+            if let Some(winner) = fuzz.game.state.winner_if_game_over() {
+                return Ok(Outcome::Won {
+                    winner: winner.name.clone(),
+                    turns: fuzz.game.state.turns,
+                });
+            }
+
+            let action = {
+                let mut bot = lock(&fuzz.bot);
+                bot.observe(&fuzz.game.state);
+                bot.next_action(&fuzz.game.state)
+            };
+            fuzz.send(action).await?;
+        }
+
+        Ok(Outcome::TurnLimit)
+    }
+
+    /// Handle a client message the same way the server does, then resolve the effects it queued.
+    async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()> {
+        lock(&self.bot).observe(&self.game.state);
+        self.game.handle_message(&message).await?;
+        self.settle().await?;
+        self.game.update().await
+    }
+
+    /// Resolve queued effects one by one, checking invariants after each of them.
+    async fn settle(&mut self) -> anyhow::Result<()> {
+        let mut resolved = 0;
+        while !self.game.state.effects.is_empty() {
+            if resolved == MAX_EFFECTS_PER_ACTION {
+                return Err(InvariantViolation(format!(
+                    "effect queue did not settle after {MAX_EFFECTS_PER_ACTION} effects"
+                ))
+                .into());
+            }
+            if let Some(effect) = self.game.state.effects.iter().next_back() {
+                lock(&self.bot).resolving(effect);
+            }
+
+            EffectEngine::step_with_log(&mut self.game).await?;
+            self.invariants
+                .check_after_effect(&self.game.state)
+                .map_err(InvariantViolation)?;
+            resolved += 1;
+            // Yield so the game timeout can fire if effects keep queueing each other.
+            tokio::task::yield_now().await;
+        }
+
+        self.invariants
+            .check_settled(&self.game.state)
+            .map_err(InvariantViolation)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorcerers::{deck::precon::ALL_PRECONS, query::QueryCache};
+
+    fn precons() -> Vec<DeckChoice> {
+        QueryCache::init();
+        ALL_PRECONS
+            .iter()
+            .map(|(precon, _)| DeckChoice::Precon((*precon).clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn a_seed_replays_the_same_game() {
+        let decks = precons();
+        let first = FuzzGame::run(7, &decks, 4, None).await;
+        let second = FuzzGame::run(7, &decks, 4, None).await;
+
+        assert_eq!(first.decks, second.decks);
+        assert!(!first.replay.is_empty());
+        assert_eq!(first.replay, second.replay);
+    }
+
+    #[tokio::test]
+    async fn bots_only_pass_after_the_decision_limit() {
+        let decks = precons();
+        let report = FuzzGame::run(7, &decks, 4, Some(0)).await;
+
+        assert!(matches!(report.outcome, Outcome::TurnLimit));
+        assert!(report.replay.iter().all(|step| !step.contains(" clicks ")));
+    }
+}
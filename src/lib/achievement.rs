@@ -0,0 +1,349 @@
+use crate::{
+    card::AncientDragon,
+    effect::{Effect, LoggedEffect},
+    game::{Element, GameOutcome, PlayerId},
+    state::State,
+};
+use serde::{Deserialize, Serialize};
+
+/// A condition checked against a finished game from one player's point of view. Conditions are
+/// data, so new achievements are added to [`ACHIEVEMENTS`] without touching the game loop.
+#[derive(Debug)]
+pub enum Condition {
+    /// The player won the game.
+    Won,
+    /// The game ended within this many turns.
+    EndedByTurn(usize),
+    /// The player's avatar ended the game on Death's Door.
+    AtDeathsDoor,
+    /// The player killed an opponent's minion with this name.
+    KilledMinionNamed(&'static str),
+    /// The player killed at least this many minions.
+    KilledMinions(usize),
+    /// A single hit from one of the player's cards dealt at least this much damage.
+    DealtDamageInOneHit(u16),
+    /// The player controlled at least this many sites of an element when the game ended.
+    SitesInPlay { element: Element, count: usize },
+    /// Every condition holds.
+    All(&'static [Condition]),
+}
+
+impl Condition {
+    pub fn holds(&self, game: &FinishedGame) -> bool {
+        let owned_by_player = |card_id| {
+            game.state
+                .try_get_card(card_id)
+                .is_some_and(|card| card.get_owner_id() == game.player_id)
+        };
+        match self {
+            Self::Won => &game.outcome.winner_id == game.player_id,
+            Self::EndedByTurn(turn) => game.state.turns <= *turn,
+            Self::AtDeathsDoor => game
+                .state
+                .get_player_avatar_id(game.player_id)
+                .ok()
+                .and_then(|avatar_id| game.state.try_get_card(&avatar_id))
+                .and_then(|avatar| avatar.get_avatar_base())
+                .is_some_and(|avatar_base| avatar_base.deaths_door),
+            Self::KilledMinionNamed(name) => {
+                game.logged_effects().any(|logged| match &logged.effect {
+                    Effect::KillMinion {
+                        card_id, killer_id, ..
+                    } => {
+                        owned_by_player(killer_id)
+                            && logged
+                                .victim_controller_id
+                                .is_some_and(|controller_id| &controller_id != game.player_id)
+                            && game
+                                .state
+                                .try_get_card(card_id)
+                                .is_some_and(|card| card.get_name() == *name)
+                    }
+                    _ => false,
+                })
+            }
+            Self::KilledMinions(count) => {
+                game.effects()
+                    .filter(|effect| match effect {
+                        Effect::KillMinion { killer_id, .. } => owned_by_player(killer_id),
+                        _ => false,
+                    })
+                    .count()
+                    >= *count
+            }
+            Self::DealtDamageInOneHit(amount) => game.effects().any(|effect| match effect {
+                Effect::TakeDamage { from, damage, .. } => {
+                    damage.amount >= *amount && owned_by_player(from)
+                }
+                _ => false,
+            }),
+            Self::SitesInPlay { element, count } => {
+                game.state
+                    .cards_in_play()
+                    .filter(|card| {
+                        card.is_site()
+                            && card.get_controller_id(game.state) == *game.player_id
+                            && card
+                                .get_elements(game.state)
+                                .is_ok_and(|elements| elements.contains(element))
+                    })
+                    .count()
+                    >= *count
+            }
+            Self::All(conditions) => conditions.iter().all(|condition| condition.holds(game)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: Condition,
+}
+
+pub static ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first-victory",
+        name: "First Victory",
+        description: "Win a game.",
+        condition: Condition::Won,
+    },
+    Achievement {
+        id: "deaths-door-victory",
+        name: "Back from the Brink",
+        description: "Win a game while your avatar is on Death's Door.",
+        condition: Condition::All(&[Condition::Won, Condition::AtDeathsDoor]),
+    },
+    Achievement {
+        id: "dragon-slayer",
+        name: "Dragon Slayer",
+        description: "Kill an Ancient Dragon.",
+        condition: Condition::KilledMinionNamed(AncientDragon::NAME),
+    },
+    Achievement {
+        id: "flood-the-realm",
+        name: "Flood the Realm",
+        description: "Control 10 water sites at the end of a game.",
+        condition: Condition::SitesInPlay {
+            element: Element::Water,
+            count: 10,
+        },
+    },
+    Achievement {
+        id: "scorched-earth",
+        name: "Scorched Earth",
+        description: "Control 10 fire sites at the end of a game.",
+        condition: Condition::SitesInPlay {
+            element: Element::Fire,
+            count: 10,
+        },
+    },
+    Achievement {
+        id: "swift-victory",
+        name: "Swift Victory",
+        description: "Win a game within 8 turns.",
+        condition: Condition::All(&[Condition::Won, Condition::EndedByTurn(8)]),
+    },
+    Achievement {
+        id: "massacre",
+        name: "Massacre",
+        description: "Kill 10 minions in a single game.",
+        condition: Condition::KilledMinions(10),
+    },
+    Achievement {
+        id: "crushing-blow",
+        name: "Crushing Blow",
+        description: "Deal 8 or more damage with a single hit.",
+        condition: Condition::DealtDamageInOneHit(8),
+    },
+];
+
+pub fn achievement_by_id(id: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|achievement| achievement.id == id)
+}
+
+/// A finished game seen from one of its players.
+pub struct FinishedGame<'a> {
+    pub state: &'a State,
+    pub outcome: &'a GameOutcome,
+    pub player_id: &'a PlayerId,
+}
+
+impl FinishedGame<'_> {
+    /// The game's logged effects, in the order they resolved.
+    fn logged_effects(&self) -> impl Iterator<Item = &LoggedEffect> {
+        self.state.effect_log().iter()
+    }
+
+    /// The game's effects, in the order they resolved.
+    fn effects(&self) -> impl Iterator<Item = &Effect> {
+        self.logged_effects().map(|logged| &logged.effect)
+    }
+
+    /// Every achievement whose condition holds for this game.
+    pub fn earned(&self) -> Vec<&'static Achievement> {
+        ACHIEVEMENTS
+            .iter()
+            .filter(|achievement| achievement.condition.holds(self))
+            .collect()
+    }
+}
+
+/// An achievement as announced to a player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnlockedAchievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+impl From<&Achievement> for UnlockedAchievement {
+    fn from(achievement: &Achievement) -> Self {
+        Self {
+            id: achievement.id.to_string(),
+            name: achievement.name.to_string(),
+            description: achievement.description.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::{KiteArcher, Region},
+        harness::Harness,
+        scenario::{Scenario, ScenarioAvatar, ScenarioCard, ScenarioPlayer},
+        zone::{Location, Zone},
+    };
+
+    fn earned_ids(
+        harness: &Harness,
+        winner_id: PlayerId,
+        player_id: &PlayerId,
+    ) -> Vec<&'static str> {
+        let outcome = GameOutcome {
+            game_id: harness.state().game_id,
+            winner_id,
+            player_ids: vec![harness.player_one, harness.player_two],
+        };
+        FinishedGame {
+            state: harness.state(),
+            outcome: &outcome,
+            player_id,
+        }
+        .earned()
+        .into_iter()
+        .map(|achievement| achievement.id)
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn deaths_door_and_site_achievements_read_the_final_state() {
+        let sites = (1..=10)
+            .map(|square| ScenarioCard {
+                name: "Spring River".to_string(),
+                zone: Zone::Location(Location::Square(square, Region::Surface)),
+                damage: 0,
+                tapped: false,
+                statuses: vec![],
+            })
+            .collect();
+        let harness = Harness::new(&Scenario {
+            name: "Flooded".to_string(),
+            players: vec![ScenarioPlayer {
+                avatar: Some(ScenarioAvatar {
+                    deaths_door: true,
+                    ..Default::default()
+                }),
+                cards: sites,
+                ..Default::default()
+            }],
+        });
+        let (one, two) = (harness.player_one, harness.player_two);
+
+        let earned = earned_ids(&harness, one, &one);
+        assert!(earned.contains(&"first-victory"));
+        assert!(earned.contains(&"deaths-door-victory"));
+        assert!(earned.contains(&"flood-the-realm"));
+        assert!(!earned.contains(&"scorched-earth"));
+
+        let lost = earned_ids(&harness, two, &one);
+        assert!(!lost.contains(&"deaths-door-victory"));
+        assert!(lost.contains(&"flood-the-realm"));
+        assert!(earned_ids(&harness, one, &two).is_empty());
+    }
+
+    fn on_square(name: &str, square: u8) -> ScenarioCard {
+        ScenarioCard {
+            name: name.to_string(),
+            zone: Zone::Location(Location::Square(square, Region::Surface)),
+            damage: 0,
+            tapped: false,
+            statuses: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn only_killing_an_opponents_dragon_slays_it() {
+        let mut harness = Harness::new(&Scenario {
+            name: "Dragons".to_string(),
+            players: vec![
+                ScenarioPlayer {
+                    cards: vec![
+                        on_square(KiteArcher::NAME, 8),
+                        on_square(AncientDragon::NAME, 8),
+                    ],
+                    ..Default::default()
+                },
+                ScenarioPlayer {
+                    cards: vec![on_square(AncientDragon::NAME, 13)],
+                    ..Default::default()
+                },
+            ],
+        });
+        let (one, two) = (harness.player_one, harness.player_two);
+        let archer = harness.card(KiteArcher::NAME);
+        let dragon_of = |harness: &Harness, player_id: PlayerId| {
+            harness
+                .cards(AncientDragon::NAME)
+                .into_iter()
+                .find(|card_id| harness.state().get_card(card_id).get_owner_id() == &player_id)
+                .unwrap()
+        };
+
+        let own_dragon = dragon_of(&harness, one);
+        harness
+            .resolve([Effect::KillMinion {
+                card_id: own_dragon,
+                killer_id: archer,
+                from_attack: false,
+            }])
+            .await
+            .unwrap();
+        assert!(!earned_ids(&harness, two, &one).contains(&"dragon-slayer"));
+
+        let enemy_dragon = dragon_of(&harness, two);
+        harness
+            .resolve([Effect::KillMinion {
+                card_id: enemy_dragon,
+                killer_id: archer,
+                from_attack: false,
+            }])
+            .await
+            .unwrap();
+        assert!(earned_ids(&harness, two, &one).contains(&"dragon-slayer"));
+    }
+
+    #[test]
+    fn achievement_ids_are_unique() {
+        for achievement in ACHIEVEMENTS {
+            assert!(std::ptr::eq(
+                achievement_by_id(achievement.id).unwrap(),
+                achievement
+            ));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    card::{ALL_CARDS, Ability, Card, CardType, Edition, Rarity},
+    game::Thresholds,
+};
+use serde::{Deserialize, Serialize};
+
+/// The printed data of an implemented card, as set up by its constructor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub edition: Edition,
+    pub card_type: CardType,
+    pub rarity: Rarity,
+    /// Printed mana cost, `None` for variable (X) costs.
+    pub mana_cost: Option<u8>,
+    /// Thresholds required to cast the card. Empty for sites.
+    pub threshold_cost: Thresholds,
+    /// Thresholds the card provides once in play. Empty for everything but sites.
+    pub provided_thresholds: Thresholds,
+    pub power: Option<u16>,
+    pub toughness: Option<u16>,
+    pub abilities: Vec<Ability>,
+    pub types: Vec<String>,
+    pub description: String,
+}
+
+impl CatalogEntry {
+    pub fn from_card(card: &dyn Card) -> Self {
+        let base = card.get_base();
+        let unit_base = card.get_unit_base();
+        let site_base = card.get_site_base();
+
+        let mut abilities = vec![];
+        let mut types = vec![];
+        if let Some(unit) = unit_base {
+            abilities.extend(unit.abilities.iter().cloned());
+            types.extend(unit.types.iter().map(|t| format!("{t:?}")));
+        }
+        if let Some(site) = site_base {
+            abilities.extend(site.abilities.iter().cloned());
+            types.extend(site.types.iter().map(|t| format!("{t:?}")));
+        }
+
+        Self {
+            name: card.get_name().to_string(),
+            edition: base.edition.clone(),
+            card_type: card.get_card_type(),
+            rarity: base.rarity.clone(),
+            mana_cost: base.costs.printed_mana_value(),
+            threshold_cost: base.costs.printed_thresholds().clone(),
+            provided_thresholds: site_base
+                .map(|site| site.provided_thresholds.clone())
+                .unwrap_or_default(),
+            power: unit_base.map(|unit| unit.power),
+            toughness: unit_base.map(|unit| unit.toughness),
+            abilities,
+            types,
+            description: card.get_description().to_string(),
+        }
+    }
+}
+
+/// Returns every registered card except tokens, sorted by name.
+pub fn catalog() -> Vec<CatalogEntry> {
+    let mut entries: Vec<CatalogEntry> = ALL_CARDS
+        .iter()
+        .map(|(_, constructor)| constructor(uuid::Uuid::nil()))
+        .filter(|card| !card.get_base().is_token)
+        .map(|card| CatalogEntry::from_card(card.as_ref()))
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{AridDesert, FootSoldier, KiteArcher};
+
+    #[test]
+    fn catalog_describes_units_and_sites() {
+        let entries = catalog();
+
+        let archer = entries
+            .iter()
+            .find(|entry| entry.name == KiteArcher::NAME)
+            .unwrap();
+        assert_eq!(archer.card_type, CardType::Minion);
+        assert_eq!(archer.edition, Edition::Beta);
+        assert!(archer.power.is_some() && archer.toughness.is_some());
+        assert!(archer.mana_cost.is_some());
+        assert_eq!(archer.threshold_cost, Thresholds::parse("A"));
+        assert_eq!(archer.provided_thresholds, Thresholds::default());
+
+        let desert = entries
+            .iter()
+            .find(|entry| entry.name == AridDesert::NAME)
+            .unwrap();
+        assert_eq!(desert.card_type, CardType::Site);
+        assert_eq!(desert.power, None);
+        assert_eq!(desert.threshold_cost, Thresholds::default());
+        assert_eq!(desert.provided_thresholds, Thresholds::parse("F"));
+
+        assert!(entries.iter().all(|entry| entry.name != FootSoldier::NAME));
+        assert!(entries.windows(2).all(|pair| pair[0].name <= pair[1].name));
+    }
+}
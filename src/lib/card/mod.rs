@@ -1,4 +1,5 @@
 pub mod beta;
+pub mod catalog;
 pub mod foot_soldier;
 pub mod frog;
 pub mod rubble;
@@ -509,8 +510,8 @@ impl PayableCost {
             };
 
             effect.apply(state).await?;
-            let turn = state.turns;
-            state.effect_log_mut().push(LoggedEffect::new(effect, turn));
+            let logged = LoggedEffect::new(effect, state);
+            state.effect_log_mut().push(logged);
             crate::game::force_sync(player_id, state).await?;
         }
 
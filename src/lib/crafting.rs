@@ -0,0 +1,249 @@
+use crate::{
+    card::{Edition, Rarity, card_exists, from_name},
+    collection::CollectedCard,
+    deck::{CardNameWithCount, DeckList, copy_limit},
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Crafting and dusting a foil printing is worth this many non-foil copies.
+pub const FOIL_MULTIPLIER: u32 = 4;
+
+/// Dust received for disenchanting one copy of a card.
+pub fn dust_value(rarity: &Rarity, is_foil: bool) -> u32 {
+    let value = match rarity {
+        Rarity::Ordinary => 5,
+        Rarity::Exceptional => 20,
+        Rarity::Elite => 100,
+        Rarity::Unique => 400,
+    };
+    if is_foil { value * FOIL_MULTIPLIER } else { value }
+}
+
+/// Dust spent to craft one copy of a card.
+pub fn craft_cost(rarity: &Rarity, is_foil: bool) -> u32 {
+    let cost = match rarity {
+        Rarity::Ordinary => 40,
+        Rarity::Exceptional => 100,
+        Rarity::Elite => 400,
+        Rarity::Unique => 1600,
+    };
+    if is_foil { cost * FOIL_MULTIPLIER } else { cost }
+}
+
+pub fn card_rarity(name: &str) -> Option<Rarity> {
+    card_exists(name).then(|| from_name(name, &uuid::Uuid::nil()).get_base().rarity.clone())
+}
+
+/// The rarity of a card that can be crafted. Only cards found in Beta boosters can be crafted.
+pub fn craftable_rarity(name: &str) -> Option<Rarity> {
+    if !card_exists(name) {
+        return None;
+    }
+    let card = from_name(name, &uuid::Uuid::nil());
+    let base = card.get_base();
+    (base.edition == Edition::Beta && !base.is_token && !card.is_avatar())
+        .then(|| base.rarity.clone())
+}
+
+/// Dust needed to craft `cards`, or an error naming the first card that cannot be crafted.
+pub fn crafting_cost(cards: &[CardNameWithCount]) -> Result<u32, String> {
+    cards.iter().try_fold(0, |total, card| {
+        let rarity = craftable_rarity(&card.name)
+            .ok_or_else(|| format!("{} cannot be crafted.", card.name))?;
+        Ok(total + craft_cost(&rarity, card.is_foil) * u32::from(card.count))
+    })
+}
+
+/// Refuse crafts that would leave more copies of a printing than a collection can hold.
+pub fn check_owned_copies(
+    cards: &[CardNameWithCount],
+    collection: &[CollectedCard],
+) -> Result<(), String> {
+    let mut owned: BTreeMap<(&str, bool), u32> = BTreeMap::new();
+    for card in collection {
+        *owned.entry((card.name.as_str(), card.is_foil)).or_default() += u32::from(card.count);
+    }
+    for card in cards {
+        let count = owned.entry((card.name.as_str(), card.is_foil)).or_default();
+        *count += u32::from(card.count);
+        if *count > u32::from(u8::MAX) {
+            return Err(format!(
+                "You can't own more than {} copies of {}.",
+                u8::MAX,
+                card.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Copies beyond the deck copy limit that can be dusted. Non-foil copies go first, and copies the
+/// player's saved decks rely on are always kept.
+pub fn excess_copies(
+    collection: &[CollectedCard],
+    locked: &HashMap<(String, bool), u8>,
+) -> Vec<CardNameWithCount> {
+    let mut owned: BTreeMap<&str, (u8, u8)> = BTreeMap::new();
+    for card in collection {
+        let (regular, foil) = owned.entry(card.name.as_str()).or_default();
+        if card.is_foil {
+            *foil = foil.saturating_add(card.count);
+        } else {
+            *regular = regular.saturating_add(card.count);
+        }
+    }
+
+    let mut excess = Vec::new();
+    for (name, (regular, foil)) in owned {
+        let Some(rarity) = card_rarity(name) else {
+            continue;
+        };
+        let mut extra = regular
+            .saturating_add(foil)
+            .saturating_sub(copy_limit(&rarity));
+        for (is_foil, count) in [(false, regular), (true, foil)] {
+            let kept = locked
+                .get(&(name.to_string(), is_foil))
+                .copied()
+                .unwrap_or_default();
+            let dusted = extra.min(count.saturating_sub(kept));
+            if dusted > 0 {
+                excess.push(CardNameWithCount {
+                    name: name.to_string(),
+                    count: dusted,
+                    is_foil,
+                });
+                extra -= dusted;
+            }
+        }
+    }
+    excess
+}
+
+/// Cards in `deck` that the collection does not have enough copies of.
+pub fn missing_cards(deck: &DeckList, collection: &[CollectedCard]) -> Vec<CardNameWithCount> {
+    let mut needed: BTreeMap<(String, bool), u8> = BTreeMap::new();
+    for card in deck.sites.iter().chain(&deck.spells) {
+        let count = needed.entry((card.name.clone(), card.is_foil)).or_default();
+        *count = count.saturating_add(card.count);
+    }
+    needed
+        .into_iter()
+        .filter_map(|((name, is_foil), count)| {
+            let owned = collection
+                .iter()
+                .filter(|card| card.name == name && card.is_foil == is_foil)
+                .map(|card| card.count)
+                .sum::<u8>();
+            let count = count.saturating_sub(owned);
+            (count > 0).then_some(CardNameWithCount {
+                name,
+                count,
+                is_foil,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collected(name: &str, count: u8, is_foil: bool) -> CollectedCard {
+        CollectedCard {
+            name: name.to_string(),
+            count,
+            is_foil,
+        }
+    }
+
+    #[test]
+    fn foils_are_worth_more_and_crafting_costs_more_than_dusting() {
+        for rarity in [
+            Rarity::Ordinary,
+            Rarity::Exceptional,
+            Rarity::Elite,
+            Rarity::Unique,
+        ] {
+            assert!(dust_value(&rarity, false) < craft_cost(&rarity, false));
+            assert_eq!(
+                dust_value(&rarity, true),
+                dust_value(&rarity, false) * FOIL_MULTIPLIER
+            );
+        }
+        assert!(craftable_rarity("Sorcerer").is_none());
+        assert!(craftable_rarity("Pit Vipers").is_some());
+    }
+
+    #[test]
+    fn excess_copies_keep_the_copy_limit_and_deck_locks() {
+        let rarity = card_rarity("Pit Vipers").unwrap();
+        let limit = copy_limit(&rarity);
+        let collection = vec![
+            collected("Pit Vipers", limit + 1, false),
+            collected("Pit Vipers", 2, true),
+        ];
+
+        let excess = excess_copies(&collection, &HashMap::new());
+        assert_eq!(excess.len(), 1);
+        assert!(!excess[0].is_foil);
+        assert_eq!(excess[0].count, 3);
+
+        let locked = HashMap::from([(("Pit Vipers".to_string(), false), limit)]);
+        let excess = excess_copies(&collection, &locked);
+        assert_eq!(excess.iter().map(|card| card.count).sum::<u8>(), 3);
+        assert_eq!(excess[0].count, 1);
+        assert!(excess[1].is_foil && excess[1].count == 2);
+    }
+
+    #[test]
+    fn crafts_cannot_overflow_a_printing() {
+        let collection = vec![
+            collected("Pit Vipers", u8::MAX - 1, false),
+            collected("Pit Vipers", 3, true),
+        ];
+        let craft = |count, is_foil| {
+            vec![CardNameWithCount {
+                name: "Pit Vipers".to_string(),
+                count,
+                is_foil,
+            }]
+        };
+        assert!(check_owned_copies(&craft(1, false), &collection).is_ok());
+        assert!(check_owned_copies(&craft(2, false), &collection).is_err());
+        assert!(check_owned_copies(&craft(2, true), &collection).is_ok());
+
+        let mut twice = craft(1, false);
+        twice.extend(craft(1, false));
+        assert!(check_owned_copies(&twice, &collection).is_err());
+    }
+
+    #[test]
+    fn missing_cards_are_counted_per_printing() {
+        let deck = DeckList {
+            name: "Vipers".to_string(),
+            sites: vec![],
+            spells: vec![
+                CardNameWithCount {
+                    name: "Pit Vipers".to_string(),
+                    count: 3,
+                    is_foil: false,
+                },
+                CardNameWithCount {
+                    name: "Pit Vipers".to_string(),
+                    count: 1,
+                    is_foil: true,
+                },
+            ],
+            avatar: "Sorcerer".to_string(),
+        };
+        let missing = missing_cards(&deck, &[collected("Pit Vipers", 2, true)]);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].count, 3);
+        assert!(!missing[0].is_foil);
+        assert_eq!(
+            crafting_cost(&missing).unwrap(),
+            craft_cost(&card_rarity("Pit Vipers").unwrap(), false) * 3
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use super::DeckList;
+use crate::{
+    card::{CardType, Rarity, card_exists, from_name},
+    game::{Element, Thresholds},
+};
+use std::collections::BTreeMap;
+
+/// Sites in the opening hand.
+pub const OPENING_SITES: usize = 3;
+/// Spells in the opening hand.
+pub const OPENING_SPELLS: usize = 3;
+
+/// How much a deck asks of one element, and how much its atlas gives back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementStats {
+    /// Spells with at least one threshold of this element.
+    pub spells: u32,
+    /// The highest threshold of this element any spell needs.
+    pub required: u8,
+    /// Sites that provide this element.
+    pub sites: u32,
+    /// Total affinity of this element across the atlas.
+    pub affinity: u32,
+}
+
+/// Statistics for a deck list, computed from the printed cards.
+#[derive(Debug, Clone, Default)]
+pub struct DeckStats {
+    pub atlas_size: u32,
+    pub spellbook_size: u32,
+    /// Spells by printed mana cost. Variable costs are counted in `variable_cost` instead.
+    pub mana_curve: BTreeMap<u8, u32>,
+    pub variable_cost: u32,
+    pub elements: Vec<(Element, ElementStats)>,
+    pub card_types: Vec<(CardType, u32)>,
+    pub rarities: Vec<(Rarity, u32)>,
+}
+
+fn threshold_of(thresholds: &Thresholds, element: &Element) -> u8 {
+    match element {
+        Element::Fire => thresholds.fire,
+        Element::Air => thresholds.air,
+        Element::Earth => thresholds.earth,
+        Element::Water => thresholds.water,
+    }
+}
+
+impl DeckStats {
+    pub fn from_deck(deck: &DeckList) -> Self {
+        let mut stats = Self {
+            elements: [Element::Fire, Element::Air, Element::Earth, Element::Water]
+                .into_iter()
+                .map(|element| (element, ElementStats::default()))
+                .collect(),
+            card_types: [
+                CardType::Minion,
+                CardType::Magic,
+                CardType::Aura,
+                CardType::Artifact,
+                CardType::Site,
+            ]
+            .into_iter()
+            .map(|card_type| (card_type, 0))
+            .collect(),
+            rarities: [
+                Rarity::Ordinary,
+                Rarity::Exceptional,
+                Rarity::Elite,
+                Rarity::Unique,
+            ]
+            .into_iter()
+            .map(|rarity| (rarity, 0))
+            .collect(),
+            ..Self::default()
+        };
+
+        for entry in deck.sites.iter().chain(&deck.spells) {
+            if !card_exists(&entry.name) {
+                continue;
+            }
+            let count = u32::from(entry.count);
+            let card = from_name(&entry.name, &uuid::Uuid::nil());
+            let base = card.get_base();
+            let card_type = card.get_card_type();
+            if let Some((_, total)) = stats.card_types.iter_mut().find(|(t, _)| t == &card_type) {
+                *total += count;
+            }
+            if let Some((_, total)) = stats.rarities.iter_mut().find(|(r, _)| r == &base.rarity) {
+                *total += count;
+            }
+
+            if let Some(site) = card.get_site_base() {
+                stats.atlas_size += count;
+                for (element, element_stats) in &mut stats.elements {
+                    let affinity = threshold_of(&site.provided_thresholds, element);
+                    if affinity > 0 {
+                        element_stats.sites += count;
+                        element_stats.affinity += u32::from(affinity) * count;
+                    }
+                }
+                continue;
+            }
+
+            stats.spellbook_size += count;
+            match base.costs.printed_mana_value() {
+                Some(mana) => *stats.mana_curve.entry(mana).or_default() += count,
+                None => stats.variable_cost += count,
+            }
+            let thresholds = base.costs.printed_thresholds();
+            for (element, element_stats) in &mut stats.elements {
+                let required = threshold_of(thresholds, element);
+                if required > 0 {
+                    element_stats.spells += count;
+                    element_stats.required = element_stats.required.max(required);
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn element(&self, element: &Element) -> &ElementStats {
+        &self
+            .elements
+            .iter()
+            .find(|(e, _)| e == element)
+            .expect("every element is tracked")
+            .1
+    }
+
+    /// Odds of having seen at least `threshold` sites of `element` by `turn`. Each site is
+    /// counted once, so sites with two affinity make the odds slightly pessimistic.
+    pub fn threshold_odds(&self, element: &Element, threshold: u8, turn: usize) -> f64 {
+        hypergeometric_at_least(
+            self.atlas_size as usize,
+            self.element(element).sites as usize,
+            sites_seen_by_turn(turn),
+            usize::from(threshold),
+        )
+    }
+}
+
+/// Sites seen by the start of `turn` (counting from 1), drawing from the atlas every turn after
+/// the opening hand.
+pub fn sites_seen_by_turn(turn: usize) -> usize {
+    OPENING_SITES + turn.saturating_sub(1)
+}
+
+fn choose(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1.0, |total, i| total * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Probability of at least `at_least` successes when drawing `draws` cards without replacement
+/// from `population` cards, `successes` of which count.
+pub fn hypergeometric_at_least(
+    population: usize,
+    successes: usize,
+    draws: usize,
+    at_least: usize,
+) -> f64 {
+    let draws = draws.min(population);
+    if at_least == 0 {
+        return 1.0;
+    }
+    let total = choose(population, draws);
+    if total == 0.0 {
+        return 0.0;
+    }
+    (at_least..=draws.min(successes))
+        .map(|hits| choose(successes, hits) * choose(population - successes, draws - hits) / total)
+        .sum::<f64>()
+        .min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::CardNameWithCount;
+
+    #[test]
+    fn hypergeometric_odds_match_known_values() {
+        // One success in a 2-card population, drawing 1.
+        assert!((hypergeometric_at_least(2, 1, 1, 1) - 0.5).abs() < 1e-9);
+        // Drawing the whole population always hits every success.
+        assert!((hypergeometric_at_least(30, 10, 30, 10) - 1.0).abs() < 1e-9);
+        assert_eq!(hypergeometric_at_least(30, 0, 5, 1), 0.0);
+        // 10 successes in 30, drawing 3: 1 - C(20,3)/C(30,3).
+        let expected = 1.0 - 1140.0 / 4060.0;
+        assert!((hypergeometric_at_least(30, 10, 3, 1) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stats_split_the_atlas_from_the_spellbook() {
+        let deck = DeckList {
+            name: "Rivers".to_string(),
+            sites: vec![CardNameWithCount {
+                count: 10,
+                name: "Spring River".to_string(),
+                is_foil: false,
+            }],
+            spells: vec![CardNameWithCount {
+                count: 4,
+                name: "Pit Vipers".to_string(),
+                is_foil: false,
+            }],
+            avatar: "Sorcerer".to_string(),
+        };
+        let stats = DeckStats::from_deck(&deck);
+
+        assert_eq!(stats.atlas_size, 10);
+        assert_eq!(stats.spellbook_size, 4);
+        assert_eq!(stats.mana_curve.values().sum::<u32>(), 4);
+        assert_eq!(stats.element(&Element::Water).sites, 10);
+        assert_eq!(stats.threshold_odds(&Element::Water, 3, 1), 1.0);
+        assert_eq!(stats.threshold_odds(&Element::Fire, 1, 5), 0.0);
+        assert_eq!(
+            stats
+                .card_types
+                .iter()
+                .find(|(card_type, _)| card_type == &CardType::Site)
+                .unwrap()
+                .1,
+            10
+        );
+    }
+}
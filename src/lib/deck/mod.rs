@@ -1,12 +1,24 @@
+pub mod analytics;
 pub mod precon;
+pub mod text;
 
 use crate::{
-    card::Card,
+    card::{Card, Rarity},
     effect::{DrawKind, Effect},
     game::{CardId, PlayerId},
 };
 use serde::{Deserialize, Serialize};
 
+/// The most copies of a card of this rarity a constructed deck may hold.
+pub fn copy_limit(rarity: &Rarity) -> u8 {
+    match rarity {
+        Rarity::Ordinary => 4,
+        Rarity::Exceptional => 3,
+        Rarity::Elite => 2,
+        Rarity::Unique => 1,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CardNameWithCount {
     pub count: u8,
@@ -107,21 +119,10 @@ impl DeckList {
     /// Rules: 1 avatar, ≥60 spellbook cards, ≥30 atlas sites,
     /// and copy limits: Ordinary ≤4, Exceptional ≤3, Elite ≤2, Unique ≤1.
     pub fn validate(&self) -> Result<(), String> {
-        use crate::card::{Rarity, card_exists, from_name};
+        use crate::card::{card_exists, from_name};
         use std::collections::HashMap;
 
-        if self.name.is_empty() {
-            return Err("Deck name cannot be empty.".to_string());
-        }
-        if let Err(err) = self.safe_filename() {
-            return Err(err.to_string());
-        }
-        if self.avatar.is_empty() {
-            return Err("Please select an avatar.".to_string());
-        }
-        if !card_exists(&self.avatar) {
-            return Err(format!("Unknown avatar: \"{}\".", self.avatar));
-        }
+        self.validate_name_and_avatar()?;
 
         // Spellbook size
         let spell_count = self.spells.iter().map(|c| c.count as usize).sum::<usize>();
@@ -153,12 +154,7 @@ impl DeckList {
         }
         for (name, &count) in &spell_counts {
             let card = from_name(name, &dummy_id);
-            let limit = match card.get_base().rarity {
-                Rarity::Ordinary => 4,
-                Rarity::Exceptional => 3,
-                Rarity::Elite => 2,
-                Rarity::Unique => 1,
-            };
+            let limit = usize::from(copy_limit(&card.get_base().rarity));
             if count > limit {
                 return Err(format!(
                     "Too many copies of \"{name}\" ({count} — max {limit} for {:?}).",
@@ -178,12 +174,7 @@ impl DeckList {
         }
         for (name, &count) in &site_counts {
             let card = from_name(name, &dummy_id);
-            let limit = match card.get_base().rarity {
-                Rarity::Ordinary => 4,
-                Rarity::Exceptional => 3,
-                Rarity::Elite => 2,
-                Rarity::Unique => 1,
-            };
+            let limit = usize::from(copy_limit(&card.get_base().rarity));
             if count > limit {
                 return Err(format!(
                     "Too many copies of site \"{name}\" ({count} — max {limit} for {:?}).",
@@ -195,6 +186,25 @@ impl DeckList {
         Ok(())
     }
 
+    /// Checks shared by every format: the deck has a usable name and a known avatar.
+    pub(crate) fn validate_name_and_avatar(&self) -> Result<(), String> {
+        use crate::card::card_exists;
+
+        if self.name.is_empty() {
+            return Err("Deck name cannot be empty.".to_string());
+        }
+        if let Err(err) = self.safe_filename() {
+            return Err(err.to_string());
+        }
+        if self.avatar.is_empty() {
+            return Err("Please select an avatar.".to_string());
+        }
+        if !card_exists(&self.avatar) {
+            return Err(format!("Unknown avatar: \"{}\".", self.avatar));
+        }
+        Ok(())
+    }
+
     /// Build a Deck and card list from this DeckList.
     pub fn build(&self, player_id: &PlayerId) -> (Deck, Vec<Box<dyn Card>>) {
         use crate::card::from_name;
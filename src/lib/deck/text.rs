@@ -0,0 +1,344 @@
+//! Plain-text deck lists.
+//!
+//! Two layouts are written: our own list, with `Avatar:`, `Atlas` and `Spellbook` sections, and
+//! the curiosa.io layout, which groups cards by type with a count after each header:
+//!
+//! ```text
+//! Avatar: Sorcerer            Avatar (1)
+//!                             1 Sorcerer
+//! Atlas
+//! 4 Arid Desert               Minion (4)
+//!                             4 Pit Vipers
+//! Spellbook
+//! 4 Pit Vipers                Site (4)
+//!                             4 Arid Desert
+//! ```
+//!
+//! Both are read by [`parse_deck_text`], which also accepts `4x Name`, a bare name for one copy,
+//! a trailing `(foil)`, and names with or without accents.
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::LazyLock,
+};
+
+use unidecode::unidecode;
+
+use super::{CardNameWithCount, DeckList};
+use crate::card::{CARD_CONSTRUCTORS, CardType, from_name};
+
+/// A problem with one line of an imported deck list. Lines are numbered from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportDiagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+/// The result of reading a deck list. Lines that cannot be read are reported and skipped.
+#[derive(Debug, Clone)]
+pub struct ImportedDeck {
+    pub deck: DeckList,
+    pub diagnostics: Vec<ImportDiagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Avatar,
+    Atlas,
+    Spellbook,
+}
+
+/// Card names keyed by their [`normalize`]d form.
+static NORMALIZED_NAMES: LazyLock<HashMap<String, &'static str>> = LazyLock::new(|| {
+    CARD_CONSTRUCTORS
+        .keys()
+        .map(|name| (normalize(name), *name))
+        .collect()
+});
+
+/// Transliterate accents the same way card image paths do, and ignore case and punctuation.
+fn normalize(name: &str) -> String {
+    unidecode(name)
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn resolve_name(name: &str) -> Option<&'static str> {
+    NORMALIZED_NAMES.get(&normalize(name)).copied()
+}
+
+fn card_type(name: &str) -> CardType {
+    from_name(name, &uuid::Uuid::nil()).get_card_type()
+}
+
+/// Read a section header such as `Atlas`, `Spellbook (60)` or `Minions:`.
+fn parse_header(line: &str) -> Option<Section> {
+    let header = line
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_end_matches(':')
+        .to_lowercase();
+    match header.as_str() {
+        "avatar" => Some(Section::Avatar),
+        "atlas" | "site" | "sites" => Some(Section::Atlas),
+        "spellbook" | "spells" | "minion" | "minions" | "magic" | "magics" | "aura" | "auras"
+        | "artifact" | "artifacts" => Some(Section::Spellbook),
+        _ => None,
+    }
+}
+
+/// Split `4x Name (foil)` into its count, name and foil flag. A line without a count is one copy.
+fn parse_entry(line: &str) -> Result<(u8, &str, bool), String> {
+    let (count, rest) = match line.split_once(char::is_whitespace) {
+        Some((count, rest)) if count.starts_with(|c: char| c.is_ascii_digit()) => {
+            let digits = count.strip_suffix(['x', 'X']).unwrap_or(count);
+            let count = digits
+                .parse::<u8>()
+                .map_err(|_| format!("\"{count}\" is not a valid number of copies."))?;
+            (count, rest.trim())
+        }
+        _ => (1, line),
+    };
+    if count == 0 {
+        return Err("A card needs at least one copy.".to_string());
+    }
+    let (name, is_foil) = match rest.strip_suffix("(foil)") {
+        Some(name) => (name.trim(), true),
+        None => (rest, false),
+    };
+    Ok((count, name, is_foil))
+}
+
+/// Read a plain-text deck list. The deck is named `name`; sites and spells are sorted by card
+/// type, whatever section they were listed under.
+pub fn parse_deck_text(name: &str, text: &str) -> ImportedDeck {
+    let mut diagnostics = Vec::new();
+    let mut avatar: Option<String> = None;
+    let mut sites: BTreeMap<(String, bool), u8> = BTreeMap::new();
+    let mut spells: BTreeMap<(String, bool), u8> = BTreeMap::new();
+    let mut section = None;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let mut report = |message: String| {
+            diagnostics.push(ImportDiagnostic {
+                line: line_number,
+                message,
+            })
+        };
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        if let Some(header) = parse_header(line) {
+            section = Some(header);
+            continue;
+        }
+        let (line, in_section) = match line.split_once(':') {
+            Some((label, value)) if label.trim().eq_ignore_ascii_case("avatar") => {
+                (value.trim(), Some(Section::Avatar))
+            }
+            _ => (line, section),
+        };
+
+        let (count, entry_name, is_foil) = match parse_entry(line) {
+            Ok(entry) => entry,
+            Err(message) => {
+                report(message);
+                continue;
+            }
+        };
+        let Some(card_name) = resolve_name(entry_name) else {
+            report(format!("Unknown card \"{entry_name}\"."));
+            continue;
+        };
+        let card_type = card_type(card_name);
+        let target = match card_type {
+            CardType::Avatar => {
+                if count > 1 {
+                    report(format!("A deck has one avatar; using one {card_name}."));
+                }
+                if let Some(previous) = avatar.replace(card_name.to_string()) {
+                    report(format!("{card_name} replaces {previous} as the avatar."));
+                }
+                continue;
+            }
+            CardType::Site => &mut sites,
+            _ => &mut spells,
+        };
+        let misplaced = match in_section {
+            Some(Section::Avatar) => Some(format!(
+                "{card_name} is not an avatar; added to the deck instead."
+            )),
+            Some(Section::Atlas) if card_type != CardType::Site => Some(format!(
+                "{card_name} is not a site; added to the spellbook."
+            )),
+            Some(Section::Spellbook) if card_type == CardType::Site => {
+                Some(format!("{card_name} is a site; added to the atlas."))
+            }
+            _ => None,
+        };
+        if let Some(message) = misplaced {
+            report(message);
+        }
+        let copies = target.entry((card_name.to_string(), is_foil)).or_default();
+        *copies = copies.saturating_add(count);
+    }
+
+    let into_cards = |cards: BTreeMap<(String, bool), u8>| {
+        cards
+            .into_iter()
+            .map(|((name, is_foil), count)| CardNameWithCount {
+                count,
+                name,
+                is_foil,
+            })
+            .collect()
+    };
+    ImportedDeck {
+        deck: DeckList {
+            name: name.to_string(),
+            sites: into_cards(sites),
+            spells: into_cards(spells),
+            avatar: avatar.unwrap_or_default(),
+        },
+        diagnostics,
+    }
+}
+
+fn write_cards(text: &mut String, cards: &[&CardNameWithCount]) {
+    for card in cards {
+        text.push_str(&format!("{} {}", card.count, card.name));
+        if card.is_foil {
+            text.push_str(" (foil)");
+        }
+        text.push('\n');
+    }
+}
+
+fn sorted(cards: &[CardNameWithCount]) -> Vec<&CardNameWithCount> {
+    let mut cards: Vec<_> = cards.iter().collect();
+    cards.sort_by(|a, b| a.name.cmp(&b.name).then(a.is_foil.cmp(&b.is_foil)));
+    cards
+}
+
+impl DeckList {
+    /// Write the deck as a plain-text list with `Avatar:`, `Atlas` and `Spellbook` sections.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Avatar: {}\n\nAtlas\n", self.avatar);
+        write_cards(&mut text, &sorted(&self.sites));
+        text.push_str("\nSpellbook\n");
+        write_cards(&mut text, &sorted(&self.spells));
+        text
+    }
+
+    /// Write the deck in the curiosa.io layout, with one section per card type.
+    pub fn to_curiosa_text(&self) -> String {
+        let mut text = format!("Avatar (1)\n1 {}\n", self.avatar);
+        let spells = sorted(&self.spells);
+        let sections = [
+            ("Aura", CardType::Aura),
+            ("Artifact", CardType::Artifact),
+            ("Minion", CardType::Minion),
+            ("Magic", CardType::Magic),
+        ];
+        for (header, section_type) in sections {
+            let cards: Vec<_> = spells
+                .iter()
+                .copied()
+                .filter(|card| card_exists_as(&card.name, &section_type))
+                .collect();
+            if cards.is_empty() {
+                continue;
+            }
+            let count: u32 = cards.iter().map(|card| u32::from(card.count)).sum();
+            text.push_str(&format!("\n{header} ({count})\n"));
+            write_cards(&mut text, &cards);
+        }
+        let sites = sorted(&self.sites);
+        let count: u32 = sites.iter().map(|card| u32::from(card.count)).sum();
+        text.push_str(&format!("\nSite ({count})\n"));
+        write_cards(&mut text, &sites);
+        text
+    }
+}
+
+fn card_exists_as(name: &str, expected: &CardType) -> bool {
+    crate::card::card_exists(name) && &card_type(name) == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_and_curiosa_lists_round_trip() {
+        let deck = DeckList {
+            name: "Vipers".to_string(),
+            sites: vec![CardNameWithCount {
+                count: 3,
+                name: "Arid Desert".to_string(),
+                is_foil: false,
+            }],
+            spells: vec![
+                CardNameWithCount {
+                    count: 4,
+                    name: "Pit Vipers".to_string(),
+                    is_foil: false,
+                },
+                CardNameWithCount {
+                    count: 1,
+                    name: "Pit Vipers".to_string(),
+                    is_foil: true,
+                },
+            ],
+            avatar: "Sorcerer".to_string(),
+        };
+
+        for text in [deck.to_text(), deck.to_curiosa_text()] {
+            let imported = parse_deck_text("Vipers", &text);
+            assert!(imported.diagnostics.is_empty(), "{text}");
+            assert_eq!(imported.deck.avatar, "Sorcerer");
+            assert_eq!(imported.deck.sites.len(), 1);
+            assert_eq!(imported.deck.sites[0].count, 3);
+            assert_eq!(imported.deck.spells.len(), 2);
+            assert!(
+                imported
+                    .deck
+                    .spells
+                    .iter()
+                    .any(|card| card.is_foil && card.count == 1)
+            );
+        }
+    }
+
+    #[test]
+    fn imports_report_problems_per_line_and_tolerate_accents() {
+        let text = "Avatar: sorcerer\n\
+                    Spellbook\n\
+                    2x pit vípers\n\
+                    4 Not A Real Card\n\
+                    0 Pit Vipers\n\
+                    Arid Desert\n";
+        let imported = parse_deck_text("Imported", text);
+
+        assert_eq!(imported.deck.avatar, "Sorcerer");
+        assert_eq!(imported.deck.spells[0].name, "Pit Vipers");
+        assert_eq!(imported.deck.spells[0].count, 2);
+        assert_eq!(imported.deck.sites[0].name, "Arid Desert");
+        assert_eq!(
+            imported
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.line)
+                .collect::<Vec<_>>(),
+            vec![4, 5, 6]
+        );
+    }
+}
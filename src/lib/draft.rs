@@ -0,0 +1,541 @@
+use crate::{
+    booster::{
+        BETA_ELITE_OR_UNIQUE_CARDS, BETA_EXCEPTIONAL_CARDS, BETA_ORDINARY_CARDS, BoosterCard,
+        BoosterPack,
+    },
+    card::{Rarity, from_name},
+    game::{PlayerId, Thresholds},
+    sealed::SealedPool,
+    state::Player,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+pub const DRAFT_MIN_SEATS: usize = 2;
+pub const DRAFT_MAX_SEATS: usize = 8;
+/// Number of packs each seat opens over the draft.
+pub const DRAFT_ROUNDS: usize = 3;
+pub const DRAFT_PACK_SIZE: usize =
+    BETA_ORDINARY_CARDS + BETA_EXCEPTIONAL_CARDS + BETA_ELITE_OR_UNIQUE_CARDS;
+/// How long a player has to pick before the server picks for them.
+pub const DRAFT_PICK_SECONDS: u64 = 45;
+/// How long a pod waits for players before its empty seats are filled with bots.
+pub const DRAFT_LOBBY_SECONDS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PassDirection {
+    Left,
+    Right,
+}
+
+impl PassDirection {
+    /// Packs go left in the first and last round and right in between.
+    pub fn for_round(round: usize) -> Self {
+        if round.is_multiple_of(2) {
+            PassDirection::Left
+        } else {
+            PassDirection::Right
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftSeat {
+    /// The player in this seat, or `None` for a bot drafter.
+    pub player: Option<Player>,
+    /// Packs passed to this seat that are waiting for a pick, oldest first.
+    pub packs: VecDeque<Vec<BoosterCard>>,
+    pub pool: Vec<BoosterCard>,
+}
+
+impl DraftSeat {
+    pub fn is_bot(&self) -> bool {
+        self.player.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DraftPhase {
+    Lobby,
+    Picking { round: usize },
+    Building,
+}
+
+/// A draft pod: its seats, the packs moving between them and the cards each seat has picked.
+#[derive(Debug, Clone)]
+pub struct DraftPod {
+    pub id: uuid::Uuid,
+    pub capacity: usize,
+    pub seats: Vec<DraftSeat>,
+    pub phase: DraftPhase,
+}
+
+impl DraftPod {
+    pub fn new(capacity: usize) -> Result<Self, String> {
+        if !(DRAFT_MIN_SEATS..=DRAFT_MAX_SEATS).contains(&capacity) {
+            return Err(format!(
+                "Draft pods have between {DRAFT_MIN_SEATS} and {DRAFT_MAX_SEATS} seats."
+            ));
+        }
+        Ok(Self {
+            id: uuid::Uuid::new_v4(),
+            capacity,
+            seats: Vec::new(),
+            phase: DraftPhase::Lobby,
+        })
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.seats.len() >= self.capacity
+    }
+
+    pub fn join(&mut self, player: Player) -> Result<usize, String> {
+        if self.phase != DraftPhase::Lobby {
+            return Err("This draft has already started.".to_string());
+        }
+        if self.is_full() {
+            return Err("This draft pod is full.".to_string());
+        }
+        self.seats.push(DraftSeat {
+            player: Some(player),
+            packs: VecDeque::new(),
+            pool: Vec::new(),
+        });
+        Ok(self.seats.len() - 1)
+    }
+
+    /// Fill the empty seats with bots and open the first round of packs.
+    pub fn start(&mut self) {
+        while !self.is_full() {
+            self.seats.push(DraftSeat {
+                player: None,
+                packs: VecDeque::new(),
+                pool: Vec::new(),
+            });
+        }
+        self.open_round(0);
+    }
+
+    fn open_round(&mut self, round: usize) {
+        for seat in &mut self.seats {
+            seat.packs.push_back(BoosterPack::beta().cards);
+        }
+        self.phase = DraftPhase::Picking { round };
+    }
+
+    pub fn seat_of(&self, player_id: &PlayerId) -> Option<usize> {
+        self.seats.iter().position(|seat| {
+            seat.player
+                .as_ref()
+                .is_some_and(|player| &player.id == player_id)
+        })
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = &Player> {
+        self.seats.iter().filter_map(|seat| seat.player.as_ref())
+    }
+
+    pub fn current_pack(&self, seat: usize) -> Option<&[BoosterCard]> {
+        self.seats
+            .get(seat)
+            .and_then(|seat| seat.packs.front())
+            .map(Vec::as_slice)
+    }
+
+    /// The 1-based number of the pick the seat is making from its current pack.
+    pub fn pick_number(&self, seat: usize) -> usize {
+        self.current_pack(seat)
+            .map(|pack| DRAFT_PACK_SIZE.saturating_sub(pack.len()) + 1)
+            .unwrap_or_default()
+    }
+
+    fn neighbour(&self, seat: usize, direction: PassDirection) -> usize {
+        let seats = self.seats.len();
+        match direction {
+            PassDirection::Left => (seat + 1) % seats,
+            PassDirection::Right => (seat + seats - 1) % seats,
+        }
+    }
+
+    /// Take the card at `index` from the seat's current pack and pass the rest on. Once every
+    /// pack of a round is empty the next round is opened, and after the last round the pod
+    /// moves on to deckbuilding.
+    pub fn pick(&mut self, seat: usize, index: usize) -> Result<BoosterCard, String> {
+        let DraftPhase::Picking { round } = self.phase else {
+            return Err("The draft is not taking picks.".to_string());
+        };
+        let Some(draft_seat) = self.seats.get_mut(seat) else {
+            return Err("There is no such seat in this pod.".to_string());
+        };
+        let Some(pack) = draft_seat.packs.front_mut() else {
+            return Err("You have no pack to pick from yet.".to_string());
+        };
+        if index >= pack.len() {
+            return Err("That card is not in your pack.".to_string());
+        }
+
+        let card = pack.remove(index);
+        draft_seat.pool.push(card.clone());
+        let pack = draft_seat.packs.pop_front().unwrap_or_default();
+        if !pack.is_empty() {
+            let neighbour = self.neighbour(seat, PassDirection::for_round(round));
+            self.seats[neighbour].packs.push_back(pack);
+        }
+
+        if self.seats.iter().all(|seat| seat.packs.is_empty()) {
+            if round + 1 < DRAFT_ROUNDS {
+                self.open_round(round + 1);
+            } else {
+                self.phase = DraftPhase::Building;
+            }
+        }
+        Ok(card)
+    }
+
+    /// Pick for the seat as a bot would. Used for bot seats and for players whose pick timer
+    /// ran out.
+    pub fn auto_pick(&mut self, seat: usize) -> Result<BoosterCard, String> {
+        let draft_seat = self
+            .seats
+            .get(seat)
+            .ok_or_else(|| "There is no such seat in this pod.".to_string())?;
+        let pack = draft_seat
+            .packs
+            .front()
+            .ok_or_else(|| "You have no pack to pick from yet.".to_string())?;
+        let index = bot_pick(pack, &draft_seat.pool);
+        self.pick(seat, index)
+    }
+
+    /// Let every bot pick from the packs in front of it until all bots are waiting on players.
+    pub fn run_bots(&mut self) {
+        loop {
+            let waiting_bots: Vec<usize> = (0..self.seats.len())
+                .filter(|&seat| self.seats[seat].is_bot() && !self.seats[seat].packs.is_empty())
+                .collect();
+            if waiting_bots.is_empty() || !matches!(self.phase, DraftPhase::Picking { .. }) {
+                return;
+            }
+            for seat in waiting_bots {
+                if !self.seats[seat].packs.is_empty() {
+                    self.auto_pick(seat).ok();
+                }
+            }
+        }
+    }
+
+    /// Remove a player from the pod. Their seat is freed while the pod is still filling and
+    /// handed to a bot once drafting has started.
+    pub fn leave(&mut self, player_id: &PlayerId) {
+        let Some(seat) = self.seat_of(player_id) else {
+            return;
+        };
+        if self.phase == DraftPhase::Lobby {
+            self.seats.remove(seat);
+        } else {
+            self.seats[seat].player = None;
+            self.run_bots();
+        }
+    }
+
+    /// The seat's drafted cards as a limited pool. Drafted decks follow the same rules as
+    /// sealed decks.
+    pub fn pool(&self, seat: usize) -> Option<SealedPool> {
+        self.seats.get(seat).map(|draft_seat| SealedPool {
+            id: self.id,
+            cards: draft_seat.pool.clone(),
+        })
+    }
+}
+
+/// Choose the card a bot takes from `pack`: the rarest card, with ties broken by how well the
+/// card's elements match what the bot has already picked.
+pub fn bot_pick(pack: &[BoosterCard], pool: &[BoosterCard]) -> usize {
+    let mut affinity = Thresholds::default();
+    for card in pool {
+        let thresholds = card_thresholds(&card.name);
+        affinity.fire += u8::from(thresholds.fire > 0);
+        affinity.air += u8::from(thresholds.air > 0);
+        affinity.earth += u8::from(thresholds.earth > 0);
+        affinity.water += u8::from(thresholds.water > 0);
+    }
+
+    let score = |card: &BoosterCard| {
+        let rarity = match from_name(&card.name, &uuid::Uuid::nil()).get_base().rarity {
+            Rarity::Ordinary => 0,
+            Rarity::Exceptional => 1,
+            Rarity::Elite => 2,
+            Rarity::Unique => 3,
+        };
+        let thresholds = card_thresholds(&card.name);
+        let elements = [
+            (thresholds.fire, affinity.fire),
+            (thresholds.air, affinity.air),
+            (thresholds.earth, affinity.earth),
+            (thresholds.water, affinity.water),
+        ];
+        let matching = elements
+            .iter()
+            .filter(|(needed, _)| *needed > 0)
+            .map(|(_, picked)| usize::from(*picked))
+            .sum::<usize>();
+        (rarity, matching)
+    };
+
+    let mut best = 0;
+    for (index, card) in pack.iter().enumerate().skip(1) {
+        if score(card) > score(&pack[best]) {
+            best = index;
+        }
+    }
+    best
+}
+
+fn card_thresholds(name: &str) -> Thresholds {
+    let card = from_name(name, &uuid::Uuid::nil());
+    match card.get_site_base() {
+        Some(site) => site.provided_thresholds.clone(),
+        None => card.get_base().costs.printed_thresholds().clone(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BracketMatch {
+    pub players: (PlayerId, PlayerId),
+    pub winner: Option<PlayerId>,
+}
+
+/// A match of a [`DraftBracket`], identified by its round and its position in that round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketSlot {
+    pub round: usize,
+    pub index: usize,
+}
+
+/// A single-elimination bracket between the players of a pod. Bots draft but do not play.
+#[derive(Debug, Clone)]
+pub struct DraftBracket {
+    pub round: usize,
+    pub matches: Vec<BracketMatch>,
+    /// Player who sits out the current round and advances automatically.
+    pub bye: Option<PlayerId>,
+    pub champion: Option<PlayerId>,
+}
+
+impl DraftBracket {
+    pub fn new(players: Vec<PlayerId>) -> Self {
+        let mut bracket = Self {
+            round: 0,
+            matches: Vec::new(),
+            bye: None,
+            champion: None,
+        };
+        bracket.pair(players);
+        bracket
+    }
+
+    fn pair(&mut self, mut players: Vec<PlayerId>) {
+        if players.len() <= 1 {
+            self.champion = players.pop();
+            self.matches.clear();
+            self.bye = None;
+            return;
+        }
+        self.bye = (players.len() % 2 == 1).then(|| players.remove(players.len() - 1));
+        self.matches = players
+            .chunks(2)
+            .map(|pair| BracketMatch {
+                players: (pair[0], pair[1]),
+                winner: None,
+            })
+            .collect();
+    }
+
+    pub fn match_of(&self, player_id: &PlayerId) -> Option<&BracketMatch> {
+        self.slot_of(player_id)
+            .map(|slot| &self.matches[slot.index])
+    }
+
+    /// The unfinished match of the current round the player is in.
+    pub fn slot_of(&self, player_id: &PlayerId) -> Option<BracketSlot> {
+        self.matches
+            .iter()
+            .position(|m| {
+                m.winner.is_none() && (&m.players.0 == player_id || &m.players.1 == player_id)
+            })
+            .map(|index| BracketSlot {
+                round: self.round,
+                index,
+            })
+    }
+
+    /// Record the winner of the match in `slot`. Results for a match that already has a winner,
+    /// or from an earlier round, are ignored. Returns true once that completes the round and the
+    /// next one has been paired.
+    pub fn record_winner(&mut self, slot: BracketSlot, winner: &PlayerId) -> bool {
+        if slot.round != self.round {
+            return false;
+        }
+        let Some(bracket_match) = self
+            .matches
+            .get_mut(slot.index)
+            .filter(|m| m.winner.is_none() && (&m.players.0 == winner || &m.players.1 == winner))
+        else {
+            return false;
+        };
+        bracket_match.winner = Some(*winner);
+
+        if self.matches.iter().any(|m| m.winner.is_none()) {
+            return false;
+        }
+        let mut advancing: Vec<PlayerId> = self.matches.iter().filter_map(|m| m.winner).collect();
+        advancing.extend(self.bye);
+        self.round += 1;
+        self.pair(advancing);
+        true
+    }
+
+    /// Drop the player from the bracket, giving their current match to their opponent. Returns
+    /// false if the player had nothing left to play.
+    pub fn forfeit(&mut self, player_id: &PlayerId) -> bool {
+        if self.bye.as_ref() == Some(player_id) {
+            self.bye = None;
+            return true;
+        }
+        let Some(slot) = self.slot_of(player_id) else {
+            return false;
+        };
+        let players = self.matches[slot.index].players;
+        let opponent = if &players.0 == player_id {
+            players.1
+        } else {
+            players.0
+        };
+        self.record_winner(slot, &opponent);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str) -> Player {
+        Player {
+            id: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn bots_fill_empty_seats_and_every_seat_drafts_three_packs() {
+        let mut pod = DraftPod::new(4).unwrap();
+        let human = player("drafter");
+        let seat = pod.join(human.clone()).unwrap();
+        pod.start();
+        assert_eq!(pod.seats.len(), 4);
+        assert_eq!(pod.players().count(), 1);
+
+        pod.run_bots();
+        while let DraftPhase::Picking { .. } = pod.phase {
+            assert!(pod.current_pack(seat).is_some());
+            pod.pick(seat, 0).unwrap();
+            pod.run_bots();
+        }
+
+        assert_eq!(pod.phase, DraftPhase::Building);
+        for draft_seat in &pod.seats {
+            assert_eq!(draft_seat.pool.len(), DRAFT_ROUNDS * DRAFT_PACK_SIZE);
+        }
+        assert_eq!(
+            pod.pool(seat).unwrap().cards.len(),
+            DRAFT_ROUNDS * DRAFT_PACK_SIZE
+        );
+    }
+
+    #[test]
+    fn packs_alternate_direction_between_rounds() {
+        let mut pod = DraftPod::new(3).unwrap();
+        for name in ["a", "b", "c"] {
+            pod.join(player(name)).unwrap();
+        }
+        pod.start();
+
+        pod.pick(0, 0).unwrap();
+        assert_eq!(pod.seats[1].packs.len(), 2);
+
+        while pod.phase == (DraftPhase::Picking { round: 0 }) {
+            let seat = (0..3)
+                .find(|&seat| pod.current_pack(seat).is_some())
+                .unwrap();
+            pod.pick(seat, 0).unwrap();
+        }
+
+        assert_eq!(pod.phase, DraftPhase::Picking { round: 1 });
+        pod.pick(0, 0).unwrap();
+        assert_eq!(pod.seats[2].packs.len(), 2);
+    }
+
+    #[test]
+    fn picks_need_a_pack_and_a_card_in_it() {
+        let mut pod = DraftPod::new(2).unwrap();
+        pod.join(player("a")).unwrap();
+        pod.join(player("b")).unwrap();
+        assert!(pod.pick(0, 0).is_err());
+
+        pod.start();
+        assert!(pod.pick(0, DRAFT_PACK_SIZE).is_err());
+        assert_eq!(pod.pick_number(0), 1);
+        pod.pick(0, 0).unwrap();
+        assert_eq!(pod.pick_number(1), 1);
+        pod.pick(1, 0).unwrap();
+        assert_eq!(pod.pick_number(1), 2);
+        assert!(DraftPod::new(DRAFT_MAX_SEATS + 1).is_err());
+    }
+
+    #[test]
+    fn bots_take_the_rarest_card() {
+        let pack = BoosterPack::beta().cards;
+        let index = bot_pick(&pack, &[]);
+        let rarity = from_name(&pack[index].name, &uuid::Uuid::nil())
+            .get_base()
+            .rarity
+            .clone();
+        assert!(matches!(rarity, Rarity::Elite | Rarity::Unique));
+    }
+
+    #[test]
+    fn brackets_advance_winners_and_byes_until_a_champion_remains() {
+        let players: Vec<PlayerId> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        let mut bracket = DraftBracket::new(players.clone());
+        assert_eq!(bracket.matches.len(), 1);
+        assert_eq!(bracket.bye, Some(players[2]));
+
+        let slot = bracket.slot_of(&players[1]).unwrap();
+        assert!(bracket.record_winner(slot, &players[0]));
+        assert_eq!(bracket.round, 1);
+        assert_eq!(bracket.matches[0].players, (players[0], players[2]));
+
+        assert!(bracket.forfeit(&players[0]));
+        assert_eq!(bracket.champion, Some(players[2]));
+        assert!(DraftBracket::new(vec![players[1]]).champion.is_some());
+    }
+
+    #[test]
+    fn results_only_count_for_the_match_they_were_played_in() {
+        let players: Vec<PlayerId> = (0..4).map(|_| uuid::Uuid::new_v4()).collect();
+        let mut bracket = DraftBracket::new(players.clone());
+        let first = bracket.slot_of(&players[0]).unwrap();
+        let second = bracket.slot_of(&players[2]).unwrap();
+
+        // Player 0 leaves while their game is still running.
+        assert!(bracket.forfeit(&players[0]));
+        assert!(bracket.record_winner(second, &players[2]));
+        assert_eq!(bracket.matches[0].players, (players[1], players[2]));
+
+        // The abandoned game ends later and must not decide the final.
+        assert!(!bracket.record_winner(first, &players[1]));
+        assert_eq!(bracket.matches[0].winner, None);
+        assert!(bracket.champion.is_none());
+    }
+}
@@ -14,6 +14,7 @@ use crate::{
     state::{OngoingEffect, Phase, State, Turn},
 };
 use std::{collections::HashMap, fmt::Debug};
+use strum_macros::IntoStaticStr;
 
 pub mod lifecycle;
 pub mod log;
@@ -150,7 +151,7 @@ pub struct SummonCard {
     pub to_location: Location,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, IntoStaticStr)]
 pub enum Effect {
     Noop,
     Notify {
@@ -480,6 +481,11 @@ impl Effect {
         Ok(sound)
     }
 
+    /// The variant's name, e.g. `"TakeDamage"`.
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
     pub fn source_id(&self) -> Option<&uuid::Uuid> {
         match self {
             Effect::Noop => None,
@@ -1455,6 +1461,8 @@ impl Effect {
 
                     if let Some(card_id) = card_id {
                         state.set_card_zone_with_sequence(&card_id, Zone::Hand);
+                    } else if state.dummy_player.as_ref() == Some(player_id) {
+                        break;
                     } else {
                         state.queue_one(Effect::PlayerLost {
                             player_id: *player_id,
@@ -1716,7 +1724,8 @@ impl Effect {
                 // The first player skips their draw on the very first turn of the game.
                 let is_first_players_first_turn =
                     state.turns == 0 && player_id == &state.player_one;
-                if !is_first_players_first_turn {
+                let is_dummy = state.dummy_player.as_ref() == Some(player_id);
+                if !is_first_players_first_turn && !is_dummy {
                     let options: Vec<BaseAction> =
                         vec![BaseAction::DrawSite, BaseAction::DrawSpell];
                     let option_labels: Vec<String> =
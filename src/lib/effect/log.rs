@@ -1,4 +1,8 @@
-use crate::{game::Game, networking::message::ServerMessage};
+use crate::{
+    game::{Game, PlayerId},
+    networking::message::ServerMessage,
+    state::State,
+};
 use chrono::Utc;
 
 use super::Effect;
@@ -7,11 +11,25 @@ use super::Effect;
 pub struct LoggedEffect {
     pub effect: Effect,
     pub turn: usize,
+    /// Who controlled the minion when a `KillMinion` resolved. The minion has usually changed
+    /// hands by the time the log is read.
+    pub victim_controller_id: Option<PlayerId>,
 }
 
 impl LoggedEffect {
-    pub fn new(effect: Effect, turn: usize) -> Self {
-        Self { effect, turn }
+    /// Log `effect` as resolved in `state`'s current turn.
+    pub fn new(effect: Effect, state: &State) -> Self {
+        let victim_controller_id = match &effect {
+            Effect::KillMinion { card_id, .. } => state
+                .try_get_card(card_id)
+                .map(|card| card.get_controller_id(state)),
+            _ => None,
+        };
+        Self {
+            effect,
+            turn: state.turns,
+            victim_controller_id,
+        }
     }
 }
 
@@ -50,10 +68,8 @@ impl EffectLogEmitter {
             .await?;
         }
 
-        let turn = game.state.turns;
-        game.state
-            .effect_log_mut()
-            .push(LoggedEffect::new(effect, turn));
+        let logged = LoggedEffect::new(effect, &game.state);
+        game.state.effect_log_mut().push(logged);
 
         Ok(())
     }
@@ -223,7 +223,12 @@ impl EffectEngine {
             match effect.apply(&mut game.state).await {
                 Ok(_) => {}
                 Err(e) => {
-                    println!("Error applying effect {:?}: {:?}", effect, e);
+                    tracing::warn!(
+                        game_id = %game.id,
+                        effect = ?effect,
+                        error = ?e,
+                        "error applying effect"
+                    );
                 }
             }
 
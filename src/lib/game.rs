@@ -3,18 +3,26 @@ use crate::{
     card::{Ability, AdditionalCost, CardType, Cost, Region},
     effect::{Effect, EffectEngine},
     error::GameError,
+    goldfish::Goldfish,
     networking::{
+        StreamWriter,
         client::Client,
-        message::{ClientMessage, ServerMessage},
+        message::{ClientMessage, DeckChoice, ServerMessage},
     },
     query::{CardQuery, QueryCache},
-    state::{Phase, PlayerWithDeck, State},
+    sandbox::{self, SANDBOX_OPPONENT_NAME},
+    state::{Phase, Player, PlayerWithDeck, State},
 };
 use async_channel::{Receiver, Sender};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, iter::Sum, sync::Arc};
+use std::{
+    collections::HashMap,
+    iter::Sum,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use strum_macros::EnumIter;
-use tokio::{net::tcp::OwnedWriteHalf, sync::Mutex};
+use tokio::sync::Mutex;
 
 pub type PlayerId = uuid::Uuid;
 pub type CardId = uuid::Uuid;
@@ -1339,11 +1347,16 @@ impl ActivatedAbility for UnitAction {
 pub struct Game {
     pub id: uuid::Uuid,
     pub state: State,
-    streams: HashMap<PlayerId, Arc<Mutex<OwnedWriteHalf>>>,
+    streams: HashMap<PlayerId, Arc<Mutex<StreamWriter>>>,
     client_receiver: Receiver<ClientMessage>,
     server_receiver: Receiver<ServerMessage>,
+    goldfish: Option<Goldfish>,
+    drain_observer: Option<EffectDrainObserver>,
 }
 
+/// Called with how long each non-empty drain of the effect queue took.
+pub type EffectDrainObserver = Arc<dyn Fn(Duration) + Send + Sync>;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GameOutcome {
     pub game_id: uuid::Uuid,
@@ -1353,7 +1366,7 @@ pub struct GameOutcome {
 
 impl Game {
     pub fn new(
-        players_with_streams: Vec<(PlayerWithDeck, Arc<Mutex<OwnedWriteHalf>>)>,
+        players_with_streams: Vec<(PlayerWithDeck, Arc<Mutex<StreamWriter>>)>,
         receiver: Receiver<ClientMessage>,
         server_sender: Sender<ServerMessage>,
         server_receiver: Receiver<ServerMessage>,
@@ -1371,10 +1384,86 @@ impl Game {
             state: State::new(game_id, players, server_sender.clone(), receiver.clone()),
             client_receiver: receiver,
             server_receiver,
+            goldfish: None,
+            drain_observer: None,
         }
     }
 
-    pub async fn start(&mut self) -> anyhow::Result<GameOutcome> {
+    /// Build a goldfish game: the player's deck against an inert dummy that has no stream.
+    pub fn goldfish(
+        goldfish: Goldfish,
+        stream: Arc<Mutex<StreamWriter>>,
+        receiver: Receiver<ClientMessage>,
+        server_sender: Sender<ServerMessage>,
+        server_receiver: Receiver<ServerMessage>,
+    ) -> Self {
+        let game_id = uuid::Uuid::new_v4();
+        Game {
+            id: game_id,
+            streams: HashMap::from([(goldfish.player.id, stream)]),
+            state: goldfish.new_state(game_id, server_sender, receiver.clone()),
+            client_receiver: receiver,
+            server_receiver,
+            goldfish: Some(goldfish),
+            drain_observer: None,
+        }
+    }
+
+    /// Build a sandbox game: `player` controls both seats, so both share their stream.
+    pub fn sandbox(
+        player: Player,
+        deck: &DeckChoice,
+        stream: Arc<Mutex<StreamWriter>>,
+        receiver: Receiver<ClientMessage>,
+        server_sender: Sender<ServerMessage>,
+        server_receiver: Receiver<ServerMessage>,
+    ) -> Self {
+        let game_id = uuid::Uuid::new_v4();
+        let opponent = Player {
+            id: uuid::Uuid::new_v4(),
+            name: SANDBOX_OPPONENT_NAME.to_string(),
+        };
+        let state = sandbox::new_state(
+            game_id,
+            &player,
+            &opponent,
+            deck,
+            server_sender,
+            receiver.clone(),
+        );
+        Game {
+            id: game_id,
+            streams: HashMap::from([(player.id, Arc::clone(&stream)), (opponent.id, stream)]),
+            state,
+            client_receiver: receiver,
+            server_receiver,
+            goldfish: None,
+            drain_observer: None,
+        }
+    }
+
+    /// Build a game around an existing state without any connected client streams. Used by tests
+    /// and headless runners that answer prompts over the state's channels directly.
+    pub fn from_state(state: State) -> Self {
+        let (_, server_receiver) = async_channel::unbounded();
+        Game {
+            id: state.game_id,
+            streams: HashMap::new(),
+            client_receiver: state.get_receiver(),
+            server_receiver,
+            state,
+            goldfish: None,
+            drain_observer: None,
+        }
+    }
+
+    /// Report how long the game spends resolving effects, for the server's metrics.
+    pub fn set_drain_observer(&mut self, observer: EffectDrainObserver) {
+        self.drain_observer = Some(observer);
+    }
+
+    /// Place the avatars, draw the opening hands and tell the players the game has started.
+    async fn set_up(&mut self) -> anyhow::Result<()> {
         self.state.queue(self.place_avatars());
         self.state.queue(self.draw_initial_six());
 
@@ -1389,13 +1478,28 @@ impl Game {
         })
         .await?;
         self.process_effects().await?;
-        self.broadcast(&self.make_sync()?).await?;
+        self.broadcast(&self.make_sync()?).await
+    }
 
+    pub async fn start(&mut self) -> anyhow::Result<GameOutcome> {
+        self.set_up().await?;
+
+        let game_id = self.id;
         let streams = self.streams.clone();
         let receiver = self.server_receiver.clone();
+        let goldfish = self.goldfish.clone();
         tokio::spawn(async move {
             loop {
                 if let Ok(message) = receiver.recv().await {
+                    if let Some(goldfish) = &goldfish
+                        && message.player_id() == goldfish.dummy.id
+                    {
+                        // The game is over once nothing is left to receive the dummy's answers.
+                        if goldfish.answer(game_id, &message).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
                     let stream = streams
                         .get(&message.player_id())
                         .expect("stream to be found");
@@ -1997,7 +2101,9 @@ impl Game {
         Ok(true)
     }
 
-    async fn handle_message(&mut self, message: &ClientMessage) -> anyhow::Result<()> {
+    /// Handle a single client message and return any error instead of logging it, unlike
+    /// [`Game::process_message`].
+    pub async fn handle_message(&mut self, message: &ClientMessage) -> anyhow::Result<()> {
         self.state.validate_client_message(message)?;
         match message {
             ClientMessage::PlayerDisconnected { player_id, .. } => {
@@ -2223,10 +2329,18 @@ impl Game {
             ClientMessage::ToggleSteppedEffects { .. } => {
                 self.state.stepped_effects = !self.state.stepped_effects;
             }
-            ClientMessage::StepNextEffect { .. } => {
-                if self.state.stepped_effects {
-                    EffectEngine::step_with_log(self).await?;
-                }
+            ClientMessage::StepNextEffect { .. } if self.state.stepped_effects => {
+                EffectEngine::step_with_log(self).await?;
+            }
+            ClientMessage::ResetGoldfish { .. } => {
+                self.reset_goldfish().await?;
+            }
+            ClientMessage::RedrawOpeners { player_id, .. } => {
+                self.redraw_openers(player_id).await?;
+            }
+            ClientMessage::Sandbox { command, .. } => {
+                command.apply(&mut self.state)?;
+                self.broadcast(&self.make_sync()?).await?;
             }
             ClientMessage::PickCards {
                 card_ids,
@@ -2309,7 +2423,7 @@ impl Game {
                         }
                     }
                 } else {
-                    println!("Error processing message: {:?}", e);
+                    tracing::error!(game_id = %self.id, error = ?e, "error processing message");
                 }
             }
         }
@@ -2319,6 +2433,7 @@ impl Game {
 
     pub async fn update(&mut self) -> anyhow::Result<()> {
         self.process_effects().await?;
+        self.pass_dummy_turn().await?;
 
         // Move attached artifacts to the same zone as the unit they are attached to
         let attached_artifacts: Vec<(uuid::Uuid, uuid::Uuid)> = CardQuery::new()
@@ -2344,8 +2459,88 @@ impl Game {
         Ok(())
     }
 
+    /// In a goldfish game the dummy ends every turn it is given as soon as it starts.
+    async fn pass_dummy_turn(&mut self) -> anyhow::Result<()> {
+        let Some(dummy_id) = self.state.dummy_player else {
+            return Ok(());
+        };
+        if self.state.phase != Phase::Main
+            || !self.state.effects.is_empty()
+            || self.state.current_turn_controller() != dummy_id
+        {
+            return Ok(());
+        }
+
+        self.state.queue_one(Effect::EndTurn {
+            player_id: dummy_id,
+        });
+        self.process_effects().await
+    }
+
+    /// Start a goldfish game over with freshly shuffled decks.
+    async fn reset_goldfish(&mut self) -> anyhow::Result<()> {
+        let Some(goldfish) = &self.goldfish else {
+            return Ok(());
+        };
+        let state = goldfish.new_state(
+            self.id,
+            self.state.get_sender(),
+            self.client_receiver.clone(),
+        );
+        QueryCache::clear_game_cache(&self.id);
+        self.state = state;
+        self.set_up().await
+    }
+
+    /// Shuffle the player's hand back into their deck and draw a new opening hand. Only
+    /// available in goldfish games, where nobody is waiting on the redraw.
+    async fn redraw_openers(&mut self, player_id: &PlayerId) -> anyhow::Result<()> {
+        if self.goldfish.is_none() {
+            return Ok(());
+        }
+
+        let hand: Vec<CardId> = self
+            .state
+            .all_cards()
+            .filter(|card| card.get_zone() == &Zone::Hand && card.get_owner_id() == player_id)
+            .map(|card| *card.get_id())
+            .collect();
+        let mut deck = self.state.get_player_deck(player_id)?.clone();
+        for card_id in hand {
+            let card = self.state.get_card_mut(&card_id);
+            if card.get_card_type() == CardType::Site {
+                deck.sites.push(card_id);
+                card.set_zone(Zone::Atlasbook);
+            } else {
+                deck.spells.push(card_id);
+                card.set_zone(Zone::Spellbook);
+            }
+        }
+        deck.shuffle();
+        self.state.decks.insert(*player_id, deck);
+        self.state.queue(vec![
+            Effect::DrawCard {
+                player_id: *player_id,
+                count: 3,
+                kind: DrawKind::Site,
+            },
+            Effect::DrawCard {
+                player_id: *player_id,
+                count: 3,
+                kind: DrawKind::Spell,
+            },
+        ]);
+        Ok(())
+    }
+
     pub async fn broadcast(&self, message: &ServerMessage) -> anyhow::Result<()> {
+        // Both seats of a sandbox game share one stream, which should get each message once.
+        let mut sent: Vec<&Arc<Mutex<StreamWriter>>> = Vec::new();
         for stream in self.streams.values() {
+            if sent.iter().any(|other| Arc::ptr_eq(other, stream)) {
+                continue;
+            }
+            sent.push(stream);
             Client::send_to_stream(message, Arc::clone(stream)).await?;
         }
         Ok(())
@@ -2440,10 +2635,15 @@ impl Game {
     }
 
     pub async fn process_effects(&mut self) -> anyhow::Result<()> {
-        if self.state.stepped_effects {
+        if self.state.stepped_effects || self.state.effects.is_empty() {
             return Ok(());
         }
-        EffectEngine::drain_with_log(self).await
+        let started = Instant::now();
+        let result = EffectEngine::drain_with_log(self).await;
+        if let Some(observer) = &self.drain_observer {
+            observer(started.elapsed());
+        }
+        result
     }
 }
 
@@ -2456,6 +2656,7 @@ mod tests {
             SpringRiver, TvinnaxBerserker, YokaiKappas,
         },
         deck::Deck,
+        networking::message::DebugData,
         state::Player,
     };
 
@@ -2522,6 +2723,8 @@ mod tests {
             streams: HashMap::new(),
             client_receiver: client_rx,
             server_receiver: unused_server_rx,
+            goldfish: None,
+            drain_observer: None,
         };
 
         (
@@ -2720,6 +2923,8 @@ mod tests {
             streams: HashMap::new(),
             client_receiver: client_rx,
             server_receiver: unused_server_rx,
+            goldfish: None,
+            drain_observer: None,
         };
 
         tokio::spawn(async move {
@@ -2909,6 +3114,8 @@ mod tests {
             streams: HashMap::new(),
             client_receiver: client_rx,
             server_receiver: unused_server_rx,
+            goldfish: None,
+            drain_observer: None,
         };
 
         game.handle_message(&ClientMessage::PlayCardAtLocation {
@@ -3264,4 +3471,34 @@ mod tests {
             }] if *attacker_id == tvinnax_id && *target_id == defender_id
         ));
     }
+
+    #[tokio::test]
+    async fn stepped_effects_are_rejected_outside_dev_mode() {
+        let (mut game, player_id, ..) = test_game_with_avatars();
+        let toggle = ClientMessage::ToggleSteppedEffects {
+            game_id: game.id,
+            player_id,
+        };
+
+        assert!(game.handle_message(&toggle).await.is_err());
+        assert!(!game.state.stepped_effects);
+        assert!(matches!(
+            game.make_sync().unwrap(),
+            ServerMessage::Sync { debug: None, .. }
+        ));
+
+        game.state.dev_mode = true;
+        game.handle_message(&toggle).await.unwrap();
+        assert!(game.state.stepped_effects);
+        assert!(matches!(
+            game.make_sync().unwrap(),
+            ServerMessage::Sync {
+                debug: Some(DebugData {
+                    stepped_effects: true,
+                    ..
+                }),
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,276 @@
+//! Goldfish games: a deck played alone against an inert dummy avatar.
+//!
+//! The dummy is a Sorcerer with an empty deck. It keeps its empty opening hand, never draws,
+//! passes every turn it is given and answers any prompt with the first option, so the real
+//! engine runs the player's deck without a second human in the queue.
+use crate::{
+    card::{Card, Sorcerer},
+    deck::Deck,
+    networking::message::{ClientMessage, DeckChoice, ServerMessage},
+    state::{Player, PlayerWithDeck, State},
+};
+use async_channel::{Receiver, Sender};
+use std::collections::HashMap;
+
+/// The name shown for the dummy opponent.
+pub const DUMMY_NAME: &str = "Goldfish";
+
+/// The setup of a goldfish game, kept so it can be reset with freshly shuffled decks.
+#[derive(Debug, Clone)]
+pub struct Goldfish {
+    pub player: Player,
+    pub deck: DeckChoice,
+    pub dummy: Player,
+    /// Feeds the dummy's answers back into the game's client channel.
+    dummy_sender: Sender<ClientMessage>,
+}
+
+impl Goldfish {
+    pub fn new(player: Player, deck: DeckChoice, dummy_sender: Sender<ClientMessage>) -> Self {
+        Self {
+            player,
+            deck,
+            dummy: Player {
+                id: uuid::Uuid::new_v4(),
+                name: DUMMY_NAME.to_string(),
+            },
+            dummy_sender,
+        }
+    }
+
+    /// A fresh state with the player's deck shuffled. The dummy has already kept its hand, so the
+    /// game leaves the mulligan as soon as the player keeps theirs.
+    pub(crate) fn new_state(
+        &self,
+        game_id: uuid::Uuid,
+        server_tx: Sender<ServerMessage>,
+        client_rx: Receiver<ClientMessage>,
+    ) -> State {
+        let (deck, cards) = self.deck.build(&self.player.id);
+        let avatar = Sorcerer::new(self.dummy.id);
+        let dummy = PlayerWithDeck {
+            player: self.dummy.clone(),
+            deck: Deck::new(
+                &self.dummy.id,
+                DUMMY_NAME.to_string(),
+                vec![],
+                vec![],
+                *avatar.get_id(),
+            ),
+            cards: vec![Box::new(avatar) as Box<dyn Card>],
+        };
+        let player = PlayerWithDeck {
+            player: self.player.clone(),
+            deck,
+            cards,
+        };
+
+        let mut state = State::new(game_id, vec![player, dummy], server_tx, client_rx);
+        state.dummy_player = Some(self.dummy.id);
+        state.players_with_accepted_hands.insert(self.dummy.id);
+        state
+    }
+
+    /// Answer a message the engine sent to the dummy. Messages that aren't prompts are dropped.
+    pub(crate) async fn answer(
+        &self,
+        game_id: uuid::Uuid,
+        message: &ServerMessage,
+    ) -> anyhow::Result<()> {
+        if let Some(reply) = dummy_answer(game_id, message) {
+            self.dummy_sender.send(reply).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The dummy's answer to a prompt: the first option offered, no cards where several may be
+/// picked, the lowest amount, and no to optional actions. A prompt with nothing to choose from
+/// is abandoned the same way a disconnect would abandon it.
+pub fn dummy_answer(game_id: uuid::Uuid, prompt: &ServerMessage) -> Option<ClientMessage> {
+    let player_id = prompt.player_id();
+    let abandon = ClientMessage::PlayerDisconnected { game_id, player_id };
+    let answer = match prompt {
+        ServerMessage::PickCard { pickable_cards, .. } => {
+            pickable_cards
+                .first()
+                .map(|card_id| ClientMessage::PickCard {
+                    game_id,
+                    player_id,
+                    card_id: *card_id,
+                })
+        }
+        ServerMessage::PickCards { .. } => Some(ClientMessage::PickCards {
+            game_id,
+            player_id,
+            card_ids: vec![],
+        }),
+        ServerMessage::PickLocation { locations, .. } => {
+            locations
+                .first()
+                .map(|location| ClientMessage::PickLocation {
+                    game_id,
+                    player_id,
+                    location: location.clone(),
+                })
+        }
+        ServerMessage::PickAction { actions, .. } => {
+            (!actions.is_empty()).then_some(ClientMessage::PickAction {
+                game_id,
+                player_id,
+                action_idx: 0,
+            })
+        }
+        ServerMessage::PickDirection { directions, .. } => {
+            directions
+                .first()
+                .map(|direction| ClientMessage::PickDirection {
+                    game_id,
+                    player_id,
+                    direction: direction.clone(),
+                })
+        }
+        ServerMessage::PickAmount { min_amount, .. } => Some(ClientMessage::PickAmount {
+            game_id,
+            player_id,
+            amount: *min_amount,
+        }),
+        ServerMessage::PickPath { paths, .. } => {
+            paths.first().map(|path| ClientMessage::PickPath {
+                game_id,
+                player_id,
+                path: path.clone(),
+            })
+        }
+        ServerMessage::PickLocationGroup { groups, .. } => {
+            (!groups.is_empty()).then_some(ClientMessage::PickLocationGroup {
+                game_id,
+                player_id,
+                group_idx: 0,
+            })
+        }
+        ServerMessage::RevealCards {
+            action: Some(_), ..
+        } => Some(ClientMessage::ResolveAction {
+            game_id,
+            player_id,
+            take_action: false,
+        }),
+        ServerMessage::DistributeDamage {
+            defenders, damage, ..
+        } => defenders
+            .first()
+            .map(|defender_id| ClientMessage::ResolveCombat {
+                game_id,
+                player_id,
+                damage_assignment: HashMap::from([(*defender_id, *damage)]),
+            }),
+        _ => return None,
+    };
+    Some(answer.unwrap_or(abandon))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        effect::{DrawKind, Effect},
+        harness::Harness,
+        scenario::Scenario,
+        state::Phase,
+    };
+
+    #[test]
+    fn the_dummy_takes_the_first_option_and_declines_actions() {
+        let game_id = uuid::Uuid::new_v4();
+        let player_id = uuid::Uuid::new_v4();
+        let cards = vec![uuid::Uuid::new_v4(), uuid::Uuid::new_v4()];
+
+        let pick = dummy_answer(
+            game_id,
+            &ServerMessage::PickCard {
+                player_id,
+                cards: cards.clone(),
+                pickable_cards: cards.clone(),
+                prompt: "Pick a card".to_string(),
+                source_card_id: None,
+                preview: false,
+            },
+        );
+        assert!(matches!(
+            pick,
+            Some(ClientMessage::PickCard { card_id, .. }) if card_id == cards[0]
+        ));
+
+        let action = dummy_answer(
+            game_id,
+            &ServerMessage::RevealCards {
+                player_id,
+                cards: cards.clone(),
+                prompt: "Reveal".to_string(),
+                source_card_id: None,
+                action: Some("Discard".to_string()),
+            },
+        );
+        assert!(matches!(
+            action,
+            Some(ClientMessage::ResolveAction {
+                take_action: false,
+                ..
+            })
+        ));
+
+        let nothing_to_pick = dummy_answer(
+            game_id,
+            &ServerMessage::PickCard {
+                player_id,
+                cards: vec![],
+                pickable_cards: vec![],
+                prompt: "Pick a card".to_string(),
+                source_card_id: None,
+                preview: false,
+            },
+        );
+        assert!(matches!(
+            nothing_to_pick,
+            Some(ClientMessage::PlayerDisconnected { .. })
+        ));
+        assert!(dummy_answer(game_id, &ServerMessage::Resume { player_id }).is_none());
+    }
+
+    #[tokio::test]
+    async fn openers_can_only_be_redrawn_during_the_mulligan() {
+        let mut harness = Harness::new(&Scenario::default());
+        let redraw = ClientMessage::RedrawOpeners {
+            game_id: harness.state().game_id,
+            player_id: harness.player_one,
+        };
+        assert!(harness.state().validate_client_message(&redraw).is_ok());
+
+        harness.state_mut().phase = Phase::Main;
+        assert!(harness.state().validate_client_message(&redraw).is_err());
+    }
+
+    #[tokio::test]
+    async fn the_dummy_does_not_lose_to_an_empty_deck() {
+        let mut harness = Harness::new(&Scenario::default());
+        let dummy = harness.player_two;
+        harness.state_mut().dummy_player = Some(dummy);
+
+        harness
+            .resolve([Effect::DrawCard {
+                player_id: dummy,
+                count: 1,
+                kind: DrawKind::Spell,
+            }])
+            .await
+            .unwrap();
+
+        assert!(
+            !harness
+                .logged_effect_names()
+                .contains(&"PlayerLost".to_string())
+        );
+        assert!(harness.state().winner_if_game_over().is_none());
+    }
+}
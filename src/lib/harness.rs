@@ -0,0 +1,703 @@
+//! Scripted-decision harness for card behaviour tests.
+//!
+//! The harness wraps a [`Game`] built from a [`Scenario`] and answers every prompt the engine
+//! sends over `state.server_tx` from a script of [`Decision`]s, so a card test only has to
+//! describe the board, the decisions a player makes, and the expected outcome:
+//!
+//! ```ignore
+//! let mut harness = Harness::new(&scenario);
+//! let teleport = harness.card(Teleport::NAME);
+//! harness.script([Decision::CardNamed(KiteArcher::NAME), Decision::Square(8)]);
+//! harness.cast(&teleport).await.unwrap();
+//! harness.assert_logged_in_order(&["PlayMagic", "TeleportCard"]);
+//! ```
+use crate::{
+    card::{Card, Region, Sorcerer},
+    deck::Deck,
+    effect::{Effect, EffectEngine, LoggedEffect},
+    game::{CardId, Direction, Game, PlayerId},
+    networking::message::{ClientMessage, ServerMessage},
+    query::QueryCache,
+    scenario::Scenario,
+    state::{Player, PlayerWithDeck, State},
+    zone::{Location, Zone},
+};
+use async_channel::{Receiver, Sender};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// A scripted answer to the next prompt the engine sends.
+#[derive(Debug, Clone)]
+pub(crate) enum Decision {
+    /// Answer a `PickCard` prompt with the first offered card with this name.
+    CardNamed(&'static str),
+    /// Answer a `PickCard` prompt with this exact card.
+    Card(CardId),
+    /// Answer a `PickCards` prompt with one offered card per name.
+    CardsNamed(Vec<&'static str>),
+    /// Answer a `PickLocation` prompt with the offered location on this square, preferring the
+    /// surface.
+    Square(u8),
+    /// Answer a `PickLocation` prompt with this exact location.
+    Location(Location),
+    /// Answer a `PickAction` prompt with the action with this name.
+    Action(&'static str),
+    /// Answer a `PickDirection` prompt with this direction.
+    Direction(Direction),
+    /// Answer a `PickAmount` prompt with this amount.
+    Amount(u8),
+    /// Answer a `PickPath` prompt with the path at this index.
+    Path(usize),
+    /// Answer a `PickLocationGroup` prompt with the group at this index.
+    LocationGroup(usize),
+    /// Answer a `RevealCards` prompt that offers an action.
+    TakeAction(bool),
+    /// Answer a `DistributeDamage` prompt by assigning all damage to the defender with this
+    /// name.
+    AssignDamageTo(&'static str),
+}
+
+#[derive(Default)]
+struct Script {
+    decisions: VecDeque<Decision>,
+    card_names: HashMap<CardId, String>,
+    failures: Vec<String>,
+}
+
+pub(crate) struct Harness {
+    pub game: Game,
+    pub player_one: PlayerId,
+    pub player_two: PlayerId,
+    script: Arc<Mutex<Script>>,
+}
+
+impl Harness {
+    /// Create a game with two Sorcerer avatars on their starting squares and apply `scenario` on
+    /// top of it.
+    pub fn new(scenario: &Scenario) -> Self {
+        QueryCache::init();
+
+        let player_one = uuid::Uuid::new_v4();
+        let player_two = uuid::Uuid::new_v4();
+        let players = [(player_one, 3), (player_two, 18)]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (player_id, square))| {
+                let mut avatar = Sorcerer::new(player_id);
+                avatar.set_zone(Zone::Location(Location::Square(square, Region::Surface)));
+                PlayerWithDeck {
+                    player: Player {
+                        id: player_id,
+                        name: format!("Player {}", idx + 1),
+                    },
+                    deck: Deck::new(
+                        &player_id,
+                        "Test Deck".to_string(),
+                        vec![],
+                        vec![],
+                        *avatar.get_id(),
+                    ),
+                    cards: vec![Box::new(avatar) as Box<dyn Card>],
+                }
+            })
+            .collect();
+
+        let (server_tx, server_rx) = async_channel::unbounded();
+        let (client_tx, client_rx) = async_channel::unbounded();
+        let mut state = State::new(uuid::Uuid::new_v4(), players, server_tx, client_rx);
+        scenario
+            .apply(&mut state)
+            .expect("scenario should apply to the harness state");
+
+        let script = Arc::new(Mutex::new(Script::default()));
+        tokio::spawn(respond(
+            state.game_id,
+            Arc::clone(&script),
+            server_rx,
+            client_tx,
+        ));
+
+        Self {
+            game: Game::from_state(state),
+            player_one,
+            player_two,
+            script,
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.game.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut State {
+        &mut self.game.state
+    }
+
+    /// Returns the first card with the given name, in any zone.
+    pub fn card(&self, name: &str) -> CardId {
+        self.cards(name)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("no card named {name} in the harness state"))
+    }
+
+    /// Returns every card with the given name, in any zone, ordered by zone for stable results.
+    pub fn cards(&self, name: &str) -> Vec<CardId> {
+        let mut cards: Vec<&dyn Card> = self
+            .state()
+            .all_cards()
+            .filter(|card| card.get_name() == name)
+            .collect();
+        cards.sort_by(|a, b| a.get_zone().cmp(b.get_zone()));
+        cards.into_iter().map(|card| *card.get_id()).collect()
+    }
+
+    pub fn zone_of(&self, card_id: &CardId) -> &Zone {
+        self.state().get_card(card_id).get_zone()
+    }
+
+    /// Appends decisions to the script. Decisions are consumed in order, one per prompt.
+    pub fn script(&self, decisions: impl IntoIterator<Item = Decision>) {
+        self.script
+            .lock()
+            .expect("script lock")
+            .decisions
+            .extend(decisions);
+    }
+
+    /// Queue `effects` and resolve them, answering prompts from the script. Fails if a prompt
+    /// did not match the next scripted decision or if scripted decisions were left unused.
+    pub async fn resolve(
+        &mut self,
+        effects: impl IntoIterator<Item = Effect>,
+    ) -> anyhow::Result<()> {
+        {
+            let mut script = self.script.lock().expect("script lock");
+            script.card_names = self
+                .state()
+                .all_cards()
+                .map(|card| (*card.get_id(), card.get_name().to_string()))
+                .collect();
+        }
+
+        self.game.state.queue(effects);
+        let result = EffectEngine::drain_with_log(&mut self.game).await;
+
+        let script = self.script.lock().expect("script lock");
+        if !script.failures.is_empty() {
+            return Err(anyhow::anyhow!(script.failures.join("\n")));
+        }
+        result?;
+        if !script.decisions.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} scripted decisions were not used: {:?}",
+                script.decisions.len(),
+                script.decisions
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Cast a magic spell with its owner's avatar and resolve it.
+    pub async fn cast(&mut self, card_id: &CardId) -> anyhow::Result<()> {
+        let player_id = *self.state().get_card(card_id).get_owner_id();
+        let caster_id = self.state().get_player_avatar_id(&player_id)?;
+        let from = self
+            .zone_of(&caster_id)
+            .location()
+            .cloned()
+            .ok_or(anyhow::anyhow!("avatar is not in a location"))?;
+        self.resolve([Effect::PlayMagic {
+            player_id,
+            card_id: *card_id,
+            caster_id,
+            from,
+        }])
+        .await
+    }
+
+    /// Play a card at `location` with its owner's avatar as the spellcaster and resolve it.
+    pub async fn play(&mut self, card_id: &CardId, location: Location) -> anyhow::Result<()> {
+        let player_id = *self.state().get_card(card_id).get_owner_id();
+        let spellcaster = self.state().get_player_avatar_id(&player_id)?;
+        self.resolve([Effect::PlayCard {
+            player_id,
+            card_id: *card_id,
+            location,
+            spellcaster,
+        }])
+        .await
+    }
+
+    pub fn logged_effects(&self) -> &[LoggedEffect] {
+        self.state().effect_log()
+    }
+
+    /// Names of the logged effects, e.g. `"TakeDamage"`, in resolution order.
+    pub fn logged_effect_names(&self) -> Vec<String> {
+        self.logged_effects()
+            .iter()
+            .map(|logged| logged.effect.name().to_string())
+            .collect()
+    }
+
+    /// Asserts that the named effects were logged in this order. Other effects may be logged in
+    /// between.
+    pub fn assert_logged_in_order(&self, expected: &[&str]) {
+        let logged = self.logged_effect_names();
+        let mut remaining = expected.iter().peekable();
+        for name in &logged {
+            if remaining.peek().is_some_and(|next| *next == name) {
+                remaining.next();
+            }
+        }
+        assert!(
+            remaining.peek().is_none(),
+            "expected effects {:?} in order, logged {:?}",
+            expected,
+            logged
+        );
+    }
+}
+
+async fn respond(
+    game_id: uuid::Uuid,
+    script: Arc<Mutex<Script>>,
+    server_rx: Receiver<ServerMessage>,
+    client_tx: Sender<ClientMessage>,
+) {
+    while let Ok(message) = server_rx.recv().await {
+        if let ServerMessage::ForceSync { cards, .. } = &message {
+            let mut script = script.lock().expect("script lock");
+            for card in cards {
+                script.card_names.insert(card.id, card.name.clone());
+            }
+            continue;
+        }
+
+        let is_prompt = match &message {
+            ServerMessage::RevealCards { action, .. } => action.is_some(),
+            ServerMessage::PickCard { .. }
+            | ServerMessage::PickCards { .. }
+            | ServerMessage::PickLocation { .. }
+            | ServerMessage::PickAction { .. }
+            | ServerMessage::PickDirection { .. }
+            | ServerMessage::PickAmount { .. }
+            | ServerMessage::PickPath { .. }
+            | ServerMessage::PickLocationGroup { .. }
+            | ServerMessage::DistributeDamage { .. } => true,
+            _ => false,
+        };
+        if !is_prompt {
+            continue;
+        }
+
+        let player_id = message.player_id();
+        let answer = {
+            let mut script = script.lock().expect("script lock");
+            match script.decisions.pop_front() {
+                Some(decision) => answer(game_id, player_id, &decision, &message, &script)
+                    .map_err(|err| format!("decision {decision:?} does not fit prompt: {err}")),
+                None => Err(format!(
+                    "unexpected prompt with no scripted decision: {message:?}"
+                )),
+            }
+        };
+
+        let reply = match answer {
+            Ok(reply) => reply,
+            Err(failure) => {
+                script.lock().expect("script lock").failures.push(failure);
+                // Abort the pending prompt so the effect engine returns instead of waiting for
+                // an answer that will never come.
+                ClientMessage::PlayerDisconnected { game_id, player_id }
+            }
+        };
+        if client_tx.send(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn answer(
+    game_id: uuid::Uuid,
+    player_id: PlayerId,
+    decision: &Decision,
+    prompt: &ServerMessage,
+    script: &Script,
+) -> Result<ClientMessage, String> {
+    let named = |cards: &[CardId], name: &str| {
+        cards
+            .iter()
+            .find(|id| script.card_names.get(id).is_some_and(|n| n == name))
+            .copied()
+            .ok_or(format!("no offered card is named {name}"))
+    };
+
+    match (decision, prompt) {
+        (Decision::CardNamed(name), ServerMessage::PickCard { pickable_cards, .. }) => {
+            Ok(ClientMessage::PickCard {
+                game_id,
+                player_id,
+                card_id: named(pickable_cards, name)?,
+            })
+        }
+        (Decision::Card(card_id), ServerMessage::PickCard { pickable_cards, .. }) => {
+            if !pickable_cards.contains(card_id) {
+                return Err("card is not pickable".to_string());
+            }
+            Ok(ClientMessage::PickCard {
+                game_id,
+                player_id,
+                card_id: *card_id,
+            })
+        }
+        (Decision::CardsNamed(names), ServerMessage::PickCards { cards, .. }) => {
+            let mut remaining = cards.clone();
+            let mut card_ids = vec![];
+            for name in names {
+                let card_id = named(&remaining, name)?;
+                remaining.retain(|id| id != &card_id);
+                card_ids.push(card_id);
+            }
+            Ok(ClientMessage::PickCards {
+                game_id,
+                player_id,
+                card_ids,
+            })
+        }
+        (Decision::Square(square), ServerMessage::PickLocation { locations, .. }) => {
+            let on_square: Vec<&Location> = locations
+                .iter()
+                .filter(|location| location.square() == Some(*square))
+                .collect();
+            let location = on_square
+                .iter()
+                .find(|location| location.region() == &Region::Surface)
+                .or(on_square.first())
+                .ok_or(format!("square {square} is not offered in {locations:?}"))?;
+            Ok(ClientMessage::PickLocation {
+                game_id,
+                player_id,
+                location: (*location).clone(),
+            })
+        }
+        (Decision::Location(location), ServerMessage::PickLocation { locations, .. }) => {
+            if !locations.contains(location) {
+                return Err(format!("{location:?} is not offered in {locations:?}"));
+            }
+            Ok(ClientMessage::PickLocation {
+                game_id,
+                player_id,
+                location: location.clone(),
+            })
+        }
+        (Decision::Action(name), ServerMessage::PickAction { actions, .. }) => {
+            let action_idx = actions
+                .iter()
+                .position(|action| action == name)
+                .ok_or(format!("action {name} is not offered in {actions:?}"))?;
+            Ok(ClientMessage::PickAction {
+                game_id,
+                player_id,
+                action_idx,
+            })
+        }
+        (Decision::Direction(direction), ServerMessage::PickDirection { directions, .. }) => {
+            if !directions.contains(direction) {
+                return Err(format!("{direction:?} is not offered in {directions:?}"));
+            }
+            Ok(ClientMessage::PickDirection {
+                game_id,
+                player_id,
+                direction: direction.clone(),
+            })
+        }
+        (
+            Decision::Amount(amount),
+            ServerMessage::PickAmount {
+                min_amount,
+                max_amount,
+                ..
+            },
+        ) => {
+            if !(*min_amount..=*max_amount).contains(amount) {
+                return Err(format!("{amount} is outside {min_amount}..={max_amount}"));
+            }
+            Ok(ClientMessage::PickAmount {
+                game_id,
+                player_id,
+                amount: *amount,
+            })
+        }
+        (Decision::Path(idx), ServerMessage::PickPath { paths, .. }) => {
+            let path = paths
+                .get(*idx)
+                .ok_or(format!("only {} paths are offered", paths.len()))?;
+            Ok(ClientMessage::PickPath {
+                game_id,
+                player_id,
+                path: path.clone(),
+            })
+        }
+        (Decision::LocationGroup(idx), ServerMessage::PickLocationGroup { groups, .. }) => {
+            if *idx >= groups.len() {
+                return Err(format!("only {} groups are offered", groups.len()));
+            }
+            Ok(ClientMessage::PickLocationGroup {
+                game_id,
+                player_id,
+                group_idx: *idx,
+            })
+        }
+        (Decision::TakeAction(take_action), ServerMessage::RevealCards { .. }) => {
+            Ok(ClientMessage::ResolveAction {
+                game_id,
+                player_id,
+                take_action: *take_action,
+            })
+        }
+        (
+            Decision::AssignDamageTo(name),
+            ServerMessage::DistributeDamage {
+                defenders, damage, ..
+            },
+        ) => Ok(ClientMessage::ResolveCombat {
+            game_id,
+            player_id,
+            damage_assignment: HashMap::from([(named(defenders, name)?, *damage)]),
+        }),
+        _ => Err("prompt kind does not match".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::{AridDesert, KiteArcher, MarineVoyage, SpringRiver, Teleport, VanguardKnights},
+        game::{CARDINAL_DIRECTIONS, pick_direction, pick_path},
+        scenario::{ScenarioCard, ScenarioPlayer},
+    };
+
+    fn on_square(name: &str, square: u8) -> ScenarioCard {
+        ScenarioCard {
+            name: name.to_string(),
+            zone: Zone::Location(Location::Square(square, Region::Surface)),
+            damage: 0,
+            tapped: false,
+            statuses: vec![],
+        }
+    }
+
+    fn teleport_scenario() -> Scenario {
+        let mut cards: Vec<ScenarioCard> = [3, 8, 13]
+            .into_iter()
+            .map(|square| on_square(AridDesert::NAME, square))
+            .collect();
+        cards.push(on_square(KiteArcher::NAME, 3));
+        cards.push(ScenarioCard {
+            zone: Zone::Hand,
+            ..on_square(Teleport::NAME, 0)
+        });
+        Scenario {
+            name: "Teleport".to_string(),
+            players: vec![ScenarioPlayer {
+                mana: Some(5),
+                cards,
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn scripted_decisions_answer_card_and_location_prompts() {
+        let mut harness = Harness::new(&teleport_scenario());
+        let teleport = harness.card(Teleport::NAME);
+        let archer = harness.card(KiteArcher::NAME);
+
+        harness.script([Decision::CardNamed(KiteArcher::NAME), Decision::Square(13)]);
+        harness.cast(&teleport).await.unwrap();
+
+        assert_eq!(
+            harness.zone_of(&archer),
+            &Zone::Location(Location::Square(13, Region::Surface))
+        );
+        harness.assert_logged_in_order(&["PlayMagic", "TeleportCard"]);
+    }
+
+    #[tokio::test]
+    async fn played_cards_can_be_picked_by_id() {
+        let mut scenario = teleport_scenario();
+        scenario.players[0].cards.push(ScenarioCard {
+            zone: Zone::Hand,
+            ..on_square(KiteArcher::NAME, 0)
+        });
+        let mut harness = Harness::new(&scenario);
+        let teleport = harness.card(Teleport::NAME);
+        let in_hand = harness
+            .cards(KiteArcher::NAME)
+            .into_iter()
+            .find(|card_id| harness.zone_of(card_id) == &Zone::Hand)
+            .unwrap();
+
+        let square_8 = Location::Square(8, Region::Surface);
+        harness.play(&in_hand, square_8.clone()).await.unwrap();
+        assert_eq!(harness.zone_of(&in_hand), &Zone::Location(square_8));
+
+        let square_13 = Location::Square(13, Region::Surface);
+        harness.script([
+            Decision::Card(in_hand),
+            Decision::Location(square_13.clone()),
+        ]);
+        harness.cast(&teleport).await.unwrap();
+        assert_eq!(harness.zone_of(&in_hand), &Zone::Location(square_13));
+    }
+
+    #[tokio::test]
+    async fn mismatched_decisions_fail_the_resolution() {
+        let mut harness = Harness::new(&teleport_scenario());
+        let teleport = harness.card(Teleport::NAME);
+
+        harness.script([Decision::Square(13)]);
+        assert!(harness.cast(&teleport).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn unused_decisions_fail_the_resolution() {
+        let mut harness = Harness::new(&teleport_scenario());
+        let teleport = harness.card(Teleport::NAME);
+
+        harness.script([
+            Decision::CardNamed(KiteArcher::NAME),
+            Decision::Square(13),
+            Decision::Amount(1),
+        ]);
+        assert!(harness.cast(&teleport).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn scripted_decisions_answer_defence_and_damage_prompts() {
+        let mut scenario = teleport_scenario();
+        scenario.players.push(ScenarioPlayer {
+            cards: vec![
+                on_square(KiteArcher::NAME, 8),
+                on_square(VanguardKnights::NAME, 8),
+                on_square(VanguardKnights::NAME, 8),
+            ],
+            ..Default::default()
+        });
+        let mut harness = Harness::new(&scenario);
+        let attacker_id = harness
+            .state()
+            .get_player_avatar_id(&harness.player_one)
+            .unwrap();
+        let target_id = *harness
+            .cards(KiteArcher::NAME)
+            .iter()
+            .find(|card_id| harness.state().get_card(card_id).get_owner_id() == &harness.player_two)
+            .unwrap();
+
+        harness.script([
+            Decision::Action("Yes"),
+            Decision::CardsNamed(vec![VanguardKnights::NAME, VanguardKnights::NAME]),
+            Decision::AssignDamageTo(VanguardKnights::NAME),
+        ]);
+        harness
+            .resolve([Effect::DeclareAttack {
+                attacker_id,
+                target_id,
+            }])
+            .await
+            .unwrap();
+
+        harness.assert_logged_in_order(&["DeclareAttack", "DeclareDefender"]);
+    }
+
+    #[tokio::test]
+    async fn scripted_decisions_answer_direction_prompts() {
+        let harness = Harness::new(&teleport_scenario());
+        let archer = harness.card(KiteArcher::NAME);
+
+        harness.script([Decision::Direction(Direction::Up)]);
+        let direction = pick_direction(
+            harness.player_one,
+            &CARDINAL_DIRECTIONS,
+            harness.state(),
+            "Pick a direction",
+            archer,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(direction, Direction::Up);
+    }
+
+    #[tokio::test]
+    async fn scripted_decisions_answer_location_group_prompts() {
+        let mut scenario = teleport_scenario();
+        scenario.players[0].cards = vec![
+            on_square(SpringRiver::NAME, 3),
+            on_square(AridDesert::NAME, 8),
+            on_square(SpringRiver::NAME, 13),
+            ScenarioCard {
+                zone: Zone::Hand,
+                ..on_square(MarineVoyage::NAME, 0)
+            },
+        ];
+        let mut harness = Harness::new(&scenario);
+        let marine_voyage = harness.card(MarineVoyage::NAME);
+
+        harness.script([Decision::LocationGroup(1)]);
+        harness.cast(&marine_voyage).await.unwrap();
+
+        harness.assert_logged_in_order(&["PlayMagic", "AddTemporaryEffect"]);
+    }
+
+    #[tokio::test]
+    async fn scripted_decisions_answer_revealed_card_actions() {
+        let mut scenario = teleport_scenario();
+        scenario.players[0].cards.extend([
+            ScenarioCard {
+                zone: Zone::Hand,
+                ..on_square(SpringRiver::NAME, 0)
+            },
+            ScenarioCard {
+                zone: Zone::Spellbook,
+                ..on_square(KiteArcher::NAME, 0)
+            },
+        ]);
+        let mut harness = Harness::new(&scenario);
+        let spring_river = harness.card(SpringRiver::NAME);
+
+        harness.script([Decision::TakeAction(true)]);
+        harness
+            .play(&spring_river, Location::Square(4, Region::Surface))
+            .await
+            .unwrap();
+
+        harness.assert_logged_in_order(&["PlayCard", "RearrangeDeck"]);
+    }
+
+    #[tokio::test]
+    async fn scripted_decisions_answer_path_prompts() {
+        let harness = Harness::new(&teleport_scenario());
+        let paths = vec![
+            vec![Location::Square(3, Region::Surface)],
+            vec![
+                Location::Square(3, Region::Surface),
+                Location::Square(8, Region::Surface),
+            ],
+        ];
+
+        harness.script([Decision::Path(1)]);
+        let path = pick_path(harness.player_one, &paths, harness.state(), "Pick a path")
+            .await
+            .unwrap();
+
+        assert_eq!(path, paths[1]);
+    }
+}
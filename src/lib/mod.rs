@@ -1,19 +1,31 @@
+pub mod achievement;
 pub mod booster;
 #[allow(clippy::needless_update)]
 pub mod card;
 pub mod collection;
+pub mod crafting;
 pub mod deck;
+pub mod draft;
 pub mod effect;
 pub mod error;
 pub mod game;
+pub mod goldfish;
+pub mod moderation;
 pub mod networking;
 pub mod query;
+pub mod quest;
+pub mod sandbox;
+pub mod scenario;
+pub mod sealed;
 pub mod state;
+pub mod trade;
 pub mod zone;
 
 #[cfg(test)]
 mod effect_test;
 #[cfg(test)]
+pub(crate) mod harness;
+#[cfg(test)]
 mod state_test;
 
 pub(crate) mod prelude {
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Longest note a player may attach to a report.
+pub const MAX_REPORT_DETAILS: usize = 500;
+
+/// Why a player reported their opponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReportReason {
+    Cheating,
+    Harassment,
+    Stalling,
+    #[default]
+    Other,
+}
+
+impl ReportReason {
+    pub const ALL: [ReportReason; 4] = [
+        ReportReason::Cheating,
+        ReportReason::Harassment,
+        ReportReason::Stalling,
+        ReportReason::Other,
+    ];
+
+    /// The stable name stored with the report.
+    pub fn id(&self) -> &'static str {
+        match self {
+            ReportReason::Cheating => "cheating",
+            ReportReason::Harassment => "harassment",
+            ReportReason::Stalling => "stalling",
+            ReportReason::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportReason::Cheating => "Cheating",
+            ReportReason::Harassment => "Harassment",
+            ReportReason::Stalling => "Stalling",
+            ReportReason::Other => "Something else",
+        }
+    }
+}
+
+/// Check the free-text part of a report.
+pub fn validate_report_details(details: &str) -> Result<(), String> {
+    if details.chars().count() > MAX_REPORT_DETAILS {
+        return Err(format!(
+            "Keep report details under {MAX_REPORT_DETAILS} characters."
+        ));
+    }
+    Ok(())
+}
@@ -1,8 +1,7 @@
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::OwnedWriteHalf;
 
 use crate::networking::{
-    MAX_MESSAGE_SIZE,
+    MAX_MESSAGE_SIZE, StreamWriter,
     message::{Message, ServerMessage, ToMessage},
 };
 use std::net::TcpStream;
@@ -77,7 +76,7 @@ impl Client {
 
     pub async fn send_to_stream(
         message: &ServerMessage,
-        stream: Arc<tokio::sync::Mutex<OwnedWriteHalf>>,
+        stream: Arc<tokio::sync::Mutex<StreamWriter>>,
     ) -> anyhow::Result<()> {
         let bytes = rmp_serde::to_vec(&message.to_message())?;
         let len = bytes.len();
@@ -1,9 +1,15 @@
 use crate::{
-    booster::{BoosterPack, UnopenedBoosterPack},
+    achievement::UnlockedAchievement,
+    booster::{BoosterCard, BoosterPack, UnopenedBoosterPack},
     card::{Card, CardData, CardType},
     collection::CollectedCard,
-    deck::{Deck, DeckList, precon::PreconDeck},
+    deck::{CardNameWithCount, Deck, DeckList, precon::PreconDeck},
     game::{CardId, Direction, PlayerId, Resources, SoundEffect},
+    moderation::ReportReason,
+    quest::QuestProgress,
+    sandbox::SandboxCommand,
+    sealed::SealedPool,
+    trade::{TradeOffer, TradeSide},
     zone::{Location, Zone},
 };
 use serde::{Deserialize, Serialize};
@@ -59,6 +65,23 @@ pub struct EffectDebugData {
     pub description: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookDebugData {
+    pub card_id: CardId,
+    pub card_name: String,
+    pub timing: String,
+    pub trigger: String,
+}
+
+/// Engine internals that are only synced to players of games running in dev mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DebugData {
+    pub stepped_effects: bool,
+    pub effect_queue: Vec<EffectDebugData>,
+    pub ongoing_effects: Vec<OngoingEffectData>,
+    pub hooks: Vec<HookDebugData>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServerMessage {
     RevealCards {
@@ -121,6 +144,12 @@ pub enum ServerMessage {
         collection: Vec<CollectedCard>,
         unopened_booster_packs: Vec<UnopenedBoosterPack>,
         reward_points: u32,
+        /// Reward points a Beta booster costs on this server.
+        #[serde(default)]
+        beta_booster_cost: u32,
+        /// The pool of the player's current sealed event, if they started one.
+        #[serde(default)]
+        sealed_pool: Option<SealedPool>,
     },
     AuthenticationFailure {
         message: String,
@@ -129,6 +158,19 @@ pub enum ServerMessage {
         email: String,
         delivery_failed: bool,
     },
+    /// Sent for every reset request so clients can't tell which addresses have accounts.
+    PasswordResetRequested {
+        email: String,
+    },
+    /// An account change went through. `username` is set when the player was renamed.
+    AccountUpdated {
+        username: Option<String>,
+        message: String,
+    },
+    AccountUpdateFailed {
+        message: String,
+    },
+    AccountDeleted,
     StarterDeckSelection {
         username: String,
         available_decks: Vec<PreconDeck>,
@@ -149,6 +191,103 @@ pub enum ServerMessage {
     RewardRedemptionFailed {
         message: String,
     },
+    SealedPoolOpened {
+        pool: SealedPool,
+    },
+    SealedDeckRejected {
+        message: String,
+    },
+    DraftLobby {
+        pod_id: uuid::Uuid,
+        seats: u8,
+        players: Vec<String>,
+    },
+    /// The pack the player picks from next. The server picks for them once `seconds_left`
+    /// runs out.
+    DraftPack {
+        pod_id: uuid::Uuid,
+        round: u8,
+        pick: u8,
+        pack: Vec<BoosterCard>,
+        seconds_left: u64,
+    },
+    DraftPicked {
+        pod_id: uuid::Uuid,
+        card: BoosterCard,
+    },
+    /// Drafting is over and the player can build a deck from their picks.
+    DraftComplete {
+        pod_id: uuid::Uuid,
+        pool: SealedPool,
+    },
+    DraftBracketUpdated {
+        pod_id: uuid::Uuid,
+        round: u8,
+        matches: Vec<(String, String)>,
+        bye: Option<String>,
+        champion: Option<String>,
+    },
+    DraftRejected {
+        message: String,
+    },
+    /// The player's open trades, sent whenever one of them changes.
+    TradeOffers {
+        trades: Vec<TradeOffer>,
+    },
+    /// A trade went through. Carries the player's collection and balance after the transfer.
+    TradeCompleted {
+        collection: Vec<CollectedCard>,
+        reward_points: u32,
+    },
+    TradeRejected {
+        message: String,
+    },
+    /// The player's collection and dust balance after loading, dusting or crafting.
+    CraftingUpdated {
+        collection: Vec<CollectedCard>,
+        dust: u32,
+    },
+    CraftingRejected {
+        message: String,
+    },
+    /// The usernames the player has blocked, sent at login and whenever the list changes.
+    BlockedPlayers {
+        usernames: Vec<String>,
+    },
+    OpponentReported {
+        game_id: uuid::Uuid,
+    },
+    OpponentBlocked {
+        game_id: uuid::Uuid,
+    },
+    /// A report or block from the end of a match was refused.
+    ModerationRejected {
+        game_id: uuid::Uuid,
+        message: String,
+    },
+    /// The server is draining ahead of maintenance and won't start new games. `None` when the
+    /// maintenance was called off.
+    MaintenanceNotice {
+        message: Option<String>,
+    },
+    /// The game was ended without a result, so nobody won or lost it.
+    GameAborted {
+        game_id: uuid::Uuid,
+        message: String,
+    },
+    Quests {
+        quests: Vec<QuestProgress>,
+    },
+    /// Quests finished by the game that just ended.
+    QuestsCompleted {
+        completed: Vec<String>,
+        points_earned: u32,
+        reward_points: u32,
+    },
+    /// Achievements unlocked for the first time by the game that just ended.
+    AchievementsUnlocked {
+        achievements: Vec<UnlockedAchievement>,
+    },
     GameStarted {
         game_id: uuid::Uuid,
         player1: PlayerId,
@@ -161,10 +300,9 @@ pub enum ServerMessage {
         health: HashMap<PlayerId, u16>,
         current_player: PlayerId,
         turn_player: PlayerId,
+        /// Only set for games running in dev mode.
         #[serde(default)]
-        stepped_effects: bool,
-        #[serde(default)]
-        effect_queue: Vec<EffectDebugData>,
+        debug: Option<DebugData>,
     },
     PickCards {
         prompt: String,
@@ -267,11 +405,37 @@ impl ServerMessage {
             ServerMessage::AuthenticationSuccess { player_id, .. } => *player_id,
             ServerMessage::AuthenticationFailure { .. } => uuid::Uuid::nil(),
             ServerMessage::EmailConfirmationRequired { .. } => uuid::Uuid::nil(),
+            ServerMessage::PasswordResetRequested { .. } => uuid::Uuid::nil(),
+            ServerMessage::AccountUpdated { .. } => uuid::Uuid::nil(),
+            ServerMessage::AccountUpdateFailed { .. } => uuid::Uuid::nil(),
+            ServerMessage::AccountDeleted => uuid::Uuid::nil(),
             ServerMessage::StarterDeckSelection { .. } => uuid::Uuid::nil(),
             ServerMessage::BoosterPackOpened { .. } => uuid::Uuid::nil(),
             ServerMessage::MatchRewards { .. } => uuid::Uuid::nil(),
             ServerMessage::BoosterRedeemed { .. } => uuid::Uuid::nil(),
             ServerMessage::RewardRedemptionFailed { .. } => uuid::Uuid::nil(),
+            ServerMessage::SealedPoolOpened { .. } => uuid::Uuid::nil(),
+            ServerMessage::SealedDeckRejected { .. } => uuid::Uuid::nil(),
+            ServerMessage::DraftLobby { .. } => uuid::Uuid::nil(),
+            ServerMessage::DraftPack { .. } => uuid::Uuid::nil(),
+            ServerMessage::DraftPicked { .. } => uuid::Uuid::nil(),
+            ServerMessage::DraftComplete { .. } => uuid::Uuid::nil(),
+            ServerMessage::DraftBracketUpdated { .. } => uuid::Uuid::nil(),
+            ServerMessage::DraftRejected { .. } => uuid::Uuid::nil(),
+            ServerMessage::TradeOffers { .. } => uuid::Uuid::nil(),
+            ServerMessage::TradeCompleted { .. } => uuid::Uuid::nil(),
+            ServerMessage::TradeRejected { .. } => uuid::Uuid::nil(),
+            ServerMessage::CraftingUpdated { .. } => uuid::Uuid::nil(),
+            ServerMessage::CraftingRejected { .. } => uuid::Uuid::nil(),
+            ServerMessage::BlockedPlayers { .. } => uuid::Uuid::nil(),
+            ServerMessage::OpponentReported { .. } => uuid::Uuid::nil(),
+            ServerMessage::OpponentBlocked { .. } => uuid::Uuid::nil(),
+            ServerMessage::ModerationRejected { .. } => uuid::Uuid::nil(),
+            ServerMessage::MaintenanceNotice { .. } => uuid::Uuid::nil(),
+            ServerMessage::GameAborted { .. } => uuid::Uuid::nil(),
+            ServerMessage::Quests { .. } => uuid::Uuid::nil(),
+            ServerMessage::QuestsCompleted { .. } => uuid::Uuid::nil(),
+            ServerMessage::AchievementsUnlocked { .. } => uuid::Uuid::nil(),
             ServerMessage::GameStarted { .. } => uuid::Uuid::nil(),
             ServerMessage::Sync { .. } => uuid::Uuid::nil(),
             ServerMessage::ForceSync { player_id, .. } => *player_id,
@@ -311,6 +475,29 @@ pub enum ClientMessage {
     ResendEmailConfirmation {
         email: String,
     },
+    RequestPasswordReset {
+        email: String,
+    },
+    ResetPassword {
+        email: String,
+        code: String,
+        new_password: String,
+    },
+    ChangePassword {
+        current_password: String,
+        new_password: String,
+    },
+    /// Move the account to a new email address, which must be confirmed before the next login.
+    ChangeEmail {
+        password: String,
+        new_email: String,
+    },
+    ChangeUsername {
+        username: String,
+    },
+    DeleteAccount {
+        password: String,
+    },
     ChooseStarterDeck {
         deck: PreconDeck,
     },
@@ -318,6 +505,56 @@ pub enum ClientMessage {
         pack_id: uuid::Uuid,
     },
     RedeemBetaBooster,
+    /// Open a fresh sealed pool, replacing the player's previous sealed event once it is a day
+    /// old.
+    StartSealedEvent,
+    LoadTrades,
+    /// Offer `offered` to another player in exchange for `requested`. Cards used by the
+    /// proposer's saved decks can only be offered with `unlock_decks` set.
+    ProposeTrade {
+        recipient: String,
+        offered: TradeSide,
+        requested: TradeSide,
+        unlock_decks: bool,
+    },
+    /// Replace the terms of an open trade, from the countering player's point of view.
+    CounterTrade {
+        trade_id: uuid::Uuid,
+        offered: TradeSide,
+        requested: TradeSide,
+        unlock_decks: bool,
+    },
+    ConfirmTrade {
+        trade_id: uuid::Uuid,
+        unlock_decks: bool,
+    },
+    /// Decline an incoming trade or withdraw an outgoing one.
+    DeclineTrade {
+        trade_id: uuid::Uuid,
+    },
+    LoadCrafting,
+    /// Disenchant every copy beyond the deck copy limits that no saved deck uses.
+    DustExtraCopies,
+    CraftCards {
+        cards: Vec<CardNameWithCount>,
+    },
+    LoadQuests,
+    /// Report the opponent of a match the player finished.
+    ReportOpponent {
+        game_id: uuid::Uuid,
+        reason: ReportReason,
+        details: String,
+    },
+    /// Block the opponent of a match the player finished, so they are never paired again.
+    BlockOpponent {
+        game_id: uuid::Uuid,
+    },
+    BlockPlayer {
+        username: String,
+    },
+    UnblockPlayer {
+        username: String,
+    },
     ResolveAction {
         game_id: uuid::Uuid,
         player_id: PlayerId,
@@ -337,6 +574,62 @@ pub enum ClientMessage {
         player_id: PlayerId,
         deck: DeckChoice,
     },
+    /// Start a goldfish game: the deck is played alone against an inert dummy avatar.
+    StartGoldfish {
+        player_name: String,
+        player_id: PlayerId,
+        deck: DeckChoice,
+    },
+    /// Restart a goldfish game with freshly shuffled decks.
+    ResetGoldfish {
+        game_id: uuid::Uuid,
+        player_id: PlayerId,
+    },
+    /// Shuffle the hand back into the deck and draw a new opening hand in a goldfish game.
+    RedrawOpeners {
+        game_id: uuid::Uuid,
+        player_id: PlayerId,
+    },
+    /// Start a sandbox game: the player controls both seats, each playing the deck, and can edit
+    /// the board freely.
+    StartSandbox {
+        player_name: String,
+        player_id: PlayerId,
+        deck: DeckChoice,
+    },
+    /// Edit the board of a sandbox game.
+    Sandbox {
+        game_id: uuid::Uuid,
+        player_id: PlayerId,
+        command: SandboxCommand,
+    },
+    /// Queue for a sealed match with a deck built from the player's sealed pool.
+    JoinSealedQueue {
+        player_name: String,
+        player_id: PlayerId,
+        deck: DeckList,
+    },
+    /// Take a seat in a draft pod with the given number of seats.
+    JoinDraft {
+        player_name: String,
+        player_id: PlayerId,
+        seats: u8,
+    },
+    PickDraftCard {
+        player_id: PlayerId,
+        pod_id: uuid::Uuid,
+        index: usize,
+    },
+    /// Give up the player's draft seat to a bot and forfeit any remaining bracket matches.
+    LeaveDraft {
+        player_id: PlayerId,
+    },
+    /// Ready up for the player's next bracket match with a deck built from their drafted pool.
+    QueueDraftMatch {
+        player_id: PlayerId,
+        pod_id: uuid::Uuid,
+        deck: DeckList,
+    },
     DrawCard {
         game_id: uuid::Uuid,
         player_id: PlayerId,
@@ -432,14 +725,44 @@ impl ClientMessage {
             ClientMessage::Login { .. } => uuid::Uuid::nil(),
             ClientMessage::ConfirmEmail { .. } => uuid::Uuid::nil(),
             ClientMessage::ResendEmailConfirmation { .. } => uuid::Uuid::nil(),
+            ClientMessage::RequestPasswordReset { .. } => uuid::Uuid::nil(),
+            ClientMessage::ResetPassword { .. } => uuid::Uuid::nil(),
+            ClientMessage::ChangePassword { .. } => uuid::Uuid::nil(),
+            ClientMessage::ChangeEmail { .. } => uuid::Uuid::nil(),
+            ClientMessage::ChangeUsername { .. } => uuid::Uuid::nil(),
+            ClientMessage::DeleteAccount { .. } => uuid::Uuid::nil(),
             ClientMessage::ChooseStarterDeck { .. } => uuid::Uuid::nil(),
             ClientMessage::OpenBoosterPack { .. } => uuid::Uuid::nil(),
             ClientMessage::RedeemBetaBooster => uuid::Uuid::nil(),
+            ClientMessage::StartSealedEvent => uuid::Uuid::nil(),
+            ClientMessage::LoadTrades => uuid::Uuid::nil(),
+            ClientMessage::ProposeTrade { .. } => uuid::Uuid::nil(),
+            ClientMessage::CounterTrade { .. } => uuid::Uuid::nil(),
+            ClientMessage::ConfirmTrade { .. } => uuid::Uuid::nil(),
+            ClientMessage::DeclineTrade { .. } => uuid::Uuid::nil(),
+            ClientMessage::LoadCrafting => uuid::Uuid::nil(),
+            ClientMessage::DustExtraCopies => uuid::Uuid::nil(),
+            ClientMessage::CraftCards { .. } => uuid::Uuid::nil(),
+            ClientMessage::LoadQuests => uuid::Uuid::nil(),
+            ClientMessage::ReportOpponent { .. } => uuid::Uuid::nil(),
+            ClientMessage::BlockOpponent { .. } => uuid::Uuid::nil(),
+            ClientMessage::BlockPlayer { .. } => uuid::Uuid::nil(),
+            ClientMessage::UnblockPlayer { .. } => uuid::Uuid::nil(),
             ClientMessage::JoinQueue { .. } => uuid::Uuid::nil(),
+            ClientMessage::StartGoldfish { .. } => uuid::Uuid::nil(),
+            ClientMessage::StartSandbox { .. } => uuid::Uuid::nil(),
+            ClientMessage::JoinSealedQueue { .. } => uuid::Uuid::nil(),
+            ClientMessage::JoinDraft { .. } => uuid::Uuid::nil(),
+            ClientMessage::PickDraftCard { .. } => uuid::Uuid::nil(),
+            ClientMessage::LeaveDraft { .. } => uuid::Uuid::nil(),
+            ClientMessage::QueueDraftMatch { .. } => uuid::Uuid::nil(),
             ClientMessage::PlayerDisconnected { game_id, .. } => *game_id,
             ClientMessage::PickCard { game_id, .. } => *game_id,
             ClientMessage::PickAction { game_id, .. } => *game_id,
             ClientMessage::EndTurn { game_id, .. } => *game_id,
+            ClientMessage::ResetGoldfish { game_id, .. } => *game_id,
+            ClientMessage::RedrawOpeners { game_id, .. } => *game_id,
+            ClientMessage::Sandbox { game_id, .. } => *game_id,
             ClientMessage::ToggleSteppedEffects { game_id, .. } => *game_id,
             ClientMessage::StepNextEffect { game_id, .. } => *game_id,
             ClientMessage::PickLocation { game_id, .. } => *game_id,
@@ -467,13 +790,36 @@ impl ClientMessage {
             ClientMessage::Login { .. } => &NIL,
             ClientMessage::ConfirmEmail { .. } => &NIL,
             ClientMessage::ResendEmailConfirmation { .. } => &NIL,
+            ClientMessage::RequestPasswordReset { .. } => &NIL,
+            ClientMessage::ResetPassword { .. } => &NIL,
+            ClientMessage::ChangePassword { .. } => &NIL,
+            ClientMessage::ChangeEmail { .. } => &NIL,
+            ClientMessage::ChangeUsername { .. } => &NIL,
+            ClientMessage::DeleteAccount { .. } => &NIL,
             ClientMessage::ChooseStarterDeck { .. } => &NIL,
             ClientMessage::OpenBoosterPack { .. } => &NIL,
             ClientMessage::RedeemBetaBooster => &NIL,
+            ClientMessage::StartSealedEvent => &NIL,
+            ClientMessage::LoadTrades => &NIL,
+            ClientMessage::ProposeTrade { .. } => &NIL,
+            ClientMessage::CounterTrade { .. } => &NIL,
+            ClientMessage::ConfirmTrade { .. } => &NIL,
+            ClientMessage::DeclineTrade { .. } => &NIL,
+            ClientMessage::LoadCrafting => &NIL,
+            ClientMessage::DustExtraCopies => &NIL,
+            ClientMessage::CraftCards { .. } => &NIL,
+            ClientMessage::LoadQuests => &NIL,
+            ClientMessage::ReportOpponent { .. } => &NIL,
+            ClientMessage::BlockOpponent { .. } => &NIL,
+            ClientMessage::BlockPlayer { .. } => &NIL,
+            ClientMessage::UnblockPlayer { .. } => &NIL,
             ClientMessage::PlayerDisconnected { player_id, .. } => player_id,
             ClientMessage::PickCard { player_id, .. } => player_id,
             ClientMessage::PickAction { player_id, .. } => player_id,
             ClientMessage::EndTurn { player_id, .. } => player_id,
+            ClientMessage::ResetGoldfish { player_id, .. } => player_id,
+            ClientMessage::RedrawOpeners { player_id, .. } => player_id,
+            ClientMessage::Sandbox { player_id, .. } => player_id,
             ClientMessage::ToggleSteppedEffects { player_id, .. } => player_id,
             ClientMessage::StepNextEffect { player_id, .. } => player_id,
             ClientMessage::PickLocation { player_id, .. } => player_id,
@@ -487,6 +833,13 @@ impl ClientMessage {
             ClientMessage::DrawCard { player_id, .. } => player_id,
             ClientMessage::PickDirection { player_id, .. } => player_id,
             ClientMessage::JoinQueue { player_id, .. } => player_id,
+            ClientMessage::StartGoldfish { player_id, .. } => player_id,
+            ClientMessage::StartSandbox { player_id, .. } => player_id,
+            ClientMessage::JoinSealedQueue { player_id, .. } => player_id,
+            ClientMessage::JoinDraft { player_id, .. } => player_id,
+            ClientMessage::PickDraftCard { player_id, .. } => player_id,
+            ClientMessage::LeaveDraft { player_id } => player_id,
+            ClientMessage::QueueDraftMatch { player_id, .. } => player_id,
             ClientMessage::PickCards { player_id, .. } => player_id,
             ClientMessage::ResolveCombat { player_id, .. } => player_id,
             ClientMessage::ResolveAction { player_id, .. } => player_id,
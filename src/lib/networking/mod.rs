@@ -1,5 +1,8 @@
 use crate::networking::message::Message;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::{
+    io::AsyncWrite,
+    net::tcp::{OwnedReadHalf, OwnedWriteHalf},
+};
 use tokio_serde::formats::Bincode;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 
@@ -8,6 +11,9 @@ pub mod message;
 
 pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
 
+/// The sending half of a player's connection to the server, over plain TCP or TLS.
+pub type StreamWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
 pub type FramedReader = tokio_serde::Framed<
     FramedRead<OwnedReadHalf, LengthDelimitedCodec>,
     Message,
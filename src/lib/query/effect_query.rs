@@ -234,14 +234,14 @@ impl EffectQuery {
                 if let Some(source) = source
                     && !source.matches(from, state)
                 {
-                    println!("Source doesn't match");
+                    tracing::trace!("damage source doesn't match");
                     return Ok(false);
                 }
 
                 if let Some(target) = target
                     && !target.matches(card_id, state)
                 {
-                    println!("Target doesn't match");
+                    tracing::trace!("damage target doesn't match");
                     return Ok(false);
                 }
 
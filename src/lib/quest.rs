@@ -0,0 +1,379 @@
+use crate::{
+    card::{AvatarOfWater, Flamecaller, Geomancer, Sparkmage, Waveshaper},
+    effect::Effect,
+    game::{Element, GameOutcome, PlayerId},
+    state::State,
+};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Daily quests active at the same time.
+pub const DAILY_QUEST_COUNT: usize = 3;
+/// Weekly quests active at the same time.
+pub const WEEKLY_QUEST_COUNT: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuestCadence {
+    Daily,
+    Weekly,
+}
+
+impl QuestCadence {
+    /// The first day of the rotation period that contains `today`. Weekly quests rotate on Mondays.
+    pub fn period_start(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => today,
+            Self::Weekly => {
+                today - chrono::Duration::days(today.weekday().num_days_from_monday().into())
+            }
+        }
+    }
+
+    /// The last day of the rotation period that contains `today`.
+    pub fn period_end(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => today,
+            Self::Weekly => self.period_start(today) + chrono::Duration::days(6),
+        }
+    }
+
+    fn period_index(&self, today: NaiveDate) -> usize {
+        let days = self.period_start(today).num_days_from_ce().max(0) as usize;
+        match self {
+            Self::Daily => days,
+            Self::Weekly => days / 7,
+        }
+    }
+}
+
+/// What a quest counts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuestGoal {
+    PlayGames,
+    WinGames,
+    WinWithAvatar(Element),
+    PlaySites,
+    CastMagic,
+    RangedDamage,
+    KillMinions,
+}
+
+impl QuestGoal {
+    /// How far a single game moves this goal.
+    pub fn progress(&self, stats: &QuestStats) -> u32 {
+        match self {
+            Self::PlayGames => 1,
+            Self::WinGames => u32::from(stats.won),
+            Self::WinWithAvatar(element) => {
+                u32::from(stats.won && stats.avatar_element.as_ref() == Some(element))
+            }
+            Self::PlaySites => stats.sites_played,
+            Self::CastMagic => stats.magics_cast,
+            Self::RangedDamage => stats.ranged_damage,
+            Self::KillMinions => stats.minions_killed,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestDefinition {
+    pub id: &'static str,
+    pub cadence: QuestCadence,
+    pub description: &'static str,
+    pub goal: QuestGoal,
+    pub target: u32,
+    pub reward_points: u32,
+}
+
+const fn quest(
+    id: &'static str,
+    cadence: QuestCadence,
+    description: &'static str,
+    goal: QuestGoal,
+    target: u32,
+    reward_points: u32,
+) -> QuestDefinition {
+    QuestDefinition {
+        id,
+        cadence,
+        description,
+        goal,
+        target,
+        reward_points,
+    }
+}
+
+pub const QUESTS: &[QuestDefinition] = &[
+    quest(
+        "daily-play-games",
+        QuestCadence::Daily,
+        "Play 3 games",
+        QuestGoal::PlayGames,
+        3,
+        5,
+    ),
+    quest(
+        "daily-win-fire",
+        QuestCadence::Daily,
+        "Win 2 games with a Fire avatar",
+        QuestGoal::WinWithAvatar(Element::Fire),
+        2,
+        10,
+    ),
+    quest(
+        "daily-play-sites",
+        QuestCadence::Daily,
+        "Play 10 sites",
+        QuestGoal::PlaySites,
+        10,
+        5,
+    ),
+    quest(
+        "daily-win-water",
+        QuestCadence::Daily,
+        "Win 2 games with a Water avatar",
+        QuestGoal::WinWithAvatar(Element::Water),
+        2,
+        10,
+    ),
+    quest(
+        "daily-ranged-damage",
+        QuestCadence::Daily,
+        "Deal 20 damage with Ranged units",
+        QuestGoal::RangedDamage,
+        20,
+        8,
+    ),
+    quest(
+        "daily-win-earth",
+        QuestCadence::Daily,
+        "Win 2 games with an Earth avatar",
+        QuestGoal::WinWithAvatar(Element::Earth),
+        2,
+        10,
+    ),
+    quest(
+        "daily-cast-magic",
+        QuestCadence::Daily,
+        "Cast 6 magics",
+        QuestGoal::CastMagic,
+        6,
+        5,
+    ),
+    quest(
+        "daily-win-air",
+        QuestCadence::Daily,
+        "Win 2 games with an Air avatar",
+        QuestGoal::WinWithAvatar(Element::Air),
+        2,
+        10,
+    ),
+    quest(
+        "daily-kill-minions",
+        QuestCadence::Daily,
+        "Destroy 8 minions",
+        QuestGoal::KillMinions,
+        8,
+        6,
+    ),
+    quest(
+        "weekly-win-games",
+        QuestCadence::Weekly,
+        "Win 7 games",
+        QuestGoal::WinGames,
+        7,
+        30,
+    ),
+    quest(
+        "weekly-play-sites",
+        QuestCadence::Weekly,
+        "Play 60 sites",
+        QuestGoal::PlaySites,
+        60,
+        20,
+    ),
+    quest(
+        "weekly-ranged-damage",
+        QuestCadence::Weekly,
+        "Deal 100 damage with Ranged units",
+        QuestGoal::RangedDamage,
+        100,
+        25,
+    ),
+    quest(
+        "weekly-kill-minions",
+        QuestCadence::Weekly,
+        "Destroy 40 minions",
+        QuestGoal::KillMinions,
+        40,
+        25,
+    ),
+];
+
+/// The quests of `cadence` that are active on `today`. Each period takes the next slice of the
+/// pool, so consecutive periods show different quests.
+pub fn active_quests(cadence: QuestCadence, today: NaiveDate) -> Vec<&'static QuestDefinition> {
+    let pool: Vec<_> = QUESTS
+        .iter()
+        .filter(|quest| quest.cadence == cadence)
+        .collect();
+    let count = match cadence {
+        QuestCadence::Daily => DAILY_QUEST_COUNT,
+        QuestCadence::Weekly => WEEKLY_QUEST_COUNT,
+    }
+    .min(pool.len());
+    let start = cadence.period_index(today) * count;
+    (0..count).map(|i| pool[(start + i) % pool.len()]).collect()
+}
+
+/// Every quest active on `today`, dailies first.
+pub fn all_active_quests(today: NaiveDate) -> Vec<&'static QuestDefinition> {
+    let mut quests = active_quests(QuestCadence::Daily, today);
+    quests.extend(active_quests(QuestCadence::Weekly, today));
+    quests
+}
+
+pub fn quest_by_id(id: &str) -> Option<&'static QuestDefinition> {
+    QUESTS.iter().find(|quest| quest.id == id)
+}
+
+/// A quest as shown to a player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestProgress {
+    pub id: String,
+    pub description: String,
+    pub cadence: QuestCadence,
+    pub progress: u32,
+    pub target: u32,
+    pub reward_points: u32,
+    pub completed: bool,
+    pub ends_on: NaiveDate,
+}
+
+/// What one player did in a finished game, as far as quests are concerned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuestStats {
+    pub won: bool,
+    pub avatar_element: Option<Element>,
+    pub sites_played: u32,
+    pub magics_cast: u32,
+    pub ranged_damage: u32,
+    pub minions_killed: u32,
+}
+
+/// The element an avatar is aligned with, if any.
+pub fn avatar_element(name: &str) -> Option<Element> {
+    match name {
+        Flamecaller::NAME => Some(Element::Fire),
+        Waveshaper::NAME | AvatarOfWater::NAME => Some(Element::Water),
+        Geomancer::NAME => Some(Element::Earth),
+        Sparkmage::NAME => Some(Element::Air),
+        _ => None,
+    }
+}
+
+/// Tally `player_id`'s quest stats from the game's effect log.
+pub fn game_stats(state: &State, outcome: &GameOutcome, player_id: &PlayerId) -> QuestStats {
+    let owned_by_player = |card_id| {
+        state
+            .try_get_card(card_id)
+            .is_some_and(|card| card.get_owner_id() == player_id)
+    };
+    let mut stats = QuestStats {
+        won: &outcome.winner_id == player_id,
+        avatar_element: state
+            .get_player_avatar_id(player_id)
+            .ok()
+            .and_then(|avatar_id| state.try_get_card(&avatar_id))
+            .and_then(|avatar| avatar_element(avatar.get_name())),
+        ..QuestStats::default()
+    };
+    for logged in state.effect_log() {
+        match &logged.effect {
+            Effect::PlayCard {
+                player_id: played_by,
+                card_id,
+                ..
+            } if played_by == player_id
+                && state
+                    .try_get_card(card_id)
+                    .is_some_and(|card| card.is_site()) =>
+            {
+                stats.sites_played += 1;
+            }
+            Effect::PlayMagic {
+                player_id: cast_by, ..
+            } if cast_by == player_id => {
+                stats.magics_cast += 1;
+            }
+            Effect::TakeDamage { from, damage, .. }
+                if damage.is_ranged && owned_by_player(from) =>
+            {
+                stats.ranged_damage += u32::from(damage.amount);
+            }
+            Effect::KillMinion { killer_id, .. } if owned_by_player(killer_id) => {
+                stats.minions_killed += 1;
+            }
+            _ => {}
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_changes_each_period_and_stays_within_the_pool() {
+        let monday = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+        let today = active_quests(QuestCadence::Daily, monday);
+        let tomorrow = active_quests(QuestCadence::Daily, tuesday);
+        assert_eq!(today.len(), DAILY_QUEST_COUNT);
+        assert_ne!(
+            today.iter().map(|quest| quest.id).collect::<Vec<_>>(),
+            tomorrow.iter().map(|quest| quest.id).collect::<Vec<_>>()
+        );
+        assert!(
+            today
+                .iter()
+                .all(|quest| quest.cadence == QuestCadence::Daily)
+        );
+
+        let sunday = monday + chrono::Duration::days(6);
+        assert_eq!(
+            active_quests(QuestCadence::Weekly, monday)
+                .iter()
+                .map(|quest| quest.id)
+                .collect::<Vec<_>>(),
+            active_quests(QuestCadence::Weekly, sunday)
+                .iter()
+                .map(|quest| quest.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(QuestCadence::Weekly.period_start(sunday), monday);
+        assert_eq!(QuestCadence::Weekly.period_end(monday), sunday);
+    }
+
+    #[test]
+    fn avatar_quests_only_count_wins_with_that_element() {
+        let fire_win = QuestStats {
+            won: true,
+            avatar_element: Some(Element::Fire),
+            ..QuestStats::default()
+        };
+        let fire_loss = QuestStats {
+            won: false,
+            ..fire_win.clone()
+        };
+        let goal = QuestGoal::WinWithAvatar(Element::Fire);
+        assert_eq!(goal.progress(&fire_win), 1);
+        assert_eq!(goal.progress(&fire_loss), 0);
+        assert_eq!(
+            QuestGoal::WinWithAvatar(Element::Air).progress(&fire_win),
+            0
+        );
+        assert_eq!(QuestGoal::PlayGames.progress(&fire_loss), 1);
+    }
+}
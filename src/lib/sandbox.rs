@@ -0,0 +1,264 @@
+//! Sandbox games, where one player plays both seats and edits the board freely.
+//!
+//! Both seats start from the chosen deck and every turn is controlled by the sandbox player, so
+//! the engine's usual turn-control rules route each prompt to them. Board edits arrive as
+//! [`SandboxCommand`]s, which the game only accepts when [`State::sandbox`] is set.
+use crate::{
+    card::{Card, CardStatus, card_exists, from_name_and_zone},
+    game::{CardId, PlayerId},
+    networking::message::{ClientMessage, DeckChoice, ServerMessage},
+    state::{Player, PlayerWithDeck, State, TurnIterator},
+    zone::Zone,
+};
+use async_channel::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+
+/// The name of the second seat in a sandbox game.
+pub const SANDBOX_OPPONENT_NAME: &str = "Sandbox Opponent";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SandboxCommand {
+    /// Create a card for `owner_id` in any zone.
+    SpawnCard {
+        name: String,
+        owner_id: PlayerId,
+        zone: Zone,
+    },
+    MoveCard {
+        card_id: CardId,
+        zone: Zone,
+    },
+    /// Take a card out of the game. Avatars can't be removed.
+    RemoveCard {
+        card_id: CardId,
+    },
+    SetDamage {
+        card_id: CardId,
+        damage: u16,
+    },
+    SetTapped {
+        card_id: CardId,
+        tapped: bool,
+    },
+    SetStatus {
+        card_id: CardId,
+        status: CardStatus,
+        enabled: bool,
+    },
+    SetMana {
+        player_id: PlayerId,
+        mana: u8,
+    },
+}
+
+impl SandboxCommand {
+    pub fn apply(&self, state: &mut State) -> anyhow::Result<()> {
+        match self {
+            Self::SpawnCard {
+                name,
+                owner_id,
+                zone,
+            } => {
+                if !card_exists(name) {
+                    return Err(anyhow::anyhow!("unknown card \"{name}\""));
+                }
+                state.get_player(owner_id)?;
+                let card = from_name_and_zone(name, owner_id, Zone::None);
+                let card_id = *card.get_id();
+                state.add_card(card);
+                place(state, &card_id, zone.clone())?;
+            }
+            Self::MoveCard { card_id, zone } => {
+                existing(state, card_id)?;
+                place(state, card_id, zone.clone())?;
+            }
+            Self::RemoveCard { card_id } => {
+                if existing(state, card_id)?.is_avatar() {
+                    return Err(anyhow::anyhow!("avatars can't be removed from a sandbox"));
+                }
+                place(state, card_id, Zone::None)?;
+                state.remove_card(card_id);
+            }
+            Self::SetDamage { card_id, damage } => {
+                existing(state, card_id)?;
+                let card = state.get_card_mut(card_id);
+                if let Some(unit) = card.get_unit_base_mut() {
+                    unit.damage = *damage;
+                } else if let Some(artifact) = card.get_artifact_base_mut() {
+                    artifact.damage = Some(*damage);
+                } else {
+                    return Err(anyhow::anyhow!("{} can't take damage", card.get_name()));
+                }
+            }
+            Self::SetTapped { card_id, tapped } => {
+                existing(state, card_id)?;
+                state.get_card_mut(card_id).set_tapped(*tapped);
+            }
+            Self::SetStatus {
+                card_id,
+                status,
+                enabled,
+            } => {
+                existing(state, card_id)?;
+                let statuses = &mut state.get_card_mut(card_id).get_base_mut().statuses;
+                statuses.retain(|s| s != status);
+                if *enabled {
+                    statuses.push(status.clone());
+                }
+            }
+            Self::SetMana { player_id, mana } => {
+                state.get_player(player_id)?;
+                *state.get_player_mana_mut(player_id) = *mana;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn existing<'a>(state: &'a State, card_id: &CardId) -> anyhow::Result<&'a dyn Card> {
+    state
+        .try_get_card(card_id)
+        .ok_or(anyhow::anyhow!("card {card_id} is not in the game"))
+}
+
+/// Move a card to `zone`, keeping its owner's atlas and spellbook piles in step: a card leaving
+/// a pile is taken out of it and a card entering one goes on top.
+fn place(state: &mut State, card_id: &CardId, zone: Zone) -> anyhow::Result<()> {
+    let owner_id = *existing(state, card_id)?.get_owner_id();
+    let deck = state.get_player_deck_mut(&owner_id)?;
+    deck.sites.retain(|id| id != card_id);
+    deck.spells.retain(|id| id != card_id);
+    match zone {
+        Zone::Atlasbook => deck.sites.push(*card_id),
+        Zone::Spellbook => deck.spells.push(*card_id),
+        _ => {}
+    }
+    state.set_card_zone_with_sequence(card_id, zone);
+    Ok(())
+}
+
+/// A sandbox state: both seats play `deck`, `player` controls every turn, and both opening hands
+/// count as kept so the player only confirms their own mulligan.
+pub(crate) fn new_state(
+    game_id: uuid::Uuid,
+    player: &Player,
+    opponent: &Player,
+    deck: &DeckChoice,
+    server_tx: Sender<ServerMessage>,
+    client_rx: Receiver<ClientMessage>,
+) -> State {
+    let seats: Vec<PlayerWithDeck> = [player, opponent]
+        .into_iter()
+        .map(|seat| {
+            let (deck, cards) = deck.build(&seat.id);
+            PlayerWithDeck {
+                player: seat.clone(),
+                deck,
+                cards,
+            }
+        })
+        .collect();
+
+    let mut state = State::new(game_id, seats, server_tx, client_rx);
+    state.sandbox = true;
+    state.curr_turn = TurnIterator::controlled_by(vec![player.id, opponent.id], player.id);
+    state.players_with_accepted_hands.insert(opponent.id);
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card::Region, harness::Harness, scenario::Scenario, zone::Location};
+
+    #[tokio::test]
+    async fn spawned_cards_can_be_edited_moved_and_removed() {
+        let mut harness = Harness::new(&Scenario::default());
+        let opponent = harness.player_two;
+        let square = Zone::Location(Location::Square(8, Region::Surface));
+        SandboxCommand::SpawnCard {
+            name: "Pit Vipers".to_string(),
+            owner_id: opponent,
+            zone: square.clone(),
+        }
+        .apply(harness.state_mut())
+        .unwrap();
+        let vipers = harness.card("Pit Vipers");
+        assert_eq!(harness.zone_of(&vipers), &square);
+        assert_eq!(harness.state().get_card(&vipers).get_owner_id(), &opponent);
+
+        for command in [
+            SandboxCommand::SetDamage {
+                card_id: vipers,
+                damage: 1,
+            },
+            SandboxCommand::SetTapped {
+                card_id: vipers,
+                tapped: true,
+            },
+            SandboxCommand::SetStatus {
+                card_id: vipers,
+                status: CardStatus::Silenced,
+                enabled: true,
+            },
+        ] {
+            command.apply(harness.state_mut()).unwrap();
+        }
+        let card = harness.state().get_card(&vipers);
+        assert_eq!(card.get_unit_base().unwrap().damage, 1);
+        assert!(card.is_tapped());
+        assert!(card.get_base().statuses.contains(&CardStatus::Silenced));
+
+        SandboxCommand::MoveCard {
+            card_id: vipers,
+            zone: Zone::Spellbook,
+        }
+        .apply(harness.state_mut())
+        .unwrap();
+        assert_eq!(
+            harness
+                .state()
+                .get_player_deck(&opponent)
+                .unwrap()
+                .peek_spell(),
+            Some(&vipers)
+        );
+
+        SandboxCommand::RemoveCard { card_id: vipers }
+            .apply(harness.state_mut())
+            .unwrap();
+        assert!(harness.state().try_get_card(&vipers).is_none());
+        assert!(
+            harness
+                .state()
+                .get_player_deck(&opponent)
+                .unwrap()
+                .spells
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn avatars_and_unknown_cards_are_refused() {
+        let mut harness = Harness::new(&Scenario::default());
+        let avatar_id = harness
+            .state()
+            .get_player_avatar_id(&harness.player_one)
+            .unwrap();
+        assert!(
+            SandboxCommand::RemoveCard { card_id: avatar_id }
+                .apply(harness.state_mut())
+                .is_err()
+        );
+        assert!(
+            SandboxCommand::SpawnCard {
+                name: "Not A Real Card".to_string(),
+                owner_id: harness.player_one,
+                zone: Zone::Hand,
+            }
+            .apply(harness.state_mut())
+            .is_err()
+        );
+    }
+}
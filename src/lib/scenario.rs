@@ -0,0 +1,261 @@
+use crate::{
+    card::{CardStatus, card_exists, from_name_and_zone},
+    game::PlayerId,
+    state::State,
+    zone::{Location, Zone},
+};
+use serde::{Deserialize, Serialize};
+
+/// A declarative board setup that can be applied on top of a freshly created game state.
+///
+/// Scenarios are stored as JSON, for example:
+///
+/// ```json
+/// {
+///   "name": "Archers at the river",
+///   "players": [
+///     {
+///       "mana": 10,
+///       "avatar": { "life": 5, "deaths_door": false },
+///       "cards": [
+///         { "name": "Arid Desert", "zone": { "Location": { "Square": [8, "Surface"] } } },
+///         { "name": "Kite Archer", "zone": { "Location": { "Square": [8, "Surface"] } }, "tapped": true },
+///         { "name": "Summer River", "zone": "Hand" }
+///       ]
+///     },
+///     { "cards": [] }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: String,
+    /// Board setup per player, in seat order. Missing seats are left untouched.
+    #[serde(default)]
+    pub players: Vec<ScenarioPlayer>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenarioPlayer {
+    #[serde(default)]
+    pub mana: Option<u8>,
+    #[serde(default)]
+    pub avatar: Option<ScenarioAvatar>,
+    #[serde(default)]
+    pub cards: Vec<ScenarioCard>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenarioAvatar {
+    /// Moves the avatar to this location instead of its starting square.
+    #[serde(default)]
+    pub location: Option<Location>,
+    /// Remaining life. Damage is set to the avatar's toughness minus this value.
+    #[serde(default)]
+    pub life: Option<u16>,
+    #[serde(default)]
+    pub deaths_door: bool,
+    #[serde(default)]
+    pub tapped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioCard {
+    pub name: String,
+    pub zone: Zone,
+    #[serde(default)]
+    pub damage: u16,
+    #[serde(default)]
+    pub tapped: bool,
+    #[serde(default)]
+    pub statuses: Vec<CardStatus>,
+}
+
+impl Scenario {
+    pub fn from_file(filepath: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(filepath)?;
+        let scenario: Scenario = serde_json::from_reader(file)?;
+        scenario.validate().map_err(anyhow::Error::msg)?;
+        Ok(scenario)
+    }
+
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let scenario: Scenario = serde_json::from_str(json)?;
+        scenario.validate().map_err(anyhow::Error::msg)?;
+        Ok(scenario)
+    }
+
+    /// Check that every card named in the scenario exists.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.players.len() > 2 {
+            return Err(format!(
+                "Scenario describes {} players, but games have 2 seats.",
+                self.players.len()
+            ));
+        }
+        for card in self.players.iter().flat_map(|p| &p.cards) {
+            if !card_exists(&card.name) {
+                return Err(format!("Unknown card: \"{}\".", card.name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the scenario's cards to `state` and apply its mana and avatar settings. Players are
+    /// matched to the scenario by seat order.
+    pub fn apply(&self, state: &mut State) -> anyhow::Result<()> {
+        self.validate().map_err(anyhow::Error::msg)?;
+
+        let player_ids: Vec<PlayerId> = state.players.iter().map(|p| p.id).collect();
+        for (player_id, setup) in player_ids.iter().zip(&self.players) {
+            for card in &setup.cards {
+                let mut new_card = from_name_and_zone(&card.name, player_id, card.zone.clone());
+                if card.tapped {
+                    new_card.set_tapped(true);
+                }
+                if card.damage > 0 {
+                    if let Some(unit) = new_card.get_unit_base_mut() {
+                        unit.damage = card.damage;
+                    } else if let Some(artifact) = new_card.get_artifact_base_mut() {
+                        artifact.damage = Some(card.damage);
+                    }
+                }
+                new_card
+                    .get_base_mut()
+                    .statuses
+                    .extend(card.statuses.iter().cloned());
+
+                let card_id = *new_card.get_id();
+                let is_site = new_card.is_site();
+                state.add_card(new_card);
+                match card.zone {
+                    Zone::Atlasbook if is_site => {
+                        state.get_player_deck_mut(player_id)?.sites.push(card_id)
+                    }
+                    Zone::Spellbook if !is_site => {
+                        state.get_player_deck_mut(player_id)?.spells.push(card_id)
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(mana) = setup.mana {
+                *state.get_player_mana_mut(player_id) = mana;
+            }
+
+            if let Some(avatar) = &setup.avatar {
+                let avatar_id = state.get_player_avatar_id(player_id)?;
+                if let Some(location) = &avatar.location {
+                    state.set_card_zone_with_sequence(&avatar_id, Zone::Location(location.clone()));
+                }
+                let avatar_card = state.get_card_mut(&avatar_id);
+                avatar_card.set_tapped(avatar.tapped);
+                if let Some(life) = avatar.life {
+                    let unit = avatar_card
+                        .get_unit_base_mut()
+                        .ok_or(anyhow::anyhow!("no unit base in avatar"))?;
+                    unit.damage = unit.toughness.saturating_sub(life);
+                }
+                avatar_card
+                    .get_avatar_base_mut()
+                    .ok_or(anyhow::anyhow!("no avatar base in avatar"))?
+                    .deaths_door = avatar.deaths_door;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::{AridDesert, KiteArcher, Region},
+        query::QueryCache,
+    };
+
+    const SCENARIO: &str = r#"{
+        "name": "Archers",
+        "players": [
+            {
+                "mana": 7,
+                "avatar": { "life": 4, "deaths_door": true },
+                "cards": [
+                    { "name": "Arid Desert", "zone": { "Location": { "Square": [8, "Surface"] } } },
+                    {
+                        "name": "Kite Archer",
+                        "zone": { "Location": { "Square": [8, "Surface"] } },
+                        "damage": 1,
+                        "tapped": true
+                    },
+                    { "name": "Arid Desert", "zone": "Atlasbook" }
+                ]
+            },
+            {
+                "cards": [
+                    { "name": "Kite Archer", "zone": "Hand" }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn scenario_places_cards_and_sets_player_resources() {
+        QueryCache::init();
+
+        let scenario = Scenario::from_json(SCENARIO).unwrap();
+        let state = State::new_mock_state_from_scenario(&scenario).unwrap();
+
+        let player_one = state.players[0].id;
+        let player_two = state.players[1].id;
+        let square_eight = Zone::Location(Location::Square(8, Region::Surface));
+
+        let archers: Vec<_> = state
+            .all_cards()
+            .filter(|card| card.get_name() == KiteArcher::NAME)
+            .map(|card| *card.get_id())
+            .collect();
+        assert_eq!(archers.len(), 2);
+        let in_play = archers
+            .iter()
+            .map(|id| state.get_card(id))
+            .find(|card| card.get_zone() == &square_eight)
+            .unwrap();
+        assert!(in_play.is_tapped());
+        assert_eq!(in_play.get_damage_taken().unwrap(), 1);
+        assert!(archers.iter().any(|id| {
+            let card = state.get_card(id);
+            card.get_zone() == &Zone::Hand && card.get_owner_id() == &player_two
+        }));
+
+        let atlas = &state.get_player_deck(&player_one).unwrap().sites;
+        assert_eq!(atlas.len(), 1);
+        assert_eq!(state.get_card(&atlas[0]).get_name(), AridDesert::NAME);
+
+        assert_eq!(state.get_player_resources(&player_one).unwrap().mana, 7);
+        let avatar = state.get_card(&state.get_player_avatar_id(&player_one).unwrap());
+        let unit = avatar.get_unit_base().unwrap();
+        assert_eq!(unit.toughness - unit.damage, 4);
+        assert!(avatar.get_avatar_base().unwrap().deaths_door);
+    }
+
+    #[test]
+    fn scenario_rejects_unknown_cards() {
+        let json = r#"{ "players": [ { "cards": [ { "name": "Not A Card", "zone": "Hand" } ] } ] }"#;
+        assert!(Scenario::from_json(json).is_err());
+    }
+
+    #[test]
+    fn bundled_scenarios_load() {
+        QueryCache::init();
+
+        let scenario =
+            Scenario::from_json(include_str!("../../scenarios/test_state.json")).unwrap();
+        let state = State::new_mock_state_from_scenario(&scenario).unwrap();
+        let player_one = state.players[0].id;
+        assert_eq!(state.get_player_resources(&player_one).unwrap().mana, 10);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{
+    booster::{BoosterCard, BoosterPack},
+    card::{ALL_CARDS, card_exists, from_name},
+    collection::CollectedCard,
+    deck::DeckList,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Number of Beta boosters opened into a sealed pool.
+pub const SEALED_PACKS: usize = 6;
+pub const SEALED_MIN_SPELLS: usize = 24;
+pub const SEALED_MIN_SITES: usize = 12;
+
+/// Plain threshold sites that may be added to a sealed atlas in any number, whether or not they
+/// were opened.
+pub const BASIC_SITES: [&str; 12] = [
+    "Arid Desert",
+    "Red Desert",
+    "Remote Desert",
+    "Autumn River",
+    "Spring River",
+    "Summer River",
+    "Humble Village",
+    "Rustic Village",
+    "Simple Village",
+    "Dark Tower",
+    "Gothic Tower",
+    "Lone Tower",
+];
+
+/// The cards opened for a sealed event. The pool is only used to build and validate a sealed
+/// deck and never becomes part of the player's collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedPool {
+    pub id: uuid::Uuid,
+    pub cards: Vec<BoosterCard>,
+}
+
+impl SealedPool {
+    /// Open [`SEALED_PACKS`] Beta boosters into a new pool.
+    pub fn open() -> Self {
+        Self::from_packs((0..SEALED_PACKS).map(|_| BoosterPack::beta()))
+    }
+
+    pub fn from_packs(packs: impl IntoIterator<Item = BoosterPack>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            cards: packs.into_iter().flat_map(|pack| pack.cards).collect(),
+        }
+    }
+
+    fn counts(&self) -> HashMap<(&str, bool), usize> {
+        let mut counts = HashMap::new();
+        for card in &self.cards {
+            *counts.entry((card.name.as_str(), card.is_foil)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// The cards a sealed deck can be built from: the opened cards, unlimited basic sites and
+    /// every avatar, since boosters never contain one.
+    pub fn collection(&self) -> Vec<CollectedCard> {
+        let mut cards: Vec<CollectedCard> = self
+            .counts()
+            .into_iter()
+            .map(|((name, is_foil), count)| CollectedCard {
+                name: name.to_string(),
+                count: u8::try_from(count).unwrap_or(u8::MAX),
+                is_foil,
+            })
+            .collect();
+        for name in BASIC_SITES {
+            match cards
+                .iter_mut()
+                .find(|card| card.name == name && !card.is_foil)
+            {
+                Some(card) => card.count = u8::MAX,
+                None => cards.push(CollectedCard {
+                    name: name.to_string(),
+                    count: u8::MAX,
+                    is_foil: false,
+                }),
+            }
+        }
+        cards.extend(
+            ALL_CARDS
+                .iter()
+                .map(|(_, constructor)| constructor(uuid::Uuid::nil()))
+                .filter(|card| card.is_avatar())
+                .map(|card| CollectedCard {
+                    name: card.get_name().to_string(),
+                    count: 1,
+                    is_foil: false,
+                }),
+        );
+        cards.sort_by(|a, b| a.name.cmp(&b.name).then(a.is_foil.cmp(&b.is_foil)));
+        cards
+    }
+
+    /// Validate a deck against the sealed rules: any avatar, at least
+    /// [`SEALED_MIN_SPELLS`] spells and [`SEALED_MIN_SITES`] sites, and no more copies of a
+    /// card than were opened, except for non-foil [`BASIC_SITES`].
+    pub fn validate(&self, deck: &DeckList) -> Result<(), String> {
+        deck.validate_name_and_avatar()?;
+        if !from_name(&deck.avatar, &uuid::Uuid::nil()).is_avatar() {
+            return Err(format!("\"{}\" is not an avatar.", deck.avatar));
+        }
+
+        let spell_count = deck.spells.iter().map(|c| c.count as usize).sum::<usize>();
+        if spell_count < SEALED_MIN_SPELLS {
+            return Err(format!(
+                "Sealed spellbooks need at least {SEALED_MIN_SPELLS} cards (you have {spell_count})."
+            ));
+        }
+        let site_count = deck.sites.iter().map(|c| c.count as usize).sum::<usize>();
+        if site_count < SEALED_MIN_SITES {
+            return Err(format!(
+                "Sealed atlases need at least {SEALED_MIN_SITES} sites (you have {site_count})."
+            ));
+        }
+
+        let mut used: HashMap<(&str, bool), usize> = HashMap::new();
+        for card in deck.spells.iter().chain(&deck.sites) {
+            if !card_exists(&card.name) {
+                return Err(format!("Unknown card: \"{}\".", card.name));
+            }
+            *used.entry((card.name.as_str(), card.is_foil)).or_insert(0) += card.count as usize;
+        }
+
+        let opened = self.counts();
+        for ((name, is_foil), count) in used {
+            if !is_foil && BASIC_SITES.contains(&name) {
+                continue;
+            }
+            let available = opened.get(&(name, is_foil)).copied().unwrap_or_default();
+            if count > available {
+                let printing = if is_foil { "foil " } else { "" };
+                return Err(format!(
+                    "Your sealed pool only has {available} {printing}\"{name}\" (the deck uses {count})."
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::CardNameWithCount;
+
+    fn card(count: u8, name: &str) -> CardNameWithCount {
+        CardNameWithCount {
+            count,
+            name: name.to_string(),
+            is_foil: false,
+        }
+    }
+
+    fn pool(names: &[&str]) -> SealedPool {
+        SealedPool {
+            id: uuid::Uuid::nil(),
+            cards: names
+                .iter()
+                .map(|name| BoosterCard {
+                    name: name.to_string(),
+                    is_foil: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn sealed_decks_are_limited_by_the_pool_but_not_by_basic_sites() {
+        let pool = pool(&["Kite Archer"; 24]);
+        let mut deck = DeckList {
+            name: "Sealed".to_string(),
+            avatar: "Avatar of Water".to_string(),
+            spells: vec![card(24, "Kite Archer")],
+            sites: vec![card(12, "Spring River")],
+        };
+        assert_eq!(pool.validate(&deck), Ok(()));
+
+        deck.spells = vec![card(25, "Kite Archer")];
+        assert!(pool.validate(&deck).unwrap_err().contains("only has 24"));
+
+        deck.spells = vec![card(23, "Kite Archer")];
+        assert!(pool.validate(&deck).unwrap_err().contains("at least 24"));
+
+        deck.spells = vec![card(24, "Kite Archer")];
+        deck.sites = vec![card(12, "Floodplain")];
+        assert!(pool.validate(&deck).unwrap_err().contains("only has 0"));
+    }
+
+    #[test]
+    fn opened_pools_hold_every_pack_and_offer_basic_sites() {
+        let pool = SealedPool::open();
+        assert_eq!(pool.cards.len(), SEALED_PACKS * 15);
+
+        let collection = pool.collection();
+        for name in BASIC_SITES {
+            assert!(card_exists(name));
+            assert!(
+                collection
+                    .iter()
+                    .any(|card| card.name == name && card.count == u8::MAX)
+            );
+        }
+    }
+}
@@ -5,7 +5,10 @@ use crate::{
     deck::Deck,
     effect::{Counter, Effect, EffectEngine, EffectState},
     game::{ActivatedAbility, CardId, Element, PlayerId, Resources, Thresholds, ThresholdsDiff},
-    networking::message::{ClientMessage, EffectDebugData, OngoingEffectData, ServerMessage},
+    networking::message::{
+        ClientMessage, DebugData, EffectDebugData, HookDebugData, OngoingEffectData,
+        ServerMessage,
+    },
     query::{CardQuery, LocationQuery, ZoneQuery},
     zone::{Location, Zone},
 };
@@ -845,6 +848,16 @@ impl TurnIterator {
         }
     }
 
+    /// Turns in the usual order, all of them played by `controller_id`.
+    pub fn controlled_by(normal: Vec<PlayerId>, controller_id: PlayerId) -> Self {
+        let mut turns = Self::new(normal);
+        for turn in &mut turns.normal {
+            *turn = Turn::controlled_by(turn.player_id, controller_id);
+        }
+        turns.current = turns.normal[0].clone();
+        turns
+    }
+
     pub fn current(&self) -> &Turn {
         &self.current
     }
@@ -917,6 +930,15 @@ pub struct State {
     pub ongoing_effects: Vec<TimedOngoingEffect>,
     pub player_mana: HashMap<PlayerId, u8>,
     pub eliminated_players: HashSet<PlayerId>,
+    /// Allows debug controls such as stepped effects and syncs engine internals to players. Set by
+    /// the server for development sessions only.
+    pub dev_mode: bool,
+    /// The inert opponent of a goldfish game. It never draws, so an empty deck doesn't lose it
+    /// the game.
+    pub dummy_player: Option<PlayerId>,
+    /// Lets the players edit the board with sandbox commands. Set by the server for sandbox games
+    /// only.
+    pub sandbox: bool,
     pub stepped_effects: bool,
     pub players_with_accepted_hands: HashSet<PlayerId>,
     pub marked_for_death: HashMap<CardId, Zone>,
@@ -966,6 +988,9 @@ impl State {
             ongoing_effects: Vec::new(),
             player_mana,
             eliminated_players: HashSet::new(),
+            dev_mode: false,
+            dummy_player: None,
+            sandbox: false,
             stepped_effects: false,
             players_with_accepted_hands: HashSet::new(),
             marked_for_death: HashMap::new(),
@@ -1052,6 +1077,11 @@ impl State {
         card
     }
 
+    /// Cards that have left the game, e.g. banished cards and dead tokens.
+    pub fn removed_cards(&self) -> impl Iterator<Item = &dyn Card> {
+        self.removed_cards.values().map(|card| &**card)
+    }
+
     pub fn add_removed_card(&mut self, card: Box<dyn Card>) {
         self.removed_cards.insert(*card.get_id(), card);
     }
@@ -1105,6 +1135,31 @@ impl State {
             ));
         }
 
+        // Debug controls freeze effect resolution for both players, so they are only available
+        // in dev mode.
+        if !self.dev_mode
+            && matches!(
+                msg,
+                ClientMessage::ToggleSteppedEffects { .. } | ClientMessage::StepNextEffect { .. }
+            )
+        {
+            return Err(anyhow::anyhow!(
+                "debug controls are only available in dev mode"
+            ));
+        }
+
+        if !self.sandbox && matches!(msg, ClientMessage::Sandbox { .. }) {
+            return Err(anyhow::anyhow!(
+                "board edits are only available in sandbox games"
+            ));
+        }
+
+        if self.phase != Phase::Mulligan && matches!(msg, ClientMessage::RedrawOpeners { .. }) {
+            return Err(anyhow::anyhow!(
+                "opening hands can only be redrawn before they are kept"
+            ));
+        }
+
         // Validate that all cards mentioned in the message exist in the game.
         match msg {
             ClientMessage::ClickCard { card_id, .. }
@@ -2115,6 +2170,28 @@ impl State {
             current_player: self.current_turn_controller(),
             turn_player: self.current_player(),
             health,
+            debug: self.dev_mode.then(|| self.debug_data()),
+        })
+    }
+
+    fn debug_data(&self) -> DebugData {
+        let mut hooks = vec![];
+        for card in self.all_cards() {
+            for hook in card.hooks(self).unwrap_or_default() {
+                if !hook.source_zones.matches(card.get_zone()) {
+                    continue;
+                }
+
+                hooks.push(HookDebugData {
+                    card_id: *card.get_id(),
+                    card_name: card.get_name().to_string(),
+                    timing: format!("{:?}", hook.timing),
+                    trigger: format!("{:?}", hook.trigger),
+                });
+            }
+        }
+
+        DebugData {
             stepped_effects: self.stepped_effects,
             effect_queue: self
                 .effects
@@ -2122,7 +2199,9 @@ impl State {
                 .into_iter()
                 .map(|(name, description)| EffectDebugData { name, description })
                 .collect(),
-        })
+            ongoing_effects: self.ongoing_effects_data(),
+            hooks,
+        }
     }
 
     pub fn get_receiver(&self) -> Receiver<ClientMessage> {
@@ -2233,4 +2312,14 @@ impl State {
         let (_, client_rx) = async_channel::unbounded();
         State::new(uuid::Uuid::new_v4(), players, server_tx, client_rx)
     }
+
+    /// Build a mock state with two Sorcerer avatars and apply `scenario` on top of it.
+    #[cfg(any(test, feature = "benchmark"))]
+    pub fn new_mock_state_from_scenario(
+        scenario: &crate::scenario::Scenario,
+    ) -> anyhow::Result<State> {
+        let mut state = State::new_mock_state([]);
+        scenario.apply(&mut state)?;
+        Ok(state)
+    }
 }
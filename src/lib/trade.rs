@@ -0,0 +1,170 @@
+use crate::{
+    card::card_exists,
+    deck::{CardNameWithCount, DeckList},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Most distinct cards a single side of a trade may put up.
+pub const MAX_TRADE_CARDS: usize = 30;
+
+/// What one player puts into a trade.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeSide {
+    pub cards: Vec<CardNameWithCount>,
+    pub reward_points: u32,
+}
+
+impl TradeSide {
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty() && self.reward_points == 0
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cards.len() > MAX_TRADE_CARDS {
+            return Err(format!(
+                "A trade can include at most {MAX_TRADE_CARDS} different cards per side."
+            ));
+        }
+        let mut seen = HashSet::new();
+        for card in &self.cards {
+            if card.count == 0 {
+                return Err(format!("Choose at least one copy of {}.", card.name));
+            }
+            if !card_exists(&card.name) {
+                return Err(format!("{} is not a known card.", card.name));
+            }
+            if !seen.insert((card.name.as_str(), card.is_foil)) {
+                return Err(format!("{} is listed more than once.", card.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeStatus {
+    Pending,
+    Completed,
+    Declined,
+    Cancelled,
+}
+
+impl TradeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Completed => "completed",
+            Self::Declined => "declined",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A trade offer as seen by one of its two players. Cards only change hands once both players
+/// have confirmed the same terms; changing the terms clears the other player's confirmation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeOffer {
+    pub id: uuid::Uuid,
+    pub partner: String,
+    /// Whether the partner proposed the trade.
+    pub incoming: bool,
+    pub yours: TradeSide,
+    pub theirs: TradeSide,
+    pub you_confirmed: bool,
+    pub partner_confirmed: bool,
+    pub status: TradeStatus,
+}
+
+/// Validate the terms of a trade before it is proposed or countered.
+pub fn validate_terms(offered: &TradeSide, requested: &TradeSide) -> Result<(), String> {
+    if offered.is_empty() && requested.is_empty() {
+        return Err("A trade needs at least one card or reward point.".to_string());
+    }
+    offered.validate()?;
+    requested.validate()
+}
+
+/// How many copies of each card the player's saved decks rely on. Decks share the collection, so
+/// a card is locked up to the most copies any single deck uses.
+pub fn deck_locked_copies(decks: &[DeckList]) -> HashMap<(String, bool), u8> {
+    let mut locked: HashMap<(String, bool), u8> = HashMap::new();
+    for deck in decks {
+        let mut counts: HashMap<(String, bool), u8> = HashMap::new();
+        for card in deck.sites.iter().chain(&deck.spells) {
+            let count = counts.entry((card.name.clone(), card.is_foil)).or_default();
+            *count = count.saturating_add(card.count);
+        }
+        *counts.entry((deck.avatar.clone(), false)).or_default() += 1;
+        for (key, count) in counts {
+            let copies = locked.entry(key).or_default();
+            *copies = (*copies).max(count);
+        }
+    }
+    locked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(name: &str, count: u8, is_foil: bool) -> CardNameWithCount {
+        CardNameWithCount {
+            name: name.to_string(),
+            count,
+            is_foil,
+        }
+    }
+
+    #[test]
+    fn empty_and_duplicate_terms_are_rejected() {
+        let empty = TradeSide::default();
+        assert!(validate_terms(&empty, &empty).is_err());
+
+        let points = TradeSide {
+            cards: vec![],
+            reward_points: 5,
+        };
+        assert!(validate_terms(&points, &empty).is_ok());
+
+        let duplicate = TradeSide {
+            cards: vec![card("Arid Desert", 1, false), card("Arid Desert", 2, false)],
+            reward_points: 0,
+        };
+        assert!(validate_terms(&duplicate, &empty).is_err());
+
+        let foil_and_plain = TradeSide {
+            cards: vec![card("Arid Desert", 1, false), card("Arid Desert", 1, true)],
+            reward_points: 0,
+        };
+        assert!(validate_terms(&foil_and_plain, &empty).is_ok());
+
+        let unknown = TradeSide {
+            cards: vec![card("Not A Real Card", 1, false)],
+            reward_points: 0,
+        };
+        assert!(validate_terms(&empty, &unknown).is_err());
+    }
+
+    #[test]
+    fn deck_locks_use_the_largest_single_deck_count() {
+        let first = DeckList {
+            name: "First".to_string(),
+            sites: vec![card("Arid Desert", 2, false)],
+            spells: vec![card("Pit Vipers", 1, true)],
+            avatar: "Sorcerer".to_string(),
+        };
+        let second = DeckList {
+            name: "Second".to_string(),
+            sites: vec![card("Arid Desert", 3, false)],
+            spells: vec![],
+            avatar: "Sorcerer".to_string(),
+        };
+
+        let locked = deck_locked_copies(&[first, second]);
+        assert_eq!(locked[&("Arid Desert".to_string(), false)], 3);
+        assert_eq!(locked[&("Pit Vipers".to_string(), true)], 1);
+        assert_eq!(locked[&("Sorcerer".to_string(), false)], 1);
+        assert!(!locked.contains_key(&("Pit Vipers".to_string(), false)));
+    }
+}
@@ -0,0 +1,395 @@
+//! `sorcerers-admin`: operator commands that run directly against the server's database, so
+//! nobody has to edit SQLite by hand.
+//!
+//! The database comes from `--database-url` or `DATABASE_URL`, or else from the server config
+//! file named by `--config` or `SORCERERS_CONFIG`. Users can be named by id, username or email.
+// Only the config file parsing is used here.
+#[allow(dead_code)]
+mod config;
+// Only used to email password reset codes.
+#[allow(dead_code)]
+mod email;
+// Shared with the server, most of whose queries this tool never calls.
+#[path = "../repository/mod.rs"]
+#[allow(dead_code, unused_imports)]
+mod repository;
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use sorcerers::{booster::BoosterPack, deck::CardNameWithCount};
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    config::Config,
+    email::EmailSender,
+    repository::{CONFIRMATION_CODE_LIFETIME_MINUTES, Repository},
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "sorcerers-admin",
+    about = "Operates on a Sorcerers server's database"
+)]
+struct Args {
+    /// Server config file to read the database URL from.
+    #[arg(long, env = "SORCERERS_CONFIG", global = true)]
+    config: Option<PathBuf>,
+    /// Database to operate on, for example sqlite://sorcerers.db. Overrides the config file.
+    #[arg(long, env = "DATABASE_URL", global = true)]
+    database_url: Option<String>,
+    /// Make destructive changes without asking first.
+    #[arg(long, short, global = true)]
+    yes: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List users, newest first. With SEARCH, only those whose username or email contains it.
+    Users {
+        search: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+    /// Show a user's account.
+    Show { user: String },
+    /// Add reward points to a user's balance.
+    GrantPoints { user: String, points: u32 },
+    /// Give a user unopened Beta booster packs.
+    GrantBoosters {
+        user: String,
+        #[arg(default_value_t = 1)]
+        count: u32,
+    },
+    /// Add copies of a card to a user's collection.
+    AddCards {
+        user: String,
+        card: String,
+        #[arg(long, default_value_t = 1)]
+        count: u8,
+        #[arg(long)]
+        foil: bool,
+    },
+    /// Confirm a user's email address without a code.
+    ConfirmEmail { user: String },
+    /// Email a user a password reset code, with the email settings in the server config file.
+    /// They enter it under "Forgot your password?" in the client.
+    ResetPassword {
+        user: String,
+        /// Set a new password read from stdin instead, for users who can't receive email.
+        #[arg(long)]
+        set: bool,
+    },
+    /// Stop a user from logging in.
+    Ban {
+        user: String,
+        /// Shown to the user when they try to log in.
+        #[arg(long)]
+        reason: Option<String>,
+        /// Lift the ban after this many days instead of never.
+        #[arg(long)]
+        days: Option<u32>,
+    },
+    /// Let a banned user log in again.
+    Unban { user: String },
+    /// List player reports, newest first. With USER, only the reports against them.
+    Reports {
+        user: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+    /// List the matches in progress on the server.
+    Games,
+    /// Stop the running server from starting matches, ahead of a restart. Players are told, and
+    /// matches in progress can finish.
+    Drain {
+        /// End the matches still going after this many minutes, without a result.
+        #[arg(long, conflicts_with = "cancel")]
+        minutes: Option<u32>,
+        /// Let the server start matches again.
+        #[arg(long)]
+        cancel: bool,
+    },
+    /// Write everything stored about a user as JSON.
+    Export {
+        user: String,
+        /// File to write to instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = args.config.as_deref().map(Config::from_file).transpose()?;
+    let database_url = match (args.database_url, &config) {
+        (Some(database_url), _) => database_url,
+        (None, Some(config)) => config.database_url.clone(),
+        (None, None) => String::new(),
+    };
+    if database_url.is_empty() {
+        bail!("set the database with --database-url, DATABASE_URL or a server config file");
+    }
+    let repository = Repository::connect(&database_url).await?;
+
+    match args.command {
+        Command::Users { search, limit } => {
+            let users = repository.search_users(search.as_deref(), limit).await?;
+            if users.is_empty() {
+                println!("No users found.");
+            }
+            for user in users {
+                let status = if user.is_banned() {
+                    "banned"
+                } else if user.email_confirmed_at.is_none() {
+                    "unconfirmed"
+                } else {
+                    ""
+                };
+                println!(
+                    "{}  {:<32}  {:<40}  {:>6} points  {status}",
+                    user.id,
+                    user.username,
+                    user.email.as_deref().unwrap_or("-"),
+                    user.reward_points
+                );
+            }
+        }
+        Command::Show { user } => {
+            let user = find_user(&repository, &user).await?;
+            println!("id:              {}", user.id);
+            println!("username:        {}", user.username);
+            println!("email:           {}", user.email.as_deref().unwrap_or("-"));
+            println!(
+                "email confirmed: {}",
+                user.email_confirmed_at.as_deref().unwrap_or("no")
+            );
+            println!("reward points:   {}", user.reward_points);
+            println!("created:         {}", user.created_at);
+            if let Some(banned_at) = &user.banned_at {
+                println!(
+                    "banned:          {banned_at} ({})",
+                    user.ban_reason.as_deref().unwrap_or("no reason given")
+                );
+                println!(
+                    "banned until:    {}",
+                    user.banned_until.as_deref().unwrap_or("forever")
+                );
+            }
+        }
+        Command::GrantPoints { user, points } => {
+            let user = find_user(&repository, &user).await?;
+            let balance = repository.grant_reward_points(user.id, points).await?;
+            println!(
+                "Gave {} {points} points; they now have {balance}.",
+                user.username
+            );
+        }
+        Command::GrantBoosters { user, count } => {
+            let user = find_user(&repository, &user).await?;
+            let packs = (0..count).map(|_| BoosterPack::beta()).collect::<Vec<_>>();
+            repository.grant_booster_packs(user.id, &packs).await?;
+            println!("Gave {} {count} Beta booster packs.", user.username);
+        }
+        Command::AddCards {
+            user,
+            card,
+            count,
+            foil,
+        } => {
+            let user = find_user(&repository, &user).await?;
+            let cards = [CardNameWithCount {
+                name: card,
+                count,
+                is_foil: foil,
+            }];
+            repository.grant_cards(user.id, &cards).await?;
+            println!(
+                "Added {count} {}{} to {}'s collection.",
+                if foil { "foil " } else { "" },
+                cards[0].name,
+                user.username
+            );
+        }
+        Command::ConfirmEmail { user } => {
+            let user = find_user(&repository, &user).await?;
+            repository.mark_email_confirmed(user.id).await?;
+            println!("Confirmed {}'s email address.", user.username);
+        }
+        Command::ResetPassword { user, set: true } => {
+            let user = find_user(&repository, &user).await?;
+            if !confirm(&format!("Replace {}'s password?", user.username), args.yes)? {
+                return Ok(());
+            }
+            // Read rather than taken as an argument, so it stays out of shell history and process
+            // listings.
+            print!("New password: ");
+            std::io::stdout().flush()?;
+            let mut password = String::new();
+            std::io::stdin().lock().read_line(&mut password)?;
+            let password = password.trim_end_matches(['\r', '\n']);
+            repository.set_password(user.id, password).await?;
+            println!(
+                "Set {}'s password. A session they already have open lasts until they disconnect.",
+                user.username
+            );
+        }
+        Command::ResetPassword { user, set: false } => {
+            let user = find_user(&repository, &user).await?;
+            let email = user.email.as_deref().with_context(|| {
+                format!(
+                    "{} has no email address; set a password with --set",
+                    user.username
+                )
+            })?;
+            let mut config = config.context(
+                "reset codes are sent with the server's email settings; name its config file \
+                 with --config, or set a password with --set",
+            )?;
+            config.apply_smtp_password();
+            let sender = EmailSender::from_config(&config.email, config.dev.enabled)?;
+            if !confirm(
+                &format!("Email {} a password reset code at {email}?", user.username),
+                args.yes,
+            )? {
+                return Ok(());
+            }
+            let pending = repository
+                .request_password_reset(email)
+                .await?
+                .context("the user's email address changed; try again")?;
+            sender
+                .send_password_reset_code(&pending.email, &pending.code)
+                .await?;
+            println!(
+                "Sent {} a password reset code at {email}. It works for \
+                 {CONFIRMATION_CODE_LIFETIME_MINUTES} minutes.",
+                user.username
+            );
+        }
+        Command::Ban { user, reason, days } => {
+            let user = find_user(&repository, &user).await?;
+            let until = days.map(|days| chrono::Utc::now() + chrono::Duration::days(days.into()));
+            let length = match days {
+                Some(days) => format!(" for {days} days"),
+                None => String::new(),
+            };
+            if !confirm(&format!("Ban {}{length}?", user.username), args.yes)? {
+                return Ok(());
+            }
+            repository
+                .ban_user(user.id, reason.as_deref(), until)
+                .await?;
+            println!(
+                "Banned {}{length}. A session they already have open lasts until they disconnect.",
+                user.username
+            );
+        }
+        Command::Unban { user } => {
+            let user = find_user(&repository, &user).await?;
+            repository.unban_user(user.id).await?;
+            println!("Unbanned {}.", user.username);
+        }
+        Command::Reports { user, limit } => {
+            let reported = match user {
+                Some(user) => Some(find_user(&repository, &user).await?.id),
+                None => None,
+            };
+            let reports = repository.list_reports(reported, limit).await?;
+            if reports.is_empty() {
+                println!("No reports found.");
+            }
+            for report in reports {
+                println!(
+                    "#{}  {}  {} reported {} for {} in {}",
+                    report.id,
+                    report.created_at,
+                    report.reporter,
+                    report.reported,
+                    report.reason,
+                    report.game_id
+                );
+                if !report.details.is_empty() {
+                    println!("    {}", report.details);
+                }
+            }
+        }
+        Command::Games => {
+            let games = repository.live_games().await?;
+            if games.is_empty() {
+                println!("No matches in progress.");
+            }
+            for game in games {
+                println!(
+                    "{}  {} vs {}  started {}",
+                    game.game_id, game.players[0], game.players[1], game.started_at
+                );
+            }
+        }
+        Command::Drain { cancel: true, .. } => {
+            repository.cancel_drain().await?;
+            println!("Called off the drain. The server starts matches again within a few seconds.");
+        }
+        Command::Drain { minutes, .. } => {
+            let games = repository.live_games().await?.len();
+            // A deadline ends matches without a result, so ask first.
+            if let Some(minutes) = minutes
+                && !confirm(
+                    &format!("End the {games} matches in progress in {minutes} minutes?"),
+                    args.yes,
+                )?
+            {
+                return Ok(());
+            }
+            let deadline = minutes
+                .map(|minutes| chrono::Utc::now() + chrono::Duration::minutes(minutes.into()));
+            repository.request_drain(deadline).await?;
+            match minutes {
+                Some(minutes) => println!(
+                    "Draining: no new matches, and the {games} in progress end in {minutes} minutes."
+                ),
+                None => println!("Draining: no new matches; the {games} in progress can finish."),
+            }
+            println!("Run `sorcerers-admin games` to see what is left.");
+        }
+        Command::Export { user, output } => {
+            let user = find_user(&repository, &user).await?;
+            let export = repository.export_user(user).await?;
+            let json = serde_json::to_string_pretty(&export)?;
+            match output {
+                Some(path) => std::fs::write(&path, json)
+                    .with_context(|| format!("failed to write {}", path.display()))?,
+                None => println!("{json}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn find_user(repository: &Repository, user: &str) -> Result<repository::UserSummary> {
+    repository
+        .find_user(user)
+        .await?
+        .with_context(|| format!("no user has the id, username or email {user:?}"))
+}
+
+/// Ask before a destructive change, unless `--yes` was given.
+fn confirm(question: &str, yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    let confirmed = matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+    if !confirmed {
+        println!("Nothing changed.");
+    }
+    Ok(confirmed)
+}
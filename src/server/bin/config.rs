@@ -0,0 +1,727 @@
+//! Server settings.
+//!
+//! Settings are layered, each layer overriding the one before it: built-in defaults, a TOML file
+//! named by `--config` or `SORCERERS_CONFIG`, environment variables, and command line flags. A
+//! complete file looks like:
+//!
+//! ```toml
+//! listen = "0.0.0.0:5000"
+//! database_url = "sqlite://sorcerers.db"
+//!
+//! [tls]
+//! cert = "/etc/sorcerers/fullchain.pem"
+//! key = "/etc/sorcerers/privkey.pem"
+//!
+//! [email]
+//! transport = "smtp"
+//!
+//! [email.smtp]
+//! relay = "smtp.example.com"
+//! port = 587
+//! tls = "starttls"
+//! from = "Sorcerers <noreply@example.com>"
+//! username = "sorcerers"
+//! password = "..."
+//!
+//! [rewards]
+//! win_points = 10
+//! play_points = 2
+//! beta_booster_cost = 30
+//! weekly_boosters = 3
+//!
+//! [matchmaking]
+//! draft_pick_seconds = 45
+//! draft_lobby_seconds = 30
+//!
+//! [log]
+//! level = "info"
+//! format = "text"
+//!
+//! [metrics]
+//! enabled = true
+//! listen = "127.0.0.1:5100"
+//!
+//! [shutdown]
+//! drain_seconds = 600
+//!
+//! [dev]
+//! enabled = false
+//! test_state = false
+//! ```
+use anyhow::{Context, Result, bail};
+use clap::{Parser, ValueEnum, builder::BoolishValueParser};
+use serde::Deserialize;
+use sorcerers::draft::{DRAFT_LOBBY_SECONDS, DRAFT_PICK_SECONDS};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The address the lobby accepts connections on, over TLS when `tls` is set.
+    pub listen: SocketAddr,
+    /// Where accounts and collections are stored. Only `sqlite:` URLs are supported.
+    pub database_url: String,
+    pub tls: TlsConfig,
+    pub email: EmailConfig,
+    pub rewards: RewardConfig,
+    pub matchmaking: MatchmakingConfig,
+    pub log: LogConfig,
+    pub metrics: MetricsConfig,
+    pub shutdown: ShutdownConfig,
+    pub dev: DevConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([0, 0, 0, 0], 5000)),
+            database_url: String::new(),
+            tls: TlsConfig::default(),
+            email: EmailConfig::default(),
+            rewards: RewardConfig::default(),
+            matchmaking: MatchmakingConfig::default(),
+            log: LogConfig::default(),
+            metrics: MetricsConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            dev: DevConfig::default(),
+        }
+    }
+}
+
+/// Encrypts the lobby's connections. With neither path set, the lobby speaks plain TCP.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file with the server's certificate, followed by its intermediates.
+    pub cert: Option<PathBuf>,
+    /// PEM file with the certificate's private key.
+    pub key: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Read the certificate and key, or `None` when TLS is off.
+    pub fn acceptor(&self) -> Result<Option<TlsAcceptor>> {
+        let (cert, key) = match (&self.cert, &self.key) {
+            (None, None) => return Ok(None),
+            (Some(cert), Some(key)) => (cert, key),
+            _ => bail!("tls.cert and tls.key must be set together"),
+        };
+        let chain = CertificateDer::pem_file_iter(cert)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("failed to read tls.cert {}", cert.display()))?;
+        if chain.is_empty() {
+            bail!("tls.cert {} contains no certificates", cert.display());
+        }
+        let key = PrivateKeyDer::from_pem_file(key)
+            .with_context(|| format!("failed to read tls.key {}", key.display()))?;
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(chain, key)
+            .context("tls.key is not the private key of tls.cert")?;
+        Ok(Some(TlsAcceptor::from(Arc::new(config))))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmailConfig {
+    /// How emails are delivered. Unset, servers with an SMTP relay use it, dev servers print
+    /// emails to the console and others refuse to start.
+    pub transport: Option<EmailTransportKind>,
+    /// The maildir the `maildir` transport writes to.
+    pub maildir: Option<PathBuf>,
+    pub smtp: SmtpConfig,
+}
+
+impl EmailConfig {
+    /// The transport to deliver with, or `None` when nothing is configured outside dev mode.
+    pub fn transport(&self, dev: bool) -> Option<EmailTransportKind> {
+        match self.transport {
+            Some(transport) => Some(transport),
+            None if self.smtp.relay.is_some() => Some(EmailTransportKind::Smtp),
+            None if dev => Some(EmailTransportKind::Console),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTransportKind {
+    Smtp,
+    Maildir,
+    Console,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub relay: Option<String>,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub from: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            relay: None,
+            port: 587,
+            tls: SmtpTls::default(),
+            from: None,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+/// How the connection to the SMTP relay is secured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with STARTTLS, usually on port 587.
+    #[default]
+    Starttls,
+    /// Connect over TLS from the start, usually on port 465.
+    Tls,
+    /// Send everything in the clear. Only use this for a relay on the same machine.
+    None,
+}
+
+/// The reward economy.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewardConfig {
+    /// Reward points for winning a match.
+    pub win_points: u32,
+    /// Reward points for finishing a match without winning it.
+    pub play_points: u32,
+    /// Reward points a Beta booster costs.
+    pub beta_booster_cost: u32,
+    /// Beta boosters every player receives each week.
+    pub weekly_boosters: u32,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            win_points: 10,
+            play_points: 2,
+            beta_booster_cost: 30,
+            weekly_boosters: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchmakingConfig {
+    /// How long a drafter has to pick before the server picks for them.
+    pub draft_pick_seconds: u64,
+    /// How long a draft pod waits for players before filling its empty seats with bots.
+    pub draft_lobby_seconds: u64,
+}
+
+impl Default for MatchmakingConfig {
+    fn default() -> Self {
+        Self {
+            draft_pick_seconds: DRAFT_PICK_SECONDS,
+            draft_lobby_seconds: DRAFT_LOBBY_SECONDS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// The most detailed level to log, or a filter such as "info,sorcerers=debug".
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One readable line per event.
+    #[default]
+    Text,
+    /// One JSON object per event, for log collectors.
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    /// Where `GET /metrics` is served. Only loopback addresses are allowed.
+    pub listen: SocketAddr,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            listen: SocketAddr::from(([127, 0, 0, 1], 5100)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// How long a stopping server lets the games in progress finish before ending them.
+    pub drain_seconds: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self { drain_seconds: 600 }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DevConfig {
+    /// Enables debug controls such as stepped effects in every game.
+    pub enabled: bool,
+    /// Seed new games with the scenario in this file.
+    pub scenario: Option<PathBuf>,
+    /// Seed new games with the bundled development board.
+    pub test_state: bool,
+}
+
+/// Command line flags. Each one can also be given through the environment variable named in its
+/// help, and overrides the config file.
+#[derive(Debug, Default, Parser)]
+#[command(about = "Runs the Sorcerers lobby and matchmaking server")]
+pub struct Args {
+    /// TOML file to read settings from.
+    #[arg(long, env = "SORCERERS_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address to accept connections on.
+    #[arg(long, env = "SORCERERS_LISTEN")]
+    pub listen: Option<SocketAddr>,
+    /// Database to store accounts in, for example sqlite://sorcerers.db.
+    #[arg(long, env = "DATABASE_URL")]
+    pub database_url: Option<String>,
+    /// PEM certificate chain to accept TLS connections with.
+    #[arg(long, env = "SORCERERS_TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key of the TLS certificate.
+    #[arg(long, env = "SORCERERS_TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+    /// How to deliver emails.
+    #[arg(long, env = "EMAIL_TRANSPORT")]
+    pub email_transport: Option<EmailTransportKind>,
+    /// Maildir to write emails to with the maildir transport.
+    #[arg(long, env = "EMAIL_MAILDIR")]
+    pub email_maildir: Option<PathBuf>,
+    /// SMTP relay to send emails through.
+    #[arg(long, env = "SMTP_RELAY")]
+    pub smtp_relay: Option<String>,
+    #[arg(long, env = "SMTP_PORT")]
+    pub smtp_port: Option<u16>,
+    #[arg(long, env = "SMTP_TLS")]
+    pub smtp_tls: Option<SmtpTls>,
+    /// Sender of outgoing emails, for example "Sorcerers <noreply@example.com>".
+    #[arg(long, env = "SMTP_FROM")]
+    pub smtp_from: Option<String>,
+    #[arg(long, env = "SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+    /// What to log, for example "info" or "info,sorcerers=debug".
+    #[arg(long, env = "RUST_LOG")]
+    pub log_level: Option<String>,
+    #[arg(long, env = "SORCERERS_LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+    /// Local address to serve metrics on.
+    #[arg(long, env = "SORCERERS_METRICS_LISTEN")]
+    pub metrics_listen: Option<SocketAddr>,
+    /// Enable debug controls in every game. `--dev=false` turns them off again.
+    #[arg(
+        long,
+        env = "SORCERERS_DEV_MODE",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub dev: Option<bool>,
+    /// Seed new games with the scenario in this file.
+    #[arg(long, env = "SORCERERS_SCENARIO")]
+    pub scenario: Option<PathBuf>,
+    /// Seed new games with the bundled development board. `--test-state=false` turns it off.
+    #[arg(
+        long,
+        env = "SORCERERS_TEST_STATE",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub test_state: Option<bool>,
+}
+
+impl Config {
+    /// Read the settings from the command line, the environment and the config file it names.
+    pub fn load() -> Result<Self> {
+        let args = Args::parse();
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_smtp_password();
+        config.apply(args);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &std::path::Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    /// Take the SMTP password from `SMTP_PASSWORD`. It is never taken as a flag, so it can't show
+    /// up in process listings.
+    pub fn apply_smtp_password(&mut self) {
+        if let Ok(password) = std::env::var("SMTP_PASSWORD") {
+            self.email.smtp.password = Some(password);
+        }
+    }
+
+    fn apply(&mut self, args: Args) {
+        if let Some(listen) = args.listen {
+            self.listen = listen;
+        }
+        if let Some(database_url) = args.database_url {
+            self.database_url = database_url;
+        }
+        self.tls.cert = args.tls_cert.or(self.tls.cert.take());
+        self.tls.key = args.tls_key.or(self.tls.key.take());
+        let email = &mut self.email;
+        email.transport = args.email_transport.or(email.transport);
+        email.maildir = args.email_maildir.or(email.maildir.take());
+        email.smtp.relay = args.smtp_relay.or(email.smtp.relay.take());
+        email.smtp.port = args.smtp_port.unwrap_or(email.smtp.port);
+        email.smtp.tls = args.smtp_tls.unwrap_or(email.smtp.tls);
+        email.smtp.from = args.smtp_from.or(email.smtp.from.take());
+        email.smtp.username = args.smtp_username.or(email.smtp.username.take());
+        if let Some(level) = args.log_level {
+            self.log.level = level;
+        }
+        self.log.format = args.log_format.unwrap_or(self.log.format);
+        self.metrics.listen = args.metrics_listen.unwrap_or(self.metrics.listen);
+        self.dev.enabled = args.dev.unwrap_or(self.dev.enabled);
+        self.dev.scenario = args.scenario.or(self.dev.scenario.take());
+        self.dev.test_state = args.test_state.unwrap_or(self.dev.test_state);
+    }
+
+    /// Reject settings the server can't run with.
+    pub fn validate(&self) -> Result<()> {
+        if self.database_url.is_empty() {
+            bail!(
+                "database_url must be set in the config file, with DATABASE_URL or with \
+                 --database-url, for example sqlite://sorcerers.db"
+            );
+        }
+        if !self.database_url.starts_with("sqlite:") {
+            bail!(
+                "database_url must use a sqlite: URL, not {:?}",
+                self.database_url
+            );
+        }
+        self.tls.acceptor()?;
+        let email = &self.email;
+        let Some(transport) = email.transport(self.dev.enabled) else {
+            bail!(
+                "email.transport must be set, or email.smtp.relay given, so players receive \
+                 their confirmation codes; only dev servers print emails to the console"
+            );
+        };
+        match transport {
+            EmailTransportKind::Smtp => {
+                let smtp = &email.smtp;
+                for (name, value) in [
+                    ("relay", &smtp.relay),
+                    ("from", &smtp.from),
+                    ("username", &smtp.username),
+                    ("password", &smtp.password),
+                ] {
+                    if value.is_none() {
+                        bail!("email.smtp.{name} must be set to send emails over SMTP");
+                    }
+                }
+                if let Some(from) = &smtp.from {
+                    from.parse::<lettre::message::Mailbox>().with_context(|| {
+                        format!("email.smtp.from {from:?} is not a valid mailbox")
+                    })?;
+                }
+                if smtp.port == 0 {
+                    bail!("email.smtp.port must not be 0");
+                }
+            }
+            EmailTransportKind::Maildir if email.maildir.is_none() => {
+                bail!("email.maildir must be set to deliver emails to a maildir");
+            }
+            EmailTransportKind::Maildir | EmailTransportKind::Console => {}
+        }
+        let rewards = &self.rewards;
+        if rewards.beta_booster_cost == 0 {
+            bail!("rewards.beta_booster_cost must be at least 1");
+        }
+        // Every reward row records at least one point.
+        if rewards.win_points == 0 {
+            bail!("rewards.win_points must be at least 1");
+        }
+        if rewards.play_points == 0 {
+            bail!("rewards.play_points must be at least 1");
+        }
+        if rewards.play_points > rewards.win_points {
+            bail!("rewards.play_points must not be more than rewards.win_points");
+        }
+        if self.matchmaking.draft_pick_seconds == 0 {
+            bail!("matchmaking.draft_pick_seconds must be at least 1");
+        }
+        tracing_subscriber::EnvFilter::try_new(&self.log.level)
+            .with_context(|| format!("log.level {:?} is not a valid filter", self.log.level))?;
+        if self.metrics.enabled && !self.metrics.listen.ip().is_loopback() {
+            bail!(
+                "metrics.listen must be a loopback address such as 127.0.0.1:5100, not {}",
+                self.metrics.listen
+            );
+        }
+        if self.dev.scenario.is_some() && self.dev.test_state {
+            bail!("dev.scenario and dev.test_state can't both be set");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<Config> {
+        let config: Config = toml::from_str(contents)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[test]
+    fn dev_files_only_need_a_database() {
+        let config = parse("database_url = \"sqlite://sorcerers.db\"\ndev.enabled = true").unwrap();
+        assert_eq!(config.listen, "0.0.0.0:5000".parse().unwrap());
+        assert_eq!(config.rewards, RewardConfig::default());
+        assert_eq!(config.metrics.listen, "127.0.0.1:5100".parse().unwrap());
+        assert_eq!(
+            config.email.transport(config.dev.enabled),
+            Some(EmailTransportKind::Console)
+        );
+
+        assert!(parse("").unwrap_err().to_string().contains("database_url"));
+    }
+
+    #[test]
+    fn only_dev_servers_fall_back_to_printing_emails() {
+        let database = "database_url = \"sqlite://sorcerers.db\"\n";
+        let error = parse(database).unwrap_err().to_string();
+        assert!(error.contains("email.transport"), "{error}");
+
+        let config = parse(&format!("{database}[email]\ntransport = \"console\"")).unwrap();
+        assert_eq!(
+            config.email.transport(false),
+            Some(EmailTransportKind::Console)
+        );
+        let config = parse(&format!(
+            "{database}[email.smtp]\nrelay = \"smtp.example.com\"\nfrom = \"a@example.com\"\n\
+             username = \"a\"\npassword = \"b\""
+        ))
+        .unwrap();
+        assert_eq!(
+            config.email.transport(false),
+            Some(EmailTransportKind::Smtp)
+        );
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected_with_the_setting_named() {
+        let error = |contents: &str| format!("{:#}", parse(contents).unwrap_err());
+        let database = "database_url = \"sqlite://sorcerers.db\"\ndev.enabled = true\n";
+
+        assert!(error(&format!("{database}[rewards]\nwin_point = 5")).contains("win_point"));
+        assert!(error(&format!("{database}listen = \"localhost\"")).contains("listen"));
+        assert!(
+            error(&format!("{database}[rewards]\nbeta_booster_cost = 0"))
+                .contains("rewards.beta_booster_cost")
+        );
+        assert!(
+            error(&format!("{database}[rewards]\nwin_points = 0")).contains("rewards.win_points")
+        );
+        assert!(
+            error(&format!("{database}[rewards]\nplay_points = 0")).contains("rewards.play_points")
+        );
+        assert!(
+            error(&format!(
+                "{database}[email.smtp]\nrelay = \"smtp.example.com\""
+            ))
+            .contains("email.smtp.from")
+        );
+        assert!(error(&format!("{database}[email]\ntransport = \"maildir\"")).contains("maildir"));
+        assert!(error("database_url = \"postgres://localhost\"").contains("sqlite:"));
+        assert!(
+            error(&format!("{database}[metrics]\nlisten = \"0.0.0.0:5100\""))
+                .contains("metrics.listen")
+        );
+        assert!(
+            error(&format!("{database}[log]\nlevel = \"sorcerers=loud\"")).contains("log.level")
+        );
+    }
+
+    #[test]
+    fn flags_and_environment_override_the_file() {
+        let mut config = parse(
+            "database_url = \"sqlite://file.db\"\n[email]\ntransport = \"console\"\n[dev]\nenabled = true",
+        )
+        .unwrap();
+        config.apply(Args {
+            listen: Some("127.0.0.1:6000".parse().unwrap()),
+            database_url: Some("sqlite://flag.db".to_string()),
+            email_transport: Some(EmailTransportKind::Maildir),
+            email_maildir: Some("mail".into()),
+            ..Args::default()
+        });
+
+        assert_eq!(config.listen, "127.0.0.1:6000".parse().unwrap());
+        assert_eq!(config.database_url, "sqlite://flag.db");
+        assert_eq!(
+            config.email.transport(config.dev.enabled),
+            Some(EmailTransportKind::Maildir)
+        );
+        // Leaving a flag off keeps what the file says.
+        assert!(config.dev.enabled);
+        config.validate().unwrap();
+    }
+
+    fn testdata(file: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "bin", "testdata", file]
+            .iter()
+            .collect()
+    }
+
+    fn tls(cert: &str, key: &str) -> TlsConfig {
+        TlsConfig {
+            cert: Some(testdata(cert)),
+            key: Some(testdata(key)),
+        }
+    }
+
+    #[test]
+    fn tls_needs_a_readable_certificate_and_its_key() {
+        let error = |tls: TlsConfig| format!("{:#}", tls.acceptor().err().unwrap());
+
+        assert!(TlsConfig::default().acceptor().unwrap().is_none());
+        assert!(
+            tls("localhost.pem", "localhost-key.pem")
+                .acceptor()
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            error(TlsConfig {
+                cert: Some(testdata("localhost.pem")),
+                key: None,
+            })
+            .contains("set together")
+        );
+        assert!(error(tls("missing.pem", "localhost-key.pem")).contains("tls.cert"));
+        assert!(error(tls("localhost-key.pem", "localhost-key.pem")).contains("tls.cert"));
+        assert!(error(tls("localhost.pem", "localhost.pem")).contains("tls.key"));
+
+        let database = "database_url = \"sqlite://sorcerers.db\"\ndev.enabled = true\n";
+        let error = format!(
+            "{:#}",
+            parse(&format!("{database}[tls]\ncert = \"cert.pem\"")).unwrap_err()
+        );
+        assert!(error.contains("tls.cert and tls.key"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn tls_connections_are_encrypted_with_the_configured_certificate() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_rustls::{
+            TlsConnector,
+            rustls::{ClientConfig, RootCertStore, pki_types::ServerName},
+        };
+
+        let acceptor = tls("localhost.pem", "localhost-key.pem")
+            .acceptor()
+            .unwrap()
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let mut hello = [0; 5];
+            stream.read_exact(&mut hello).await.unwrap();
+            hello
+        });
+
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(testdata("localhost.pem")).unwrap() {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let connector = TlsConnector::from(Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        ));
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut stream = connector
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        stream.write_all(b"hello").await.unwrap();
+        stream.flush().await.unwrap();
+
+        assert_eq!(&server.await.unwrap(), b"hello");
+    }
+
+    #[test]
+    fn dev_flags_override_the_file_both_ways() {
+        let args = |flags: &[&str]| {
+            Args::try_parse_from(std::iter::once("server").chain(flags.iter().copied())).unwrap()
+        };
+        assert_eq!(args(&[]).dev, None);
+        assert_eq!(args(&["--dev"]).dev, Some(true));
+        assert_eq!(args(&["--dev=false"]).dev, Some(false));
+        assert_eq!(args(&["--test-state=0"]).test_state, Some(false));
+
+        let mut config =
+            parse("database_url = \"sqlite://file.db\"\n[dev]\nenabled = true\ntest_state = true")
+                .unwrap();
+        config.apply(args(&["--dev=false", "--test-state=false"]));
+        assert!(!config.dev.enabled);
+        assert!(!config.dev.test_state);
+
+        config.apply(args(&["--dev"]));
+        assert!(config.dev.enabled);
+        assert!(!config.dev.test_state);
+    }
+}
@@ -1,47 +1,319 @@
+//! Outgoing email: the templates the server sends and the transports that deliver them.
+//!
+//! `email.transport` in the server config picks the transport: `smtp` relays through
+//! `email.smtp.relay`, `maildir` drops each email into the maildir at `email.maildir`, and
+//! `console` prints it.
 use anyhow::{Context, Result};
 use lettre::{
     AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
     transport::smtp::authentication::Credentials,
 };
+use std::{future::Future, path::PathBuf, pin::Pin, sync::Arc};
 
-pub struct EmailSender {
+use crate::config::{EmailConfig, EmailTransportKind, SmtpConfig, SmtpTls};
+use crate::repository::CONFIRMATION_CODE_LIFETIME_MINUTES;
+
+/// Every email the server sends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Email {
+    ConfirmationCode {
+        code: String,
+    },
+    PasswordResetCode {
+        code: String,
+    },
+    /// Tells the owner of an account that its password was just changed or reset.
+    PasswordChanged,
+}
+
+impl Email {
+    pub fn subject(&self) -> &'static str {
+        match self {
+            Self::ConfirmationCode { .. } => "Confirm your Sorcerers email",
+            Self::PasswordResetCode { .. } => "Reset your Sorcerers password",
+            Self::PasswordChanged => "Your Sorcerers password was changed",
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            Self::ConfirmationCode { code } => format!(
+                "Your Sorcerers confirmation code is {code}. It expires in {CONFIRMATION_CODE_LIFETIME_MINUTES} minutes.\n\nIf you did not create an account, you can ignore this email."
+            ),
+            Self::PasswordResetCode { code } => format!(
+                "Your Sorcerers password reset code is {code}. It expires in {CONFIRMATION_CODE_LIFETIME_MINUTES} minutes.\n\nIf you did not ask to reset your password, you can ignore this email."
+            ),
+            Self::PasswordChanged => "The password for your Sorcerers account was just changed.\n\nIf this wasn't you, reset it from the login screen right away.".to_string(),
+        }
+    }
+
+    /// The one-time code in the email, if it carries one.
+    #[cfg(test)]
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::ConfirmationCode { code } | Self::PasswordResetCode { code } => Some(code),
+            Self::PasswordChanged => None,
+        }
+    }
+}
+
+pub type Delivery<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Somewhere emails can be delivered.
+pub trait EmailTransport: Send + Sync {
+    fn deliver<'a>(&'a self, to: &'a str, email: &'a Email) -> Delivery<'a>;
+}
+
+/// Relays emails through an SMTP server.
+pub struct SmtpTransport {
     transport: AsyncSmtpTransport<Tokio1Executor>,
     from: Mailbox,
 }
 
-impl EmailSender {
-    pub fn from_env() -> Result<Self> {
-        let relay = std::env::var("SMTP_RELAY").context("SMTP_RELAY must be set")?;
-        let from = std::env::var("SMTP_FROM")
-            .context("SMTP_FROM must be set")?
+impl SmtpTransport {
+    pub fn new(config: &SmtpConfig) -> Result<Self> {
+        let setting = |value: &Option<String>, name: &str| {
+            value
+                .clone()
+                .with_context(|| format!("email.smtp.{name} must be set"))
+        };
+        let relay = setting(&config.relay, "relay")?;
+        let from = setting(&config.from, "from")?
             .parse()
-            .context("SMTP_FROM must be a valid mailbox")?;
-        let username = std::env::var("SMTP_USERNAME").context("SMTP_USERNAME must be set")?;
-        let password = std::env::var("SMTP_PASSWORD").context("SMTP_PASSWORD must be set")?;
-        let port = std::env::var("SMTP_PORT")
-            .ok()
-            .map(|value| value.parse().context("SMTP_PORT must be a number"))
-            .transpose()?
-            .unwrap_or(587);
-        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&relay)
-            .context("SMTP_RELAY must be a valid hostname")?
-            .port(port)
-            .credentials(Credentials::new(username, password))
+            .context("email.smtp.from must be a valid mailbox")?;
+        let builder = match config.tls {
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&relay)
+                .context("email.smtp.relay must be a valid hostname")?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&relay)
+                .context("email.smtp.relay must be a valid hostname")?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&relay),
+        };
+        let transport = builder
+            .port(config.port)
+            .credentials(Credentials::new(
+                setting(&config.username, "username")?,
+                setting(&config.password, "password")?,
+            ))
             .build();
         Ok(Self { transport, from })
     }
+}
+
+impl EmailTransport for SmtpTransport {
+    fn deliver<'a>(&'a self, to: &'a str, email: &'a Email) -> Delivery<'a> {
+        Box::pin(async move {
+            let message = build_message(&self.from, to, email)?;
+            self.transport
+                .send(message)
+                .await
+                .context("failed to deliver email")?;
+            Ok(())
+        })
+    }
+}
+
+/// Writes each email as a file in a maildir, for reading with a local mail client.
+pub struct MaildirTransport {
+    directory: PathBuf,
+    from: Mailbox,
+}
+
+impl MaildirTransport {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            from: "Sorcerers <noreply@localhost>"
+                .parse()
+                .expect("the default sender is a valid mailbox"),
+        }
+    }
+}
+
+impl EmailTransport for MaildirTransport {
+    fn deliver<'a>(&'a self, to: &'a str, email: &'a Email) -> Delivery<'a> {
+        Box::pin(async move {
+            let message = build_message(&self.from, to, email)?;
+            let name = format!(
+                "{}.{}.sorcerers",
+                chrono::Utc::now().timestamp(),
+                uuid::Uuid::new_v4().simple()
+            );
+            // Maildir readers only look in `new`, and expect files to appear there complete.
+            let tmp = self.directory.join("tmp");
+            let new = self.directory.join("new");
+            for directory in [&tmp, &new, &self.directory.join("cur")] {
+                tokio::fs::create_dir_all(directory)
+                    .await
+                    .with_context(|| format!("failed to create {}", directory.display()))?;
+            }
+            tokio::fs::write(tmp.join(&name), message.formatted())
+                .await
+                .context("failed to write email")?;
+            tokio::fs::rename(tmp.join(&name), new.join(&name))
+                .await
+                .context("failed to deliver email to the maildir")?;
+            Ok(())
+        })
+    }
+}
+
+/// Prints each email to stdout.
+pub struct ConsoleTransport;
+
+impl EmailTransport for ConsoleTransport {
+    fn deliver<'a>(&'a self, to: &'a str, email: &'a Email) -> Delivery<'a> {
+        Box::pin(async move {
+            println!("Email to {to}: {}\n{}\n", email.subject(), email.body());
+            Ok(())
+        })
+    }
+}
+
+fn build_message(from: &Mailbox, to: &str, email: &Email) -> Result<Message> {
+    Message::builder()
+        .from(from.clone())
+        .to(to.parse().context("recipient email is invalid")?)
+        .subject(email.subject())
+        .body(email.body())
+        .context("failed to build email")
+}
+
+/// Cheap to clone, so deliveries can be handed off to a background task.
+#[derive(Clone)]
+pub struct EmailSender {
+    transport: Arc<dyn EmailTransport>,
+}
+
+impl EmailSender {
+    pub fn new(transport: impl EmailTransport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+        }
+    }
+
+    pub fn from_config(config: &EmailConfig, dev: bool) -> Result<Self> {
+        let transport = config
+            .transport(dev)
+            .context("email.transport must be set")?;
+        Ok(match transport {
+            EmailTransportKind::Smtp => Self::new(SmtpTransport::new(&config.smtp)?),
+            EmailTransportKind::Maildir => Self::new(MaildirTransport::new(
+                config
+                    .maildir
+                    .clone()
+                    .context("email.maildir must be set")?,
+            )),
+            EmailTransportKind::Console => {
+                if config.transport.is_none() {
+                    tracing::warn!(
+                        "no email transport is configured; dev mode prints emails to the console"
+                    );
+                }
+                Self::new(ConsoleTransport)
+            }
+        })
+    }
+
+    pub async fn send(&self, to: &str, email: Email) -> Result<()> {
+        self.transport.deliver(to, &email).await
+    }
 
     pub async fn send_confirmation_code(&self, email: &str, code: &str) -> Result<()> {
-        let message = Message::builder()
-            .from(self.from.clone())
-            .to(email.parse().context("confirmation email is invalid")?)
-            .subject("Confirm your Sorcerers email")
-            .body(format!(
-                "Your Sorcerers confirmation code is {code}. It expires in 15 minutes.\n\nIf you did not create an account, you can ignore this email."
-            ))
-            .context("failed to build confirmation email")?;
-        self.transport.send(message).await?;
-        // .context("failed to deliver confirmation email")?;
-        Ok(())
+        self.send(
+            email,
+            Email::ConfirmationCode {
+                code: code.to_string(),
+            },
+        )
+        .await
+    }
+
+    pub async fn send_password_reset_code(&self, email: &str, code: &str) -> Result<()> {
+        self.send(
+            email,
+            Email::PasswordResetCode {
+                code: code.to_string(),
+            },
+        )
+        .await
+    }
+}
+
+/// Keeps every email sent through it, so tests can read the codes players would have received.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct CapturedEmails {
+    sent: std::sync::Arc<std::sync::Mutex<Vec<(String, Email)>>>,
+}
+
+#[cfg(test)]
+impl CapturedEmails {
+    pub fn sender(&self) -> EmailSender {
+        EmailSender::new(self.clone())
+    }
+
+    /// The emails sent to `to`, oldest first.
+    pub fn sent_to(&self, to: &str) -> Vec<Email> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(recipient, _)| recipient == to)
+            .map(|(_, email)| email.clone())
+            .collect()
+    }
+
+    /// The code in the latest email to `to` that carried one.
+    pub fn last_code(&self, to: &str) -> Option<String> {
+        self.sent_to(to)
+            .iter()
+            .rev()
+            .find_map(|email| email.code().map(str::to_string))
+    }
+
+    /// The password reset code sent to `to`, waiting for a delivery that runs in the background.
+    pub async fn wait_for_reset_code(&self, to: &str) -> String {
+        for _ in 0..100 {
+            if let Some(Email::PasswordResetCode { code }) = self.sent_to(to).last() {
+                return code.clone();
+            }
+            tokio::task::yield_now().await;
+        }
+        panic!("no password reset code was sent to {to}");
+    }
+}
+
+#[cfg(test)]
+impl EmailTransport for CapturedEmails {
+    fn deliver<'a>(&'a self, to: &'a str, email: &'a Email) -> Delivery<'a> {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((to.to_string(), email.clone()));
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn maildir_deliveries_land_in_new() {
+        let directory =
+            std::env::temp_dir().join(format!("sorcerers-mail-{}", uuid::Uuid::new_v4()));
+        let sender = EmailSender::new(MaildirTransport::new(&directory));
+        sender
+            .send_confirmation_code("mage@example.com", "123456")
+            .await
+            .unwrap();
+
+        let mut delivered = std::fs::read_dir(directory.join("new")).unwrap();
+        let message = std::fs::read_to_string(delivered.next().unwrap().unwrap().path()).unwrap();
+        assert!(delivered.next().is_none());
+        assert!(message.contains("To: mage@example.com"));
+        assert!(message.contains("123456"));
+        assert_eq!(std::fs::read_dir(directory.join("tmp")).unwrap().count(), 0);
+        std::fs::remove_dir_all(directory).unwrap();
     }
 }
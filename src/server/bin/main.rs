@@ -1,45 +1,122 @@
+mod config;
 mod email;
+mod metrics;
 mod server;
+// Shared with `sorcerers-admin`, whose operator queries the server never calls.
 #[path = "../repository/mod.rs"]
+#[allow(dead_code, unused_imports)]
 mod repository;
 
 use crate::server::Server;
 use crate::repository::Repository;
 use crate::email::EmailSender;
+use crate::config::{Config, DevConfig, LogConfig, LogFormat};
+use crate::metrics::Gauge;
+use anyhow::Context;
 use sorcerers::{
     networking::{
-        MAX_MESSAGE_SIZE,
+        MAX_MESSAGE_SIZE, StreamWriter,
         message::{ClientMessage, Message},
     },
     query::QueryCache,
+    scenario::Scenario,
 };
-use std::{net::SocketAddr, sync::Arc};
-use tokio::{io::AsyncReadExt, net::TcpListener, sync::Mutex};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+use tokio_rustls::TlsAcceptor;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+
+/// How often rate limit rows left behind by keys that never came back are deleted.
+const RATE_LIMIT_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long a client has to finish the TLS handshake before it is disconnected.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let config = Config::load()?;
+    init_logging(&config.log);
     QueryCache::init();
 
-    // Enable board-evaluation debug output with `--eval` or `SORCERERS_DEBUG_EVAL=1`.
-    let test_state = std::env::args().any(|a| a == "--test-state")
-        || std::env::var("SORCERERS_TEST_STATE").is_ok_and(|v| v == "1");
-    if test_state {
-        println!("Server test-state mode enabled – new games will include the seeded test board.");
+    let scenario = load_scenario(&config.dev)?;
+    if let Some(scenario) = &scenario {
+        tracing::info!(
+            scenario = %scenario.name,
+            "scenario mode enabled; new games will start from the scenario"
+        );
+    }
+
+    if config.dev.enabled {
+        tracing::info!("dev mode enabled; debug controls are available in every game");
     }
 
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set, for example sqlite://sorcerers.db");
-    let users = Repository::connect(&database_url).await?;
-    let email_sender = EmailSender::from_env()?;
+    let users = Repository::connect(&config.database_url).await?;
+    users.clear_live_games().await?;
+    users.cancel_drain().await?;
+    let email_sender = EmailSender::from_config(&config.email, config.dev.enabled)?;
+
+    let tls = config.tls.acceptor()?;
+    let socket = TcpListener::bind(config.listen).await?;
+    let (game_outcomes_tx, game_outcomes) = async_channel::unbounded();
+    let server = Server::new(scenario, &config, users, email_sender, game_outcomes_tx);
+    let metrics = server.metrics();
+    let server = Arc::new(Mutex::new(server));
+    tracing::info!(listen = %config.listen, tls = tls.is_some(), "accepting connections");
 
-    let socket = TcpListener::bind("0.0.0.0:5000".parse::<SocketAddr>()?).await?;
-    let server = Arc::new(Mutex::new(Server::new(test_state, users, email_sender)));
+    if config.metrics.enabled {
+        let listener = TcpListener::bind(config.metrics.listen).await?;
+        tracing::info!(listen = %config.metrics.listen, "serving metrics at /metrics");
+        tokio::spawn(metrics::serve(listener, Arc::clone(&server)));
+    }
 
+    // Drive draft pick timers and drains, feed finished games back into draft brackets, and
+    // sweep expired rate limits.
+    let ticking_server = Arc::clone(&server);
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_secs(1));
+        let mut sweeps = tokio::time::interval(RATE_LIMIT_SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {
+                    let mut server = ticking_server.lock().await;
+                    server.tick_drafts().await;
+                    server.tick_drain().await;
+                }
+                _ = sweeps.tick() => ticking_server.lock().await.prune_rate_limits().await,
+                Ok(outcome) = game_outcomes.recv() => {
+                    ticking_server.lock().await.record_game_outcome(outcome).await
+                }
+            }
+        }
+    });
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
     loop {
-        let (stream, addr) = socket.accept().await?;
+        let (stream, addr) = tokio::select! {
+            accepted = socket.accept() => accepted?,
+            signal = &mut shutdown => {
+                signal?;
+                break;
+            }
+        };
         let server_clone = Arc::clone(&server);
-        tokio::spawn(async move {
-            let (mut reader, writer) = stream.into_split();
+        let connection = metrics.track(Gauge::ConnectedSockets);
+        let tls = tls.clone();
+        let task = async move {
+            let _connection = connection;
+            let (mut reader, writer) = match open_stream(stream, tls).await {
+                Ok(halves) => halves,
+                Err(err) => {
+                    tracing::warn!(error = %format!("{err:#}"), "closing connection");
+                    return;
+                }
+            };
             let writer = Arc::new(Mutex::new(writer));
             loop {
                 let mut len: [u8; std::mem::size_of::<usize>()] = [0; std::mem::size_of::<usize>()];
@@ -58,7 +135,7 @@ async fn main() -> anyhow::Result<()> {
 
                 let len = usize::from_be_bytes(len);
                 if len > MAX_MESSAGE_SIZE {
-                    eprintln!("closing connection from {addr}: message too large ({len} bytes)");
+                    tracing::warn!(len, "closing connection: message too large");
                     break;
                 }
 
@@ -94,7 +171,7 @@ async fn main() -> anyhow::Result<()> {
                 let msg: Message = match rmp_serde::from_slice(&buf) {
                     Ok(msg) => msg,
                     Err(err) => {
-                        eprintln!("closing connection from {addr}: invalid message: {err}");
+                        tracing::warn!(error = %err, "closing connection: invalid message");
                         break;
                     }
                 };
@@ -103,10 +180,94 @@ async fn main() -> anyhow::Result<()> {
                     .process_message(&msg, Arc::clone(&writer), &addr)
                     .await
                 {
-                    eprintln!("closing connection from {addr}: {err}");
+                    tracing::warn!(error = %err, "closing connection");
                     break;
                 }
             }
-        });
+        };
+        tokio::spawn(task.instrument(tracing::info_span!("connection", %addr)));
+    }
+
+    // Players already connected stay, so they can finish their games.
+    drop(socket);
+    let grace = Duration::from_secs(config.shutdown.drain_seconds);
+    server.lock().await.begin_shutdown(grace).await;
+    let games_finished = async {
+        let mut checks = tokio::time::interval(Duration::from_secs(1));
+        while server.lock().await.live_games() > 0 {
+            checks.tick().await;
+        }
+    };
+    tokio::select! {
+        _ = games_finished => {}
+        _ = shutdown_signal() => {
+            tracing::warn!("asked to stop again; ending the games in progress now");
+            server.lock().await.end_live_games().await;
+        }
     }
+    tracing::info!("stopped");
+    Ok(())
+}
+
+/// Split an accepted connection into its halves, after a TLS handshake when TLS is on.
+async fn open_stream(
+    stream: TcpStream,
+    tls: Option<TlsAcceptor>,
+) -> anyhow::Result<(Box<dyn AsyncRead + Send + Unpin>, StreamWriter)> {
+    let Some(acceptor) = tls else {
+        let (reader, writer) = stream.into_split();
+        return Ok((Box::new(reader), Box::new(writer)));
+    };
+    let stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+        .await
+        .context("TLS handshake timed out")?
+        .context("TLS handshake failed")?;
+    let (reader, writer) = tokio::io::split(stream);
+    Ok((Box::new(reader), Box::new(writer)))
+}
+
+/// Wait until the process is asked to stop, with Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+/// Log to stderr at the configured level and format.
+fn init_logging(log: &LogConfig) {
+    // `Config::validate` has already checked the filter.
+    let logger = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&log.level))
+        .with_writer(std::io::stderr);
+    match log.format {
+        LogFormat::Text => logger.init(),
+        LogFormat::Json => logger.json().init(),
+    }
+}
+
+/// The development board used by `--test-state`.
+const TEST_STATE_SCENARIO: &str = include_str!("../../../scenarios/test_state.json");
+
+/// Resolve the board new games are seeded with: the scenario file in `dev.scenario`, or the
+/// bundled development board with `dev.test_state`.
+fn load_scenario(dev: &DevConfig) -> anyhow::Result<Option<Scenario>> {
+    if let Some(path) = &dev.scenario {
+        let scenario = Scenario::from_file(path)
+            .map_err(|err| anyhow::anyhow!("failed to load scenario {}: {err}", path.display()))?;
+        return Ok(Some(scenario));
+    }
+
+    if dev.test_state {
+        return Ok(Some(Scenario::from_json(TEST_STATE_SCENARIO)?));
+    }
+
+    Ok(None)
 }
@@ -0,0 +1,260 @@
+//! Numbers an operator can scrape to see how the server is holding up.
+//!
+//! [`serve`] answers `GET /metrics` in the Prometheus text format. It only listens on loopback
+//! addresses and hangs up on connections from anywhere else, so the endpoint stays private to the
+//! host unless the operator deliberately proxies it.
+use crate::repository::{Storage, TimingSnapshot, Timings};
+use crate::server::Server;
+use sorcerers::game::EffectDrainObserver;
+use std::{
+    fmt::Write as _,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+/// How long a scraper has to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    connected_sockets: AtomicU64,
+    live_games: AtomicU64,
+    effect_drains: Arc<Timings>,
+}
+
+/// Something [`Metrics`] counts while it exists.
+#[derive(Debug, Clone, Copy)]
+pub enum Gauge {
+    ConnectedSockets,
+    /// Games in progress, including goldfish and sandbox games.
+    LiveGames,
+}
+
+/// Counts towards a [`Gauge`] until dropped.
+pub struct Tracked {
+    metrics: Arc<Metrics>,
+    gauge: Gauge,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.metrics
+            .gauge(self.gauge)
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// How many players are waiting in each matchmaking queue.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueueLengths {
+    pub constructed: usize,
+    pub sealed: usize,
+}
+
+impl Metrics {
+    fn gauge(&self, gauge: Gauge) -> &AtomicU64 {
+        match gauge {
+            Gauge::ConnectedSockets => &self.connected_sockets,
+            Gauge::LiveGames => &self.live_games,
+        }
+    }
+
+    pub fn track(self: &Arc<Self>, gauge: Gauge) -> Tracked {
+        self.gauge(gauge).fetch_add(1, Ordering::Relaxed);
+        Tracked {
+            metrics: Arc::clone(self),
+            gauge,
+        }
+    }
+
+    /// Hand to each game so the time it spends resolving effects is recorded.
+    pub fn effect_drain_observer(&self) -> EffectDrainObserver {
+        let effect_drains = Arc::clone(&self.effect_drains);
+        Arc::new(move |elapsed| effect_drains.record(elapsed))
+    }
+
+    pub fn render(&self, queues: QueueLengths, queries: Option<TimingSnapshot>) -> String {
+        let mut out = String::new();
+        gauge(
+            &mut out,
+            "sorcerers_connected_sockets",
+            "Open client connections, logged in or not.",
+            &[("", self.connected_sockets.load(Ordering::Relaxed))],
+        );
+        gauge(
+            &mut out,
+            "sorcerers_matchmaking_queue_length",
+            "Players waiting for an opponent.",
+            &[
+                ("queue=\"constructed\"", queues.constructed as u64),
+                ("queue=\"sealed\"", queues.sealed as u64),
+            ],
+        );
+        gauge(
+            &mut out,
+            "sorcerers_live_games",
+            "Games in progress, including goldfish and sandbox games.",
+            &[("", self.live_games.load(Ordering::Relaxed))],
+        );
+        summary(
+            &mut out,
+            "sorcerers_effect_drain_seconds",
+            "Time a game update spends resolving queued effects.",
+            self.effect_drains.snapshot(),
+        );
+        if let Some(queries) = queries {
+            summary(
+                &mut out,
+                "sorcerers_db_call_seconds",
+                "Time each call into the database takes.",
+                queries,
+            );
+        }
+        out
+    }
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, values: &[(&str, u64)]) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} gauge").unwrap();
+    for (labels, value) in values {
+        if labels.is_empty() {
+            writeln!(out, "{name} {value}").unwrap();
+        } else {
+            writeln!(out, "{name}{{{labels}}} {value}").unwrap();
+        }
+    }
+}
+
+fn summary(out: &mut String, name: &str, help: &str, timings: TimingSnapshot) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} summary").unwrap();
+    writeln!(out, "{name}_sum {}", timings.total.as_secs_f64()).unwrap();
+    writeln!(out, "{name}_count {}", timings.count).unwrap();
+    writeln!(
+        out,
+        "# HELP {name}_max The slowest since the previous scrape."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE {name}_max gauge").unwrap();
+    writeln!(out, "{name}_max {}", timings.max.as_secs_f64()).unwrap();
+}
+
+/// Answer scrapes on `listener` until the server stops.
+pub async fn serve<S: Storage>(listener: TcpListener, server: Arc<Mutex<Server<S>>>) {
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(error) => {
+                tracing::warn!(%error, "failed to accept a metrics connection");
+                continue;
+            }
+        };
+        if !addr.ip().is_loopback() {
+            tracing::warn!(%addr, "refused a metrics connection from a non-local address");
+            continue;
+        }
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+            if let Err(error) = respond(stream, &server).await {
+                tracing::debug!(%addr, %error, "failed to answer a metrics request");
+            }
+        });
+    }
+}
+
+async fn respond<S: Storage>(
+    mut stream: TcpStream,
+    server: &Mutex<Server<S>>,
+) -> std::io::Result<()> {
+    let mut request = [0; 1024];
+    let read = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut request))
+        .await
+        .map_err(|_| std::io::ErrorKind::TimedOut)??;
+    let (status, body) = if is_metrics_request(&request[..read]) {
+        ("200 OK", server.lock().await.render_metrics())
+    } else {
+        (
+            "404 Not Found",
+            "Metrics are served at /metrics.\n".to_string(),
+        )
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn is_metrics_request(request: &[u8]) -> bool {
+    let request = String::from_utf8_lossy(request);
+    let mut request_line = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    matches!(
+        (request_line.next(), request_line.next()),
+        (Some("GET"), Some("/metrics"))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_gauges_and_timings_in_the_prometheus_format() {
+        let metrics = Arc::new(Metrics::default());
+        let socket = metrics.track(Gauge::ConnectedSockets);
+        let _game = metrics.track(Gauge::LiveGames);
+        {
+            let _closed = metrics.track(Gauge::ConnectedSockets);
+        }
+        metrics.effect_drain_observer()(Duration::from_millis(250));
+        let queries = Timings::default();
+        queries.record(Duration::from_millis(2));
+
+        let text = metrics.render(
+            QueueLengths {
+                constructed: 3,
+                sealed: 1,
+            },
+            Some(queries.snapshot()),
+        );
+        assert!(text.contains("\nsorcerers_connected_sockets 1\n"));
+        assert!(text.contains("\nsorcerers_matchmaking_queue_length{queue=\"constructed\"} 3\n"));
+        assert!(text.contains("\nsorcerers_matchmaking_queue_length{queue=\"sealed\"} 1\n"));
+        assert!(text.contains("\nsorcerers_live_games 1\n"));
+        assert!(text.contains("\nsorcerers_effect_drain_seconds_count 1\n"));
+        assert!(text.contains("\nsorcerers_effect_drain_seconds_max 0.25\n"));
+        assert!(text.contains("\nsorcerers_db_call_seconds_sum 0.002\n"));
+
+        drop(socket);
+        let text = metrics.render(QueueLengths::default(), None);
+        assert!(text.contains("\nsorcerers_connected_sockets 0\n"));
+        // The slowest drain is reported once per scrape.
+        assert!(text.contains("\nsorcerers_effect_drain_seconds_max 0\n"));
+        assert!(!text.contains("sorcerers_db_call_seconds"));
+    }
+
+    #[test]
+    fn only_get_metrics_is_served() {
+        assert!(is_metrics_request(
+            b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        ));
+        assert!(!is_metrics_request(b"GET / HTTP/1.1\r\n\r\n"));
+        assert!(!is_metrics_request(b"POST /metrics HTTP/1.1\r\n\r\n"));
+        assert!(!is_metrics_request(b""));
+    }
+}
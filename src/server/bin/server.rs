@@ -1,85 +1,227 @@
 use async_channel::Sender;
 use chrono::Datelike;
 use sorcerers::{
+    achievement::{FinishedGame, UnlockedAchievement, achievement_by_id},
     booster::BoosterPack,
-    card::{self, *},
     collection::CollectedCard,
     deck::{CardNameWithCount, DeckList, precon::PreconDeck},
-    game::Game,
+    draft::{BracketSlot, DraftBracket, DraftPhase, DraftPod},
+    game::{Game, GameOutcome, PlayerId},
+    goldfish::Goldfish,
     networking::{
+        StreamWriter,
         client::Client,
         message::{ClientMessage, DeckChoice, Message, ServerMessage},
     },
+    quest,
+    scenario::Scenario,
+    sealed::SealedPool,
     state::{Player, PlayerWithDeck},
-    zone::{Location, Zone},
+    zone::Zone,
 };
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
-use tokio::{net::tcp::OwnedWriteHalf, sync::Mutex};
+use tokio::{sync::Mutex, task::JoinHandle};
 
 use crate::{
-    email::EmailSender,
-    repository::{Repository, RepositoryError, User},
+    config::{Config, MatchmakingConfig, RewardConfig},
+    email::{Email, EmailSender},
+    metrics::{Gauge, Metrics, QueueLengths},
+    repository::{
+        DrainRequest, RateLimit, Repository, RepositoryError, Storage, TradeUpdate, User,
+    },
 };
 
-pub struct Server {
+/// How long players can report or block their opponent after a match starts.
+const RECENT_MATCH_LIFETIME: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Why the server stopped starting games, and what happens to the ones in progress.
+struct Drain {
+    /// When the games still going are ended, or `None` to let them finish.
+    deadline: Option<Instant>,
+    /// Set once the server was told to stop. A shutdown can't be called off.
+    shutting_down: bool,
+    /// The `sorcerers-admin drain` this drain follows.
+    request: Option<DrainRequest>,
+    /// What players are told about the drain.
+    notice: String,
+}
+
+/// A draft pod together with the server-side bookkeeping needed to run it.
+struct DraftEvent {
+    pod: DraftPod,
+    created_at: Instant,
+    streams: HashMap<PlayerId, Arc<Mutex<StreamWriter>>>,
+    /// When the current pick of each human seat times out.
+    pick_deadlines: HashMap<usize, Instant>,
+    bracket: Option<DraftBracket>,
+    /// Players waiting for their bracket opponent, with the deck they submitted.
+    ready: HashMap<PlayerId, (Player, DeckList)>,
+}
+
+/// The lobby and matchmaking server. `S` holds accounts, collections and rewards; it is a
+/// [`Repository`] everywhere except in tests.
+pub struct Server<S: Storage = Repository> {
     pub games: HashMap<uuid::Uuid, Sender<ClientMessage>>,
     pub game_players: HashMap<uuid::Uuid, Vec<Player>>,
     pub looking_for_match: Vec<(uuid::Uuid, (Player, DeckChoice))>,
-    pub streams: HashMap<uuid::Uuid, Arc<Mutex<OwnedWriteHalf>>>,
+    /// Players waiting for a sealed match. Sealed decks are only ever paired with each other.
+    pub looking_for_sealed_match: Vec<(uuid::Uuid, (Player, DeckChoice))>,
+    pub streams: HashMap<uuid::Uuid, Arc<Mutex<StreamWriter>>>,
     pub addr_to_player: HashMap<std::net::SocketAddr, uuid::Uuid>,
     addr_to_user: HashMap<std::net::SocketAddr, uuid::Uuid>,
     player_to_user: HashMap<uuid::Uuid, uuid::Uuid>,
+    /// The connection of each logged-in user, used to tell them about trades made by others.
+    user_streams: HashMap<uuid::Uuid, Arc<Mutex<StreamWriter>>>,
+    /// The players each logged-in user has blocked. Matchmaking never pairs them.
+    blocked_users: HashMap<uuid::Uuid, HashSet<uuid::Uuid>>,
+    /// When each recent match started and the users who played it, so they can report or block
+    /// each other afterwards.
+    recent_matches: HashMap<uuid::Uuid, (Instant, [Option<uuid::Uuid>; 2])>,
     pending_starter_selection: HashMap<std::net::SocketAddr, User>,
-    users: Repository,
+    users: S,
     email_sender: EmailSender,
-    /// When `true`, seed newly-created games with the local development test board.
-    /// Enable with `--test-state` or `SORCERERS_TEST_STATE=1`.
-    pub test_state: bool,
+    /// When set, seed newly-created games with this board. Load one with `dev.scenario`, or use
+    /// the bundled development board with `dev.test_state`.
+    pub scenario: Option<Scenario>,
+    /// Enables debug controls such as stepped effects and syncs engine internals to players in
+    /// every game. Turn it on with `dev.enabled`.
+    pub dev_mode: bool,
+    rewards: RewardConfig,
+    matchmaking: MatchmakingConfig,
+    drafts: HashMap<uuid::Uuid, DraftEvent>,
+    /// Bracket games, with the draft pod and the bracket match each one decides.
+    draft_games: HashMap<uuid::Uuid, (uuid::Uuid, BracketSlot)>,
+    game_outcomes: Sender<GameOutcome>,
+    metrics: Arc<Metrics>,
+    /// The task running each game, so games can be ended when a drain's deadline passes.
+    game_tasks: HashMap<uuid::Uuid, JoinHandle<()>>,
+    /// Set while new games are refused ahead of maintenance.
+    drain: Option<Drain>,
 }
 
-impl Server {
-    pub fn new(test_state: bool, users: Repository, email_sender: EmailSender) -> Self {
+impl<S: Storage> Server<S> {
+    pub fn new(
+        scenario: Option<Scenario>,
+        config: &Config,
+        users: S,
+        email_sender: EmailSender,
+        game_outcomes: Sender<GameOutcome>,
+    ) -> Self {
         Self {
             looking_for_match: Vec::new(),
+            looking_for_sealed_match: Vec::new(),
             streams: HashMap::new(),
             games: HashMap::new(),
             game_players: HashMap::new(),
             addr_to_player: HashMap::new(),
             addr_to_user: HashMap::new(),
             player_to_user: HashMap::new(),
+            user_streams: HashMap::new(),
+            blocked_users: HashMap::new(),
+            recent_matches: HashMap::new(),
             pending_starter_selection: HashMap::new(),
             users,
             email_sender,
-            test_state,
+            scenario,
+            dev_mode: config.dev.enabled,
+            rewards: config.rewards.clone(),
+            matchmaking: config.matchmaking.clone(),
+            drafts: HashMap::new(),
+            draft_games: HashMap::new(),
+            game_outcomes,
+            metrics: Arc::default(),
+            game_tasks: HashMap::new(),
+            drain: None,
         }
     }
 
+    pub fn metrics(&self) -> Arc<Metrics> {
+        Arc::clone(&self.metrics)
+    }
+
+    /// The current metrics, for the metrics endpoint.
+    pub fn render_metrics(&self) -> String {
+        let queues = QueueLengths {
+            constructed: self.looking_for_match.len(),
+            sealed: self.looking_for_sealed_match.len(),
+        };
+        self.metrics.render(
+            queues,
+            self.users.query_timings().map(|timings| timings.snapshot()),
+        )
+    }
+
     pub async fn process_message(
         &mut self,
         message: &Message,
-        stream: Arc<Mutex<OwnedWriteHalf>>,
+        stream: Arc<Mutex<StreamWriter>>,
         addr: &std::net::SocketAddr,
     ) -> anyhow::Result<()> {
+        if starts_game(message)
+            && let Some(drain) = &self.drain
+        {
+            return Client::send_to_stream(
+                &ServerMessage::MaintenanceNotice {
+                    message: Some(drain.notice.clone()),
+                },
+                stream,
+            )
+            .await;
+        }
         match message {
             Message::ClientMessage(ClientMessage::Register {
                 username,
                 email,
                 password,
-            }) => match self.users.register(username, email, password).await {
-                Ok(pending) => {
-                    self.send_email_confirmation_required(pending.email, pending.code, stream)
-                        .await?
+            }) => {
+                if let Err(error) = self
+                    .check_rate_limits(None, RateLimit::REGISTER_PER_ADDRESS, addr)
+                    .await
+                {
+                    return self
+                        .send_authentication_failure(error.user_message().to_string(), stream)
+                        .await;
                 }
-                Err(error) => {
-                    self.send_authentication_failure(error.user_message().to_string(), stream)
-                        .await?
+                match self.users.register(username, email, password).await {
+                    Ok(pending) => {
+                        self.send_email_confirmation_required(pending.email, pending.code, stream)
+                            .await?
+                    }
+                    Err(error) => {
+                        self.send_authentication_failure(error.user_message().to_string(), stream)
+                            .await?
+                    }
                 }
-            },
+            }
             Message::ClientMessage(ClientMessage::Login { email, password }) => {
-                match self.users.verify_login(email, password).await {
+                let account = email.trim().to_lowercase();
+                if let Err(error) = self
+                    .check_rate_limits(
+                        Some((RateLimit::LOGIN_PER_ACCOUNT, &account)),
+                        RateLimit::LOGIN_PER_ADDRESS,
+                        addr,
+                    )
+                    .await
+                {
+                    return self
+                        .send_authentication_failure(error.user_message().to_string(), stream)
+                        .await;
+                }
+                let result = self.users.verify_login(email, password).await;
+                if matches!(
+                    result,
+                    Ok(_) | Err(RepositoryError::EmailConfirmationRequired(_))
+                ) {
+                    // Only wrong passwords count against the account.
+                    self.users
+                        .clear_attempts(RateLimit::LOGIN_PER_ACCOUNT, &account)
+                        .await?;
+                }
+                match result {
                     Ok(user) => self.begin_authenticated_session(user, stream, addr).await?,
                     Err(RepositoryError::EmailConfirmationRequired(email)) => {
                         match self.users.resend_email_confirmation(&email).await {
@@ -107,6 +249,14 @@ impl Server {
                 }
             }
             Message::ClientMessage(ClientMessage::ConfirmEmail { email, code }) => {
+                if let Err(error) = self
+                    .check_rate_limits(None, RateLimit::ENTER_CODE_PER_ADDRESS, addr)
+                    .await
+                {
+                    return self
+                        .send_authentication_failure(error.user_message().to_string(), stream)
+                        .await;
+                }
                 match self.users.confirm_email(email, code).await {
                     Ok(user) => self.begin_authenticated_session(user, stream, addr).await?,
                     Err(error) => {
@@ -116,6 +266,11 @@ impl Server {
                 }
             }
             Message::ClientMessage(ClientMessage::ResendEmailConfirmation { email }) => {
+                if let Err(error) = self.check_send_code_limits(email, addr).await {
+                    return self
+                        .send_authentication_failure(error.user_message().to_string(), stream)
+                        .await;
+                }
                 match self.users.resend_email_confirmation(email).await {
                     Ok(pending) => {
                         self.send_email_confirmation_required(pending.email, pending.code, stream)
@@ -127,6 +282,155 @@ impl Server {
                     }
                 }
             }
+            Message::ClientMessage(ClientMessage::RequestPasswordReset { email }) => {
+                if let Err(error) = self.check_send_code_limits(email, addr).await {
+                    return self
+                        .send_authentication_failure(error.user_message().to_string(), stream)
+                        .await;
+                }
+                match self.users.request_password_reset(email).await {
+                    // Deliver in the background and answer every address the same way, so
+                    // neither the reply nor its timing shows which addresses have accounts.
+                    Ok(Some(pending)) => {
+                        let email_sender = self.email_sender.clone();
+                        tokio::spawn(async move {
+                            if let Err(error) = email_sender
+                                .send_password_reset_code(&pending.email, &pending.code)
+                                .await
+                            {
+                                tracing::error!(
+                                    email = %pending.email,
+                                    %error,
+                                    "failed to send password reset email"
+                                );
+                            }
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        return self
+                            .send_authentication_failure(error.user_message().to_string(), stream)
+                            .await;
+                    }
+                }
+                Client::send_to_stream(
+                    &ServerMessage::PasswordResetRequested {
+                        email: email.trim().to_lowercase(),
+                    },
+                    stream,
+                )
+                .await?;
+            }
+            Message::ClientMessage(ClientMessage::ResetPassword {
+                email,
+                code,
+                new_password,
+            }) => {
+                if let Err(error) = self
+                    .check_rate_limits(None, RateLimit::ENTER_CODE_PER_ADDRESS, addr)
+                    .await
+                {
+                    return self
+                        .send_authentication_failure(error.user_message().to_string(), stream)
+                        .await;
+                }
+                match self.users.reset_password(email, code, new_password).await {
+                    Ok(user) => {
+                        self.send_password_changed_notice(user.id, &email.trim().to_lowercase())
+                            .await;
+                        self.begin_authenticated_session(user, stream, addr).await?
+                    }
+                    Err(error) => {
+                        self.send_authentication_failure(error.user_message().to_string(), stream)
+                            .await?
+                    }
+                }
+            }
+            Message::ClientMessage(ClientMessage::ChangePassword {
+                current_password,
+                new_password,
+            }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = self
+                    .users
+                    .change_password(user_id, current_password, new_password)
+                    .await;
+                if let Ok(email) = &result {
+                    self.send_password_changed_notice(user_id, email).await;
+                }
+                let result = result.map(|_| (None, "your password has been changed".to_string()));
+                self.send_account_update(result, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::ChangeEmail {
+                password,
+                new_email,
+            }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = match self.users.change_email(user_id, password, new_email).await {
+                    Ok(pending) => {
+                        let message = match self
+                            .email_sender
+                            .send_confirmation_code(&pending.email, &pending.code)
+                            .await
+                        {
+                            Ok(()) => format!(
+                                "your email is now {}; enter the code we sent there the next time you log in",
+                                pending.email
+                            ),
+                            Err(error) => {
+                                tracing::error!(
+                                    %user_id,
+                                    email = %pending.email,
+                                    %error,
+                                    "failed to send confirmation email"
+                                );
+                                format!(
+                                    "your email is now {}, but we couldn't send a confirmation code; request a new one when you next log in",
+                                    pending.email
+                                )
+                            }
+                        };
+                        Ok((None, message))
+                    }
+                    Err(error) => Err(error),
+                };
+                self.send_account_update(result, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::ChangeUsername { username }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = self
+                    .users
+                    .change_username(user_id, username)
+                    .await
+                    .map(|()| {
+                        (
+                            Some(username.clone()),
+                            format!("you are now known as {username}"),
+                        )
+                    });
+                self.send_account_update(result, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::DeleteAccount { password }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                if let Err(error) = self.users.delete_account(user_id, password).await {
+                    return self.send_account_update(Err(error), stream).await;
+                }
+                if let Some(player_id) = self.addr_to_player.remove(addr) {
+                    self.forget_player(&player_id).await;
+                }
+                self.addr_to_user.remove(addr);
+                self.user_streams.remove(&user_id);
+                self.blocked_users.remove(&user_id);
+                Client::send_to_stream(&ServerMessage::AccountDeleted, stream).await?;
+            }
             // Authentication must precede all gameplay messages.
             Message::ClientMessage(ClientMessage::Connect) => {
                 self.send_authentication_failure(
@@ -187,11 +491,26 @@ impl Server {
                 let Some(&user_id) = self.addr_to_user.get(addr) else {
                     return Ok(());
                 };
-                match self
-                    .users
-                    .redeem_beta_booster(user_id, BoosterPack::beta())
+                let result = match self
+                    .check_rate_limits(
+                        Some((RateLimit::REDEEM_BOOSTER_PER_ACCOUNT, &user_id.to_string())),
+                        RateLimit::REDEEM_BOOSTER_PER_ADDRESS,
+                        addr,
+                    )
                     .await
                 {
+                    Ok(()) => {
+                        self.users
+                            .redeem_beta_booster(
+                                user_id,
+                                BoosterPack::beta(),
+                                self.rewards.beta_booster_cost,
+                            )
+                            .await
+                    }
+                    Err(error) => Err(error),
+                };
+                match result {
                     Ok((reward_points, pack)) => {
                         Client::send_to_stream(
                             &ServerMessage::BoosterRedeemed {
@@ -213,7 +532,178 @@ impl Server {
                     }
                 }
             }
-            Message::ClientMessage(ClientMessage::JoinQueue {
+            Message::ClientMessage(ClientMessage::StartSealedEvent) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let pool = SealedPool::open();
+                let message = match self.users.start_sealed_event(user_id, &pool).await {
+                    Ok(()) => ServerMessage::SealedPoolOpened { pool },
+                    Err(error @ RepositoryError::SealedPoolTooRecent { .. }) => {
+                        ServerMessage::SealedDeckRejected {
+                            message: error.user_message().to_string(),
+                        }
+                    }
+                    Err(error) => return Err(error.into()),
+                };
+                Client::send_to_stream(&message, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::LoadTrades) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                self.send_trades(user_id).await?;
+            }
+            Message::ClientMessage(ClientMessage::ProposeTrade {
+                recipient,
+                offered,
+                requested,
+                unlock_decks,
+            }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let update = self
+                    .users
+                    .propose_trade(user_id, recipient, offered, requested, *unlock_decks)
+                    .await;
+                self.finish_trade_update(user_id, update, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::CounterTrade {
+                trade_id,
+                offered,
+                requested,
+                unlock_decks,
+            }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let update = self
+                    .users
+                    .counter_trade(user_id, *trade_id, offered, requested, *unlock_decks)
+                    .await;
+                self.finish_trade_update(user_id, update, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::ConfirmTrade {
+                trade_id,
+                unlock_decks,
+            }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let update = self
+                    .users
+                    .confirm_trade(user_id, *trade_id, *unlock_decks)
+                    .await;
+                self.finish_trade_update(user_id, update, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::DeclineTrade { trade_id }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let update = self.users.decline_trade(user_id, *trade_id).await;
+                self.finish_trade_update(user_id, update, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::LoadCrafting) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let dust = self.users.crafting_dust(user_id).await;
+                self.send_crafting_update(user_id, dust, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::DustExtraCopies) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let dust = self.users.dust_extra_copies(user_id).await;
+                self.send_crafting_update(user_id, dust, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::CraftCards { cards }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let dust = self.users.craft_cards(user_id, cards).await;
+                self.send_crafting_update(user_id, dust, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::LoadQuests) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let today = chrono::Utc::now().date_naive();
+                let quests = self.users.load_quests(user_id, today).await?;
+                Client::send_to_stream(&ServerMessage::Quests { quests }, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::ReportOpponent {
+                game_id,
+                reason,
+                details,
+            }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = match self.opponent_of(game_id, user_id) {
+                    Ok(opponent_id) => {
+                        self.users
+                            .report_player(user_id, opponent_id, *game_id, *reason, details)
+                            .await
+                    }
+                    Err(error) => Err(error),
+                };
+                let message = match result {
+                    Ok(()) => ServerMessage::OpponentReported { game_id: *game_id },
+                    Err(error) => ServerMessage::ModerationRejected {
+                        game_id: *game_id,
+                        message: error.user_message().to_string(),
+                    },
+                };
+                Client::send_to_stream(&message, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::BlockOpponent { game_id }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = match self.opponent_of(game_id, user_id) {
+                    Ok(opponent_id) => self.users.block_player(user_id, opponent_id).await,
+                    Err(error) => Err(error),
+                };
+                let message = match result {
+                    Ok(()) => {
+                        self.send_blocked_players(user_id, Arc::clone(&stream))
+                            .await?;
+                        ServerMessage::OpponentBlocked { game_id: *game_id }
+                    }
+                    Err(error) => ServerMessage::ModerationRejected {
+                        game_id: *game_id,
+                        message: error.user_message().to_string(),
+                    },
+                };
+                Client::send_to_stream(&message, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::BlockPlayer { username }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = self.users.block_username(user_id, username).await;
+                if result.is_ok() {
+                    self.send_blocked_players(user_id, Arc::clone(&stream))
+                        .await?;
+                }
+                let result = result.map(|()| (None, format!("you blocked {}", username.trim())));
+                self.send_account_update(result, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::UnblockPlayer { username }) => {
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
+                let result = self.users.unblock_username(user_id, username).await;
+                if result.is_ok() {
+                    self.send_blocked_players(user_id, Arc::clone(&stream))
+                        .await?;
+                }
+                let result = result.map(|()| (None, format!("you unblocked {}", username.trim())));
+                self.send_account_update(result, stream).await?;
+            }
+            Message::ClientMessage(ClientMessage::JoinSealedQueue {
                 player_id,
                 player_name,
                 deck,
@@ -221,70 +711,253 @@ impl Server {
                 let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
                     return Ok(());
                 };
+                let Some(&user_id) = self.addr_to_user.get(addr) else {
+                    return Ok(());
+                };
                 if player_id != &registered_player_id {
                     return Ok(());
                 }
 
+                let validation = match self.users.load_sealed_pool(user_id).await? {
+                    Some(pool) => pool.validate(deck),
+                    None => Err("Open a sealed pool before queueing for sealed.".to_string()),
+                };
+                if let Err(message) = validation {
+                    Client::send_to_stream(&ServerMessage::SealedDeckRejected { message }, stream)
+                        .await?;
+                    return Ok(());
+                }
+
                 let player = Player {
                     id: registered_player_id,
                     name: player_name.clone(),
                 };
                 self.looking_for_match
-                    .push((registered_player_id, (player, deck.clone())));
+                    .retain(|(id, _)| id != &registered_player_id);
+                self.looking_for_sealed_match.push((
+                    registered_player_id,
+                    (player, DeckChoice::Custom(deck.clone())),
+                ));
                 self.streams.insert(registered_player_id, stream);
 
-                if let Some((player1, player2)) = self.find_match() {
-                    self.create_game(&player1.0, player1.1, &player2.0, player2.1)
-                        .await?;
+                if let Some((player1, player2)) = self.find_sealed_match()
+                    && let Err(error) = self
+                        .create_game(&player1.0, player1.1, &player2.0, player2.1)
+                        .await
+                {
+                    tracing::error!(error = ?error, "failed to start sealed match");
                 }
             }
-            Message::ClientMessage(ClientMessage::Disconnect) => {
-                let player_id = self
-                    .addr_to_player
-                    .remove(addr)
-                    .unwrap_or(uuid::Uuid::nil());
-                self.looking_for_match.retain(|(id, _)| id != &player_id);
-                self.pending_starter_selection.remove(addr);
-                self.addr_to_user.remove(addr);
-                self.player_to_user.remove(&player_id);
-                self.streams.retain(|_, s| !Arc::ptr_eq(s, &stream));
-
-                if player_id == uuid::Uuid::nil() {
+            Message::ClientMessage(ClientMessage::JoinDraft {
+                player_id,
+                player_name,
+                seats,
+            }) => {
+                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+                    return Ok(());
+                };
+                if player_id != &registered_player_id {
                     return Ok(());
                 }
-
-                let game_id = self
-                    .game_players
-                    .iter()
-                    .find(|(_, players)| players.iter().any(|p| p.id == player_id))
-                    .map(|(game_id, _)| *game_id);
-                if let Some(game_id) = game_id
-                    && let Some(tx) = self.games.get_mut(&game_id)
-                {
-                    tx.send(ClientMessage::PlayerDisconnected { game_id, player_id })
+                let player = Player {
+                    id: registered_player_id,
+                    name: player_name.clone(),
+                };
+                if let Err(message) = self.join_draft(player, usize::from(*seats), &stream).await {
+                    Client::send_to_stream(&ServerMessage::DraftRejected { message }, stream)
                         .await?;
                 }
             }
-            Message::ClientMessage(msg) => {
-                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+            Message::ClientMessage(ClientMessage::PickDraftCard {
+                player_id,
+                pod_id,
+                index,
+            }) => {
+                if self.addr_to_player.get(addr) != Some(player_id) {
+                    return Ok(());
+                }
+                let Some(event) = self.drafts.get_mut(pod_id) else {
                     return Ok(());
                 };
-                if msg.player_id() != &registered_player_id {
+                let Some(seat) = event.pod.seat_of(player_id) else {
                     return Ok(());
+                };
+                match event.pod.pick(seat, *index) {
+                    Ok(card) => {
+                        event.pick_deadlines.remove(&seat);
+                        Client::send_to_stream(
+                            &ServerMessage::DraftPicked {
+                                pod_id: *pod_id,
+                                card,
+                            },
+                            stream,
+                        )
+                        .await?;
+                        self.advance_draft(*pod_id).await;
+                    }
+                    Err(message) => {
+                        Client::send_to_stream(&ServerMessage::DraftRejected { message }, stream)
+                            .await?;
+                    }
                 }
-
-                let game_id = msg.game_id();
-                let is_player_in_game = self
-                    .game_players
-                    .get(&game_id)
-                    .is_some_and(|players| players.iter().any(|p| p.id == registered_player_id));
-                if !is_player_in_game {
+            }
+            Message::ClientMessage(ClientMessage::LeaveDraft { player_id }) => {
+                if self.addr_to_player.get(addr) != Some(player_id) {
                     return Ok(());
                 }
-
-                self.games
-                    .get_mut(&game_id)
-                    .ok_or(anyhow::anyhow!("failed to get game by game id"))?
+                self.leave_drafts(player_id).await;
+            }
+            Message::ClientMessage(ClientMessage::QueueDraftMatch {
+                player_id,
+                pod_id,
+                deck,
+            }) => {
+                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+                    return Ok(());
+                };
+                if player_id != &registered_player_id {
+                    return Ok(());
+                }
+                if let Err(message) = self
+                    .queue_draft_match(*pod_id, registered_player_id, deck, &stream)
+                    .await
+                {
+                    Client::send_to_stream(&ServerMessage::DraftRejected { message }, stream)
+                        .await?;
+                }
+            }
+            Message::ClientMessage(ClientMessage::JoinQueue {
+                player_id,
+                player_name,
+                deck,
+            }) => {
+                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+                    return Ok(());
+                };
+                if player_id != &registered_player_id {
+                    return Ok(());
+                }
+
+                let player = Player {
+                    id: registered_player_id,
+                    name: player_name.clone(),
+                };
+                self.looking_for_sealed_match
+                    .retain(|(id, _)| id != &registered_player_id);
+                self.looking_for_match
+                    .push((registered_player_id, (player, deck.clone())));
+                self.streams.insert(registered_player_id, stream);
+
+                if let Some((player1, player2)) = self.find_match()
+                    && let Err(error) = self
+                        .create_game(&player1.0, player1.1, &player2.0, player2.1)
+                        .await
+                {
+                    tracing::error!(error = ?error, "failed to start match");
+                }
+            }
+            Message::ClientMessage(ClientMessage::StartGoldfish {
+                player_id,
+                player_name,
+                deck,
+            }) => {
+                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+                    return Ok(());
+                };
+                if player_id != &registered_player_id {
+                    return Ok(());
+                }
+
+                let player = Player {
+                    id: registered_player_id,
+                    name: player_name.clone(),
+                };
+                self.looking_for_match
+                    .retain(|(id, _)| id != &registered_player_id);
+                self.looking_for_sealed_match
+                    .retain(|(id, _)| id != &registered_player_id);
+                self.create_goldfish_game(player, deck.clone(), stream);
+            }
+            Message::ClientMessage(ClientMessage::StartSandbox {
+                player_id,
+                player_name,
+                deck,
+            }) => {
+                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+                    return Ok(());
+                };
+                if player_id != &registered_player_id {
+                    return Ok(());
+                }
+
+                let player = Player {
+                    id: registered_player_id,
+                    name: player_name.clone(),
+                };
+                self.looking_for_match
+                    .retain(|(id, _)| id != &registered_player_id);
+                self.looking_for_sealed_match
+                    .retain(|(id, _)| id != &registered_player_id);
+                self.create_sandbox_game(player, deck, stream);
+            }
+            Message::ClientMessage(ClientMessage::Disconnect) => {
+                let player_id = self
+                    .addr_to_player
+                    .remove(addr)
+                    .unwrap_or(uuid::Uuid::nil());
+                self.looking_for_match.retain(|(id, _)| id != &player_id);
+                self.looking_for_sealed_match
+                    .retain(|(id, _)| id != &player_id);
+                self.leave_drafts(&player_id).await;
+                self.pending_starter_selection.remove(addr);
+                if let Some(user_id) = self.addr_to_user.remove(addr)
+                    && self
+                        .user_streams
+                        .get(&user_id)
+                        .is_some_and(|s| Arc::ptr_eq(s, &stream))
+                {
+                    self.user_streams.remove(&user_id);
+                    self.blocked_users.remove(&user_id);
+                }
+                self.player_to_user.remove(&player_id);
+                self.streams.retain(|_, s| !Arc::ptr_eq(s, &stream));
+
+                if player_id == uuid::Uuid::nil() {
+                    return Ok(());
+                }
+
+                let game_id = self
+                    .game_players
+                    .iter()
+                    .find(|(_, players)| players.iter().any(|p| p.id == player_id))
+                    .map(|(game_id, _)| *game_id);
+                if let Some(game_id) = game_id
+                    && let Some(tx) = self.games.get_mut(&game_id)
+                {
+                    tx.send(ClientMessage::PlayerDisconnected { game_id, player_id })
+                        .await?;
+                }
+            }
+            Message::ClientMessage(msg) => {
+                let Some(&registered_player_id) = self.addr_to_player.get(addr) else {
+                    return Ok(());
+                };
+                if msg.player_id() != &registered_player_id {
+                    return Ok(());
+                }
+
+                let game_id = msg.game_id();
+                let is_player_in_game = self
+                    .game_players
+                    .get(&game_id)
+                    .is_some_and(|players| players.iter().any(|p| p.id == registered_player_id));
+                if !is_player_in_game {
+                    return Ok(());
+                }
+
+                self.games
+                    .get_mut(&game_id)
+                    .ok_or(anyhow::anyhow!("failed to get game by game id"))?
                     .send(msg.clone())
                     .await?;
             }
@@ -297,9 +970,14 @@ impl Server {
     async fn begin_authenticated_session(
         &mut self,
         user: User,
-        stream: Arc<Mutex<OwnedWriteHalf>>,
+        stream: Arc<Mutex<StreamWriter>>,
         addr: &std::net::SocketAddr,
     ) -> anyhow::Result<()> {
+        if let Err(error) = self.users.check_ban(user.id).await {
+            return self
+                .send_authentication_failure(error.user_message().to_string(), stream)
+                .await;
+        }
         match self.users.selected_starter_deck(user.id).await? {
             Some(deck) => {
                 let saved_decks = self.users.load_decks(user.id).await?;
@@ -345,16 +1023,14 @@ impl Server {
         saved_decks: Vec<DeckList>,
         collection: Vec<CollectedCard>,
         unopened_booster_packs: Vec<sorcerers::booster::UnopenedBoosterPack>,
-        stream: Arc<Mutex<OwnedWriteHalf>>,
+        stream: Arc<Mutex<StreamWriter>>,
         addr: &std::net::SocketAddr,
     ) -> anyhow::Result<()> {
         let user_id = user.id;
         let reward_points = self.users.reward_points(user_id).await?;
+        let sealed_pool = self.users.load_sealed_pool(user_id).await?;
         if let Some(previous_player_id) = self.addr_to_player.remove(addr) {
-            self.streams.remove(&previous_player_id);
-            self.player_to_user.remove(&previous_player_id);
-            self.looking_for_match
-                .retain(|(player_id, _)| *player_id != previous_player_id);
+            self.forget_player(&previous_player_id).await;
         }
         let player_id = uuid::Uuid::new_v4();
         Client::send_to_stream(
@@ -366,19 +1042,149 @@ impl Server {
                 collection,
                 unopened_booster_packs,
                 reward_points,
+                beta_booster_cost: self.rewards.beta_booster_cost,
+                sealed_pool,
             },
             Arc::clone(&stream),
         )
         .await?;
-        self.streams.insert(player_id, stream);
+        self.user_streams.insert(user_id, Arc::clone(&stream));
+        self.streams.insert(player_id, Arc::clone(&stream));
         self.addr_to_player.insert(*addr, player_id);
         self.addr_to_user.insert(*addr, user_id);
         self.player_to_user.insert(player_id, user_id);
+        self.send_trades(user_id).await?;
+        self.send_blocked_players(user_id, Arc::clone(&stream))
+            .await?;
+        if let Some(drain) = &self.drain {
+            Client::send_to_stream(
+                &ServerMessage::MaintenanceNotice {
+                    message: Some(drain.notice.clone()),
+                },
+                stream,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Take a player out of every queue and draft they joined, for when their session ends.
+    async fn forget_player(&mut self, player_id: &PlayerId) {
+        self.leave_drafts(player_id).await;
+        self.streams.remove(player_id);
+        self.player_to_user.remove(player_id);
+        self.looking_for_match.retain(|(id, _)| id != player_id);
+        self.looking_for_sealed_match
+            .retain(|(id, _)| id != player_id);
+    }
+
+    /// Reload the players a user has blocked, for matchmaking and for the user themselves.
+    async fn send_blocked_players(
+        &mut self,
+        user_id: uuid::Uuid,
+        stream: Arc<Mutex<StreamWriter>>,
+    ) -> anyhow::Result<()> {
+        let blocked = self.users.blocked_players(user_id).await?;
+        self.blocked_users
+            .insert(user_id, blocked.iter().map(|user| user.id).collect());
+        let usernames = blocked.into_iter().map(|user| user.username).collect();
+        Client::send_to_stream(&ServerMessage::BlockedPlayers { usernames }, stream).await
+    }
+
+    /// The user `user_id` played against in a recent match.
+    fn opponent_of(
+        &self,
+        game_id: &uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<uuid::Uuid, RepositoryError> {
+        match self.recent_matches.get(game_id) {
+            Some((_, [Some(first), Some(second)])) if *first == user_id => Ok(*second),
+            Some((_, [Some(first), Some(second)])) if *second == user_id => Ok(*first),
+            _ => Err(RepositoryError::InvalidModeration(
+                "You can no longer report or block the opponent from that match.".to_string(),
+            )),
+        }
+    }
+
+    /// Send a user their open trades, if they are online.
+    async fn send_trades(&self, user_id: uuid::Uuid) -> anyhow::Result<()> {
+        let Some(stream) = self.user_streams.get(&user_id) else {
+            return Ok(());
+        };
+        let trades = self.users.load_trades(user_id).await?;
+        Client::send_to_stream(&ServerMessage::TradeOffers { trades }, Arc::clone(stream)).await
+    }
+
+    /// Report a trade change to both players, along with their new collections once it completes.
+    async fn finish_trade_update(
+        &self,
+        user_id: uuid::Uuid,
+        update: Result<TradeUpdate, RepositoryError>,
+        stream: Arc<Mutex<StreamWriter>>,
+    ) -> anyhow::Result<()> {
+        let update = match update {
+            Ok(update) => update,
+            Err(error) => {
+                return Client::send_to_stream(
+                    &ServerMessage::TradeRejected {
+                        message: error.user_message().to_string(),
+                    },
+                    stream,
+                )
+                .await;
+            }
+        };
+        self.notify_trade(user_id, update.completed).await?;
+        // The partner may have dropped their connection; that must not fail the request.
+        if let Err(error) = self.notify_trade(update.partner_id, update.completed).await {
+            tracing::warn!(
+                %user_id,
+                partner_id = %update.partner_id,
+                %error,
+                "failed to notify trade partner"
+            );
+        }
         Ok(())
     }
 
+    async fn send_crafting_update(
+        &self,
+        user_id: uuid::Uuid,
+        dust: Result<u32, RepositoryError>,
+        stream: Arc<Mutex<StreamWriter>>,
+    ) -> anyhow::Result<()> {
+        let message = match dust {
+            Ok(dust) => ServerMessage::CraftingUpdated {
+                collection: self.users.load_collection(user_id).await?,
+                dust,
+            },
+            Err(error) => ServerMessage::CraftingRejected {
+                message: error.user_message().to_string(),
+            },
+        };
+        Client::send_to_stream(&message, stream).await
+    }
+
+    async fn notify_trade(&self, user_id: uuid::Uuid, completed: bool) -> anyhow::Result<()> {
+        if completed && let Some(stream) = self.user_streams.get(&user_id) {
+            let collection = self.users.load_collection(user_id).await?;
+            let reward_points = self.users.reward_points(user_id).await?;
+            Client::send_to_stream(
+                &ServerMessage::TradeCompleted {
+                    collection,
+                    reward_points,
+                },
+                Arc::clone(stream),
+            )
+            .await?;
+        }
+        self.send_trades(user_id).await
+    }
+
     async fn claim_weekly_boosters(&self, user_id: uuid::Uuid) -> anyhow::Result<()> {
-        let packs = (0..3).map(|_| BoosterPack::beta()).collect::<Vec<_>>();
+        let packs = (0..self.rewards.weekly_boosters)
+            .map(|_| BoosterPack::beta())
+            .collect::<Vec<_>>();
         let today = chrono::Utc::now().date_naive();
         let week_start =
             today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
@@ -390,10 +1196,71 @@ impl Server {
         Ok(())
     }
 
+    /// Count an attempt against the connection's address and, if given, an account, refusing it
+    /// once either goes over its limit.
+    async fn check_rate_limits(
+        &self,
+        account: Option<(RateLimit, &str)>,
+        address: RateLimit,
+        addr: &std::net::SocketAddr,
+    ) -> Result<(), RepositoryError> {
+        let now = chrono::Utc::now();
+        self.users
+            .record_attempt(address, &addr.ip().to_string(), now)
+            .await?;
+        if let Some((limit, key)) = account {
+            self.users.record_attempt(limit, key, now).await?;
+        }
+        Ok(())
+    }
+
+    /// Limit how many confirmation and password reset emails go to one address.
+    async fn check_send_code_limits(
+        &self,
+        email: &str,
+        addr: &std::net::SocketAddr,
+    ) -> Result<(), RepositoryError> {
+        self.check_rate_limits(
+            Some((
+                RateLimit::SEND_CODE_PER_ACCOUNT,
+                &email.trim().to_lowercase(),
+            )),
+            RateLimit::SEND_CODE_PER_ADDRESS,
+            addr,
+        )
+        .await
+    }
+
+    async fn send_account_update(
+        &self,
+        result: Result<(Option<String>, String), RepositoryError>,
+        stream: Arc<Mutex<StreamWriter>>,
+    ) -> anyhow::Result<()> {
+        let message = match result {
+            Ok((username, message)) => ServerMessage::AccountUpdated { username, message },
+            Err(error) => ServerMessage::AccountUpdateFailed {
+                message: error.user_message().to_string(),
+            },
+        };
+        Client::send_to_stream(&message, stream).await
+    }
+
+    /// Let the account's owner know its password changed, in case it wasn't them.
+    async fn send_password_changed_notice(&self, user_id: uuid::Uuid, email: &str) {
+        if let Err(error) = self.email_sender.send(email, Email::PasswordChanged).await {
+            tracing::error!(
+                %user_id,
+                %email,
+                %error,
+                "failed to send password change notice"
+            );
+        }
+    }
+
     async fn send_authentication_failure(
         &self,
         message: String,
-        stream: Arc<Mutex<OwnedWriteHalf>>,
+        stream: Arc<Mutex<StreamWriter>>,
     ) -> anyhow::Result<()> {
         Client::send_to_stream(&ServerMessage::AuthenticationFailure { message }, stream).await
     }
@@ -402,14 +1269,14 @@ impl Server {
         &self,
         email: String,
         code: String,
-        stream: Arc<Mutex<OwnedWriteHalf>>,
+        stream: Arc<Mutex<StreamWriter>>,
     ) -> anyhow::Result<()> {
         let delivery_failed = if let Err(error) = self
             .email_sender
             .send_confirmation_code(&email, &code)
             .await
         {
-            eprintln!("failed to send confirmation email to {email}: {error}");
+            tracing::error!(%email, %error, "failed to send confirmation email");
             true
         } else {
             false
@@ -430,7 +1297,7 @@ impl Server {
         deck1: DeckChoice,
         player2: &Player,
         deck2: DeckChoice,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<uuid::Uuid> {
         let (server_tx, server_rx) = async_channel::unbounded();
         let (client_tx, client_rx) = async_channel::unbounded::<ClientMessage>();
 
@@ -460,6 +1327,12 @@ impl Server {
             ),
         ];
         let users = self.users.clone();
+        let game_outcomes = self.game_outcomes.clone();
+        let RewardConfig {
+            win_points,
+            play_points,
+            ..
+        } = self.rewards;
 
         let players = vec![
             (
@@ -468,7 +1341,7 @@ impl Server {
                     deck: deck1,
                     cards: cards1,
                 },
-                stream1,
+                Arc::clone(&stream1),
             ),
             (
                 PlayerWithDeck {
@@ -476,31 +1349,72 @@ impl Server {
                     deck: deck2,
                     cards: cards2,
                 },
-                stream2,
+                Arc::clone(&stream2),
             ),
         ];
         let mut game = Game::new(players, client_rx, server_tx, server_rx);
+        let game_id = game.id;
+        game.state.dev_mode = self.dev_mode;
+        if let Some(scenario) = &self.scenario
+            && let Err(error) = scenario.apply(&mut game.state)
+        {
+            // Nothing was recorded for the match yet; hand the players back to the lobby.
+            for (player, stream) in [(player1, stream1), (player2, stream2)] {
+                Client::send_to_stream(
+                    &ServerMessage::GameAborted {
+                        game_id,
+                        message: "The match could not be started.".to_string(),
+                    },
+                    Arc::clone(&stream),
+                )
+                .await
+                .ok();
+                self.streams.insert(player.id, stream);
+            }
+            return Err(error.context("failed to apply the scenario"));
+        }
+        let started_at = Instant::now();
+        self.recent_matches
+            .retain(|_, (started, _)| started_at.duration_since(*started) < RECENT_MATCH_LIFETIME);
+        self.recent_matches.insert(
+            game_id,
+            (started_at, [reward_recipients[0].1, reward_recipients[1].1]),
+        );
         self.games.insert(game.id, client_tx);
         self.game_players
             .insert(game.id, vec![player1.clone(), player2.clone()]);
 
-        if self.test_state {
-            self.setup_test_state(&mut game);
+        if let Err(error) = self
+            .users
+            .start_live_game(game_id, [&player1.name, &player2.name])
+            .await
+        {
+            tracing::error!(%game_id, %error, "failed to record live game");
         }
-        tokio::spawn(async move {
-            let outcome = match game.start().await {
+        game.set_drain_observer(self.metrics.effect_drain_observer());
+        let live = self.metrics.track(Gauge::LiveGames);
+        let task = tokio::spawn(async move {
+            let result = game.start().await;
+            drop(live);
+            if let Err(error) = users.finish_live_game(game_id).await {
+                tracing::error!(%game_id, %error, "failed to record finished game");
+            }
+            let outcome = match result {
                 Ok(outcome) => outcome,
                 Err(error) => {
-                    eprintln!("game ended unexpectedly: {error}");
+                    tracing::error!(%game_id, error = ?error, "game ended unexpectedly");
                     return;
                 }
             };
+            game_outcomes.send(outcome.clone()).await.ok();
             for (player_id, user_id, stream) in reward_recipients {
                 let Some(user_id) = user_id else {
                     continue;
                 };
+                let won = player_id == outcome.winner_id;
+                let points = if won { win_points } else { play_points };
                 match users
-                    .award_match_points(outcome.game_id, user_id, player_id == outcome.winner_id)
+                    .award_match_points(outcome.game_id, user_id, points)
                     .await
                 {
                     Ok(reward) if reward.points_earned > 0 => {
@@ -508,147 +1422,746 @@ impl Server {
                             &ServerMessage::MatchRewards {
                                 points_earned: reward.points_earned,
                                 reward_points: reward.reward_points,
-                                won: player_id == outcome.winner_id,
+                                won,
+                            },
+                            Arc::clone(&stream),
+                        )
+                        .await
+                        .ok();
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        tracing::error!(%game_id, %user_id, %error, "failed to award match points")
+                    }
+                }
+
+                let stats = quest::game_stats(&game.state, &outcome, &player_id);
+                let today = chrono::Utc::now().date_naive();
+                match users
+                    .record_quest_progress(outcome.game_id, user_id, &stats, today)
+                    .await
+                {
+                    Ok(reward) if !reward.completed.is_empty() => {
+                        Client::send_to_stream(
+                            &ServerMessage::QuestsCompleted {
+                                completed: reward.completed,
+                                points_earned: reward.points_earned,
+                                reward_points: reward.reward_points,
                             },
+                            Arc::clone(&stream),
+                        )
+                        .await
+                        .ok();
+                    }
+                    Ok(_) => {}
+                    Err(error) => tracing::error!(
+                        %game_id,
+                        %user_id,
+                        %error,
+                        "failed to record quest progress"
+                    ),
+                }
+
+                let earned: Vec<&str> = FinishedGame {
+                    state: &game.state,
+                    outcome: &outcome,
+                    player_id: &player_id,
+                }
+                .earned()
+                .into_iter()
+                .map(|achievement| achievement.id)
+                .collect();
+                if earned.is_empty() {
+                    continue;
+                }
+                match users
+                    .unlock_achievements(user_id, outcome.game_id, &earned)
+                    .await
+                {
+                    Ok(unlocked) if !unlocked.is_empty() => {
+                        let achievements = unlocked
+                            .iter()
+                            .filter_map(|id| achievement_by_id(id))
+                            .map(UnlockedAchievement::from)
+                            .collect();
+                        Client::send_to_stream(
+                            &ServerMessage::AchievementsUnlocked { achievements },
                             stream,
                         )
                         .await
                         .ok();
                     }
                     Ok(_) => {}
-                    Err(error) => eprintln!("failed to award match points: {error}"),
+                    Err(error) => tracing::error!(
+                        %game_id,
+                        %user_id,
+                        %error,
+                        "failed to unlock achievements"
+                    ),
                 }
             }
         });
+        self.game_tasks.insert(game_id, task);
 
-        Ok(())
+        Ok(game_id)
     }
 
-    fn setup_test_state(&mut self, game: &mut Game) {
-        let player_one = game.state.players[0].id;
-        let card = card::from_name_and_zone(AramosMercenaries::NAME, &player_one, Zone::Cemetery);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(ApprenticeWizard::NAME, &player_one, Zone::Cemetery);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            CaptainBaldassare::NAME,
-            &player_one,
-            Zone::Location(Location::Square(8, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let kite_archer = card::from_name_and_zone(
-            KiteArcher::NAME,
-            &player_one,
-            Zone::Location(Location::Square(8, Region::Surface)),
-        );
-        game.state.add_card(kite_archer);
-
-        let player_two = game.state.players[1].id;
-        let card = card::from_name_and_zone(MountainGiant::NAME, &player_one, Zone::Hand);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(ApprenticeWizard::NAME, &player_one, Zone::Hand);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(KytheraMechanism::NAME, &player_one, Zone::Hand);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(AdeptIllusionist::NAME, &player_one, Zone::Hand);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(AdeptIllusionist::NAME, &player_one, Zone::Cemetery);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(DwarvenDiggingTeam::NAME, &player_one, Zone::Cemetery);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(AdeptIllusionist::NAME, &player_one, Zone::Spellbook);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(CallToWar::NAME, &player_one, Zone::Hand);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(SummerRiver::NAME, &player_one, Zone::Hand);
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            SummerRiver::NAME,
-            &player_one,
-            Zone::Location(Location::Square(3, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            SummerRiver::NAME,
-            &player_one,
-            Zone::Location(Location::Square(9, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            SummerRiver::NAME,
-            &player_one,
-            Zone::Location(Location::Square(4, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            HumbleVillage::NAME,
-            &player_one,
-            Zone::Location(Location::Square(6, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            HumbleVillage::NAME,
-            &player_one,
-            Zone::Location(Location::Square(7, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            LoneTower::NAME,
-            &player_one,
-            Zone::Location(Location::Square(2, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            AridDesert::NAME,
-            &player_one,
-            Zone::Location(Location::Square(8, Region::Surface)),
-        );
-        game.state.add_card(card);
-
-        let card = card::from_name_and_zone(
-            AridDesert::NAME,
-            &player_two,
-            Zone::Location(Location::Square(13, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            AridDesert::NAME,
-            &player_two,
-            Zone::Location(Location::Square(18, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            KiteArcher::NAME,
-            &player_two,
-            Zone::Location(Location::Square(3, Region::Surface)),
-        );
-        game.state.add_card(card);
-        let card = card::from_name_and_zone(
-            FelbogFrogMen::NAME,
-            &player_one,
-            Zone::Location(Location::Square(13, Region::Surface)),
-        );
-        game.state.add_card(card);
+    /// Start a goldfish game for `player`. Goldfish games don't count towards rewards, quests or
+    /// achievements.
+    pub fn create_goldfish_game(
+        &mut self,
+        player: Player,
+        deck: DeckChoice,
+        stream: Arc<Mutex<StreamWriter>>,
+    ) -> uuid::Uuid {
+        let (server_tx, server_rx) = async_channel::unbounded();
+        let (client_tx, client_rx) = async_channel::unbounded::<ClientMessage>();
 
-        let avatar_id = game.state.get_player_avatar_id(&player_one).unwrap();
-        let avatar_card = game.state.get_card_mut(&avatar_id);
-        avatar_card.get_unit_base_mut().unwrap().damage = 20;
-        avatar_card.get_avatar_base_mut().unwrap().deaths_door = true;
+        let goldfish = Goldfish::new(player, deck, client_tx.clone());
+        let players = vec![goldfish.player.clone(), goldfish.dummy.clone()];
+        let mut game = Game::goldfish(goldfish, stream, client_rx, server_tx, server_rx);
+        let game_id = game.id;
+        self.games.insert(game_id, client_tx);
+        self.game_players.insert(game_id, players);
 
-        let player_mana = game.state.get_player_mana_mut(&player_one);
-        *player_mana = 10;
-    }
+        game.state.dev_mode = self.dev_mode;
+        game.set_drain_observer(self.metrics.effect_drain_observer());
+        let live = self.metrics.track(Gauge::LiveGames);
+        let task = tokio::spawn(async move {
+            if let Err(error) = game.start().await {
+                tracing::error!(%game_id, error = ?error, "goldfish game ended unexpectedly");
+            }
+            drop(live);
+        });
+        self.game_tasks.insert(game_id, task);
 
-    pub fn find_match(&mut self) -> Option<((Player, DeckChoice), (Player, DeckChoice))> {
-        if self.looking_for_match.len() >= 2 {
-            let player1 = self.looking_for_match.remove(0);
-            let player2 = self.looking_for_match.remove(0);
-            Some((player1.1, player2.1))
-        } else {
-            None
-        }
+        game_id
     }
-}
+
+    /// Start a sandbox game for `player`, who controls both seats and may edit the board. Like
+    /// goldfish games, sandbox games don't count towards rewards, quests or achievements.
+    pub fn create_sandbox_game(
+        &mut self,
+        player: Player,
+        deck: &DeckChoice,
+        stream: Arc<Mutex<StreamWriter>>,
+    ) -> uuid::Uuid {
+        let (server_tx, server_rx) = async_channel::unbounded();
+        let (client_tx, client_rx) = async_channel::unbounded::<ClientMessage>();
+
+        let mut game = Game::sandbox(player, deck, stream, client_rx, server_tx, server_rx);
+        let game_id = game.id;
+        self.games.insert(game_id, client_tx);
+        self.game_players
+            .insert(game_id, game.state.players.clone());
+
+        game.state.dev_mode = self.dev_mode;
+        game.set_drain_observer(self.metrics.effect_drain_observer());
+        let live = self.metrics.track(Gauge::LiveGames);
+        let task = tokio::spawn(async move {
+            if let Err(error) = game.start().await {
+                tracing::error!(%game_id, error = ?error, "sandbox game ended unexpectedly");
+            }
+            drop(live);
+        });
+        self.game_tasks.insert(game_id, task);
+
+        game_id
+    }
+
+    /// The number of games still in progress.
+    pub fn live_games(&mut self) -> usize {
+        self.game_tasks.retain(|_, task| !task.is_finished());
+        self.game_tasks.len()
+    }
+
+    /// Stop starting games because the server is stopping. The games in progress get `grace` to
+    /// finish.
+    pub async fn begin_shutdown(&mut self, grace: Duration) {
+        let mut deadline = Instant::now() + grace;
+        if let Some(earlier) = self.drain.as_ref().and_then(|drain| drain.deadline) {
+            deadline = deadline.min(earlier);
+        }
+        tracing::info!(
+            live_games = self.live_games(),
+            grace_seconds = deadline.saturating_duration_since(Instant::now()).as_secs(),
+            "shutting down once the games in progress finish"
+        );
+        self.begin_drain(Some(deadline), true, None).await;
+    }
+
+    /// Drop rate limit attempts and lockouts that have expired, including those of keys that
+    /// never came back to be checked.
+    pub async fn prune_rate_limits(&self) {
+        match self.users.prune_rate_limits(chrono::Utc::now()).await {
+            Ok(pruned) => tracing::debug!(pruned, "pruned expired rate limits"),
+            Err(error) => tracing::error!(%error, "failed to prune expired rate limits"),
+        }
+    }
+
+    /// Follow `sorcerers-admin drain` requests, and end the games still going once a drain's
+    /// deadline passes.
+    pub async fn tick_drain(&mut self) {
+        if !self.drain.as_ref().is_some_and(|drain| drain.shutting_down) {
+            match self.users.drain_request().await {
+                Ok(request) if request.as_ref() == self.drain_request() => {}
+                Ok(Some(request)) => {
+                    let deadline = request.deadline.map(|deadline| {
+                        let remaining =
+                            (deadline - chrono::Utc::now()).to_std().unwrap_or_default();
+                        Instant::now() + remaining
+                    });
+                    tracing::info!(?request.deadline, "draining for maintenance");
+                    self.begin_drain(deadline, false, Some(request)).await;
+                }
+                Ok(None) => {
+                    tracing::info!("maintenance drain called off");
+                    self.drain = None;
+                    self.broadcast(&ServerMessage::MaintenanceNotice { message: None })
+                        .await;
+                }
+                Err(error) => tracing::error!(%error, "failed to check for a drain request"),
+            }
+        }
+
+        if let Some(drain) = &mut self.drain
+            && drain
+                .deadline
+                .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            drain.deadline = None;
+            self.end_live_games().await;
+        }
+    }
+
+    fn drain_request(&self) -> Option<&DrainRequest> {
+        self.drain.as_ref().and_then(|drain| drain.request.as_ref())
+    }
+
+    async fn begin_drain(
+        &mut self,
+        deadline: Option<Instant>,
+        shutting_down: bool,
+        request: Option<DrainRequest>,
+    ) {
+        let notice = maintenance_notice(deadline);
+        self.drain = Some(Drain {
+            deadline,
+            shutting_down,
+            request,
+            notice: notice.clone(),
+        });
+        // Nobody waiting can be matched any more; the notice tells them why.
+        self.looking_for_match.clear();
+        self.looking_for_sealed_match.clear();
+        self.broadcast(&ServerMessage::MaintenanceNotice {
+            message: Some(notice),
+        })
+        .await;
+    }
+
+    /// End the games still in progress without a result, so nobody is rewarded or penalized
+    /// for them.
+    pub async fn end_live_games(&mut self) {
+        self.live_games();
+        for (game_id, task) in std::mem::take(&mut self.game_tasks) {
+            task.abort();
+            tracing::warn!(%game_id, "ended a game in progress for maintenance");
+            if let Err(error) = self.users.finish_live_game(game_id).await {
+                tracing::error!(%game_id, %error, "failed to record finished game");
+            }
+            self.games.remove(&game_id);
+            self.draft_games.remove(&game_id);
+            let players = self.game_players.remove(&game_id).unwrap_or_default();
+            for stream in players
+                .iter()
+                .filter_map(|player| self.player_stream(&player.id))
+            {
+                Client::send_to_stream(
+                    &ServerMessage::GameAborted {
+                        game_id,
+                        message: "The server is restarting for maintenance, so this match \
+                                  ended without a result."
+                            .to_string(),
+                    },
+                    Arc::clone(stream),
+                )
+                .await
+                .ok();
+            }
+        }
+    }
+
+    /// The connection of a player, including one whose stream was handed to a match.
+    fn player_stream(&self, player_id: &PlayerId) -> Option<&Arc<Mutex<StreamWriter>>> {
+        self.streams.get(player_id).or_else(|| {
+            self.player_to_user
+                .get(player_id)
+                .and_then(|user_id| self.user_streams.get(user_id))
+        })
+    }
+
+    /// Send `message` to every connected player once, whether they are in the lobby or in a
+    /// match.
+    async fn broadcast(&self, message: &ServerMessage) {
+        let mut streams: Vec<&Arc<Mutex<StreamWriter>>> = Vec::new();
+        for stream in self.user_streams.values().chain(self.streams.values()) {
+            if !streams.iter().any(|sent| Arc::ptr_eq(sent, stream)) {
+                streams.push(stream);
+            }
+        }
+        for stream in streams {
+            Client::send_to_stream(message, Arc::clone(stream))
+                .await
+                .ok();
+        }
+    }
+
+    pub fn find_match(&mut self) -> Option<((Player, DeckChoice), (Player, DeckChoice))> {
+        let (player_to_user, blocked_users) = (&self.player_to_user, &self.blocked_users);
+        take_pair(&mut self.looking_for_match, |first, second| {
+            !is_blocked(player_to_user, blocked_users, first, second)
+        })
+    }
+
+    pub fn find_sealed_match(&mut self) -> Option<((Player, DeckChoice), (Player, DeckChoice))> {
+        let (player_to_user, blocked_users) = (&self.player_to_user, &self.blocked_users);
+        take_pair(&mut self.looking_for_sealed_match, |first, second| {
+            !is_blocked(player_to_user, blocked_users, first, second)
+        })
+    }
+
+    async fn join_draft(
+        &mut self,
+        player: Player,
+        seats: usize,
+        stream: &Arc<Mutex<StreamWriter>>,
+    ) -> Result<(), String> {
+        if self
+            .drafts
+            .values()
+            .any(|event| event.pod.seat_of(&player.id).is_some())
+        {
+            return Err("You are already in a draft.".to_string());
+        }
+
+        let pod_id = match self
+            .drafts
+            .values()
+            .find(|event| event.pod.phase == DraftPhase::Lobby && event.pod.capacity == seats)
+        {
+            Some(event) => event.pod.id,
+            None => {
+                let pod = DraftPod::new(seats)?;
+                let pod_id = pod.id;
+                self.drafts.insert(
+                    pod_id,
+                    DraftEvent {
+                        pod,
+                        created_at: Instant::now(),
+                        streams: HashMap::new(),
+                        pick_deadlines: HashMap::new(),
+                        bracket: None,
+                        ready: HashMap::new(),
+                    },
+                );
+                pod_id
+            }
+        };
+
+        let event = self
+            .drafts
+            .get_mut(&pod_id)
+            .expect("draft pod was just found or created");
+        event.streams.insert(player.id, Arc::clone(stream));
+        event.pod.join(player)?;
+        if event.pod.is_full() {
+            self.start_draft(pod_id).await;
+        } else {
+            self.send_draft_lobby(pod_id).await;
+        }
+        Ok(())
+    }
+
+    async fn send_draft_lobby(&self, pod_id: uuid::Uuid) {
+        let Some(event) = self.drafts.get(&pod_id) else {
+            return;
+        };
+        let message = ServerMessage::DraftLobby {
+            pod_id,
+            seats: u8::try_from(event.pod.capacity).unwrap_or(u8::MAX),
+            players: event.pod.players().map(|p| p.name.clone()).collect(),
+        };
+        for stream in event.streams.values() {
+            Client::send_to_stream(&message, Arc::clone(stream))
+                .await
+                .ok();
+        }
+    }
+
+    async fn start_draft(&mut self, pod_id: uuid::Uuid) {
+        if let Some(event) = self.drafts.get_mut(&pod_id) {
+            event.pod.start();
+        }
+        self.send_draft_lobby(pod_id).await;
+        self.advance_draft(pod_id).await;
+    }
+
+    /// Let the bots catch up, then either hand the players their next packs or, once the last
+    /// pack is empty, send them their pools and pair the bracket.
+    async fn advance_draft(&mut self, pod_id: uuid::Uuid) {
+        let Some(event) = self.drafts.get_mut(&pod_id) else {
+            return;
+        };
+        event.pod.run_bots();
+
+        match event.pod.phase {
+            DraftPhase::Lobby => {}
+            DraftPhase::Picking { round } => {
+                let now = Instant::now();
+                for (seat, draft_seat) in event.pod.seats.iter().enumerate() {
+                    let (Some(player), Some(pack)) = (&draft_seat.player, draft_seat.packs.front())
+                    else {
+                        continue;
+                    };
+                    if event.pick_deadlines.contains_key(&seat) {
+                        continue;
+                    }
+                    event.pick_deadlines.insert(
+                        seat,
+                        now + Duration::from_secs(self.matchmaking.draft_pick_seconds),
+                    );
+                    let Some(stream) = event.streams.get(&player.id) else {
+                        continue;
+                    };
+                    Client::send_to_stream(
+                        &ServerMessage::DraftPack {
+                            pod_id,
+                            round: u8::try_from(round + 1).unwrap_or(u8::MAX),
+                            pick: u8::try_from(event.pod.pick_number(seat)).unwrap_or(u8::MAX),
+                            pack: pack.clone(),
+                            seconds_left: self.matchmaking.draft_pick_seconds,
+                        },
+                        Arc::clone(stream),
+                    )
+                    .await
+                    .ok();
+                }
+            }
+            DraftPhase::Building => {
+                if event.bracket.is_some() {
+                    return;
+                }
+                event.pick_deadlines.clear();
+                for (seat, draft_seat) in event.pod.seats.iter().enumerate() {
+                    let (Some(player), Some(pool)) = (&draft_seat.player, event.pod.pool(seat))
+                    else {
+                        continue;
+                    };
+                    if let Some(stream) = event.streams.get(&player.id) {
+                        Client::send_to_stream(
+                            &ServerMessage::DraftComplete { pod_id, pool },
+                            Arc::clone(stream),
+                        )
+                        .await
+                        .ok();
+                    }
+                }
+                event.bracket = Some(DraftBracket::new(
+                    event.pod.players().map(|player| player.id).collect(),
+                ));
+                self.send_draft_bracket(pod_id).await;
+            }
+        }
+    }
+
+    async fn send_draft_bracket(&mut self, pod_id: uuid::Uuid) {
+        let Some(event) = self.drafts.get(&pod_id) else {
+            return;
+        };
+        let Some(bracket) = &event.bracket else {
+            return;
+        };
+        let name = |player_id: &PlayerId| {
+            event
+                .pod
+                .players()
+                .find(|player| &player.id == player_id)
+                .map(|player| player.name.clone())
+                .unwrap_or_default()
+        };
+        let message = ServerMessage::DraftBracketUpdated {
+            pod_id,
+            round: u8::try_from(bracket.round + 1).unwrap_or(u8::MAX),
+            matches: bracket
+                .matches
+                .iter()
+                .map(|m| (name(&m.players.0), name(&m.players.1)))
+                .collect(),
+            bye: bracket.bye.as_ref().map(name),
+            champion: bracket.champion.as_ref().map(name),
+        };
+        for stream in event.streams.values() {
+            Client::send_to_stream(&message, Arc::clone(stream))
+                .await
+                .ok();
+        }
+        if bracket.champion.is_some() {
+            self.drafts.remove(&pod_id);
+        }
+    }
+
+    async fn queue_draft_match(
+        &mut self,
+        pod_id: uuid::Uuid,
+        player_id: PlayerId,
+        deck: &DeckList,
+        stream: &Arc<Mutex<StreamWriter>>,
+    ) -> Result<(), String> {
+        let event = self
+            .drafts
+            .get_mut(&pod_id)
+            .ok_or_else(|| "That draft has ended.".to_string())?;
+        let seat = event
+            .pod
+            .seat_of(&player_id)
+            .ok_or_else(|| "You are not in that draft.".to_string())?;
+        let (slot, opponent) = event
+            .bracket
+            .as_ref()
+            .and_then(|bracket| {
+                let slot = bracket.slot_of(&player_id)?;
+                let players = bracket.matches[slot.index].players;
+                Some((
+                    slot,
+                    if players.0 == player_id {
+                        players.1
+                    } else {
+                        players.0
+                    },
+                ))
+            })
+            .ok_or_else(|| "You have no bracket match to play.".to_string())?;
+        event
+            .pod
+            .pool(seat)
+            .ok_or_else(|| "You are not in that draft.".to_string())?
+            .validate(deck)?;
+
+        let player = event.pod.seats[seat]
+            .player
+            .clone()
+            .ok_or_else(|| "You are not in that draft.".to_string())?;
+        event.streams.insert(player_id, Arc::clone(stream));
+        event.ready.insert(player_id, (player, deck.clone()));
+        let Some((opponent_player, opponent_deck)) = event.ready.remove(&opponent) else {
+            return Ok(());
+        };
+        let (player, deck) = event
+            .ready
+            .remove(&player_id)
+            .expect("player was just readied");
+        for ready_player in [&player, &opponent_player] {
+            if let Some(stream) = event.streams.get(&ready_player.id) {
+                self.streams.insert(ready_player.id, Arc::clone(stream));
+            }
+        }
+
+        match self
+            .create_game(
+                &player,
+                DeckChoice::Custom(deck),
+                &opponent_player,
+                DeckChoice::Custom(opponent_deck),
+            )
+            .await
+        {
+            Ok(game_id) => {
+                self.draft_games.insert(game_id, (pod_id, slot));
+                Ok(())
+            }
+            Err(error) => {
+                tracing::error!(%pod_id, error = ?error, "failed to start draft bracket game");
+                Err("The bracket match could not be started.".to_string())
+            }
+        }
+    }
+
+    /// Start pods whose lobby wait is over and pick for players whose pick timer ran out.
+    pub async fn tick_drafts(&mut self) {
+        let now = Instant::now();
+        let lobby_wait = Duration::from_secs(self.matchmaking.draft_lobby_seconds);
+        let mut to_start = Vec::new();
+        let mut to_advance = Vec::new();
+        for (pod_id, event) in &mut self.drafts {
+            match event.pod.phase {
+                DraftPhase::Lobby if now.duration_since(event.created_at) >= lobby_wait => {
+                    to_start.push(*pod_id);
+                }
+                DraftPhase::Picking { .. } => {
+                    let expired: Vec<usize> = event
+                        .pick_deadlines
+                        .iter()
+                        .filter(|(_, deadline)| **deadline <= now)
+                        .map(|(seat, _)| *seat)
+                        .collect();
+                    for seat in expired {
+                        event.pick_deadlines.remove(&seat);
+                        let Ok(card) = event.pod.auto_pick(seat) else {
+                            continue;
+                        };
+                        let stream = event.pod.seats[seat]
+                            .player
+                            .as_ref()
+                            .and_then(|player| event.streams.get(&player.id));
+                        if let Some(stream) = stream {
+                            Client::send_to_stream(
+                                &ServerMessage::DraftPicked {
+                                    pod_id: *pod_id,
+                                    card,
+                                },
+                                Arc::clone(stream),
+                            )
+                            .await
+                            .ok();
+                        }
+                        to_advance.push(*pod_id);
+                    }
+                }
+                _ => {}
+            }
+        }
+        for pod_id in to_start {
+            self.start_draft(pod_id).await;
+        }
+        for pod_id in to_advance {
+            self.advance_draft(pod_id).await;
+        }
+    }
+
+    /// Advance the bracket of the pod a finished game belongs to.
+    pub async fn record_game_outcome(&mut self, outcome: GameOutcome) {
+        let Some((pod_id, slot)) = self.draft_games.remove(&outcome.game_id) else {
+            return;
+        };
+        if let Some(bracket) = self
+            .drafts
+            .get_mut(&pod_id)
+            .and_then(|event| event.bracket.as_mut())
+        {
+            bracket.record_winner(slot, &outcome.winner_id);
+            self.send_draft_bracket(pod_id).await;
+        }
+    }
+
+    async fn leave_drafts(&mut self, player_id: &PlayerId) {
+        let Some(pod_id) = self
+            .drafts
+            .iter()
+            .find(|(_, event)| event.pod.seat_of(player_id).is_some())
+            .map(|(pod_id, _)| *pod_id)
+        else {
+            return;
+        };
+        let Some(event) = self.drafts.get_mut(&pod_id) else {
+            return;
+        };
+        if let Some(seat) = event.pod.seat_of(player_id) {
+            event.pick_deadlines.remove(&seat);
+        }
+        event.streams.remove(player_id);
+        event.ready.remove(player_id);
+        // The player's bracket game no longer decides anything once they forfeit it.
+        if let Some(slot) = event
+            .bracket
+            .as_ref()
+            .and_then(|bracket| bracket.slot_of(player_id))
+        {
+            self.draft_games.retain(|_, game| *game != (pod_id, slot));
+        }
+        let forfeited = event
+            .bracket
+            .as_mut()
+            .is_some_and(|bracket| bracket.forfeit(player_id));
+        event.pod.leave(player_id);
+
+        if event.pod.players().next().is_none() {
+            self.drafts.remove(&pod_id);
+        } else if forfeited {
+            self.send_draft_bracket(pod_id).await;
+        } else if event.pod.phase == DraftPhase::Lobby {
+            self.send_draft_lobby(pod_id).await;
+        } else {
+            self.advance_draft(pod_id).await;
+        }
+    }
+}
+
+/// Take the longest-waiting pair of players that `can_pair` allows out of `queue`.
+fn take_pair(
+    queue: &mut Vec<(uuid::Uuid, (Player, DeckChoice))>,
+    can_pair: impl Fn(&PlayerId, &PlayerId) -> bool,
+) -> Option<((Player, DeckChoice), (Player, DeckChoice))> {
+    let (first, second) = (0..queue.len())
+        .flat_map(|first| (first + 1..queue.len()).map(move |second| (first, second)))
+        .find(|&(first, second)| can_pair(&queue[first].0, &queue[second].0))?;
+    let player2 = queue.remove(second);
+    let player1 = queue.remove(first);
+    Some((player1.1, player2.1))
+}
+
+/// Whether `message` would start a game or put the player in line for one.
+fn starts_game(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::ClientMessage(
+            ClientMessage::JoinQueue { .. }
+                | ClientMessage::JoinSealedQueue { .. }
+                | ClientMessage::JoinDraft { .. }
+                | ClientMessage::QueueDraftMatch { .. }
+                | ClientMessage::StartGoldfish { .. }
+                | ClientMessage::StartSandbox { .. }
+        )
+    )
+}
+
+/// What players are told while the server drains.
+fn maintenance_notice(deadline: Option<Instant>) -> String {
+    let Some(deadline) = deadline else {
+        return "The server is restarting for maintenance soon. New matches can't be started, \
+                but matches in progress can finish."
+            .to_string();
+    };
+    let minutes = deadline
+        .saturating_duration_since(Instant::now())
+        .as_secs()
+        .div_ceil(60)
+        .max(1);
+    let plural = if minutes == 1 { "" } else { "s" };
+    format!(
+        "The server is restarting for maintenance. New matches can't be started, and matches in \
+         progress end without a result in {minutes} minute{plural}."
+    )
+}
+
+/// Whether either player has blocked the other.
+fn is_blocked(
+    player_to_user: &HashMap<uuid::Uuid, uuid::Uuid>,
+    blocked_users: &HashMap<uuid::Uuid, HashSet<uuid::Uuid>>,
+    first: &PlayerId,
+    second: &PlayerId,
+) -> bool {
+    let (Some(first), Some(second)) = (player_to_user.get(first), player_to_user.get(second))
+    else {
+        return false;
+    };
+    let blocks = |user: &uuid::Uuid, other: &uuid::Uuid| {
+        blocked_users
+            .get(user)
+            .is_some_and(|blocked| blocked.contains(other))
+    };
+    blocks(first, second) || blocks(second, first)
+}
 
 fn starter_deck_list(deck: &PreconDeck) -> DeckList {
     let (_, cards) = deck.build(&uuid::Uuid::nil());
@@ -695,3 +2208,771 @@ fn card_counts(cards: BTreeMap<String, u8>) -> Vec<CardNameWithCount> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{email::CapturedEmails, repository::MemoryStorage};
+    use sorcerers::moderation::ReportReason;
+    use tokio::{
+        io::AsyncReadExt,
+        net::{TcpListener, TcpStream, tcp::OwnedReadHalf},
+    };
+
+    const PASSWORD: &str = "very-secret-password";
+
+    /// A server on in-memory storage with one client connected to it.
+    struct Connection {
+        server: Server<MemoryStorage>,
+        storage: MemoryStorage,
+        emails: CapturedEmails,
+        stream: Arc<Mutex<StreamWriter>>,
+        reader: OwnedReadHalf,
+        addr: std::net::SocketAddr,
+    }
+
+    impl Connection {
+        async fn open() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let client = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let (accepted, addr) = listener.accept().await.unwrap();
+            let (_, writer) = accepted.into_split();
+            let (reader, _) = client.into_split();
+            let storage = MemoryStorage::new();
+            let emails = CapturedEmails::default();
+            let (game_outcomes, _) = async_channel::unbounded();
+            Self {
+                server: Server::new(
+                    None,
+                    &Config::default(),
+                    storage.clone(),
+                    emails.sender(),
+                    game_outcomes,
+                ),
+                storage,
+                emails,
+                stream: Arc::new(Mutex::new(Box::new(writer))),
+                reader,
+                addr,
+            }
+        }
+
+        async fn send(&mut self, message: ClientMessage) {
+            self.server
+                .process_message(
+                    &Message::ClientMessage(message),
+                    Arc::clone(&self.stream),
+                    &self.addr,
+                )
+                .await
+                .unwrap();
+        }
+
+        async fn receive(&mut self) -> ServerMessage {
+            let mut len = [0; std::mem::size_of::<usize>()];
+            self.reader.read_exact(&mut len).await.unwrap();
+            let mut bytes = vec![0; usize::from_be_bytes(len)];
+            self.reader.read_exact(&mut bytes).await.unwrap();
+            match rmp_serde::from_slice(&bytes).unwrap() {
+                Message::ServerMessage(message) => message,
+                Message::ClientMessage(message) => panic!("unexpected {message:?}"),
+            }
+        }
+
+        /// The next message `wanted` accepts, skipping the game traffic sent before it.
+        async fn receive_matching(
+            &mut self,
+            wanted: impl Fn(&ServerMessage) -> bool,
+        ) -> ServerMessage {
+            loop {
+                let message = self.receive().await;
+                if wanted(&message) {
+                    return message;
+                }
+            }
+        }
+
+        /// Register, confirm the email and pick a starter deck, returning the new user's id.
+        async fn sign_up(&mut self, username: &str, email: &str) -> uuid::Uuid {
+            self.send(ClientMessage::Register {
+                username: username.to_string(),
+                email: email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .await;
+            let ServerMessage::EmailConfirmationRequired { email, .. } = self.receive().await
+            else {
+                panic!("registering should ask for the confirmation code");
+            };
+            let code = self.emails.last_code(&email).unwrap();
+            self.send(ClientMessage::ConfirmEmail {
+                email: email.clone(),
+                code,
+            })
+            .await;
+            assert!(matches!(
+                self.receive().await,
+                ServerMessage::StarterDeckSelection { .. }
+            ));
+            self.send(ClientMessage::ChooseStarterDeck {
+                deck: PreconDeck::BetaFire,
+            })
+            .await;
+            assert!(matches!(
+                self.receive().await,
+                ServerMessage::AuthenticationSuccess { .. }
+            ));
+            assert!(matches!(
+                self.receive().await,
+                ServerMessage::TradeOffers { .. }
+            ));
+            assert!(matches!(
+                self.receive().await,
+                ServerMessage::BlockedPlayers { .. }
+            ));
+            self.storage
+                .verify_login(&email, PASSWORD)
+                .await
+                .unwrap()
+                .id
+        }
+    }
+
+    #[tokio::test]
+    async fn new_players_confirm_pick_a_starter_deck_and_open_boosters() {
+        let mut connection = Connection::open().await;
+        connection
+            .send(ClientMessage::Register {
+                username: "mage_one".to_string(),
+                email: "Mage@Example.com".to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .await;
+        let ServerMessage::EmailConfirmationRequired {
+            email,
+            delivery_failed,
+        } = connection.receive().await
+        else {
+            panic!("registering should ask for the confirmation code");
+        };
+        assert_eq!(email, "mage@example.com");
+        assert!(!delivery_failed);
+
+        connection
+            .send(ClientMessage::ConfirmEmail {
+                email: email.clone(),
+                code: "not a code".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AuthenticationFailure { .. }
+        ));
+
+        let code = connection.emails.last_code(&email).unwrap();
+        connection
+            .send(ClientMessage::ConfirmEmail {
+                email: email.clone(),
+                code,
+            })
+            .await;
+        let ServerMessage::StarterDeckSelection {
+            username,
+            available_decks,
+        } = connection.receive().await
+        else {
+            panic!("a new player should pick a starter deck");
+        };
+        assert_eq!(username, "mage_one");
+        assert_eq!(available_decks.len(), 4);
+
+        connection
+            .send(ClientMessage::ChooseStarterDeck {
+                deck: PreconDeck::BetaWater,
+            })
+            .await;
+        let ServerMessage::AuthenticationSuccess {
+            saved_decks,
+            collection,
+            unopened_booster_packs,
+            reward_points,
+            ..
+        } = connection.receive().await
+        else {
+            panic!("picking a starter deck should log the player in");
+        };
+        assert_eq!(saved_decks.len(), 1);
+        assert!(!collection.is_empty());
+        assert_eq!(unopened_booster_packs.len(), 3);
+        assert_eq!(reward_points, 0);
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::TradeOffers { trades } if trades.is_empty()
+        ));
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::BlockedPlayers { usernames } if usernames.is_empty()
+        ));
+
+        let pack_id = unopened_booster_packs[0].id;
+        connection
+            .send(ClientMessage::OpenBoosterPack { pack_id })
+            .await;
+        let ServerMessage::BoosterPackOpened { pack, .. } = connection.receive().await else {
+            panic!("opening a pack should reveal its cards");
+        };
+        let user_id = connection
+            .storage
+            .verify_login(&email, PASSWORD)
+            .await
+            .unwrap()
+            .id;
+        let total = |cards: &[CollectedCard]| {
+            cards
+                .iter()
+                .map(|card| usize::from(card.count))
+                .sum::<usize>()
+        };
+        assert_eq!(
+            total(&connection.storage.load_collection(user_id).await.unwrap()),
+            total(&collection) + pack.cards.len()
+        );
+        assert_eq!(
+            connection
+                .storage
+                .load_unopened_booster_packs(user_id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn match_rewards_pay_for_a_beta_booster() {
+        let mut connection = Connection::open().await;
+        let user_id = connection.sign_up("mage_one", "mage@example.com").await;
+
+        connection.send(ClientMessage::RedeemBetaBooster).await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::RewardRedemptionFailed { .. }
+        ));
+
+        for _ in 0..3 {
+            connection
+                .storage
+                .award_match_points(uuid::Uuid::new_v4(), user_id, 10)
+                .await
+                .unwrap();
+        }
+        connection.send(ClientMessage::RedeemBetaBooster).await;
+        let ServerMessage::BoosterRedeemed {
+            reward_points,
+            pack,
+        } = connection.receive().await
+        else {
+            panic!("three wins should pay for a booster");
+        };
+        assert_eq!(reward_points, 0);
+        assert!(
+            connection
+                .storage
+                .load_unopened_booster_packs(user_id)
+                .await
+                .unwrap()
+                .iter()
+                .any(|unopened| unopened.id == pack.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn forgotten_passwords_reset_and_accounts_can_be_deleted() {
+        let mut connection = Connection::open().await;
+        connection.sign_up("mage_one", "mage@example.com").await;
+
+        // Unknown addresses get the same answer as known ones.
+        connection
+            .send(ClientMessage::RequestPasswordReset {
+                email: "nobody@example.com".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::PasswordResetRequested { .. }
+        ));
+        connection
+            .send(ClientMessage::RequestPasswordReset {
+                email: "Mage@Example.com".to_string(),
+            })
+            .await;
+        let ServerMessage::PasswordResetRequested { email } = connection.receive().await else {
+            panic!("requesting a reset should be acknowledged");
+        };
+        let code = connection.emails.wait_for_reset_code(&email).await;
+        connection
+            .send(ClientMessage::ResetPassword {
+                email: email.clone(),
+                code,
+                new_password: "another-secret".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AuthenticationSuccess { .. }
+        ));
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::TradeOffers { .. }
+        ));
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::BlockedPlayers { .. }
+        ));
+        assert_eq!(
+            connection.emails.sent_to(&email).last(),
+            Some(&Email::PasswordChanged)
+        );
+
+        connection
+            .send(ClientMessage::ChangeUsername {
+                username: "archmage".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AccountUpdated { username: Some(username), .. } if username == "archmage"
+        ));
+        connection
+            .send(ClientMessage::DeleteAccount {
+                password: PASSWORD.to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AccountUpdateFailed { .. }
+        ));
+        connection
+            .send(ClientMessage::DeleteAccount {
+                password: "another-secret".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AccountDeleted
+        ));
+        assert!(matches!(
+            connection
+                .storage
+                .verify_login(&email, "another-secret")
+                .await,
+            Err(RepositoryError::InvalidCredentials)
+        ));
+        assert!(connection.server.addr_to_user.is_empty());
+    }
+
+    #[tokio::test]
+    async fn changing_the_password_notifies_the_account_email() {
+        let mut connection = Connection::open().await;
+        connection.sign_up("mage_one", "mage@example.com").await;
+
+        connection
+            .send(ClientMessage::ChangePassword {
+                current_password: "wrong-password".to_string(),
+                new_password: "another-secret".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AccountUpdateFailed { .. }
+        ));
+        assert_ne!(
+            connection.emails.sent_to("mage@example.com").last(),
+            Some(&Email::PasswordChanged)
+        );
+
+        connection
+            .send(ClientMessage::ChangePassword {
+                current_password: PASSWORD.to_string(),
+                new_password: "another-secret".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AccountUpdated { username: None, .. }
+        ));
+        assert_eq!(
+            connection.emails.sent_to("mage@example.com").last(),
+            Some(&Email::PasswordChanged)
+        );
+    }
+
+    #[tokio::test]
+    async fn repeated_failed_logins_lock_the_account_out() {
+        let mut connection = Connection::open().await;
+        connection.sign_up("mage_one", "mage@example.com").await;
+
+        let mut failures = Vec::new();
+        for _ in 0..=RateLimit::LOGIN_PER_ACCOUNT.max_attempts {
+            connection
+                .send(ClientMessage::Login {
+                    email: "mage@example.com".to_string(),
+                    password: "wrong-password".to_string(),
+                })
+                .await;
+            let ServerMessage::AuthenticationFailure { message } = connection.receive().await
+            else {
+                panic!("a wrong password should fail");
+            };
+            failures.push(message);
+        }
+        assert_eq!(failures[0], "invalid username or password");
+        assert_eq!(
+            failures.last().unwrap(),
+            "too many attempts; try again in 60 seconds"
+        );
+
+        // The lockout holds even for the right password.
+        connection
+            .send(ClientMessage::Login {
+                email: "mage@example.com".to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AuthenticationFailure { message } if message.starts_with("too many")
+        ));
+    }
+
+    #[tokio::test]
+    async fn guessing_codes_across_accounts_locks_the_address_out() {
+        let mut connection = Connection::open().await;
+        connection.sign_up("mage_one", "mage@example.com").await;
+
+        let mut failures = Vec::new();
+        for attempt in 0..=RateLimit::ENTER_CODE_PER_ADDRESS.max_attempts {
+            connection
+                .send(ClientMessage::ResetPassword {
+                    email: format!("mage{attempt}@example.com"),
+                    code: "000000".to_string(),
+                    new_password: "another-secret".to_string(),
+                })
+                .await;
+            let ServerMessage::AuthenticationFailure { message } = connection.receive().await
+            else {
+                panic!("a wrong code should fail");
+            };
+            failures.push(message);
+        }
+        assert!(!failures[0].starts_with("too many"));
+        assert!(failures.last().unwrap().starts_with("too many attempts"));
+
+        // Confirmation codes count against the same limit.
+        connection
+            .send(ClientMessage::ConfirmEmail {
+                email: "mage@example.com".to_string(),
+                code: "000000".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AuthenticationFailure { message } if message.starts_with("too many")
+        ));
+    }
+
+    #[tokio::test]
+    async fn banned_players_are_refused_until_the_ban_lifts() {
+        let mut connection = Connection::open().await;
+        let user_id = connection.sign_up("mage_one", "mage@example.com").await;
+        let login = ClientMessage::Login {
+            email: "mage@example.com".to_string(),
+            password: PASSWORD.to_string(),
+        };
+
+        connection.storage.ban(user_id, Some("cheating"), None);
+        connection.send(login.clone()).await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AuthenticationFailure { message }
+                if message == "this account has been banned: cheating"
+        ));
+
+        let expired = chrono::Utc::now() - chrono::Duration::minutes(1);
+        connection.storage.ban(user_id, None, Some(expired));
+        connection.send(login).await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AuthenticationSuccess { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn blocked_players_are_not_matched_and_opponents_can_be_reported() {
+        let mut connection = Connection::open().await;
+        let alice = connection.sign_up("mage_alice", "alice@example.com").await;
+        let bob = connection.sign_up("mage_bob", "bob@example.com").await;
+
+        connection
+            .send(ClientMessage::BlockPlayer {
+                username: "mage_alice".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::BlockedPlayers { usernames } if usernames == ["mage_alice"]
+        ));
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::AccountUpdated { .. }
+        ));
+
+        // Alice queued first, then Bob, then a player neither of them blocked.
+        let alice_player = uuid::Uuid::new_v4();
+        connection.server.player_to_user.insert(alice_player, alice);
+        let bob_player = connection.server.addr_to_player[&connection.addr];
+        let carol_player = uuid::Uuid::new_v4();
+        for (id, name) in [
+            (alice_player, "mage_alice"),
+            (bob_player, "mage_bob"),
+            (carol_player, "mage_carol"),
+        ] {
+            let player = Player {
+                id,
+                name: name.to_string(),
+            };
+            connection
+                .server
+                .looking_for_match
+                .push((id, (player, DeckChoice::Precon(PreconDeck::BetaFire))));
+        }
+        let (first, second) = connection.server.find_match().unwrap();
+        assert_eq!((first.0.id, second.0.id), (alice_player, carol_player));
+        assert!(connection.server.find_match().is_none());
+
+        let game_id = uuid::Uuid::new_v4();
+        let report = ClientMessage::ReportOpponent {
+            game_id,
+            reason: ReportReason::Stalling,
+            details: "never passed priority".to_string(),
+        };
+        connection.send(report.clone()).await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::ModerationRejected { .. }
+        ));
+        connection
+            .server
+            .recent_matches
+            .insert(game_id, (Instant::now(), [Some(alice), Some(bob)]));
+        connection.send(report.clone()).await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::OpponentReported { game_id: reported } if reported == game_id
+        ));
+        connection.send(report).await;
+        assert!(matches!(
+            connection.receive().await,
+            ServerMessage::ModerationRejected { message, .. }
+                if message == "You have already reported this match."
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_bracket_game_abandoned_by_a_leaver_does_not_decide_a_later_match() {
+        let mut connection = Connection::open().await;
+        let mut pod = DraftPod::new(4).unwrap();
+        let players: Vec<PlayerId> = ["mage_a", "mage_b", "mage_c", "mage_d"]
+            .into_iter()
+            .map(|name| {
+                let player = Player {
+                    id: uuid::Uuid::new_v4(),
+                    name: name.to_string(),
+                };
+                pod.join(player.clone()).unwrap();
+                player.id
+            })
+            .collect();
+        pod.phase = DraftPhase::Building;
+        let bracket = DraftBracket::new(players.clone());
+        let pod_id = pod.id;
+        let abandoned = uuid::Uuid::new_v4();
+        let other = uuid::Uuid::new_v4();
+        let server = &mut connection.server;
+        server
+            .draft_games
+            .insert(abandoned, (pod_id, bracket.slot_of(&players[0]).unwrap()));
+        server
+            .draft_games
+            .insert(other, (pod_id, bracket.slot_of(&players[2]).unwrap()));
+        server.drafts.insert(
+            pod_id,
+            DraftEvent {
+                pod,
+                created_at: Instant::now(),
+                streams: HashMap::new(),
+                pick_deadlines: HashMap::new(),
+                bracket: Some(bracket),
+                ready: HashMap::new(),
+            },
+        );
+
+        // The first player leaves mid-game, and the other semi-final finishes.
+        server.leave_drafts(&players[0]).await;
+        assert!(!server.draft_games.contains_key(&abandoned));
+        server
+            .record_game_outcome(GameOutcome {
+                game_id: other,
+                winner_id: players[2],
+                player_ids: vec![players[2], players[3]],
+            })
+            .await;
+        // Then the abandoned game ends.
+        server
+            .record_game_outcome(GameOutcome {
+                game_id: abandoned,
+                winner_id: players[1],
+                player_ids: vec![players[0], players[1]],
+            })
+            .await;
+
+        let bracket = server.drafts[&pod_id].bracket.as_ref().unwrap();
+        assert_eq!(bracket.round, 1);
+        assert_eq!(bracket.matches[0].players, (players[1], players[2]));
+        assert_eq!(bracket.matches[0].winner, None);
+        assert!(bracket.champion.is_none());
+    }
+
+    #[tokio::test]
+    async fn draining_reaches_players_in_a_match_and_ends_it_at_the_deadline() {
+        let mut connection = Connection::open().await;
+        let alice = connection.sign_up("mage_alice", "alice@example.com").await;
+        let alice_player = connection.server.addr_to_player[&connection.addr];
+        connection.sign_up("mage_bob", "bob@example.com").await;
+        let bob_player = connection.server.addr_to_player[&connection.addr];
+        // Both players share the test connection, and Bob's sign-up replaced Alice's player on it.
+        connection
+            .server
+            .streams
+            .insert(alice_player, Arc::clone(&connection.stream));
+        connection.server.player_to_user.insert(alice_player, alice);
+        let join_queue = ClientMessage::JoinQueue {
+            player_id: bob_player,
+            player_name: "mage_bob".to_string(),
+            deck: DeckChoice::Precon(PreconDeck::BetaFire),
+        };
+
+        let is_notice =
+            |message: &ServerMessage| matches!(message, ServerMessage::MaintenanceNotice { .. });
+
+        let game_id = connection
+            .server
+            .create_game(
+                &Player {
+                    id: alice_player,
+                    name: "mage_alice".to_string(),
+                },
+                DeckChoice::Precon(PreconDeck::BetaFire),
+                &Player {
+                    id: bob_player,
+                    name: "mage_bob".to_string(),
+                },
+                DeckChoice::Precon(PreconDeck::BetaWater),
+            )
+            .await
+            .unwrap();
+        assert!(connection.server.streams.is_empty());
+
+        // The notice reaches the players even though their streams now belong to the match.
+        connection
+            .storage
+            .set_drain(Some(DrainRequest { deadline: None }));
+        connection.server.tick_drain().await;
+        assert!(matches!(
+            connection.receive_matching(is_notice).await,
+            ServerMessage::MaintenanceNotice { message: Some(message) }
+                if message.contains("can finish")
+        ));
+        connection.send(join_queue.clone()).await;
+        assert!(matches!(
+            connection.receive_matching(is_notice).await,
+            ServerMessage::MaintenanceNotice { message: Some(_) }
+        ));
+        assert!(connection.server.looking_for_match.is_empty());
+        assert_eq!(connection.server.live_games(), 1);
+
+        connection.server.begin_shutdown(Duration::ZERO).await;
+        assert!(matches!(
+            connection.receive_matching(is_notice).await,
+            ServerMessage::MaintenanceNotice { message: Some(message) }
+                if message.contains("end without a result")
+        ));
+
+        // Admins can't call off a shutdown, and its deadline has passed.
+        connection.storage.set_drain(None);
+        connection.server.tick_drain().await;
+        assert!(matches!(
+            connection
+                .receive_matching(|message| matches!(message, ServerMessage::GameAborted { .. }))
+                .await,
+            ServerMessage::GameAborted { game_id: aborted, .. } if aborted == game_id
+        ));
+        assert_eq!(connection.server.live_games(), 0);
+        assert!(!connection.server.games.contains_key(&game_id));
+        assert!(!connection.server.game_players.contains_key(&game_id));
+        connection.send(join_queue).await;
+        assert!(matches!(
+            connection.receive_matching(is_notice).await,
+            ServerMessage::MaintenanceNotice { message: Some(_) }
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_scenario_that_fails_to_apply_returns_players_to_the_lobby() {
+        let mut connection = Connection::open().await;
+        let alice = connection.sign_up("mage_alice", "alice@example.com").await;
+        let alice_player = connection.server.addr_to_player[&connection.addr];
+        connection.sign_up("mage_bob", "bob@example.com").await;
+        let bob_player = connection.server.addr_to_player[&connection.addr];
+        // Both players share the test connection, and Bob's sign-up replaced Alice's player on it.
+        connection
+            .server
+            .streams
+            .insert(alice_player, Arc::clone(&connection.stream));
+        connection.server.player_to_user.insert(alice_player, alice);
+        // Skips `Scenario::from_json`, which would refuse a third seat up front.
+        connection.server.scenario =
+            Some(serde_json::from_str(r#"{"players": [{}, {}, {}]}"#).unwrap());
+
+        let result = connection
+            .server
+            .create_game(
+                &Player {
+                    id: alice_player,
+                    name: "mage_alice".to_string(),
+                },
+                DeckChoice::Precon(PreconDeck::BetaFire),
+                &Player {
+                    id: bob_player,
+                    name: "mage_bob".to_string(),
+                },
+                DeckChoice::Precon(PreconDeck::BetaWater),
+            )
+            .await;
+        assert!(result.is_err());
+        for _ in 0..2 {
+            assert!(matches!(
+                connection.receive().await,
+                ServerMessage::GameAborted { .. }
+            ));
+        }
+        assert!(connection.server.games.is_empty());
+        assert!(connection.server.game_players.is_empty());
+        assert!(connection.server.recent_matches.is_empty());
+        assert_eq!(connection.server.live_games(), 0);
+        assert!(connection.server.streams.contains_key(&alice_player));
+        assert!(connection.server.streams.contains_key(&bob_player));
+    }
+}
@@ -0,0 +1,58 @@
+use super::{Repository, RepositoryError as UserRepositoryError};
+
+impl Repository {
+    /// Record the achievements earned in a game and return the ids the player did not have yet.
+    pub async fn unlock_achievements(
+        &self,
+        user_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        achievement_ids: &[&str],
+    ) -> Result<Vec<String>, UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        let mut unlocked = Vec::new();
+        for &achievement_id in achievement_ids {
+            let inserted = sqlx::query(
+                "INSERT INTO user_achievements (user_id, achievement_id, game_id) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (user_id, achievement_id) DO NOTHING",
+            )
+            .bind(user_id.to_string())
+            .bind(achievement_id)
+            .bind(game_id.to_string())
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected();
+            if inserted > 0 {
+                unlocked.push(achievement_id.to_string());
+            }
+        }
+        transaction.commit().await?;
+        Ok(unlocked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Repository, confirmed_user};
+
+    #[tokio::test]
+    async fn achievements_unlock_once() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let user = confirmed_user(&repository, "achiever").await;
+
+        let unlocked = repository
+            .unlock_achievements(user.id, uuid::Uuid::new_v4(), &["first-victory"])
+            .await
+            .unwrap();
+        assert_eq!(unlocked, vec!["first-victory".to_string()]);
+
+        let unlocked = repository
+            .unlock_achievements(
+                user.id,
+                uuid::Uuid::new_v4(),
+                &["first-victory", "dragon-slayer"],
+            )
+            .await
+            .unwrap();
+        assert_eq!(unlocked, vec!["dragon-slayer".to_string()]);
+    }
+}
@@ -0,0 +1,630 @@
+//! Operator queries used by `sorcerers-admin`: looking users up, granting rewards, password
+//! resets, bans, reports, live games, drain requests and data exports.
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use sorcerers::{
+    booster::{BoosterPack, UnopenedBoosterPack},
+    collection::CollectedCard,
+    crafting::card_rarity,
+    deck::{CardNameWithCount, DeckList},
+    sealed::SealedPool,
+    trade::TradeOffer,
+};
+
+use super::{
+    Repository, RepositoryError as UserRepositoryError, TIMESTAMP_FORMAT,
+    cards::add_user_cards,
+    users::{MIN_PASSWORD_LENGTH, hash_password},
+};
+
+const USER_COLUMNS: &str = "CAST(id AS TEXT), username, email, CAST(email_confirmed_at AS TEXT),
+    CAST(reward_points AS BIGINT), CAST(banned_at AS TEXT), ban_reason, CAST(banned_until AS TEXT),
+    CAST(created_at AS TEXT)";
+
+type UserRow = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    i64,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+);
+
+/// An account as operators see it.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserSummary {
+    pub id: uuid::Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub email_confirmed_at: Option<String>,
+    pub reward_points: u32,
+    pub banned_at: Option<String>,
+    pub ban_reason: Option<String>,
+    /// When a temporary ban lifts.
+    pub banned_until: Option<String>,
+    pub created_at: String,
+}
+
+impl UserSummary {
+    fn from_row(row: UserRow) -> Result<Self, UserRepositoryError> {
+        let (
+            id,
+            username,
+            email,
+            email_confirmed_at,
+            reward_points,
+            banned_at,
+            ban_reason,
+            banned_until,
+            created_at,
+        ) = row;
+        Ok(Self {
+            id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+            username,
+            email,
+            email_confirmed_at,
+            reward_points: reward_points.max(0) as u32,
+            banned_at,
+            ban_reason,
+            banned_until,
+            created_at,
+        })
+    }
+
+    /// Whether the user is banned right now, rather than never or formerly.
+    pub fn is_banned(&self) -> bool {
+        let now = Utc::now().format(TIMESTAMP_FORMAT).to_string();
+        self.banned_at.is_some()
+            && self
+                .banned_until
+                .as_ref()
+                .is_none_or(|until| until.as_str() > now.as_str())
+    }
+}
+
+/// A report one player filed against another.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerReport {
+    pub id: i64,
+    pub reporter: String,
+    pub reported: String,
+    pub game_id: String,
+    pub reason: String,
+    pub details: String,
+    pub created_at: String,
+}
+
+/// A match in progress, as recorded by the server.
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveGame {
+    pub game_id: uuid::Uuid,
+    pub players: [String; 2],
+    pub started_at: String,
+}
+
+/// A request from `sorcerers-admin drain` for the server to stop starting games.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainRequest {
+    /// When the games still going are ended, or `None` to let them finish.
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+/// Points paid out for one finished game.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameReward {
+    pub game_id: String,
+    pub points: u32,
+    pub awarded_at: String,
+}
+
+/// Everything stored about one user.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserExport {
+    pub account: UserSummary,
+    pub crafting_dust: u32,
+    pub collection: Vec<CollectedCard>,
+    pub decks: Vec<DeckList>,
+    pub unopened_booster_packs: Vec<UnopenedBoosterPack>,
+    pub sealed_pool: Option<SealedPool>,
+    pub trades: Vec<TradeOffer>,
+    pub achievements: Vec<String>,
+    pub game_rewards: Vec<GameReward>,
+    pub blocked_players: Vec<String>,
+}
+
+impl Repository {
+    /// Users whose username or email contains `search`, newest first.
+    pub async fn search_users(
+        &self,
+        search: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<UserSummary>, UserRepositoryError> {
+        // Usernames are full of underscores, which LIKE would otherwise treat as wildcards.
+        let pattern = search.map(|search| {
+            let escaped = search
+                .trim()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        });
+        let rows: Vec<UserRow> = sqlx::query_as(&format!(
+            "SELECT {USER_COLUMNS} FROM users
+             WHERE ?1 IS NULL OR username LIKE ?1 ESCAPE '\\' OR email LIKE ?1 ESCAPE '\\'
+             ORDER BY created_at DESC, username
+             LIMIT ?2"
+        ))
+        .bind(pattern)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(UserSummary::from_row).collect()
+    }
+
+    /// The user with the given id, username or email address.
+    pub async fn find_user(&self, user: &str) -> Result<Option<UserSummary>, UserRepositoryError> {
+        let user = user.trim();
+        let row: Option<UserRow> = sqlx::query_as(&format!(
+            "SELECT {USER_COLUMNS} FROM users WHERE id = ?1 OR username = ?1 OR email = ?2"
+        ))
+        .bind(user)
+        .bind(user.to_lowercase())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(UserSummary::from_row).transpose()
+    }
+
+    /// Add reward points to a user's balance and return the new balance.
+    pub async fn grant_reward_points(
+        &self,
+        user_id: uuid::Uuid,
+        points: u32,
+    ) -> Result<u32, UserRepositoryError> {
+        let reward_points: i64 = sqlx::query_scalar(
+            "UPDATE users SET reward_points = reward_points + ?1 WHERE id = ?2
+             RETURNING CAST(reward_points AS BIGINT)",
+        )
+        .bind(i64::from(points))
+        .bind(user_id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(reward_points.max(0) as u32)
+    }
+
+    pub async fn grant_booster_packs(
+        &self,
+        user_id: uuid::Uuid,
+        packs: &[BoosterPack],
+    ) -> Result<(), UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        for pack in packs {
+            let cards = serde_json::to_string(&pack.cards)
+                .map_err(|_| UserRepositoryError::Serialization)?;
+            sqlx::query(
+                "INSERT INTO booster_packs (id, user_id, set_name, cards) VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(user_id.to_string())
+            .bind(&pack.set_name)
+            .bind(cards)
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn grant_cards(
+        &self,
+        user_id: uuid::Uuid,
+        cards: &[CardNameWithCount],
+    ) -> Result<(), UserRepositoryError> {
+        if let Some(card) = cards.iter().find(|card| card_rarity(&card.name).is_none()) {
+            return Err(UserRepositoryError::UnknownCard(card.name.clone()));
+        }
+        let mut transaction = self.pool.begin().await?;
+        for card in cards {
+            add_user_cards(&mut transaction, user_id, card).await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Confirm a user's email address without a code, for players who can't receive one.
+    pub async fn mark_email_confirmed(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<(), UserRepositoryError> {
+        sqlx::query(
+            "UPDATE users SET email_confirmed_at = COALESCE(email_confirmed_at, CURRENT_TIMESTAMP),
+                confirmation_code_hash = NULL, confirmation_code_expires_at = NULL,
+                confirmation_attempts = 0
+             WHERE id = ?1",
+        )
+        .bind(user_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Replace a user's password without their current one, and void any reset code they hold.
+    pub async fn set_password(
+        &self,
+        user_id: uuid::Uuid,
+        new_password: &str,
+    ) -> Result<(), UserRepositoryError> {
+        if new_password.len() < MIN_PASSWORD_LENGTH {
+            return Err(UserRepositoryError::InvalidPassword);
+        }
+        sqlx::query(
+            "UPDATE users SET password_hash = ?1,
+                password_reset_code_hash = NULL, password_reset_expires_at = NULL,
+                password_reset_attempts = 0
+             WHERE id = ?2",
+        )
+        .bind(hash_password(new_password)?)
+        .bind(user_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Stop a user from logging in, until `until` or for good. Sessions that are already open
+    /// last until they disconnect.
+    pub async fn ban_user(
+        &self,
+        user_id: uuid::Uuid,
+        reason: Option<&str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), UserRepositoryError> {
+        sqlx::query(
+            "UPDATE users SET banned_at = CURRENT_TIMESTAMP, ban_reason = ?1, banned_until = ?2
+             WHERE id = ?3",
+        )
+        .bind(reason)
+        .bind(until.map(|until| until.format(TIMESTAMP_FORMAT).to_string()))
+        .bind(user_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn unban_user(&self, user_id: uuid::Uuid) -> Result<(), UserRepositoryError> {
+        sqlx::query(
+            "UPDATE users SET banned_at = NULL, ban_reason = NULL, banned_until = NULL WHERE id = ?1",
+        )
+        .bind(user_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Reports against `reported`, or against anyone, newest first.
+    pub async fn list_reports(
+        &self,
+        reported: Option<uuid::Uuid>,
+        limit: u32,
+    ) -> Result<Vec<PlayerReport>, UserRepositoryError> {
+        let rows: Vec<(i64, String, String, String, String, String, String)> = sqlx::query_as(
+            "SELECT CAST(player_reports.id AS BIGINT), reporters.username, reported.username,
+                game_id, reason, details, CAST(player_reports.created_at AS TEXT)
+             FROM player_reports
+             JOIN users AS reporters ON reporters.id = player_reports.reporter_id
+             JOIN users AS reported ON reported.id = player_reports.reported_id
+             WHERE ?1 IS NULL OR player_reports.reported_id = ?1
+             ORDER BY player_reports.id DESC
+             LIMIT ?2",
+        )
+        .bind(reported.map(|id| id.to_string()))
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, reporter, reported, game_id, reason, details, created_at)| PlayerReport {
+                    id,
+                    reporter,
+                    reported,
+                    game_id,
+                    reason,
+                    details,
+                    created_at,
+                },
+            )
+            .collect())
+    }
+
+    pub async fn start_live_game(
+        &self,
+        game_id: uuid::Uuid,
+        players: [&str; 2],
+    ) -> Result<(), UserRepositoryError> {
+        sqlx::query("INSERT INTO live_games (game_id, player_one, player_two) VALUES (?1, ?2, ?3)")
+            .bind(game_id.to_string())
+            .bind(players[0])
+            .bind(players[1])
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn finish_live_game(&self, game_id: uuid::Uuid) -> Result<(), UserRepositoryError> {
+        sqlx::query("DELETE FROM live_games WHERE game_id = ?1")
+            .bind(game_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Forget the games of a previous run, which ended when that server stopped.
+    pub async fn clear_live_games(&self) -> Result<(), UserRepositoryError> {
+        sqlx::query("DELETE FROM live_games")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Ask the running server to drain ahead of maintenance. Asking again replaces the deadline.
+    pub async fn request_drain(
+        &self,
+        deadline: Option<DateTime<Utc>>,
+    ) -> Result<(), UserRepositoryError> {
+        sqlx::query(
+            "INSERT INTO server_drain (id, deadline) VALUES (1, ?1)
+             ON CONFLICT (id) DO UPDATE SET
+                deadline = excluded.deadline,
+                requested_at = CURRENT_TIMESTAMP",
+        )
+        .bind(deadline.map(|deadline| deadline.format(TIMESTAMP_FORMAT).to_string()))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Call off a drain. A starting server does this too, since the drain was for the server
+    /// before it.
+    pub async fn cancel_drain(&self) -> Result<(), UserRepositoryError> {
+        sqlx::query("DELETE FROM server_drain")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn drain_request(&self) -> Result<Option<DrainRequest>, UserRepositoryError> {
+        let row: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT CAST(deadline AS TEXT) FROM server_drain WHERE id = 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some((deadline,)) = row else {
+            return Ok(None);
+        };
+        let deadline = match deadline {
+            Some(deadline) => Some(
+                NaiveDateTime::parse_from_str(&deadline, TIMESTAMP_FORMAT)
+                    .map_err(|_| UserRepositoryError::Serialization)?
+                    .and_utc(),
+            ),
+            None => None,
+        };
+        Ok(Some(DrainRequest { deadline }))
+    }
+
+    pub async fn live_games(&self) -> Result<Vec<LiveGame>, UserRepositoryError> {
+        let rows: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT CAST(game_id AS TEXT), player_one, player_two, CAST(started_at AS TEXT)
+             FROM live_games ORDER BY started_at",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|(game_id, player_one, player_two, started_at)| {
+                Ok(LiveGame {
+                    game_id: game_id
+                        .parse()
+                        .map_err(|_| UserRepositoryError::Serialization)?,
+                    players: [player_one, player_two],
+                    started_at,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn export_user(
+        &self,
+        account: UserSummary,
+    ) -> Result<UserExport, UserRepositoryError> {
+        let user_id = account.id;
+        let achievements = sqlx::query_scalar(
+            "SELECT achievement_id FROM user_achievements WHERE user_id = ?1 ORDER BY unlocked_at",
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        let game_rewards: Vec<(String, i64, String)> = sqlx::query_as(
+            "SELECT game_id, CAST(points AS BIGINT), CAST(created_at AS TEXT)
+             FROM game_rewards WHERE user_id = ?1 ORDER BY created_at",
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(UserExport {
+            crafting_dust: self.crafting_dust(user_id).await?,
+            collection: self.load_collection(user_id).await?,
+            decks: self.load_decks(user_id).await?,
+            unopened_booster_packs: self.load_unopened_booster_packs(user_id).await?,
+            sealed_pool: self.load_sealed_pool(user_id).await?,
+            trades: self.load_trades(user_id).await?,
+            achievements,
+            blocked_players: self
+                .blocked_players(user_id)
+                .await?
+                .into_iter()
+                .map(|user| user.username)
+                .collect(),
+            game_rewards: game_rewards
+                .into_iter()
+                .map(|(game_id, points, awarded_at)| GameReward {
+                    game_id,
+                    points: points.max(0) as u32,
+                    awarded_at,
+                })
+                .collect(),
+            account,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sorcerers::{booster::BoosterPack, deck::CardNameWithCount};
+
+    use super::super::{DrainRequest, Repository, RepositoryError};
+
+    #[tokio::test]
+    async fn operators_can_set_a_password_and_void_reset_codes() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        repository
+            .register("mage_one", "mage@example.com", "very-secret-password")
+            .await
+            .unwrap();
+        let user = repository.find_user("mage_one").await.unwrap().unwrap();
+        repository.mark_email_confirmed(user.id).await.unwrap();
+        let pending = repository
+            .request_password_reset("mage@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(
+            repository.set_password(user.id, "short").await,
+            Err(RepositoryError::InvalidPassword)
+        ));
+        repository
+            .set_password(user.id, "another-secret-password")
+            .await
+            .unwrap();
+        assert!(
+            repository
+                .verify_login("mage@example.com", "very-secret-password")
+                .await
+                .is_err()
+        );
+        repository
+            .verify_login("mage@example.com", "another-secret-password")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .reset_password("mage@example.com", &pending.code, "third-secret-password")
+                .await,
+            Err(RepositoryError::InvalidConfirmationCode)
+        ));
+    }
+
+    #[tokio::test]
+    async fn operators_can_grant_ban_and_export() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        repository
+            .register("mage_one", "mage@example.com", "very-secret-password")
+            .await
+            .unwrap();
+        repository
+            .register("mageone", "other@example.com", "very-secret-password")
+            .await
+            .unwrap();
+
+        let found = repository.search_users(Some("mage_"), 10).await.unwrap();
+        assert_eq!(found.len(), 1);
+        let user = repository
+            .find_user("MAGE@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.username, "mage_one");
+        assert!(user.email_confirmed_at.is_none());
+
+        repository.mark_email_confirmed(user.id).await.unwrap();
+        repository
+            .verify_login("mage@example.com", "very-secret-password")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repository.grant_reward_points(user.id, 25).await.unwrap(),
+            25
+        );
+        repository
+            .grant_booster_packs(user.id, &[BoosterPack::beta()])
+            .await
+            .unwrap();
+        let card = |name: &str| CardNameWithCount {
+            name: name.to_string(),
+            count: 2,
+            is_foil: false,
+        };
+        repository
+            .grant_cards(user.id, &[card("Pit Vipers")])
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.grant_cards(user.id, &[card("Not A Card")]).await,
+            Err(RepositoryError::UnknownCard(_))
+        ));
+
+        repository
+            .ban_user(user.id, Some("cheating"), None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.check_ban(user.id).await,
+            Err(RepositoryError::AccountBanned { reason: Some(reason), until: None })
+                if reason == "cheating"
+        ));
+        assert!(
+            repository
+                .find_user("mage_one")
+                .await
+                .unwrap()
+                .unwrap()
+                .is_banned()
+        );
+        repository.unban_user(user.id).await.unwrap();
+        repository.check_ban(user.id).await.unwrap();
+
+        let game_id = uuid::Uuid::new_v4();
+        repository
+            .start_live_game(game_id, ["mage_one", "mageone"])
+            .await
+            .unwrap();
+        assert_eq!(repository.live_games().await.unwrap()[0].game_id, game_id);
+        repository.finish_live_game(game_id).await.unwrap();
+        assert!(repository.live_games().await.unwrap().is_empty());
+
+        assert_eq!(repository.drain_request().await.unwrap(), None);
+        repository.request_drain(None).await.unwrap();
+        let deadline = "2030-01-02T03:04:05Z".parse().unwrap();
+        repository.request_drain(Some(deadline)).await.unwrap();
+        assert_eq!(
+            repository.drain_request().await.unwrap(),
+            Some(DrainRequest {
+                deadline: Some(deadline)
+            })
+        );
+        repository.cancel_drain().await.unwrap();
+        assert_eq!(repository.drain_request().await.unwrap(), None);
+
+        let user = repository
+            .find_user(&user.id.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        let export = repository.export_user(user).await.unwrap();
+        assert_eq!(export.account.reward_points, 25);
+        assert_eq!(export.collection[0].count, 2);
+        assert_eq!(export.unopened_booster_packs.len(), 1);
+    }
+}
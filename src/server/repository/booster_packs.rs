@@ -2,10 +2,6 @@ use sorcerers::booster::{BoosterCard, BoosterPack, UnopenedBoosterPack};
 
 use super::{Repository, RepositoryError as UserRepositoryError};
 
-pub const WIN_REWARD_POINTS: i32 = 10;
-pub const PLAY_REWARD_POINTS: i32 = 2;
-pub const BETA_BOOSTER_COST: i32 = WIN_REWARD_POINTS * 3;
-
 pub struct MatchReward {
     pub points_earned: u32,
     pub reward_points: u32,
@@ -26,9 +22,8 @@ impl Repository {
         &self,
         game_id: uuid::Uuid,
         user_id: uuid::Uuid,
-        won: bool,
+        points: u32,
     ) -> Result<MatchReward, UserRepositoryError> {
-        let points = if won { WIN_REWARD_POINTS } else { PLAY_REWARD_POINTS };
         let mut transaction = self.pool.begin().await?;
         let awarded: Option<i64> = sqlx::query_scalar(
             "INSERT INTO game_rewards (game_id, user_id, points) VALUES (?1, ?2, ?3)
@@ -37,7 +32,7 @@ impl Repository {
         )
         .bind(game_id.to_string())
         .bind(user_id.to_string())
-        .bind(points)
+        .bind(i64::from(points))
         .fetch_optional(&mut *transaction)
         .await?;
         let reward_points: i64 = if let Some(points_earned) = awarded {
@@ -65,6 +60,7 @@ impl Repository {
         &self,
         user_id: uuid::Uuid,
         pack: BoosterPack,
+        cost: u32,
     ) -> Result<(u32, UnopenedBoosterPack), UserRepositoryError> {
         let mut transaction = self.pool.begin().await?;
         let reward_points: Option<i64> = sqlx::query_scalar(
@@ -72,7 +68,7 @@ impl Repository {
              WHERE id = ?2 AND reward_points >= ?1
              RETURNING CAST(reward_points AS BIGINT)",
         )
-        .bind(BETA_BOOSTER_COST)
+        .bind(i64::from(cost))
         .bind(user_id.to_string())
         .fetch_optional(&mut *transaction)
         .await?;
@@ -200,13 +196,3 @@ impl Repository {
         Ok(Some(BoosterPack { set_name, cards }))
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::{BETA_BOOSTER_COST, WIN_REWARD_POINTS};
-
-    #[test]
-    fn beta_booster_costs_three_wins() {
-        assert_eq!(BETA_BOOSTER_COST, WIN_REWARD_POINTS * 3);
-    }
-}
@@ -1,28 +1,832 @@
-use sorcerers::collection::CollectedCard;
+use sorcerers::{
+    collection::CollectedCard,
+    crafting::check_owned_copies,
+    deck::{CardNameWithCount, DeckList},
+    trade::{TradeOffer, TradeSide, TradeStatus, deck_locked_copies, validate_terms},
+};
+use sqlx::SqliteConnection;
+use std::collections::HashMap;
 
 use super::{Repository, RepositoryError as UserRepositoryError};
 
+/// The outcome of a change to a trade, used to notify the other player.
+pub struct TradeUpdate {
+    pub partner_id: uuid::Uuid,
+    pub completed: bool,
+}
+
+type TradeRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    bool,
+    bool,
+    bool,
+    bool,
+);
+
+/// An open trade joined with both usernames, for listing a player's trades.
+type TradeListingRow = (String, String, String, String, String, bool, bool, String);
+
+/// An open trade, with its sides kept in proposer/recipient order.
+pub(super) struct StoredTrade {
+    pub(super) id: uuid::Uuid,
+    pub(super) proposer_id: uuid::Uuid,
+    pub(super) recipient_id: uuid::Uuid,
+    pub(super) proposer_side: TradeSide,
+    pub(super) recipient_side: TradeSide,
+    pub(super) proposer_confirmed: bool,
+    pub(super) recipient_confirmed: bool,
+    pub(super) proposer_unlocks_decks: bool,
+    pub(super) recipient_unlocks_decks: bool,
+}
+
+impl StoredTrade {
+    fn from_row(row: TradeRow) -> Result<Self, UserRepositoryError> {
+        let (
+            id,
+            proposer_id,
+            recipient_id,
+            proposer_side,
+            recipient_side,
+            proposer_confirmed,
+            recipient_confirmed,
+            proposer_unlocks_decks,
+            recipient_unlocks_decks,
+        ) = row;
+        Ok(Self {
+            id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+            proposer_id: proposer_id
+                .parse()
+                .map_err(|_| UserRepositoryError::Serialization)?,
+            recipient_id: recipient_id
+                .parse()
+                .map_err(|_| UserRepositoryError::Serialization)?,
+            proposer_side: serde_json::from_str(&proposer_side)
+                .map_err(|_| UserRepositoryError::Serialization)?,
+            recipient_side: serde_json::from_str(&recipient_side)
+                .map_err(|_| UserRepositoryError::Serialization)?,
+            proposer_confirmed,
+            recipient_confirmed,
+            proposer_unlocks_decks,
+            recipient_unlocks_decks,
+        })
+    }
+
+    pub(super) fn partner_of(&self, user_id: uuid::Uuid) -> uuid::Uuid {
+        if user_id == self.proposer_id {
+            self.recipient_id
+        } else {
+            self.proposer_id
+        }
+    }
+}
+
 impl Repository {
     pub async fn load_collection(
         &self,
         user_id: uuid::Uuid,
     ) -> Result<Vec<CollectedCard>, UserRepositoryError> {
-        let cards: Vec<(String, i64, bool)> = sqlx::query_as(
-            "SELECT card_name, CAST(quantity AS BIGINT), is_foil
-             FROM user_cards WHERE user_id = ?1 ORDER BY card_name, is_foil",
+        let mut connection = self.pool.acquire().await?;
+        load_user_cards(&mut connection, user_id).await
+    }
+
+    /// Offer a trade to another player. The proposer confirms the terms by proposing them.
+    pub async fn propose_trade(
+        &self,
+        proposer_id: uuid::Uuid,
+        recipient: &str,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> Result<TradeUpdate, UserRepositoryError> {
+        validate_terms(offered, requested).map_err(UserRepositoryError::InvalidTrade)?;
+        let mut transaction = self.pool.begin().await?;
+        let recipient_id: Option<String> =
+            sqlx::query_scalar("SELECT CAST(id AS TEXT) FROM users WHERE username = ?1")
+                .bind(recipient.trim())
+                .fetch_optional(&mut *transaction)
+                .await?;
+        let recipient_id: uuid::Uuid = recipient_id
+            .ok_or(UserRepositoryError::UnknownTradePartner)?
+            .parse()
+            .map_err(|_| UserRepositoryError::Serialization)?;
+        if recipient_id == proposer_id {
+            return Err(UserRepositoryError::InvalidTrade(
+                "You cannot trade with yourself.".to_string(),
+            ));
+        }
+        check_trade_side(&mut transaction, proposer_id, offered, unlock_decks).await?;
+
+        sqlx::query(
+            "INSERT INTO trade_offers
+                (id, proposer_id, recipient_id, proposer_side, recipient_side,
+                 proposer_confirmed, proposer_unlocks_decks)
+             VALUES (?1, ?2, ?3, ?4, ?5, TRUE, ?6)",
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(proposer_id.to_string())
+        .bind(recipient_id.to_string())
+        .bind(serde_json::to_string(offered).map_err(|_| UserRepositoryError::Serialization)?)
+        .bind(serde_json::to_string(requested).map_err(|_| UserRepositoryError::Serialization)?)
+        .bind(unlock_decks)
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(TradeUpdate {
+            partner_id: recipient_id,
+            completed: false,
+        })
+    }
+
+    /// Replace the terms of an open trade. The player countering confirms the new terms, and the
+    /// other player has to confirm them again.
+    pub async fn counter_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> Result<TradeUpdate, UserRepositoryError> {
+        validate_terms(offered, requested).map_err(UserRepositoryError::InvalidTrade)?;
+        let mut transaction = self.pool.begin().await?;
+        let trade = load_open_trade(&mut transaction, user_id, trade_id).await?;
+        check_trade_side(&mut transaction, user_id, offered, unlock_decks).await?;
+
+        let is_proposer = user_id == trade.proposer_id;
+        let (proposer_side, recipient_side) = if is_proposer {
+            (offered, requested)
+        } else {
+            (requested, offered)
+        };
+        sqlx::query(
+            "UPDATE trade_offers
+             SET proposer_side = ?1, recipient_side = ?2,
+                 proposer_confirmed = ?3, recipient_confirmed = ?4,
+                 proposer_unlocks_decks = ?5, recipient_unlocks_decks = ?6,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?7",
+        )
+        .bind(serde_json::to_string(proposer_side).map_err(|_| UserRepositoryError::Serialization)?)
+        .bind(
+            serde_json::to_string(recipient_side)
+                .map_err(|_| UserRepositoryError::Serialization)?,
+        )
+        .bind(is_proposer)
+        .bind(!is_proposer)
+        .bind(is_proposer && unlock_decks)
+        .bind(!is_proposer && unlock_decks)
+        .bind(trade.id.to_string())
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(TradeUpdate {
+            partner_id: trade.partner_of(user_id),
+            completed: false,
+        })
+    }
+
+    /// Confirm the current terms of a trade. Once both players have confirmed, quantities, reward
+    /// points and deck locks are checked again and everything changes hands in one transaction.
+    pub async fn confirm_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        unlock_decks: bool,
+    ) -> Result<TradeUpdate, UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        let mut trade = load_open_trade(&mut transaction, user_id, trade_id).await?;
+        if user_id == trade.proposer_id {
+            trade.proposer_confirmed = true;
+            trade.proposer_unlocks_decks = unlock_decks;
+        } else {
+            trade.recipient_confirmed = true;
+            trade.recipient_unlocks_decks = unlock_decks;
+        }
+
+        let completed = trade.proposer_confirmed && trade.recipient_confirmed;
+        if completed {
+            check_trade_side(
+                &mut transaction,
+                trade.proposer_id,
+                &trade.proposer_side,
+                trade.proposer_unlocks_decks,
+            )
+            .await?;
+            check_trade_side(
+                &mut transaction,
+                trade.recipient_id,
+                &trade.recipient_side,
+                trade.recipient_unlocks_decks,
+            )
+            .await?;
+            check_trade_receipt(&mut transaction, trade.proposer_id, &trade.recipient_side).await?;
+            check_trade_receipt(&mut transaction, trade.recipient_id, &trade.proposer_side).await?;
+            transfer_trade_side(
+                &mut transaction,
+                trade.proposer_id,
+                trade.recipient_id,
+                &trade.proposer_side,
+            )
+            .await?;
+            transfer_trade_side(
+                &mut transaction,
+                trade.recipient_id,
+                trade.proposer_id,
+                &trade.recipient_side,
+            )
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE trade_offers
+             SET proposer_confirmed = ?1, recipient_confirmed = ?2,
+                 proposer_unlocks_decks = ?3, recipient_unlocks_decks = ?4,
+                 status = ?5, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?6",
+        )
+        .bind(trade.proposer_confirmed)
+        .bind(trade.recipient_confirmed)
+        .bind(trade.proposer_unlocks_decks)
+        .bind(trade.recipient_unlocks_decks)
+        .bind(if completed {
+            TradeStatus::Completed.as_str()
+        } else {
+            TradeStatus::Pending.as_str()
+        })
+        .bind(trade.id.to_string())
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(TradeUpdate {
+            partner_id: trade.partner_of(user_id),
+            completed,
+        })
+    }
+
+    /// Decline an incoming trade or withdraw one the player proposed.
+    pub async fn decline_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+    ) -> Result<TradeUpdate, UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        let trade = load_open_trade(&mut transaction, user_id, trade_id).await?;
+        let status = if user_id == trade.proposer_id {
+            TradeStatus::Cancelled
+        } else {
+            TradeStatus::Declined
+        };
+        sqlx::query(
+            "UPDATE trade_offers SET status = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        )
+        .bind(status.as_str())
+        .bind(trade.id.to_string())
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(TradeUpdate {
+            partner_id: trade.partner_of(user_id),
+            completed: false,
+        })
+    }
+
+    /// The open trades the player takes part in, newest activity first.
+    pub async fn load_trades(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<TradeOffer>, UserRepositoryError> {
+        let rows: Vec<TradeListingRow> = sqlx::query_as(
+            "SELECT CAST(t.id AS TEXT), CAST(t.proposer_id AS TEXT), p.username, r.username,
+                        CAST(t.proposer_side AS TEXT), t.proposer_confirmed,
+                        t.recipient_confirmed, CAST(t.recipient_side AS TEXT)
+                 FROM trade_offers t
+                 JOIN users p ON p.id = t.proposer_id
+                 JOIN users r ON r.id = t.recipient_id
+                 WHERE (t.proposer_id = ?1 OR t.recipient_id = ?1) AND t.status = ?2
+                 ORDER BY t.updated_at DESC",
         )
         .bind(user_id.to_string())
+        .bind(TradeStatus::Pending.as_str())
         .fetch_all(&self.pool)
         .await?;
-        Ok(cards
-            .into_iter()
-            .filter_map(|(name, quantity, is_foil)| {
-                u8::try_from(quantity).ok().map(|count| CollectedCard {
-                    name,
-                    count,
-                    is_foil,
-                })
-            })
-            .collect())
+        rows.into_iter()
+            .map(
+                |(
+                    id,
+                    proposer_id,
+                    proposer,
+                    recipient,
+                    proposer_side,
+                    proposer_confirmed,
+                    recipient_confirmed,
+                    recipient_side,
+                )| {
+                    let proposer_side: TradeSide = serde_json::from_str(&proposer_side)
+                        .map_err(|_| UserRepositoryError::Serialization)?;
+                    let recipient_side: TradeSide = serde_json::from_str(&recipient_side)
+                        .map_err(|_| UserRepositoryError::Serialization)?;
+                    let incoming = proposer_id != user_id.to_string();
+                    Ok(if incoming {
+                        TradeOffer {
+                            id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+                            partner: proposer,
+                            incoming,
+                            yours: recipient_side,
+                            theirs: proposer_side,
+                            you_confirmed: recipient_confirmed,
+                            partner_confirmed: proposer_confirmed,
+                            status: TradeStatus::Pending,
+                        }
+                    } else {
+                        TradeOffer {
+                            id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+                            partner: recipient,
+                            incoming,
+                            yours: proposer_side,
+                            theirs: recipient_side,
+                            you_confirmed: proposer_confirmed,
+                            partner_confirmed: recipient_confirmed,
+                            status: TradeStatus::Pending,
+                        }
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+async fn load_open_trade(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    trade_id: uuid::Uuid,
+) -> Result<StoredTrade, UserRepositoryError> {
+    let row: Option<TradeRow> = sqlx::query_as(
+        "SELECT CAST(id AS TEXT), CAST(proposer_id AS TEXT), CAST(recipient_id AS TEXT),
+                CAST(proposer_side AS TEXT), CAST(recipient_side AS TEXT),
+                proposer_confirmed, recipient_confirmed,
+                proposer_unlocks_decks, recipient_unlocks_decks
+         FROM trade_offers
+         WHERE id = ?1 AND (proposer_id = ?2 OR recipient_id = ?2) AND status = ?3",
+    )
+    .bind(trade_id.to_string())
+    .bind(user_id.to_string())
+    .bind(TradeStatus::Pending.as_str())
+    .fetch_optional(&mut *connection)
+    .await?;
+    StoredTrade::from_row(row.ok_or(UserRepositoryError::TradeNotFound)?)
+}
+
+/// Check that a player still owns everything they put into a trade. Copies their saved decks
+/// rely on stay locked unless the player chose to trade them away anyway.
+async fn check_trade_side(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    side: &TradeSide,
+    unlock_decks: bool,
+) -> Result<(), UserRepositoryError> {
+    let (username, reward_points): (String, i64) =
+        sqlx::query_as("SELECT username, CAST(reward_points AS BIGINT) FROM users WHERE id = ?1")
+            .bind(user_id.to_string())
+            .fetch_one(&mut *connection)
+            .await?;
+    let locked = if unlock_decks || side.cards.is_empty() {
+        Default::default()
+    } else {
+        load_deck_locks(connection, user_id).await?
+    };
+    let collection = load_user_cards(connection, user_id).await?;
+    check_trade_holdings(
+        &username,
+        reward_points.max(0) as u32,
+        &collection,
+        &locked,
+        side,
+    )
+}
+
+/// The storage-independent half of [`check_trade_side`], shared with the in-memory storage.
+pub(super) fn check_trade_holdings(
+    username: &str,
+    reward_points: u32,
+    collection: &[CollectedCard],
+    locked: &HashMap<(String, bool), u8>,
+    side: &TradeSide,
+) -> Result<(), UserRepositoryError> {
+    if reward_points < side.reward_points {
+        return Err(UserRepositoryError::InvalidTrade(format!(
+            "{username} does not have {} reward points to trade.",
+            side.reward_points
+        )));
+    }
+
+    for card in &side.cards {
+        let quantity = collection
+            .iter()
+            .find(|owned| owned.name == card.name && owned.is_foil == card.is_foil)
+            .map(|owned| owned.count)
+            .unwrap_or_default();
+        let foil = if card.is_foil { "foil " } else { "" };
+        if quantity < card.count {
+            return Err(UserRepositoryError::InvalidTrade(format!(
+                "{username} does not have {}x {foil}{} to trade.",
+                card.count, card.name
+            )));
+        }
+        let locked_copies = locked
+            .get(&(card.name.clone(), card.is_foil))
+            .copied()
+            .unwrap_or_default();
+        if quantity - card.count < locked_copies {
+            return Err(UserRepositoryError::InvalidTrade(format!(
+                "{foil}{} is used in one of {username}'s saved decks.",
+                card.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Refuse a completed trade that would give `user_id` more than a collection can hold.
+async fn check_trade_receipt(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    received: &TradeSide,
+) -> Result<(), UserRepositoryError> {
+    let (username, reward_points): (String, i64) =
+        sqlx::query_as("SELECT username, CAST(reward_points AS BIGINT) FROM users WHERE id = ?1")
+            .bind(user_id.to_string())
+            .fetch_one(&mut *connection)
+            .await?;
+    let collection = load_user_cards(connection, user_id).await?;
+    check_trade_capacity(
+        &username,
+        reward_points.max(0) as u32,
+        &collection,
+        received,
+    )
+}
+
+/// The storage-independent half of [`check_trade_receipt`], shared with the in-memory storage.
+pub(super) fn check_trade_capacity(
+    username: &str,
+    reward_points: u32,
+    collection: &[CollectedCard],
+    received: &TradeSide,
+) -> Result<(), UserRepositoryError> {
+    if reward_points.checked_add(received.reward_points).is_none() {
+        return Err(UserRepositoryError::InvalidTrade(format!(
+            "{username} can't hold {} more reward points.",
+            received.reward_points
+        )));
+    }
+    check_owned_copies(&received.cards, collection).map_err(|error| {
+        UserRepositoryError::InvalidTrade(format!("{username} can't receive this trade. {error}"))
+    })
+}
+
+pub(super) async fn load_user_cards(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+) -> Result<Vec<CollectedCard>, UserRepositoryError> {
+    let cards: Vec<(String, i64, bool)> = sqlx::query_as(
+        "SELECT card_name, CAST(quantity AS BIGINT), is_foil
+         FROM user_cards WHERE user_id = ?1 ORDER BY card_name, is_foil",
+    )
+    .bind(user_id.to_string())
+    .fetch_all(&mut *connection)
+    .await?;
+    Ok(cards
+        .into_iter()
+        .filter(|(_, quantity, _)| *quantity > 0)
+        .map(|(name, quantity, is_foil)| CollectedCard {
+            name,
+            // Rows above the cap predate it; show them as full rather than hiding them.
+            count: u8::try_from(quantity).unwrap_or(u8::MAX),
+            is_foil,
+        })
+        .collect())
+}
+
+/// Copies of each card the user's saved decks rely on. See [`deck_locked_copies`].
+pub(super) async fn load_deck_locks(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+) -> Result<HashMap<(String, bool), u8>, UserRepositoryError> {
+    let decks: Vec<String> =
+        sqlx::query_scalar("SELECT CAST(deck AS TEXT) FROM user_decks WHERE user_id = ?1")
+            .bind(user_id.to_string())
+            .fetch_all(&mut *connection)
+            .await?;
+    let decks = decks
+        .into_iter()
+        .map(|deck| {
+            serde_json::from_str::<DeckList>(&deck).map_err(|_| UserRepositoryError::Serialization)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(deck_locked_copies(&decks))
+}
+
+/// Remove copies the caller has already checked the user owns.
+pub(super) async fn remove_user_cards(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    card: &CardNameWithCount,
+) -> Result<(), UserRepositoryError> {
+    // `quantity` must stay positive, so the last copies delete the row instead.
+    sqlx::query(
+        "DELETE FROM user_cards
+         WHERE user_id = ?1 AND card_name = ?2 AND is_foil = ?3 AND quantity = ?4",
+    )
+    .bind(user_id.to_string())
+    .bind(&card.name)
+    .bind(card.is_foil)
+    .bind(i16::from(card.count))
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query(
+        "UPDATE user_cards SET quantity = quantity - ?4
+         WHERE user_id = ?1 AND card_name = ?2 AND is_foil = ?3 AND quantity > ?4",
+    )
+    .bind(user_id.to_string())
+    .bind(&card.name)
+    .bind(card.is_foil)
+    .bind(i16::from(card.count))
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+pub(super) async fn add_user_cards(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    card: &CardNameWithCount,
+) -> Result<(), UserRepositoryError> {
+    sqlx::query(
+        "INSERT INTO user_cards (user_id, card_name, is_foil, quantity) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (user_id, card_name, is_foil)
+         DO UPDATE SET quantity = user_cards.quantity + EXCLUDED.quantity",
+    )
+    .bind(user_id.to_string())
+    .bind(&card.name)
+    .bind(card.is_foil)
+    .bind(i16::from(card.count))
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+async fn transfer_trade_side(
+    connection: &mut SqliteConnection,
+    from: uuid::Uuid,
+    to: uuid::Uuid,
+    side: &TradeSide,
+) -> Result<(), UserRepositoryError> {
+    for card in &side.cards {
+        remove_user_cards(connection, from, card).await?;
+        add_user_cards(connection, to, card).await?;
+    }
+
+    if side.reward_points > 0 {
+        sqlx::query("UPDATE users SET reward_points = reward_points - ?1 WHERE id = ?2")
+            .bind(i64::from(side.reward_points))
+            .bind(from.to_string())
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query("UPDATE users SET reward_points = reward_points + ?1 WHERE id = ?2")
+            .bind(i64::from(side.reward_points))
+            .bind(to.to_string())
+            .execute(&mut *connection)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Repository, RepositoryError, confirmed_user};
+    use sorcerers::{
+        deck::{CardNameWithCount, DeckList, precon::PreconDeck},
+        trade::TradeSide,
+    };
+
+    fn cards(name: &str, count: u8) -> Vec<CardNameWithCount> {
+        vec![CardNameWithCount {
+            name: name.to_string(),
+            count,
+            is_foil: false,
+        }]
+    }
+
+    fn count_of(collection: &[sorcerers::collection::CollectedCard], name: &str) -> u8 {
+        collection
+            .iter()
+            .filter(|card| card.name == name && !card.is_foil)
+            .map(|card| card.count)
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn trades_need_both_confirmations_and_respect_deck_locks() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let alice = confirmed_user(&repository, "trader_alice").await;
+        let bob = confirmed_user(&repository, "trader_bob").await;
+        let deck = DeckList {
+            name: "Locked".to_string(),
+            sites: cards("Arid Desert", 2),
+            spells: vec![],
+            avatar: "Sorcerer".to_string(),
+        };
+        repository
+            .complete_starter_selection(
+                alice.id,
+                &PreconDeck::BetaFire,
+                &deck,
+                &cards("Arid Desert", 3),
+            )
+            .await
+            .unwrap();
+        repository
+            .award_match_points(uuid::Uuid::new_v4(), bob.id, 10)
+            .await
+            .unwrap();
+
+        let offered = TradeSide {
+            cards: cards("Arid Desert", 2),
+            reward_points: 0,
+        };
+        let requested = TradeSide {
+            cards: vec![],
+            reward_points: 10,
+        };
+        // Two of the three copies are used by a saved deck.
+        assert!(matches!(
+            repository
+                .propose_trade(alice.id, "trader_bob", &offered, &requested, false)
+                .await,
+            Err(RepositoryError::InvalidTrade(_))
+        ));
+        repository
+            .propose_trade(alice.id, "trader_bob", &offered, &requested, true)
+            .await
+            .unwrap();
+
+        let trade = repository.load_trades(bob.id).await.unwrap().remove(0);
+        assert!(trade.incoming);
+        assert!(trade.partner_confirmed && !trade.you_confirmed);
+        assert_eq!(trade.yours.reward_points, 10);
+
+        let update = repository
+            .confirm_trade(bob.id, trade.id, false)
+            .await
+            .unwrap();
+        assert!(update.completed);
+        assert_eq!(update.partner_id, alice.id);
+        assert_eq!(
+            count_of(
+                &repository.load_collection(alice.id).await.unwrap(),
+                "Arid Desert"
+            ),
+            1
+        );
+        assert_eq!(
+            count_of(
+                &repository.load_collection(bob.id).await.unwrap(),
+                "Arid Desert"
+            ),
+            2
+        );
+        assert_eq!(repository.reward_points(alice.id).await.unwrap(), 10);
+        assert_eq!(repository.reward_points(bob.id).await.unwrap(), 0);
+        assert!(repository.load_trades(alice.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn confirming_rechecks_quantities_and_rolls_back() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let carol = confirmed_user(&repository, "trader_carol").await;
+        let dave = confirmed_user(&repository, "trader_dave").await;
+        let deck = DeckList {
+            name: "Starter".to_string(),
+            sites: vec![],
+            spells: vec![],
+            avatar: "Sorcerer".to_string(),
+        };
+        repository
+            .complete_starter_selection(
+                carol.id,
+                &PreconDeck::BetaFire,
+                &deck,
+                &cards("Arid Desert", 2),
+            )
+            .await
+            .unwrap();
+
+        let offered = TradeSide {
+            cards: cards("Arid Desert", 2),
+            reward_points: 0,
+        };
+        let gift = TradeSide::default();
+        repository
+            .propose_trade(carol.id, "trader_dave", &offered, &gift, false)
+            .await
+            .unwrap();
+        let trade = repository.load_trades(dave.id).await.unwrap().remove(0);
+
+        // A counter-offer clears the other player's confirmation.
+        let counter_offer = TradeSide {
+            cards: vec![],
+            reward_points: 5,
+        };
+        repository
+            .counter_trade(dave.id, trade.id, &gift, &offered, false)
+            .await
+            .unwrap();
+        let countered = repository.load_trades(carol.id).await.unwrap().remove(0);
+        assert!(!countered.you_confirmed && countered.partner_confirmed);
+        // Alice now asks for reward points Dave does not have, so confirming moves nothing.
+        repository
+            .counter_trade(carol.id, trade.id, &offered, &counter_offer, false)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.confirm_trade(dave.id, trade.id, false).await,
+            Err(RepositoryError::InvalidTrade(_))
+        ));
+        assert_eq!(
+            count_of(
+                &repository.load_collection(carol.id).await.unwrap(),
+                "Arid Desert"
+            ),
+            2
+        );
+        assert!(!repository.load_trades(dave.id).await.unwrap()[0].you_confirmed);
+
+        repository.decline_trade(dave.id, trade.id).await.unwrap();
+        assert!(matches!(
+            repository.confirm_trade(carol.id, trade.id, false).await,
+            Err(RepositoryError::TradeNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn trades_cannot_overflow_the_receiving_collection() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let erin = confirmed_user(&repository, "trader_erin").await;
+        let frank = confirmed_user(&repository, "trader_frank").await;
+        let deck = DeckList {
+            name: "Starter".to_string(),
+            sites: vec![],
+            spells: vec![],
+            avatar: "Sorcerer".to_string(),
+        };
+        for (user_id, copies) in [(erin.id, 2), (frank.id, u8::MAX)] {
+            repository
+                .complete_starter_selection(
+                    user_id,
+                    &PreconDeck::BetaFire,
+                    &deck,
+                    &cards("Arid Desert", copies),
+                )
+                .await
+                .unwrap();
+        }
+
+        let offered = TradeSide {
+            cards: cards("Arid Desert", 1),
+            reward_points: 0,
+        };
+        repository
+            .propose_trade(
+                erin.id,
+                "trader_frank",
+                &offered,
+                &TradeSide::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        let trade = repository.load_trades(frank.id).await.unwrap().remove(0);
+        assert!(matches!(
+            repository.confirm_trade(frank.id, trade.id, false).await,
+            Err(RepositoryError::InvalidTrade(_))
+        ));
+        assert_eq!(
+            count_of(
+                &repository.load_collection(erin.id).await.unwrap(),
+                "Arid Desert"
+            ),
+            2
+        );
+        assert_eq!(
+            count_of(
+                &repository.load_collection(frank.id).await.unwrap(),
+                "Arid Desert"
+            ),
+            u8::MAX
+        );
     }
 }
@@ -0,0 +1,498 @@
+//! Flows every [`Storage`] has to agree on. Each flow is written once, generic over the storage,
+//! and run against both the SQLite repository and [`MemoryStorage`], so the in-memory storage
+//! the server tests rely on cannot drift from the real rules.
+use sorcerers::{
+    booster::BoosterPack,
+    collection::CollectedCard,
+    deck::{CardNameWithCount, DeckList, precon::PreconDeck},
+    moderation::ReportReason,
+    sealed::SealedPool,
+    trade::TradeSide,
+};
+
+use super::{MemoryStorage, RateLimit, Repository, RepositoryError, Storage, User, confirmed_user};
+
+const PASSWORD: &str = "very-secret-password";
+
+fn arid_deserts(count: u8) -> Vec<CardNameWithCount> {
+    vec![CardNameWithCount {
+        name: "Arid Desert".to_string(),
+        count,
+        is_foil: false,
+    }]
+}
+
+fn arid_desert_count(collection: &[CollectedCard]) -> u8 {
+    collection
+        .iter()
+        .filter(|card| card.name == "Arid Desert" && !card.is_foil)
+        .map(|card| card.count)
+        .sum()
+}
+
+/// Give `user` a starter collection of `copies` Arid Deserts and a saved deck of `in_deck` of
+/// them.
+async fn starter(storage: &impl Storage, user: &User, copies: u8, in_deck: u8) {
+    let deck = DeckList {
+        name: "Starter".to_string(),
+        sites: if in_deck == 0 {
+            vec![]
+        } else {
+            arid_deserts(in_deck)
+        },
+        spells: vec![],
+        avatar: "Sorcerer".to_string(),
+    };
+    storage
+        .complete_starter_selection(user.id, &PreconDeck::BetaFire, &deck, &arid_deserts(copies))
+        .await
+        .unwrap();
+}
+
+async fn accounts(storage: impl Storage) {
+    let pending = storage
+        .register("conform_ann", "ann@example.com", PASSWORD)
+        .await
+        .unwrap();
+    assert!(matches!(
+        storage
+            .register("conform_ann", "other@example.com", PASSWORD)
+            .await,
+        Err(RepositoryError::UsernameTaken)
+    ));
+    assert!(matches!(
+        storage
+            .register("conform_other", "ann@example.com", PASSWORD)
+            .await,
+        Err(RepositoryError::EmailTaken)
+    ));
+    assert!(matches!(
+        storage.verify_login("ann@example.com", PASSWORD).await,
+        Err(RepositoryError::EmailConfirmationRequired(_))
+    ));
+    assert!(matches!(
+        storage.confirm_email(&pending.email, "not-the-code").await,
+        Err(RepositoryError::InvalidConfirmationCode)
+    ));
+    let ann = storage
+        .confirm_email(&pending.email, &pending.code)
+        .await
+        .unwrap();
+    assert_eq!(ann.username, "conform_ann");
+
+    assert!(matches!(
+        storage
+            .verify_login("ann@example.com", "wrong-password")
+            .await,
+        Err(RepositoryError::InvalidCredentials)
+    ));
+    assert!(matches!(
+        storage
+            .change_password(ann.id, "wrong-password", "changed-password")
+            .await,
+        Err(RepositoryError::IncorrectPassword)
+    ));
+    assert_eq!(
+        storage
+            .change_password(ann.id, PASSWORD, "changed-password")
+            .await
+            .unwrap(),
+        "ann@example.com"
+    );
+    assert_eq!(
+        storage
+            .verify_login("ann@example.com", "changed-password")
+            .await
+            .unwrap()
+            .id,
+        ann.id
+    );
+
+    assert!(
+        storage
+            .request_password_reset("nobody@example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let reset = storage
+        .request_password_reset("ann@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    storage
+        .reset_password(&reset.email, &reset.code, "reset-password")
+        .await
+        .unwrap();
+    storage
+        .change_username(ann.id, "conform_anna")
+        .await
+        .unwrap();
+    assert_eq!(
+        storage
+            .verify_login("ann@example.com", "reset-password")
+            .await
+            .unwrap()
+            .username,
+        "conform_anna"
+    );
+
+    assert!(matches!(
+        storage.delete_account(ann.id, "wrong-password").await,
+        Err(RepositoryError::IncorrectPassword)
+    ));
+    storage
+        .delete_account(ann.id, "reset-password")
+        .await
+        .unwrap();
+    assert!(matches!(
+        storage
+            .verify_login("ann@example.com", "reset-password")
+            .await,
+        Err(RepositoryError::InvalidCredentials)
+    ));
+}
+
+async fn starter_decks_and_crafting(storage: impl Storage) {
+    let carl = confirmed_user(&storage, "conform_carl").await;
+    assert!(
+        storage
+            .selected_starter_deck(carl.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    starter(&storage, &carl, 12, 2).await;
+    assert!(matches!(
+        storage.selected_starter_deck(carl.id).await.unwrap(),
+        Some(PreconDeck::BetaFire)
+    ));
+    assert_eq!(storage.load_decks(carl.id).await.unwrap().len(), 1);
+    assert!(matches!(
+        storage
+            .complete_starter_selection(
+                carl.id,
+                &PreconDeck::BetaFire,
+                &storage.load_decks(carl.id).await.unwrap()[0],
+                &[],
+            )
+            .await,
+        Err(RepositoryError::StarterDeckAlreadySelected)
+    ));
+
+    assert_eq!(storage.crafting_dust(carl.id).await.unwrap(), 0);
+    assert!(matches!(
+        storage.craft_cards(carl.id, &arid_deserts(1)).await,
+        Err(RepositoryError::InsufficientDust)
+    ));
+    // Eight copies beyond the ordinary copy limit of four, at five dust each.
+    assert_eq!(storage.dust_extra_copies(carl.id).await.unwrap(), 40);
+    assert_eq!(
+        arid_desert_count(&storage.load_collection(carl.id).await.unwrap()),
+        4
+    );
+    assert_eq!(
+        storage
+            .craft_cards(carl.id, &arid_deserts(1))
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        arid_desert_count(&storage.load_collection(carl.id).await.unwrap()),
+        5
+    );
+}
+
+async fn trades(storage: impl Storage) {
+    let alice = confirmed_user(&storage, "trader_alice").await;
+    let bob = confirmed_user(&storage, "trader_bob").await;
+    starter(&storage, &alice, 3, 2).await;
+    storage
+        .award_match_points(uuid::Uuid::new_v4(), bob.id, 10)
+        .await
+        .unwrap();
+
+    let offered = TradeSide {
+        cards: arid_deserts(2),
+        reward_points: 0,
+    };
+    let requested = TradeSide {
+        cards: vec![],
+        reward_points: 10,
+    };
+    // Two of the three copies are used by a saved deck.
+    assert!(matches!(
+        storage
+            .propose_trade(alice.id, "trader_bob", &offered, &requested, false)
+            .await,
+        Err(RepositoryError::InvalidTrade(_))
+    ));
+    assert!(matches!(
+        storage
+            .propose_trade(alice.id, "nobody", &offered, &requested, true)
+            .await,
+        Err(RepositoryError::UnknownTradePartner)
+    ));
+    storage
+        .propose_trade(alice.id, "trader_bob", &offered, &requested, true)
+        .await
+        .unwrap();
+
+    let trade = storage.load_trades(bob.id).await.unwrap().remove(0);
+    assert!(trade.incoming && trade.partner_confirmed && !trade.you_confirmed);
+    let update = storage
+        .confirm_trade(bob.id, trade.id, false)
+        .await
+        .unwrap();
+    assert!(update.completed);
+    assert_eq!(update.partner_id, alice.id);
+
+    assert_eq!(storage.reward_points(alice.id).await.unwrap(), 10);
+    assert_eq!(storage.reward_points(bob.id).await.unwrap(), 0);
+    assert_eq!(
+        arid_desert_count(&storage.load_collection(alice.id).await.unwrap()),
+        1
+    );
+    assert_eq!(
+        arid_desert_count(&storage.load_collection(bob.id).await.unwrap()),
+        2
+    );
+    assert!(storage.load_trades(alice.id).await.unwrap().is_empty());
+    assert!(matches!(
+        storage.confirm_trade(bob.id, trade.id, false).await,
+        Err(RepositoryError::TradeNotFound)
+    ));
+}
+
+async fn trades_cannot_overflow_the_receiving_side(storage: impl Storage) {
+    let erin = confirmed_user(&storage, "trader_erin").await;
+    let frank = confirmed_user(&storage, "trader_frank").await;
+    starter(&storage, &erin, 2, 0).await;
+    starter(&storage, &frank, u8::MAX, 0).await;
+
+    let offered = TradeSide {
+        cards: arid_deserts(1),
+        reward_points: 0,
+    };
+    storage
+        .propose_trade(
+            erin.id,
+            "trader_frank",
+            &offered,
+            &TradeSide::default(),
+            false,
+        )
+        .await
+        .unwrap();
+    let trade = storage.load_trades(frank.id).await.unwrap().remove(0);
+    assert!(matches!(
+        storage.confirm_trade(frank.id, trade.id, false).await,
+        Err(RepositoryError::InvalidTrade(_))
+    ));
+
+    assert_eq!(
+        arid_desert_count(&storage.load_collection(erin.id).await.unwrap()),
+        2
+    );
+    assert_eq!(
+        arid_desert_count(&storage.load_collection(frank.id).await.unwrap()),
+        u8::MAX
+    );
+}
+
+async fn rewards_and_boosters(storage: impl Storage) {
+    let dana = confirmed_user(&storage, "conform_dana").await;
+    let game_id = uuid::Uuid::new_v4();
+    let reward = storage
+        .award_match_points(game_id, dana.id, 10)
+        .await
+        .unwrap();
+    assert_eq!((reward.points_earned, reward.reward_points), (10, 10));
+    let again = storage
+        .award_match_points(game_id, dana.id, 10)
+        .await
+        .unwrap();
+    assert_eq!((again.points_earned, again.reward_points), (0, 10));
+
+    assert!(matches!(
+        storage
+            .redeem_beta_booster(dana.id, BoosterPack::beta(), 20)
+            .await,
+        Err(RepositoryError::InsufficientRewardPoints)
+    ));
+    let (points, pack) = storage
+        .redeem_beta_booster(dana.id, BoosterPack::beta(), 10)
+        .await
+        .unwrap();
+    assert_eq!(points, 0);
+    assert_eq!(
+        storage
+            .load_unopened_booster_packs(dana.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(
+        storage
+            .open_booster_pack(dana.id, pack.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        storage
+            .open_booster_pack(dana.id, pack.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(!storage.load_collection(dana.id).await.unwrap().is_empty());
+
+    let week_start = chrono::NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+    let packs = [BoosterPack::beta()];
+    assert!(
+        storage
+            .claim_weekly_boosters(dana.id, week_start, &packs)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !storage
+            .claim_weekly_boosters(dana.id, week_start, &packs)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        storage
+            .load_unopened_booster_packs(dana.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+async fn sealed_pools_and_achievements(storage: impl Storage) {
+    let gail = confirmed_user(&storage, "conform_gail").await;
+    assert!(storage.load_sealed_pool(gail.id).await.unwrap().is_none());
+    let pool = SealedPool::open();
+    storage.start_sealed_event(gail.id, &pool).await.unwrap();
+    assert_eq!(
+        storage.load_sealed_pool(gail.id).await.unwrap().unwrap().id,
+        pool.id
+    );
+
+    assert_eq!(
+        storage
+            .unlock_achievements(gail.id, uuid::Uuid::new_v4(), &["first-victory"])
+            .await
+            .unwrap(),
+        vec!["first-victory"]
+    );
+    assert!(
+        storage
+            .unlock_achievements(gail.id, uuid::Uuid::new_v4(), &["first-victory"])
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+async fn rate_limits(storage: impl Storage) {
+    let limit = RateLimit::LOGIN_PER_ACCOUNT;
+    let now = chrono::Utc::now();
+    for _ in 0..limit.max_attempts {
+        storage.record_attempt(limit, "conform", now).await.unwrap();
+    }
+    assert!(matches!(
+        storage.record_attempt(limit, "conform", now).await,
+        Err(RepositoryError::RateLimited { .. })
+    ));
+    // Other keys and other limits count separately.
+    storage.record_attempt(limit, "other", now).await.unwrap();
+    storage
+        .record_attempt(RateLimit::LOGIN_PER_ADDRESS, "conform", now)
+        .await
+        .unwrap();
+
+    storage.clear_attempts(limit, "conform").await.unwrap();
+    storage.record_attempt(limit, "conform", now).await.unwrap();
+}
+
+async fn moderation_and_live_games(storage: impl Storage) {
+    let hana = confirmed_user(&storage, "conform_hana").await;
+    let ivan = confirmed_user(&storage, "conform_ivan").await;
+    storage.check_ban(hana.id).await.unwrap();
+
+    storage
+        .block_username(hana.id, "conform_ivan")
+        .await
+        .unwrap();
+    let blocked = storage.blocked_players(hana.id).await.unwrap();
+    assert_eq!(blocked.len(), 1);
+    assert_eq!(blocked[0].id, ivan.id);
+    assert!(matches!(
+        storage.block_username(hana.id, "nobody").await,
+        Err(RepositoryError::UnknownPlayer)
+    ));
+    storage
+        .unblock_username(hana.id, "conform_ivan")
+        .await
+        .unwrap();
+    assert!(storage.blocked_players(hana.id).await.unwrap().is_empty());
+
+    let game_id = uuid::Uuid::new_v4();
+    storage
+        .report_player(hana.id, ivan.id, game_id, ReportReason::Stalling, "")
+        .await
+        .unwrap();
+    assert!(matches!(
+        storage
+            .report_player(hana.id, ivan.id, game_id, ReportReason::Stalling, "")
+            .await,
+        Err(RepositoryError::InvalidModeration(_))
+    ));
+    assert!(matches!(
+        storage
+            .report_player(
+                hana.id,
+                hana.id,
+                uuid::Uuid::new_v4(),
+                ReportReason::Other,
+                ""
+            )
+            .await,
+        Err(RepositoryError::InvalidModeration(_))
+    ));
+
+    storage
+        .start_live_game(game_id, ["conform_hana", "conform_ivan"])
+        .await
+        .unwrap();
+    storage.finish_live_game(game_id).await.unwrap();
+    assert!(storage.drain_request().await.unwrap().is_none());
+}
+
+/// Run every flow, each against fresh storage from `new_storage`.
+async fn conforms<S: Storage>(new_storage: impl AsyncFn() -> S) {
+    accounts(new_storage().await).await;
+    starter_decks_and_crafting(new_storage().await).await;
+    trades(new_storage().await).await;
+    trades_cannot_overflow_the_receiving_side(new_storage().await).await;
+    rewards_and_boosters(new_storage().await).await;
+    sealed_pools_and_achievements(new_storage().await).await;
+    rate_limits(new_storage().await).await;
+    moderation_and_live_games(new_storage().await).await;
+}
+
+#[tokio::test]
+async fn the_sqlite_repository_conforms() {
+    conforms(async || Repository::connect("sqlite::memory:").await.unwrap()).await;
+}
+
+#[tokio::test]
+async fn the_memory_storage_conforms() {
+    conforms(async || MemoryStorage::new()).await;
+}
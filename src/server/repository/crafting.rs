@@ -0,0 +1,198 @@
+use sorcerers::{
+    crafting::{
+        card_rarity, check_owned_copies, craft_cost, crafting_cost, dust_value, excess_copies,
+    },
+    deck::CardNameWithCount,
+};
+use sqlx::SqliteConnection;
+
+use super::{
+    Repository, RepositoryError as UserRepositoryError,
+    cards::{add_user_cards, load_deck_locks, load_user_cards, remove_user_cards},
+};
+
+impl Repository {
+    pub async fn crafting_dust(&self, user_id: uuid::Uuid) -> Result<u32, UserRepositoryError> {
+        let dust: Option<i64> = sqlx::query_scalar(
+            "SELECT CAST(dust AS BIGINT) FROM crafting_balances WHERE user_id = ?1",
+        )
+        .bind(user_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(dust.unwrap_or_default().max(0) as u32)
+    }
+
+    /// Disenchant every copy beyond the deck copy limits that no saved deck relies on, and return
+    /// the new dust balance.
+    pub async fn dust_extra_copies(&self, user_id: uuid::Uuid) -> Result<u32, UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        let collection = load_user_cards(&mut transaction, user_id).await?;
+        let locked = load_deck_locks(&mut transaction, user_id).await?;
+        let excess = excess_copies(&collection, &locked);
+        if excess.is_empty() {
+            return Err(UserRepositoryError::InvalidCrafting(
+                "You have no extra copies to dust.".to_string(),
+            ));
+        }
+
+        let mut gained = 0;
+        for card in &excess {
+            let rarity = card_rarity(&card.name).ok_or(UserRepositoryError::Serialization)?;
+            let value = dust_value(&rarity, card.is_foil) * u32::from(card.count);
+            remove_user_cards(&mut transaction, user_id, card).await?;
+            log_crafting(&mut transaction, user_id, "dust", card, i64::from(value)).await?;
+            gained += value;
+        }
+        let dust = change_dust(&mut transaction, user_id, i64::from(gained)).await?;
+        transaction.commit().await?;
+        Ok(dust)
+    }
+
+    /// Craft `cards` into the collection, and return the new dust balance.
+    pub async fn craft_cards(
+        &self,
+        user_id: uuid::Uuid,
+        cards: &[CardNameWithCount],
+    ) -> Result<u32, UserRepositoryError> {
+        if cards.is_empty() || cards.iter().any(|card| card.count == 0) {
+            return Err(UserRepositoryError::InvalidCrafting(
+                "Choose the cards to craft.".to_string(),
+            ));
+        }
+        let cost = crafting_cost(cards).map_err(UserRepositoryError::InvalidCrafting)?;
+
+        let mut transaction = self.pool.begin().await?;
+        let collection = load_user_cards(&mut transaction, user_id).await?;
+        check_owned_copies(cards, &collection).map_err(UserRepositoryError::InvalidCrafting)?;
+        let spent: Option<i64> = sqlx::query_scalar(
+            "UPDATE crafting_balances SET dust = dust - ?1
+             WHERE user_id = ?2 AND dust >= ?1
+             RETURNING CAST(dust AS BIGINT)",
+        )
+        .bind(i64::from(cost))
+        .bind(user_id.to_string())
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(dust) = spent else {
+            transaction.rollback().await?;
+            return Err(UserRepositoryError::InsufficientDust);
+        };
+        for card in cards {
+            let rarity = card_rarity(&card.name).ok_or(UserRepositoryError::Serialization)?;
+            let cost = craft_cost(&rarity, card.is_foil) * u32::from(card.count);
+            add_user_cards(&mut transaction, user_id, card).await?;
+            log_crafting(&mut transaction, user_id, "craft", card, -i64::from(cost)).await?;
+        }
+        transaction.commit().await?;
+        Ok(dust.max(0) as u32)
+    }
+}
+
+async fn change_dust(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    change: i64,
+) -> Result<u32, UserRepositoryError> {
+    let dust: i64 = sqlx::query_scalar(
+        "INSERT INTO crafting_balances (user_id, dust) VALUES (?1, ?2)
+         ON CONFLICT (user_id) DO UPDATE SET dust = crafting_balances.dust + EXCLUDED.dust
+         RETURNING CAST(dust AS BIGINT)",
+    )
+    .bind(user_id.to_string())
+    .bind(change)
+    .fetch_one(&mut *connection)
+    .await?;
+    Ok(dust.max(0) as u32)
+}
+
+/// Record a dusted or crafted card in the audit log.
+async fn log_crafting(
+    connection: &mut SqliteConnection,
+    user_id: uuid::Uuid,
+    action: &str,
+    card: &CardNameWithCount,
+    dust_change: i64,
+) -> Result<(), UserRepositoryError> {
+    sqlx::query(
+        "INSERT INTO crafting_log (user_id, action, card_name, is_foil, quantity, dust_change)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )
+    .bind(user_id.to_string())
+    .bind(action)
+    .bind(&card.name)
+    .bind(card.is_foil)
+    .bind(i16::from(card.count))
+    .bind(dust_change)
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Repository, RepositoryError, confirmed_user};
+    use sorcerers::{
+        crafting::{card_rarity, craft_cost, dust_value},
+        deck::{CardNameWithCount, DeckList, precon::PreconDeck},
+    };
+
+    #[tokio::test]
+    async fn dusting_extras_funds_crafting_and_is_logged() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let user = confirmed_user(&repository, "crafter").await;
+        let deck = DeckList {
+            name: "Vipers".to_string(),
+            sites: vec![],
+            spells: vec![],
+            avatar: "Sorcerer".to_string(),
+        };
+        let vipers = |count| CardNameWithCount {
+            name: "Pit Vipers".to_string(),
+            count,
+            is_foil: false,
+        };
+        repository
+            .complete_starter_selection(user.id, &PreconDeck::BetaFire, &deck, &[vipers(12)])
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.craft_cards(user.id, &[vipers(1)]).await,
+            Err(RepositoryError::InsufficientDust)
+        ));
+
+        let rarity = card_rarity("Pit Vipers").unwrap();
+        let dust = repository.dust_extra_copies(user.id).await.unwrap();
+        assert_eq!(dust, dust_value(&rarity, false) * 8);
+        assert!(matches!(
+            repository.dust_extra_copies(user.id).await,
+            Err(RepositoryError::InvalidCrafting(_))
+        ));
+
+        let dust = repository.craft_cards(user.id, &[vipers(1)]).await.unwrap();
+        assert_eq!(
+            dust,
+            dust_value(&rarity, false) * 8 - craft_cost(&rarity, false)
+        );
+        assert_eq!(repository.crafting_dust(user.id).await.unwrap(), dust);
+        let collection = repository.load_collection(user.id).await.unwrap();
+        assert_eq!(collection[0].count, 5);
+
+        sqlx::query("UPDATE user_cards SET quantity = 255 WHERE user_id = ?1")
+            .bind(user.id.to_string())
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.craft_cards(user.id, &[vipers(1)]).await,
+            Err(RepositoryError::InvalidCrafting(_))
+        ));
+        assert_eq!(repository.crafting_dust(user.id).await.unwrap(), dust);
+
+        let logged: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM crafting_log WHERE user_id = ?1")
+            .bind(user.id.to_string())
+            .fetch_one(&repository.pool)
+            .await
+            .unwrap();
+        assert_eq!(logged, 2);
+    }
+}
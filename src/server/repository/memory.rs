@@ -0,0 +1,1181 @@
+//! A [`Storage`] kept entirely in memory, for testing server flows without a database.
+//!
+//! It follows the same rules as the SQLite repository and returns the same errors, but keeps
+//! passwords and confirmation codes in plain text, so it must never back a real server.
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sorcerers::{
+    booster::{BoosterPack, UnopenedBoosterPack},
+    collection::CollectedCard,
+    crafting::{card_rarity, check_owned_copies, crafting_cost, dust_value, excess_copies},
+    deck::{CardNameWithCount, DeckList, precon::PreconDeck},
+    moderation::{ReportReason, validate_report_details},
+    quest::{QuestProgress, QuestStats, all_active_quests},
+    sealed::SealedPool,
+    trade::{TradeOffer, TradeSide, TradeStatus, deck_locked_copies, validate_terms},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::{
+    DrainRequest, RateLimit, RepositoryError, Storage, TIMESTAMP_FORMAT, TradeUpdate, User,
+    booster_packs::MatchReward,
+    cards::{StoredTrade, check_trade_capacity, check_trade_holdings},
+    quests::QuestReward,
+    rate_limits::{LOCKOUT_MEMORY_SECONDS, next_lockout},
+    sealed::SEALED_POOL_HOURS,
+    users::{
+        CONFIRMATION_CODE_LIFETIME_MINUTES, MAX_CONFIRMATION_ATTEMPTS, MIN_PASSWORD_LENGTH,
+        PendingEmailConfirmation, new_pending_email_confirmation, validate_email,
+        validate_username,
+    },
+};
+
+struct StoredUser {
+    id: uuid::Uuid,
+    username: String,
+    email: String,
+    password: String,
+    /// The current confirmation code and when it expires, until the email is confirmed.
+    confirmation: Option<(String, DateTime<Utc>)>,
+    confirmation_attempts: i16,
+    email_confirmed: bool,
+    /// The outstanding password reset code and when it expires.
+    password_reset: Option<(String, DateTime<Utc>)>,
+    password_reset_attempts: i16,
+    reward_points: u32,
+    starter_deck: Option<PreconDeck>,
+    last_booster_week: Option<NaiveDate>,
+    /// The ban reason and when the ban lifts, if there is one.
+    ban: Option<(Option<String>, Option<DateTime<Utc>>)>,
+}
+
+#[derive(Default)]
+struct Data {
+    users: Vec<StoredUser>,
+    /// Each user's collection, keyed by card name and foiling.
+    cards: HashMap<uuid::Uuid, BTreeMap<(String, bool), u8>>,
+    decks: HashMap<uuid::Uuid, Vec<DeckList>>,
+    /// Open trades, least recently updated first.
+    trades: Vec<StoredTrade>,
+    rewarded_games: HashSet<(uuid::Uuid, uuid::Uuid)>,
+    booster_packs: Vec<(uuid::Uuid, UnopenedBoosterPack)>,
+    /// Each user's sealed pool and when it was opened.
+    sealed_pools: HashMap<uuid::Uuid, (SealedPool, DateTime<Utc>)>,
+    dust: HashMap<uuid::Uuid, u32>,
+    quest_games: HashSet<(uuid::Uuid, uuid::Uuid)>,
+    /// Progress and completion, keyed by user, quest and the first day of the quest's period.
+    quests: HashMap<(uuid::Uuid, &'static str, NaiveDate), (u32, bool)>,
+    achievements: HashSet<(uuid::Uuid, String)>,
+    /// Keyed by limit name and key.
+    rate_limits: HashMap<(&'static str, String), RateLimitState>,
+    live_games: HashMap<uuid::Uuid, [String; 2]>,
+    drain: Option<DrainRequest>,
+    /// Pairs of blocking and blocked user.
+    blocks: HashSet<(uuid::Uuid, uuid::Uuid)>,
+    /// Pairs of reporter and game.
+    reports: HashSet<(uuid::Uuid, uuid::Uuid)>,
+}
+
+#[derive(Default)]
+struct RateLimitState {
+    /// Unix times of the attempts still inside the window.
+    attempts: Vec<i64>,
+    /// The number of lockouts in a row and when the last one ends.
+    lockout: Option<(u32, i64)>,
+}
+
+impl Data {
+    fn user(&self, user_id: uuid::Uuid) -> Result<&StoredUser, RepositoryError> {
+        self.users
+            .iter()
+            .find(|user| user.id == user_id)
+            .ok_or(RepositoryError::Database(sqlx::Error::RowNotFound))
+    }
+
+    fn user_mut(&mut self, user_id: uuid::Uuid) -> Result<&mut StoredUser, RepositoryError> {
+        self.users
+            .iter_mut()
+            .find(|user| user.id == user_id)
+            .ok_or(RepositoryError::Database(sqlx::Error::RowNotFound))
+    }
+
+    fn check_password(&self, user_id: uuid::Uuid, password: &str) -> Result<(), RepositoryError> {
+        if self.user(user_id)?.password != password {
+            return Err(RepositoryError::IncorrectPassword);
+        }
+        Ok(())
+    }
+
+    fn collection(&self, user_id: uuid::Uuid) -> Vec<CollectedCard> {
+        self.cards
+            .get(&user_id)
+            .into_iter()
+            .flatten()
+            .map(|((name, is_foil), count)| CollectedCard {
+                name: name.clone(),
+                count: *count,
+                is_foil: *is_foil,
+            })
+            .collect()
+    }
+
+    fn add_cards(&mut self, user_id: uuid::Uuid, card: &CardNameWithCount) {
+        let count = self
+            .cards
+            .entry(user_id)
+            .or_default()
+            .entry((card.name.clone(), card.is_foil))
+            .or_default();
+        *count = count.saturating_add(card.count);
+    }
+
+    /// Remove copies the caller has already checked the user owns.
+    fn remove_cards(&mut self, user_id: uuid::Uuid, card: &CardNameWithCount) {
+        let Some(collection) = self.cards.get_mut(&user_id) else {
+            return;
+        };
+        let key = (card.name.clone(), card.is_foil);
+        if let Some(count) = collection.get_mut(&key) {
+            *count = count.saturating_sub(card.count);
+            if *count == 0 {
+                collection.remove(&key);
+            }
+        }
+    }
+
+    fn check_trade_side(
+        &self,
+        user_id: uuid::Uuid,
+        side: &TradeSide,
+        unlock_decks: bool,
+    ) -> Result<(), RepositoryError> {
+        let user = self.user(user_id)?;
+        let locked = if unlock_decks {
+            Default::default()
+        } else {
+            deck_locked_copies(self.decks.get(&user_id).map_or(&[], Vec::as_slice))
+        };
+        check_trade_holdings(
+            &user.username,
+            user.reward_points,
+            &self.collection(user_id),
+            &locked,
+            side,
+        )
+    }
+
+    fn check_trade_receipt(
+        &self,
+        user_id: uuid::Uuid,
+        received: &TradeSide,
+    ) -> Result<(), RepositoryError> {
+        let user = self.user(user_id)?;
+        check_trade_capacity(
+            &user.username,
+            user.reward_points,
+            &self.collection(user_id),
+            received,
+        )
+    }
+
+    fn transfer_trade_side(
+        &mut self,
+        from: uuid::Uuid,
+        to: uuid::Uuid,
+        side: &TradeSide,
+    ) -> Result<(), RepositoryError> {
+        for card in &side.cards {
+            self.remove_cards(from, card);
+            self.add_cards(to, card);
+        }
+        self.user_mut(from)?.reward_points -= side.reward_points;
+        let recipient = self.user_mut(to)?;
+        recipient.reward_points = recipient
+            .reward_points
+            .checked_add(side.reward_points)
+            .ok_or(RepositoryError::InvalidTrade(format!(
+                "{} can't hold {} more reward points.",
+                recipient.username, side.reward_points
+            )))?;
+        Ok(())
+    }
+
+    /// The position of an open trade the user takes part in.
+    fn open_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+    ) -> Result<usize, RepositoryError> {
+        self.trades
+            .iter()
+            .position(|trade| {
+                trade.id == trade_id
+                    && (trade.proposer_id == user_id || trade.recipient_id == user_id)
+            })
+            .ok_or(RepositoryError::TradeNotFound)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    data: Arc<Mutex<Data>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> MutexGuard<'_, Data> {
+        self.data.lock().expect("memory storage lock poisoned")
+    }
+
+    /// Ban a user, as `sorcerers-admin ban` does.
+    pub fn ban(&self, user_id: uuid::Uuid, reason: Option<&str>, until: Option<DateTime<Utc>>) {
+        if let Ok(user) = self.data().user_mut(user_id) {
+            user.ban = Some((reason.map(str::to_string), until));
+        }
+    }
+
+    /// Ask for a drain, or call it off with `None`, as `sorcerers-admin drain` does.
+    pub fn set_drain(&self, drain: Option<DrainRequest>) {
+        self.data().drain = drain;
+    }
+}
+
+impl Storage for MemoryStorage {
+    async fn register(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> Result<PendingEmailConfirmation, RepositoryError> {
+        validate_username(username)?;
+        validate_email(email)?;
+        if password.len() < MIN_PASSWORD_LENGTH {
+            return Err(RepositoryError::InvalidPassword);
+        }
+
+        let pending = new_pending_email_confirmation(email)?;
+        let mut data = self.data();
+        if data.users.iter().any(|user| user.email == pending.email) {
+            return Err(RepositoryError::EmailTaken);
+        }
+        if data.users.iter().any(|user| user.username == username) {
+            return Err(RepositoryError::UsernameTaken);
+        }
+        data.users.push(StoredUser {
+            id: uuid::Uuid::new_v4(),
+            username: username.to_string(),
+            email: pending.email.clone(),
+            password: password.to_string(),
+            confirmation: Some((
+                pending.code.clone(),
+                Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES),
+            )),
+            confirmation_attempts: 0,
+            email_confirmed: false,
+            password_reset: None,
+            password_reset_attempts: 0,
+            reward_points: 0,
+            starter_deck: None,
+            last_booster_week: None,
+            ban: None,
+        });
+        Ok(pending)
+    }
+
+    async fn verify_login(&self, email: &str, password: &str) -> Result<User, RepositoryError> {
+        let data = self.data();
+        let user = data
+            .users
+            .iter()
+            .find(|user| user.email == email && user.password == password)
+            .ok_or(RepositoryError::InvalidCredentials)?;
+        if !user.email_confirmed {
+            return Err(RepositoryError::EmailConfirmationRequired(
+                email.to_string(),
+            ));
+        }
+        Ok(User {
+            id: user.id,
+            username: user.username.clone(),
+        })
+    }
+
+    async fn resend_email_confirmation(
+        &self,
+        email: &str,
+    ) -> Result<PendingEmailConfirmation, RepositoryError> {
+        validate_email(email)?;
+        let pending = new_pending_email_confirmation(email)?;
+        let mut data = self.data();
+        let user = data
+            .users
+            .iter_mut()
+            .find(|user| user.email == pending.email && !user.email_confirmed)
+            .ok_or(RepositoryError::EmailAlreadyConfirmed)?;
+        user.confirmation = Some((
+            pending.code.clone(),
+            Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES),
+        ));
+        user.confirmation_attempts = 0;
+        Ok(pending)
+    }
+
+    async fn confirm_email(&self, email: &str, code: &str) -> Result<User, RepositoryError> {
+        validate_email(email)?;
+        let mut data = self.data();
+        let user = data
+            .users
+            .iter_mut()
+            .find(|user| user.email == email.trim() && !user.email_confirmed)
+            .ok_or(RepositoryError::InvalidConfirmationCode)?;
+        if user.confirmation_attempts >= MAX_CONFIRMATION_ATTEMPTS {
+            return Err(RepositoryError::ConfirmationAttemptsExceeded);
+        }
+        let is_valid = user
+            .confirmation
+            .as_ref()
+            .is_some_and(|(expected, expires_at)| expected == code && *expires_at > Utc::now());
+        if !is_valid {
+            user.confirmation_attempts += 1;
+            return Err(RepositoryError::InvalidConfirmationCode);
+        }
+        user.email_confirmed = true;
+        user.confirmation = None;
+        user.confirmation_attempts = 0;
+        Ok(User {
+            id: user.id,
+            username: user.username.clone(),
+        })
+    }
+
+    async fn request_password_reset(
+        &self,
+        email: &str,
+    ) -> Result<Option<PendingEmailConfirmation>, RepositoryError> {
+        let pending = new_pending_email_confirmation(email)?;
+        let mut data = self.data();
+        let Some(user) = data
+            .users
+            .iter_mut()
+            .find(|user| user.email == pending.email)
+        else {
+            return Ok(None);
+        };
+        user.password_reset = Some((
+            pending.code.clone(),
+            Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES),
+        ));
+        user.password_reset_attempts = 0;
+        Ok(Some(pending))
+    }
+
+    async fn reset_password(
+        &self,
+        email: &str,
+        code: &str,
+        new_password: &str,
+    ) -> Result<User, RepositoryError> {
+        validate_email(email)?;
+        if new_password.len() < MIN_PASSWORD_LENGTH {
+            return Err(RepositoryError::InvalidPassword);
+        }
+        let email = email.trim().to_lowercase();
+        let mut data = self.data();
+        let user = data
+            .users
+            .iter_mut()
+            .find(|user| user.email == email)
+            .ok_or(RepositoryError::InvalidConfirmationCode)?;
+        if user.password_reset_attempts >= MAX_CONFIRMATION_ATTEMPTS {
+            return Err(RepositoryError::ConfirmationAttemptsExceeded);
+        }
+        let is_valid = user
+            .password_reset
+            .as_ref()
+            .is_some_and(|(expected, expires_at)| expected == code && *expires_at > Utc::now());
+        if !is_valid {
+            user.password_reset_attempts += 1;
+            return Err(RepositoryError::InvalidConfirmationCode);
+        }
+        user.password = new_password.to_string();
+        user.password_reset = None;
+        user.password_reset_attempts = 0;
+        user.email_confirmed = true;
+        user.confirmation = None;
+        user.confirmation_attempts = 0;
+        Ok(User {
+            id: user.id,
+            username: user.username.clone(),
+        })
+    }
+
+    async fn change_password(
+        &self,
+        user_id: uuid::Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<String, RepositoryError> {
+        if new_password.len() < MIN_PASSWORD_LENGTH {
+            return Err(RepositoryError::InvalidPassword);
+        }
+        let mut data = self.data();
+        data.check_password(user_id, current_password)?;
+        let user = data.user_mut(user_id)?;
+        user.password = new_password.to_string();
+        user.password_reset = None;
+        Ok(user.email.clone())
+    }
+
+    async fn change_email(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+        new_email: &str,
+    ) -> Result<PendingEmailConfirmation, RepositoryError> {
+        validate_email(new_email)?;
+        let pending = new_pending_email_confirmation(new_email)?;
+        let mut data = self.data();
+        data.check_password(user_id, password)?;
+        if data
+            .users
+            .iter()
+            .any(|user| user.email == pending.email && user.id != user_id)
+        {
+            return Err(RepositoryError::EmailTaken);
+        }
+        let user = data.user_mut(user_id)?;
+        user.email = pending.email.clone();
+        user.email_confirmed = false;
+        user.confirmation = Some((
+            pending.code.clone(),
+            Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES),
+        ));
+        user.confirmation_attempts = 0;
+        user.password_reset = None;
+        Ok(pending)
+    }
+
+    async fn change_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> Result<(), RepositoryError> {
+        validate_username(username)?;
+        let mut data = self.data();
+        if data
+            .users
+            .iter()
+            .any(|user| user.username == username && user.id != user_id)
+        {
+            return Err(RepositoryError::UsernameTaken);
+        }
+        data.user_mut(user_id)?.username = username.to_string();
+        Ok(())
+    }
+
+    async fn delete_account(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut data = self.data();
+        data.check_password(user_id, password)?;
+        data.users.retain(|user| user.id != user_id);
+        data.cards.remove(&user_id);
+        data.decks.remove(&user_id);
+        data.trades
+            .retain(|trade| trade.proposer_id != user_id && trade.recipient_id != user_id);
+        data.rewarded_games.retain(|(_, id)| *id != user_id);
+        data.booster_packs.retain(|(id, _)| *id != user_id);
+        data.sealed_pools.remove(&user_id);
+        data.dust.remove(&user_id);
+        data.quest_games.retain(|(_, id)| *id != user_id);
+        data.quests.retain(|(id, _, _), _| *id != user_id);
+        data.achievements.retain(|(id, _)| *id != user_id);
+        data.blocks
+            .retain(|(id, blocked_id)| *id != user_id && *blocked_id != user_id);
+        data.reports.retain(|(id, _)| *id != user_id);
+        Ok(())
+    }
+
+    async fn selected_starter_deck(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<PreconDeck>, RepositoryError> {
+        Ok(self.data().user(user_id)?.starter_deck.clone())
+    }
+
+    async fn complete_starter_selection(
+        &self,
+        user_id: uuid::Uuid,
+        starter_deck: &PreconDeck,
+        deck: &DeckList,
+        cards: &[CardNameWithCount],
+    ) -> Result<(), RepositoryError> {
+        let mut data = self.data();
+        let user = data
+            .user_mut(user_id)
+            .map_err(|_| RepositoryError::StarterDeckAlreadySelected)?;
+        if user.starter_deck.is_some() {
+            return Err(RepositoryError::StarterDeckAlreadySelected);
+        }
+        user.starter_deck = Some(starter_deck.clone());
+        for card in cards {
+            data.add_cards(
+                user_id,
+                &CardNameWithCount {
+                    is_foil: false,
+                    ..card.clone()
+                },
+            );
+        }
+        data.decks.entry(user_id).or_default().push(deck.clone());
+        Ok(())
+    }
+
+    async fn load_decks(&self, user_id: uuid::Uuid) -> Result<Vec<DeckList>, RepositoryError> {
+        Ok(self.data().decks.get(&user_id).cloned().unwrap_or_default())
+    }
+
+    async fn load_collection(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<CollectedCard>, RepositoryError> {
+        Ok(self.data().collection(user_id))
+    }
+
+    async fn propose_trade(
+        &self,
+        proposer_id: uuid::Uuid,
+        recipient: &str,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> Result<TradeUpdate, RepositoryError> {
+        validate_terms(offered, requested).map_err(RepositoryError::InvalidTrade)?;
+        let mut data = self.data();
+        let recipient_id = data
+            .users
+            .iter()
+            .find(|user| user.username == recipient.trim())
+            .ok_or(RepositoryError::UnknownTradePartner)?
+            .id;
+        if recipient_id == proposer_id {
+            return Err(RepositoryError::InvalidTrade(
+                "You cannot trade with yourself.".to_string(),
+            ));
+        }
+        data.check_trade_side(proposer_id, offered, unlock_decks)?;
+        data.trades.push(StoredTrade {
+            id: uuid::Uuid::new_v4(),
+            proposer_id,
+            recipient_id,
+            proposer_side: offered.clone(),
+            recipient_side: requested.clone(),
+            proposer_confirmed: true,
+            recipient_confirmed: false,
+            proposer_unlocks_decks: unlock_decks,
+            recipient_unlocks_decks: false,
+        });
+        Ok(TradeUpdate {
+            partner_id: recipient_id,
+            completed: false,
+        })
+    }
+
+    async fn counter_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> Result<TradeUpdate, RepositoryError> {
+        validate_terms(offered, requested).map_err(RepositoryError::InvalidTrade)?;
+        let mut data = self.data();
+        let position = data.open_trade(user_id, trade_id)?;
+        data.check_trade_side(user_id, offered, unlock_decks)?;
+
+        let mut trade = data.trades.remove(position);
+        let is_proposer = user_id == trade.proposer_id;
+        let (proposer_side, recipient_side) = if is_proposer {
+            (offered, requested)
+        } else {
+            (requested, offered)
+        };
+        trade.proposer_side = proposer_side.clone();
+        trade.recipient_side = recipient_side.clone();
+        trade.proposer_confirmed = is_proposer;
+        trade.recipient_confirmed = !is_proposer;
+        trade.proposer_unlocks_decks = is_proposer && unlock_decks;
+        trade.recipient_unlocks_decks = !is_proposer && unlock_decks;
+        let partner_id = trade.partner_of(user_id);
+        data.trades.push(trade);
+        Ok(TradeUpdate {
+            partner_id,
+            completed: false,
+        })
+    }
+
+    async fn confirm_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        unlock_decks: bool,
+    ) -> Result<TradeUpdate, RepositoryError> {
+        let mut data = self.data();
+        let position = data.open_trade(user_id, trade_id)?;
+        let trade = &data.trades[position];
+        let (mut proposer_confirmed, mut proposer_unlocks_decks) =
+            (trade.proposer_confirmed, trade.proposer_unlocks_decks);
+        let (mut recipient_confirmed, mut recipient_unlocks_decks) =
+            (trade.recipient_confirmed, trade.recipient_unlocks_decks);
+        if user_id == trade.proposer_id {
+            proposer_confirmed = true;
+            proposer_unlocks_decks = unlock_decks;
+        } else {
+            recipient_confirmed = true;
+            recipient_unlocks_decks = unlock_decks;
+        }
+
+        // Nothing changes until both sides are known to hold, like the rolled back transaction.
+        let completed = proposer_confirmed && recipient_confirmed;
+        if completed {
+            data.check_trade_side(
+                trade.proposer_id,
+                &trade.proposer_side,
+                proposer_unlocks_decks,
+            )?;
+            data.check_trade_side(
+                trade.recipient_id,
+                &trade.recipient_side,
+                recipient_unlocks_decks,
+            )?;
+            data.check_trade_receipt(trade.proposer_id, &trade.recipient_side)?;
+            data.check_trade_receipt(trade.recipient_id, &trade.proposer_side)?;
+        }
+
+        let mut trade = data.trades.remove(position);
+        let partner_id = trade.partner_of(user_id);
+        if completed {
+            data.transfer_trade_side(trade.proposer_id, trade.recipient_id, &trade.proposer_side)?;
+            data.transfer_trade_side(trade.recipient_id, trade.proposer_id, &trade.recipient_side)?;
+        } else {
+            trade.proposer_confirmed = proposer_confirmed;
+            trade.recipient_confirmed = recipient_confirmed;
+            trade.proposer_unlocks_decks = proposer_unlocks_decks;
+            trade.recipient_unlocks_decks = recipient_unlocks_decks;
+            data.trades.push(trade);
+        }
+        Ok(TradeUpdate {
+            partner_id,
+            completed,
+        })
+    }
+
+    async fn decline_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+    ) -> Result<TradeUpdate, RepositoryError> {
+        let mut data = self.data();
+        let position = data.open_trade(user_id, trade_id)?;
+        let trade = data.trades.remove(position);
+        Ok(TradeUpdate {
+            partner_id: trade.partner_of(user_id),
+            completed: false,
+        })
+    }
+
+    async fn load_trades(&self, user_id: uuid::Uuid) -> Result<Vec<TradeOffer>, RepositoryError> {
+        let data = self.data();
+        data.trades
+            .iter()
+            .rev()
+            .filter(|trade| trade.proposer_id == user_id || trade.recipient_id == user_id)
+            .map(|trade| {
+                let incoming = trade.proposer_id != user_id;
+                let partner = data.user(trade.partner_of(user_id))?.username.clone();
+                Ok(if incoming {
+                    TradeOffer {
+                        id: trade.id,
+                        partner,
+                        incoming,
+                        yours: trade.recipient_side.clone(),
+                        theirs: trade.proposer_side.clone(),
+                        you_confirmed: trade.recipient_confirmed,
+                        partner_confirmed: trade.proposer_confirmed,
+                        status: TradeStatus::Pending,
+                    }
+                } else {
+                    TradeOffer {
+                        id: trade.id,
+                        partner,
+                        incoming,
+                        yours: trade.proposer_side.clone(),
+                        theirs: trade.recipient_side.clone(),
+                        you_confirmed: trade.proposer_confirmed,
+                        partner_confirmed: trade.recipient_confirmed,
+                        status: TradeStatus::Pending,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    async fn reward_points(&self, user_id: uuid::Uuid) -> Result<u32, RepositoryError> {
+        Ok(self.data().user(user_id)?.reward_points)
+    }
+
+    async fn award_match_points(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        points: u32,
+    ) -> Result<MatchReward, RepositoryError> {
+        let mut data = self.data();
+        data.user(user_id)?;
+        let points_earned = if data.rewarded_games.insert((game_id, user_id)) {
+            points
+        } else {
+            0
+        };
+        let user = data.user_mut(user_id)?;
+        user.reward_points += points_earned;
+        Ok(MatchReward {
+            points_earned,
+            reward_points: user.reward_points,
+        })
+    }
+
+    async fn redeem_beta_booster(
+        &self,
+        user_id: uuid::Uuid,
+        pack: BoosterPack,
+        cost: u32,
+    ) -> Result<(u32, UnopenedBoosterPack), RepositoryError> {
+        let mut data = self.data();
+        let user = data.user_mut(user_id)?;
+        user.reward_points = user
+            .reward_points
+            .checked_sub(cost)
+            .ok_or(RepositoryError::InsufficientRewardPoints)?;
+        let reward_points = user.reward_points;
+        let unopened = UnopenedBoosterPack {
+            id: uuid::Uuid::new_v4(),
+            pack,
+        };
+        data.booster_packs.push((user_id, unopened.clone()));
+        Ok((reward_points, unopened))
+    }
+
+    async fn claim_weekly_boosters(
+        &self,
+        user_id: uuid::Uuid,
+        week_start: NaiveDate,
+        packs: &[BoosterPack],
+    ) -> Result<bool, RepositoryError> {
+        let mut data = self.data();
+        let user = data.user_mut(user_id)?;
+        if user
+            .last_booster_week
+            .is_some_and(|claimed| claimed >= week_start)
+        {
+            return Ok(false);
+        }
+        user.last_booster_week = Some(week_start);
+        for pack in packs {
+            let unopened = UnopenedBoosterPack {
+                id: uuid::Uuid::new_v4(),
+                pack: pack.clone(),
+            };
+            data.booster_packs.push((user_id, unopened));
+        }
+        Ok(true)
+    }
+
+    async fn load_unopened_booster_packs(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<UnopenedBoosterPack>, RepositoryError> {
+        Ok(self
+            .data()
+            .booster_packs
+            .iter()
+            .filter(|(owner_id, _)| *owner_id == user_id)
+            .map(|(_, pack)| pack.clone())
+            .collect())
+    }
+
+    async fn open_booster_pack(
+        &self,
+        user_id: uuid::Uuid,
+        pack_id: uuid::Uuid,
+    ) -> Result<Option<BoosterPack>, RepositoryError> {
+        let mut data = self.data();
+        let Some(position) = data
+            .booster_packs
+            .iter()
+            .position(|(owner_id, unopened)| *owner_id == user_id && unopened.id == pack_id)
+        else {
+            return Ok(None);
+        };
+        let (_, unopened) = data.booster_packs.remove(position);
+        for card in &unopened.pack.cards {
+            data.add_cards(
+                user_id,
+                &CardNameWithCount {
+                    count: 1,
+                    name: card.name.clone(),
+                    is_foil: card.is_foil,
+                },
+            );
+        }
+        Ok(Some(unopened.pack))
+    }
+
+    async fn start_sealed_event(
+        &self,
+        user_id: uuid::Uuid,
+        pool: &SealedPool,
+    ) -> Result<(), RepositoryError> {
+        let mut data = self.data();
+        if let Some((_, opened_at)) = data.sealed_pools.get(&user_id) {
+            let available_at = *opened_at + Duration::hours(SEALED_POOL_HOURS);
+            if available_at > Utc::now() {
+                return Err(RepositoryError::SealedPoolTooRecent {
+                    available_at: available_at.format("%Y-%m-%d %H:%M").to_string(),
+                });
+            }
+        }
+        data.sealed_pools
+            .insert(user_id, (pool.clone(), Utc::now()));
+        Ok(())
+    }
+
+    async fn load_sealed_pool(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<SealedPool>, RepositoryError> {
+        Ok(self
+            .data()
+            .sealed_pools
+            .get(&user_id)
+            .map(|(pool, _)| pool.clone()))
+    }
+
+    async fn crafting_dust(&self, user_id: uuid::Uuid) -> Result<u32, RepositoryError> {
+        Ok(self.data().dust.get(&user_id).copied().unwrap_or_default())
+    }
+
+    async fn dust_extra_copies(&self, user_id: uuid::Uuid) -> Result<u32, RepositoryError> {
+        let mut data = self.data();
+        let locked = deck_locked_copies(data.decks.get(&user_id).map_or(&[], Vec::as_slice));
+        let excess = excess_copies(&data.collection(user_id), &locked);
+        if excess.is_empty() {
+            return Err(RepositoryError::InvalidCrafting(
+                "You have no extra copies to dust.".to_string(),
+            ));
+        }
+
+        let mut gained = 0;
+        for card in &excess {
+            let rarity = card_rarity(&card.name).ok_or(RepositoryError::Serialization)?;
+            gained += dust_value(&rarity, card.is_foil) * u32::from(card.count);
+        }
+        for card in &excess {
+            data.remove_cards(user_id, card);
+        }
+        let dust = data.dust.entry(user_id).or_default();
+        *dust += gained;
+        Ok(*dust)
+    }
+
+    async fn craft_cards(
+        &self,
+        user_id: uuid::Uuid,
+        cards: &[CardNameWithCount],
+    ) -> Result<u32, RepositoryError> {
+        if cards.is_empty() || cards.iter().any(|card| card.count == 0) {
+            return Err(RepositoryError::InvalidCrafting(
+                "Choose the cards to craft.".to_string(),
+            ));
+        }
+        let cost = crafting_cost(cards).map_err(RepositoryError::InvalidCrafting)?;
+
+        let mut data = self.data();
+        check_owned_copies(cards, &data.collection(user_id))
+            .map_err(RepositoryError::InvalidCrafting)?;
+        let dust = data.dust.entry(user_id).or_default();
+        *dust = dust
+            .checked_sub(cost)
+            .ok_or(RepositoryError::InsufficientDust)?;
+        let dust = *dust;
+        for card in cards {
+            data.add_cards(user_id, card);
+        }
+        Ok(dust)
+    }
+
+    async fn load_quests(
+        &self,
+        user_id: uuid::Uuid,
+        today: NaiveDate,
+    ) -> Result<Vec<QuestProgress>, RepositoryError> {
+        let data = self.data();
+        Ok(all_active_quests(today)
+            .into_iter()
+            .map(|quest| {
+                let key = (user_id, quest.id, quest.cadence.period_start(today));
+                let (progress, completed) = data.quests.get(&key).copied().unwrap_or_default();
+                QuestProgress {
+                    id: quest.id.to_string(),
+                    description: quest.description.to_string(),
+                    cadence: quest.cadence,
+                    progress: progress.min(quest.target),
+                    target: quest.target,
+                    reward_points: quest.reward_points,
+                    completed,
+                    ends_on: quest.cadence.period_end(today),
+                }
+            })
+            .collect())
+    }
+
+    async fn record_quest_progress(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        stats: &QuestStats,
+        today: NaiveDate,
+    ) -> Result<QuestReward, RepositoryError> {
+        let mut data = self.data();
+        data.user(user_id)?;
+        let mut points_earned = 0;
+        let mut completed = Vec::new();
+        if data.quest_games.insert((game_id, user_id)) {
+            for quest in all_active_quests(today) {
+                let progress = quest.goal.progress(stats);
+                if progress == 0 {
+                    continue;
+                }
+                let key = (user_id, quest.id, quest.cadence.period_start(today));
+                let (total, done) = data.quests.entry(key).or_default();
+                if *done {
+                    continue;
+                }
+                *total += progress;
+                if *total >= quest.target {
+                    *done = true;
+                    points_earned += quest.reward_points;
+                    completed.push(quest.description.to_string());
+                }
+            }
+        }
+
+        let user = data.user_mut(user_id)?;
+        user.reward_points += points_earned;
+        Ok(QuestReward {
+            points_earned,
+            reward_points: user.reward_points,
+            completed,
+        })
+    }
+
+    async fn unlock_achievements(
+        &self,
+        user_id: uuid::Uuid,
+        _game_id: uuid::Uuid,
+        achievement_ids: &[&str],
+    ) -> Result<Vec<String>, RepositoryError> {
+        let mut data = self.data();
+        Ok(achievement_ids
+            .iter()
+            .filter(|id| data.achievements.insert((user_id, id.to_string())))
+            .map(|id| id.to_string())
+            .collect())
+    }
+
+    async fn record_attempt(
+        &self,
+        limit: RateLimit,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), RepositoryError> {
+        let now = now.timestamp();
+        let mut data = self.data();
+        let state = data
+            .rate_limits
+            .entry((limit.name, key.to_string()))
+            .or_default();
+        if let Some((_, locked_until)) = state.lockout
+            && locked_until > now
+        {
+            return Err(RepositoryError::RateLimited {
+                retry_after_seconds: (locked_until - now) as u64,
+            });
+        }
+        state
+            .attempts
+            .retain(|attempted_at| *attempted_at > now - limit.window_seconds);
+        if state.attempts.len() < limit.max_attempts as usize {
+            state.attempts.push(now);
+            return Ok(());
+        }
+        state.attempts.clear();
+        let (lockouts, locked_until) = next_lockout(state.lockout, now);
+        state.lockout = Some((lockouts, locked_until));
+        Err(RepositoryError::RateLimited {
+            retry_after_seconds: (locked_until - now) as u64,
+        })
+    }
+
+    async fn clear_attempts(&self, limit: RateLimit, key: &str) -> Result<(), RepositoryError> {
+        self.data()
+            .rate_limits
+            .remove(&(limit.name, key.to_string()));
+        Ok(())
+    }
+
+    async fn prune_rate_limits(&self, now: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let now = now.timestamp();
+        let mut pruned = 0;
+        self.data().rate_limits.retain(|(name, _), state| {
+            if let Some(limit) = RateLimit::ALL.iter().find(|limit| limit.name == *name) {
+                let before = state.attempts.len();
+                state
+                    .attempts
+                    .retain(|attempted_at| *attempted_at > now - limit.window_seconds);
+                pruned += (before - state.attempts.len()) as u64;
+            }
+            if let Some((_, locked_until)) = state.lockout
+                && locked_until <= now - LOCKOUT_MEMORY_SECONDS
+            {
+                state.lockout = None;
+                pruned += 1;
+            }
+            !state.attempts.is_empty() || state.lockout.is_some()
+        });
+        Ok(pruned)
+    }
+
+    async fn start_live_game(
+        &self,
+        game_id: uuid::Uuid,
+        players: [&str; 2],
+    ) -> Result<(), RepositoryError> {
+        self.data()
+            .live_games
+            .insert(game_id, players.map(str::to_string));
+        Ok(())
+    }
+
+    async fn finish_live_game(&self, game_id: uuid::Uuid) -> Result<(), RepositoryError> {
+        self.data().live_games.remove(&game_id);
+        Ok(())
+    }
+
+    async fn drain_request(&self) -> Result<Option<DrainRequest>, RepositoryError> {
+        Ok(self.data().drain.clone())
+    }
+
+    async fn check_ban(&self, user_id: uuid::Uuid) -> Result<(), RepositoryError> {
+        match &self.data().user(user_id)?.ban {
+            Some((reason, until)) if until.is_none_or(|until| until > Utc::now()) => {
+                Err(RepositoryError::AccountBanned {
+                    reason: reason.clone(),
+                    until: until.map(|until| until.format(TIMESTAMP_FORMAT).to_string()),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn blocked_players(&self, user_id: uuid::Uuid) -> Result<Vec<User>, RepositoryError> {
+        let data = self.data();
+        let mut blocked: Vec<User> = data
+            .users
+            .iter()
+            .filter(|user| data.blocks.contains(&(user_id, user.id)))
+            .map(|user| User {
+                id: user.id,
+                username: user.username.clone(),
+            })
+            .collect();
+        blocked.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(blocked)
+    }
+
+    async fn block_player(
+        &self,
+        user_id: uuid::Uuid,
+        blocked_id: uuid::Uuid,
+    ) -> Result<(), RepositoryError> {
+        if user_id == blocked_id {
+            return Err(RepositoryError::InvalidModeration(
+                "You cannot block yourself.".to_string(),
+            ));
+        }
+        self.data().blocks.insert((user_id, blocked_id));
+        Ok(())
+    }
+
+    async fn block_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> Result<(), RepositoryError> {
+        let blocked_id = self
+            .data()
+            .users
+            .iter()
+            .find(|user| user.username == username.trim())
+            .ok_or(RepositoryError::UnknownPlayer)?
+            .id;
+        self.block_player(user_id, blocked_id).await
+    }
+
+    async fn unblock_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut data = self.data();
+        if let Some(blocked_id) = data
+            .users
+            .iter()
+            .find(|user| user.username == username.trim())
+            .map(|user| user.id)
+        {
+            data.blocks.remove(&(user_id, blocked_id));
+        }
+        Ok(())
+    }
+
+    async fn report_player(
+        &self,
+        reporter_id: uuid::Uuid,
+        reported_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        _reason: ReportReason,
+        details: &str,
+    ) -> Result<(), RepositoryError> {
+        validate_report_details(details).map_err(RepositoryError::InvalidModeration)?;
+        if reporter_id == reported_id {
+            return Err(RepositoryError::InvalidModeration(
+                "You cannot report yourself.".to_string(),
+            ));
+        }
+        if !self.data().reports.insert((reporter_id, game_id)) {
+            return Err(RepositoryError::InvalidModeration(
+                "You have already reported this match.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
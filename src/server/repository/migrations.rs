@@ -0,0 +1,422 @@
+//! Versioned schema migrations.
+//!
+//! Each applied migration is recorded in `schema_version` with a checksum of its statements, and
+//! pending migrations run in order, each in its own transaction. Migrations are never edited once
+//! released: a change to the schema is a new migration at the end of [`MIGRATIONS`].
+//!
+//! Databases created before versioning have no `schema_version` table. The first six migrations
+//! use `IF NOT EXISTS`, so running them against such a database adopts the tables it already has.
+//! Later migrations only ever run against versioned databases and can alter tables freely.
+use sqlx::SqlitePool;
+
+use super::RepositoryError;
+
+pub(super) struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub statements: &'static [&'static str],
+}
+
+impl Migration {
+    /// FNV-1a over the statements with whitespace collapsed, so reindenting a migration doesn't
+    /// change its checksum but editing its SQL does.
+    fn checksum(&self) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for statement in self.statements {
+            let normalized = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+            for byte in normalized.bytes().chain([b';']) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+        format!("{hash:016x}")
+    }
+}
+
+pub(super) static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create accounts, rewards, collections, decks and booster packs",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS users (
+                id TEXT PRIMARY KEY,
+                username TEXT NOT NULL UNIQUE,
+                email TEXT UNIQUE,
+                password_hash TEXT NOT NULL,
+                email_confirmed_at TEXT,
+                confirmation_code_hash TEXT,
+                confirmation_code_expires_at TEXT,
+                confirmation_attempts INTEGER NOT NULL DEFAULT 0,
+                starter_deck TEXT,
+                last_booster_week TEXT,
+                reward_points INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )",
+            "CREATE UNIQUE INDEX IF NOT EXISTS users_email_unique_idx
+                ON users (email) WHERE email IS NOT NULL",
+            "CREATE TABLE IF NOT EXISTS game_rewards (
+                game_id TEXT NOT NULL,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                points INTEGER NOT NULL CHECK (points > 0),
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (game_id, user_id)
+            )",
+            "CREATE TABLE IF NOT EXISTS user_cards (
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                card_name TEXT NOT NULL,
+                is_foil BOOLEAN NOT NULL DEFAULT FALSE,
+                quantity INTEGER NOT NULL CHECK (quantity > 0),
+                PRIMARY KEY (user_id, card_name, is_foil)
+            )",
+            "CREATE TABLE IF NOT EXISTS user_decks (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                deck TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (user_id, name)
+            )",
+            "CREATE TABLE IF NOT EXISTS booster_packs (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                set_name TEXT NOT NULL,
+                cards TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                opened_at TEXT
+            )",
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "Add sealed pools",
+        statements: &["CREATE TABLE IF NOT EXISTS sealed_pools (
+                user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+                id TEXT NOT NULL,
+                cards TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"],
+    },
+    Migration {
+        version: 3,
+        description: "Add trade offers",
+        statements: &["CREATE TABLE IF NOT EXISTS trade_offers (
+                id TEXT PRIMARY KEY,
+                proposer_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                recipient_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                proposer_side TEXT NOT NULL,
+                recipient_side TEXT NOT NULL,
+                proposer_confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+                recipient_confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+                proposer_unlocks_decks BOOLEAN NOT NULL DEFAULT FALSE,
+                recipient_unlocks_decks BOOLEAN NOT NULL DEFAULT FALSE,
+                status TEXT NOT NULL DEFAULT 'pending',
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"],
+    },
+    Migration {
+        version: 4,
+        description: "Add the crafting balance and log",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS crafting_balances (
+                user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+                dust INTEGER NOT NULL DEFAULT 0 CHECK (dust >= 0)
+            )",
+            "CREATE TABLE IF NOT EXISTS crafting_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                action TEXT NOT NULL CHECK (action IN ('dust', 'craft')),
+                card_name TEXT NOT NULL,
+                is_foil BOOLEAN NOT NULL,
+                quantity INTEGER NOT NULL CHECK (quantity > 0),
+                dust_change INTEGER NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )",
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "Add quest progress",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS user_quests (
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                quest_id TEXT NOT NULL,
+                period_start TEXT NOT NULL,
+                progress INTEGER NOT NULL DEFAULT 0 CHECK (progress >= 0),
+                completed_at TEXT,
+                PRIMARY KEY (user_id, quest_id, period_start)
+            )",
+            "CREATE TABLE IF NOT EXISTS quest_games (
+                game_id TEXT NOT NULL,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (game_id, user_id)
+            )",
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "Add achievements",
+        statements: &["CREATE TABLE IF NOT EXISTS user_achievements (
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                achievement_id TEXT NOT NULL,
+                game_id TEXT NOT NULL,
+                unlocked_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, achievement_id)
+            )"],
+    },
+    Migration {
+        version: 7,
+        description: "Add password reset codes",
+        statements: &[
+            "ALTER TABLE users ADD COLUMN password_reset_code_hash TEXT",
+            "ALTER TABLE users ADD COLUMN password_reset_expires_at TEXT",
+            "ALTER TABLE users
+                ADD COLUMN password_reset_attempts INTEGER NOT NULL DEFAULT 0",
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "Add rate limits",
+        statements: &[
+            "CREATE TABLE rate_limit_attempts (
+                rate_limit TEXT NOT NULL,
+                key TEXT NOT NULL,
+                attempted_at INTEGER NOT NULL
+            )",
+            "CREATE INDEX rate_limit_attempts_by_key
+                ON rate_limit_attempts (rate_limit, key, attempted_at)",
+            "CREATE TABLE rate_limit_lockouts (
+                rate_limit TEXT NOT NULL,
+                key TEXT NOT NULL,
+                lockouts INTEGER NOT NULL CHECK (lockouts > 0),
+                locked_until INTEGER NOT NULL,
+                PRIMARY KEY (rate_limit, key)
+            )",
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "Add bans and live games",
+        statements: &[
+            "ALTER TABLE users ADD COLUMN banned_at TEXT",
+            "ALTER TABLE users ADD COLUMN ban_reason TEXT",
+            "CREATE TABLE live_games (
+                game_id TEXT PRIMARY KEY,
+                player_one TEXT NOT NULL,
+                player_two TEXT NOT NULL,
+                started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )",
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "Add ban expiry, blocks and reports",
+        statements: &[
+            "ALTER TABLE users ADD COLUMN banned_until TEXT",
+            "CREATE TABLE user_blocks (
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                blocked_user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, blocked_user_id)
+            )",
+            "CREATE TABLE player_reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                reporter_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                reported_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                game_id TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                details TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (reporter_id, game_id)
+            )",
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "Add server drain requests",
+        statements: &["CREATE TABLE server_drain (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                deadline TEXT,
+                requested_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"],
+    },
+];
+
+/// Bring the database up to the last of `migrations` and return that version.
+pub(super) async fn run(
+    pool: &SqlitePool,
+    migrations: &[Migration],
+) -> Result<i64, RepositoryError> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    let applied: Vec<(i64, String)> =
+        sqlx::query_as("SELECT version, checksum FROM schema_version ORDER BY version")
+            .fetch_all(pool)
+            .await?;
+    let supported = migrations.last().map_or(0, |migration| migration.version);
+    if let Some(&(database, _)) = applied.last()
+        && database > supported
+    {
+        return Err(RepositoryError::SchemaTooNew {
+            database,
+            supported,
+        });
+    }
+    for (version, checksum) in &applied {
+        if let Some(migration) = migrations.iter().find(|m| m.version == *version)
+            && &migration.checksum() != checksum
+        {
+            return Err(RepositoryError::MigrationChanged(*version));
+        }
+    }
+
+    for migration in migrations.iter().filter(|migration| {
+        !applied
+            .iter()
+            .any(|(version, _)| *version == migration.version)
+    }) {
+        let mut transaction = pool.begin().await?;
+        for statement in migration.statements {
+            sqlx::query(statement).execute(&mut *transaction).await?;
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, checksum) VALUES (?1, ?2, ?3)",
+        )
+        .bind(migration.version)
+        .bind(migration.description)
+        .bind(migration.checksum())
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+    }
+    Ok(supported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// How many migrations existed before `schema_version` did.
+    const UNVERSIONED_RELEASES: usize = 6;
+
+    async fn empty_database() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    async fn tables(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn applied_versions(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT version FROM schema_version ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn versions_count_up_from_one() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(
+                migration.version,
+                index as i64 + 1,
+                "{}",
+                migration.description
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn every_historical_schema_upgrades_to_the_latest() {
+        let fresh = empty_database().await;
+        let latest = run(&fresh, MIGRATIONS).await.unwrap();
+        let expected_tables = tables(&fresh).await;
+
+        for released in 0..MIGRATIONS.len() {
+            // A database left by a versioned server of that release.
+            let versioned = empty_database().await;
+            run(&versioned, &MIGRATIONS[..released]).await.unwrap();
+            let mut pools = vec![versioned];
+            if released <= UNVERSIONED_RELEASES {
+                // The same schema from a server that predates `schema_version`.
+                let unversioned = empty_database().await;
+                for migration in &MIGRATIONS[..released] {
+                    for statement in migration.statements {
+                        sqlx::query(statement).execute(&unversioned).await.unwrap();
+                    }
+                }
+                pools.push(unversioned);
+            }
+
+            for pool in pools {
+                if released > 0 {
+                    sqlx::query(
+                        "INSERT INTO users (id, username, password_hash) VALUES ('1', 'a', 'h')",
+                    )
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+                }
+                assert_eq!(run(&pool, MIGRATIONS).await.unwrap(), latest);
+                assert_eq!(tables(&pool).await, expected_tables, "from {released}");
+                assert_eq!(
+                    applied_versions(&pool).await,
+                    (1..=latest).collect::<Vec<_>>()
+                );
+                if released > 0 {
+                    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+                        .fetch_one(&pool)
+                        .await
+                        .unwrap();
+                    assert_eq!(users, 1, "upgrading from {released} kept existing rows");
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn newer_databases_are_refused() {
+        let pool = empty_database().await;
+        run(&pool, MIGRATIONS).await.unwrap();
+
+        let result = run(&pool, &MIGRATIONS[..MIGRATIONS.len() - 1]).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::SchemaTooNew { database, supported })
+                if database == MIGRATIONS.len() as i64 && supported == database - 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn edited_migrations_are_refused() {
+        let pool = empty_database().await;
+        run(&pool, MIGRATIONS).await.unwrap();
+        sqlx::query("UPDATE schema_version SET checksum = 'edited' WHERE version = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            run(&pool, MIGRATIONS).await,
+            Err(RepositoryError::MigrationChanged(1))
+        ));
+    }
+}
@@ -1,15 +1,42 @@
+mod achievements;
+mod admin;
 mod booster_packs;
 mod cards;
+#[cfg(test)]
+mod conformance;
+mod crafting;
 mod decks;
+#[cfg(test)]
+mod memory;
+mod migrations;
+mod moderation;
+mod quests;
+mod rate_limits;
+mod sealed;
+mod storage;
+mod timings;
 mod users;
 
 use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use std::{borrow::Cow, sync::Arc};
 
-pub use users::User;
+pub use admin::{DrainRequest, UserSummary};
+pub use cards::TradeUpdate;
+#[cfg(test)]
+pub use memory::MemoryStorage;
+pub use rate_limits::RateLimit;
+pub use storage::Storage;
+pub use timings::{TimingSnapshot, Timings};
+pub use users::{CONFIRMATION_CODE_LIFETIME_MINUTES, User};
+
+/// The format of stored timestamps, which matches SQLite's `CURRENT_TIMESTAMP` so queries can
+/// compare the two directly.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
 #[derive(Clone)]
 pub struct Repository {
     pub(super) pool: SqlitePool,
+    query_timings: Arc<Timings>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -26,20 +53,55 @@ pub enum RepositoryError {
     EmailTaken,
     #[error("invalid username or password")]
     InvalidCredentials,
+    #[error("this account has been banned")]
+    AccountBanned {
+        reason: Option<String>,
+        /// When the ban lifts, or `None` for a permanent ban.
+        until: Option<String>,
+    },
+    #[error("the password is incorrect")]
+    IncorrectPassword,
     #[error("email confirmation is required")]
     EmailConfirmationRequired(String),
     #[error("the confirmation code is invalid or has expired")]
     InvalidConfirmationCode,
     #[error("too many confirmation attempts; request a new code")]
     ConfirmationAttemptsExceeded,
+    #[error("too many attempts; try again in {retry_after_seconds} seconds")]
+    RateLimited { retry_after_seconds: u64 },
     #[error("that email address has already been confirmed")]
     EmailAlreadyConfirmed,
     #[error("not enough reward points")]
     InsufficientRewardPoints,
     #[error("a starter deck has already been selected")]
     StarterDeckAlreadySelected,
+    #[error("no player with that username exists")]
+    UnknownTradePartner,
+    #[error("the trade is no longer open")]
+    TradeNotFound,
+    #[error("{0}")]
+    InvalidTrade(String),
+    #[error("not enough dust")]
+    InsufficientDust,
+    #[error("{0}")]
+    InvalidCrafting(String),
+    #[error("a new sealed pool can be opened after {available_at} UTC")]
+    SealedPoolTooRecent { available_at: String },
+    #[error("no player with that username exists")]
+    UnknownPlayer,
+    #[error("{0}")]
+    InvalidModeration(String),
+    #[error("no card is named {0:?}")]
+    UnknownCard(String),
     #[error("DATABASE_URL must use a sqlite: URL")]
     UnsupportedDatabase,
+    #[error(
+        "the database schema is at version {database}, but this server only supports up to \
+         version {supported}; upgrade the server"
+    )]
+    SchemaTooNew { database: i64, supported: i64 },
+    #[error("migration {0} was edited after it was applied to the database")]
+    MigrationChanged(i64),
     #[error(transparent)]
     Database(#[from] sqlx::Error),
     #[error("password processing failed")]
@@ -57,85 +119,16 @@ impl Repository {
             .max_connections(1)
             .connect(&connection_url)
             .await?;
-        let repository = Self { pool };
+        let repository = Self {
+            pool,
+            query_timings: Arc::default(),
+        };
         repository.migrate().await?;
         Ok(repository)
     }
 
-    async fn migrate(&self) -> Result<(), sqlx::Error> {
-        // SQLite has no `ADD COLUMN IF NOT EXISTS`, so its complete schema is created
-        // atomically for a new database. SQLite support is new, so there is no prior
-        // SQLite schema to upgrade.
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS users (
-                id TEXT PRIMARY KEY,
-                username TEXT NOT NULL UNIQUE,
-                email TEXT UNIQUE,
-                password_hash TEXT NOT NULL,
-                email_confirmed_at TEXT,
-                confirmation_code_hash TEXT,
-                confirmation_code_expires_at TEXT,
-                confirmation_attempts INTEGER NOT NULL DEFAULT 0,
-                starter_deck TEXT,
-                last_booster_week TEXT,
-                reward_points INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-            )",
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query(
-            "CREATE UNIQUE INDEX IF NOT EXISTS users_email_unique_idx
-             ON users (email) WHERE email IS NOT NULL",
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS game_rewards (
-                game_id TEXT NOT NULL,
-                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-                points INTEGER NOT NULL CHECK (points > 0),
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                PRIMARY KEY (game_id, user_id)
-            )",
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS user_cards (
-                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-                card_name TEXT NOT NULL,
-                is_foil BOOLEAN NOT NULL DEFAULT FALSE,
-                quantity INTEGER NOT NULL CHECK (quantity > 0),
-                PRIMARY KEY (user_id, card_name, is_foil)
-            )",
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS user_decks (
-                id TEXT PRIMARY KEY,
-                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-                name TEXT NOT NULL,
-                deck TEXT NOT NULL,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE (user_id, name)
-            )",
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS booster_packs (
-                id TEXT PRIMARY KEY,
-                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-                set_name TEXT NOT NULL,
-                cards TEXT NOT NULL,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                opened_at TEXT
-            )",
-        )
-        .execute(&self.pool)
-        .await?;
+    async fn migrate(&self) -> Result<(), RepositoryError> {
+        migrations::run(&self.pool, migrations::MIGRATIONS).await?;
         Ok(())
     }
 }
@@ -153,30 +146,80 @@ fn sqlite_connection_url(database_url: &str) -> Result<String, RepositoryError>
 }
 
 impl RepositoryError {
-    pub fn user_message(&self) -> &str {
-        match self {
+    pub fn user_message(&self) -> Cow<'_, str> {
+        Cow::Borrowed(match self {
             Self::InvalidUsername => "username must be 3-32 letters, digits, or underscores",
             Self::InvalidPassword => "password must be at least 8 characters",
             Self::InvalidEmail => "enter a valid email address",
             Self::UsernameTaken => "a user with that username already exists",
             Self::EmailTaken => "an account already uses that email address",
             Self::InvalidCredentials => "invalid username or password",
+            Self::AccountBanned { reason, until } => {
+                let mut message = "this account has been banned".to_string();
+                if let Some(until) = until {
+                    message.push_str(&format!(" until {until} UTC"));
+                }
+                if let Some(reason) = reason {
+                    message.push_str(&format!(": {reason}"));
+                }
+                return Cow::Owned(message);
+            }
+            Self::IncorrectPassword => "that password is incorrect",
             Self::EmailConfirmationRequired(_) => "confirm your email address to continue",
             Self::InvalidConfirmationCode => "that confirmation code is invalid or has expired",
             Self::ConfirmationAttemptsExceeded => {
                 "too many confirmation attempts; request a new code"
             }
+            Self::RateLimited {
+                retry_after_seconds,
+            } => {
+                return Cow::Owned(format!(
+                    "too many attempts; try again in {retry_after_seconds} seconds"
+                ));
+            }
             Self::EmailAlreadyConfirmed => "that email address has already been confirmed",
             Self::InsufficientRewardPoints => "not enough reward points for that booster",
             Self::StarterDeckAlreadySelected => "a starter deck has already been selected",
+            Self::UnknownTradePartner => "no player with that username exists",
+            Self::TradeNotFound => "that trade is no longer open",
+            Self::InvalidTrade(message) => message,
+            Self::InsufficientDust => "not enough dust to craft those cards",
+            Self::InvalidCrafting(message) => message,
+            Self::SealedPoolTooRecent { available_at } => {
+                return Cow::Owned(format!(
+                    "you can open a new sealed pool after {available_at} UTC"
+                ));
+            }
+            Self::UnknownPlayer => "no player with that username exists",
+            Self::InvalidModeration(message) => message,
+            Self::UnknownCard(_) => "no card has that name",
             Self::Database(_)
             | Self::UnsupportedDatabase
+            | Self::SchemaTooNew { .. }
+            | Self::MigrationChanged(_)
             | Self::Password
             | Self::Serialization => "authentication service is unavailable",
-        }
+        })
     }
 }
 
+/// Register `username` and confirm their email, for tests that only need an account to exist.
+#[cfg(test)]
+async fn confirmed_user(storage: &impl Storage, username: &str) -> User {
+    let pending = storage
+        .register(
+            username,
+            &format!("{username}@example.com"),
+            "very-secret-password",
+        )
+        .await
+        .unwrap();
+    storage
+        .confirm_email(&pending.email, &pending.code)
+        .await
+        .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Repository, RepositoryError, sqlite_connection_url};
@@ -225,7 +268,7 @@ mod tests {
         let game_id = uuid::Uuid::new_v4();
         assert_eq!(
             repository
-                .award_match_points(game_id, user.id, true)
+                .award_match_points(game_id, user.id, 10)
                 .await
                 .unwrap()
                 .points_earned,
@@ -233,7 +276,7 @@ mod tests {
         );
         assert_eq!(
             repository
-                .award_match_points(game_id, user.id, true)
+                .award_match_points(game_id, user.id, 10)
                 .await
                 .unwrap()
                 .points_earned,
@@ -0,0 +1,222 @@
+//! Bans, player reports and each player's block list.
+use chrono::Utc;
+use sorcerers::moderation::{ReportReason, validate_report_details};
+
+use super::{
+    Repository, RepositoryError as UserRepositoryError, TIMESTAMP_FORMAT, User,
+    users::is_unique_violation,
+};
+
+impl Repository {
+    /// Refuse a user whose ban is still in force. Bans with an end time lift by themselves.
+    pub async fn check_ban(&self, user_id: uuid::Uuid) -> Result<(), UserRepositoryError> {
+        let ban: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT ban_reason, CAST(banned_until AS TEXT) FROM users
+             WHERE id = ?1 AND banned_at IS NOT NULL
+                AND (banned_until IS NULL OR banned_until > ?2)",
+        )
+        .bind(user_id.to_string())
+        .bind(Utc::now().format(TIMESTAMP_FORMAT).to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        match ban {
+            Some((reason, until)) => Err(UserRepositoryError::AccountBanned { reason, until }),
+            None => Ok(()),
+        }
+    }
+
+    /// The players `user_id` has blocked, by username.
+    pub async fn blocked_players(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<User>, UserRepositoryError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT CAST(users.id AS TEXT), users.username FROM user_blocks
+             JOIN users ON users.id = user_blocks.blocked_user_id
+             WHERE user_blocks.user_id = ?1
+             ORDER BY users.username",
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|(id, username)| {
+                Ok(User {
+                    id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+                    username,
+                })
+            })
+            .collect()
+    }
+
+    /// Stop matchmaking from pairing `user_id` with `blocked_id`, in either direction.
+    pub async fn block_player(
+        &self,
+        user_id: uuid::Uuid,
+        blocked_id: uuid::Uuid,
+    ) -> Result<(), UserRepositoryError> {
+        if user_id == blocked_id {
+            return Err(UserRepositoryError::InvalidModeration(
+                "You cannot block yourself.".to_string(),
+            ));
+        }
+        sqlx::query("INSERT OR IGNORE INTO user_blocks (user_id, blocked_user_id) VALUES (?1, ?2)")
+            .bind(user_id.to_string())
+            .bind(blocked_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn block_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let blocked_id: Option<String> =
+            sqlx::query_scalar("SELECT CAST(id AS TEXT) FROM users WHERE username = ?1")
+                .bind(username.trim())
+                .fetch_optional(&self.pool)
+                .await?;
+        let blocked_id = blocked_id
+            .ok_or(UserRepositoryError::UnknownPlayer)?
+            .parse()
+            .map_err(|_| UserRepositoryError::Serialization)?;
+        self.block_player(user_id, blocked_id).await
+    }
+
+    pub async fn unblock_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> Result<(), UserRepositoryError> {
+        sqlx::query(
+            "DELETE FROM user_blocks
+             WHERE user_id = ?1 AND blocked_user_id = (SELECT id FROM users WHERE username = ?2)",
+        )
+        .bind(user_id.to_string())
+        .bind(username.trim())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// File a report against the opponent of a finished match. Each player can report a match
+    /// once.
+    pub async fn report_player(
+        &self,
+        reporter_id: uuid::Uuid,
+        reported_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        reason: ReportReason,
+        details: &str,
+    ) -> Result<(), UserRepositoryError> {
+        validate_report_details(details).map_err(UserRepositoryError::InvalidModeration)?;
+        if reporter_id == reported_id {
+            return Err(UserRepositoryError::InvalidModeration(
+                "You cannot report yourself.".to_string(),
+            ));
+        }
+        let result = sqlx::query(
+            "INSERT INTO player_reports (reporter_id, reported_id, game_id, reason, details)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(reporter_id.to_string())
+        .bind(reported_id.to_string())
+        .bind(game_id.to_string())
+        .bind(reason.id())
+        .bind(details.trim())
+        .execute(&self.pool)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if is_unique_violation(error.as_ref()) => {
+                Err(UserRepositoryError::InvalidModeration(
+                    "You have already reported this match.".to_string(),
+                ))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use sorcerers::moderation::ReportReason;
+
+    use super::super::{Repository, RepositoryError, confirmed_user};
+
+    #[tokio::test]
+    async fn bans_expire_and_players_can_block_and_report() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let alice = confirmed_user(&repository, "mage_alice").await;
+        let bob = confirmed_user(&repository, "mage_bob").await;
+
+        repository
+            .ban_user(
+                alice.id,
+                Some("stalling"),
+                Some(Utc::now() + Duration::days(1)),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.check_ban(alice.id).await,
+            Err(RepositoryError::AccountBanned { reason: Some(reason), until: Some(_) })
+                if reason == "stalling"
+        ));
+        repository
+            .ban_user(alice.id, None, Some(Utc::now() - Duration::minutes(1)))
+            .await
+            .unwrap();
+        repository.check_ban(alice.id).await.unwrap();
+        repository.check_ban(bob.id).await.unwrap();
+
+        assert!(matches!(
+            repository.block_username(alice.id, "nobody").await,
+            Err(RepositoryError::UnknownPlayer)
+        ));
+        assert!(matches!(
+            repository.block_player(alice.id, alice.id).await,
+            Err(RepositoryError::InvalidModeration(_))
+        ));
+        repository
+            .block_username(alice.id, "mage_bob")
+            .await
+            .unwrap();
+        repository.block_player(alice.id, bob.id).await.unwrap();
+        let blocked = repository.blocked_players(alice.id).await.unwrap();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].id, bob.id);
+        assert!(repository.blocked_players(bob.id).await.unwrap().is_empty());
+        repository
+            .unblock_username(alice.id, "mage_bob")
+            .await
+            .unwrap();
+        assert!(
+            repository
+                .blocked_players(alice.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let game_id = uuid::Uuid::new_v4();
+        repository
+            .report_player(alice.id, bob.id, game_id, ReportReason::Harassment, "rude")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .report_player(alice.id, bob.id, game_id, ReportReason::Other, "")
+                .await,
+            Err(RepositoryError::InvalidModeration(_))
+        ));
+        let reports = repository.list_reports(Some(bob.id), 10).await.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].reporter, "mage_alice");
+        assert_eq!(reports[0].reason, "harassment");
+        assert_eq!(reports[0].game_id, game_id.to_string());
+    }
+}
@@ -0,0 +1,175 @@
+use sorcerers::quest::{QuestProgress, QuestStats, all_active_quests};
+
+use super::{Repository, RepositoryError as UserRepositoryError};
+
+pub struct QuestReward {
+    pub points_earned: u32,
+    pub reward_points: u32,
+    pub completed: Vec<String>,
+}
+
+impl Repository {
+    /// The quests active on `today`, with the player's progress on each.
+    pub async fn load_quests(
+        &self,
+        user_id: uuid::Uuid,
+        today: chrono::NaiveDate,
+    ) -> Result<Vec<QuestProgress>, UserRepositoryError> {
+        let mut quests = Vec::new();
+        for quest in all_active_quests(today) {
+            let row: Option<(i64, bool)> = sqlx::query_as(
+                "SELECT CAST(progress AS BIGINT), completed_at IS NOT NULL
+                 FROM user_quests
+                 WHERE user_id = ?1 AND quest_id = ?2 AND period_start = ?3",
+            )
+            .bind(user_id.to_string())
+            .bind(quest.id)
+            .bind(quest.cadence.period_start(today).to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+            let (progress, completed) = row.unwrap_or_default();
+            quests.push(QuestProgress {
+                id: quest.id.to_string(),
+                description: quest.description.to_string(),
+                cadence: quest.cadence,
+                progress: (progress.max(0) as u32).min(quest.target),
+                target: quest.target,
+                reward_points: quest.reward_points,
+                completed,
+                ends_on: quest.cadence.period_end(today),
+            });
+        }
+        Ok(quests)
+    }
+
+    /// Advance the active quests with what the player did in a finished game, paying out reward
+    /// points for every quest that reaches its target. Each game only counts once per player.
+    pub async fn record_quest_progress(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        stats: &QuestStats,
+        today: chrono::NaiveDate,
+    ) -> Result<QuestReward, UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        let first_time = sqlx::query(
+            "INSERT INTO quest_games (game_id, user_id) VALUES (?1, ?2)
+             ON CONFLICT (game_id, user_id) DO NOTHING",
+        )
+        .bind(game_id.to_string())
+        .bind(user_id.to_string())
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected()
+            > 0;
+
+        let mut points_earned = 0;
+        let mut completed = Vec::new();
+        if first_time {
+            for quest in all_active_quests(today) {
+                let progress = quest.goal.progress(stats);
+                if progress == 0 {
+                    continue;
+                }
+                let finished: Option<bool> = sqlx::query_scalar(
+                    "INSERT INTO user_quests (user_id, quest_id, period_start, progress)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (user_id, quest_id, period_start)
+                     DO UPDATE SET progress = user_quests.progress + EXCLUDED.progress
+                     WHERE user_quests.completed_at IS NULL
+                     RETURNING progress >= ?5",
+                )
+                .bind(user_id.to_string())
+                .bind(quest.id)
+                .bind(quest.cadence.period_start(today).to_string())
+                .bind(i64::from(progress))
+                .bind(i64::from(quest.target))
+                .fetch_optional(&mut *transaction)
+                .await?;
+                if finished != Some(true) {
+                    continue;
+                }
+                sqlx::query(
+                    "UPDATE user_quests SET completed_at = CURRENT_TIMESTAMP
+                     WHERE user_id = ?1 AND quest_id = ?2 AND period_start = ?3",
+                )
+                .bind(user_id.to_string())
+                .bind(quest.id)
+                .bind(quest.cadence.period_start(today).to_string())
+                .execute(&mut *transaction)
+                .await?;
+                points_earned += quest.reward_points;
+                completed.push(quest.description.to_string());
+            }
+        }
+
+        let reward_points: i64 = sqlx::query_scalar(
+            "UPDATE users SET reward_points = reward_points + ?1 WHERE id = ?2
+             RETURNING CAST(reward_points AS BIGINT)",
+        )
+        .bind(i64::from(points_earned))
+        .bind(user_id.to_string())
+        .fetch_one(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(QuestReward {
+            points_earned,
+            reward_points: reward_points.max(0) as u32,
+            completed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Repository, confirmed_user};
+    use sorcerers::{
+        game::Element,
+        quest::{QuestCadence, QuestStats, active_quests},
+    };
+
+    #[tokio::test]
+    async fn completing_a_quest_pays_out_once() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let user = confirmed_user(&repository, "quester").await;
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let stats = QuestStats {
+            won: true,
+            avatar_element: Some(Element::Fire),
+            sites_played: 100,
+            magics_cast: 100,
+            ranged_damage: 100,
+            minions_killed: 100,
+        };
+        // A quest this one game finishes on its own.
+        let quest = active_quests(QuestCadence::Daily, today)
+            .into_iter()
+            .find(|quest| quest.goal.progress(&stats) >= quest.target)
+            .unwrap();
+
+        let game_id = uuid::Uuid::new_v4();
+        let before = repository.reward_points(user.id).await.unwrap();
+        let reward = repository
+            .record_quest_progress(game_id, user.id, &stats, today)
+            .await
+            .unwrap();
+        assert!(reward.completed.contains(&quest.description.to_string()));
+        assert_eq!(reward.reward_points, before + reward.points_earned);
+
+        let repeated = repository
+            .record_quest_progress(game_id, user.id, &stats, today)
+            .await
+            .unwrap();
+        assert_eq!(repeated.points_earned, 0);
+        let again = repository
+            .record_quest_progress(uuid::Uuid::new_v4(), user.id, &stats, today)
+            .await
+            .unwrap();
+        assert!(!again.completed.contains(&quest.description.to_string()));
+
+        let quests = repository.load_quests(user.id, today).await.unwrap();
+        let loaded = quests.iter().find(|loaded| loaded.id == quest.id).unwrap();
+        assert!(loaded.completed);
+        assert_eq!(loaded.progress, loaded.target);
+    }
+}
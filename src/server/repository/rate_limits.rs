@@ -0,0 +1,337 @@
+use super::{Repository, RepositoryError as UserRepositoryError};
+
+/// How long a key's lockouts are remembered. Another lockout within this long of the last one
+/// doubles in length; after it, the key starts over at the shortest lockout.
+pub(super) const LOCKOUT_MEMORY_SECONDS: i64 = 24 * 60 * 60;
+const FIRST_LOCKOUT_SECONDS: i64 = 60;
+const MAX_LOCKOUT_SECONDS: i64 = 24 * 60 * 60;
+
+/// A sliding-window limit on how often one account or address may attempt something. Going over
+/// it locks the key out, for twice as long each time it happens again.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// Names the limit in storage; each limit counts its own attempts.
+    pub name: &'static str,
+    pub max_attempts: u32,
+    pub window_seconds: i64,
+}
+
+impl RateLimit {
+    /// Failed logins to one account.
+    pub const LOGIN_PER_ACCOUNT: Self = Self {
+        name: "login_per_account",
+        max_attempts: 5,
+        window_seconds: 15 * 60,
+    };
+    /// Logins from one address, successful or not. Looser than the per-account limit because
+    /// several players can share an address.
+    pub const LOGIN_PER_ADDRESS: Self = Self {
+        name: "login_per_address",
+        max_attempts: 20,
+        window_seconds: 15 * 60,
+    };
+    pub const REGISTER_PER_ADDRESS: Self = Self {
+        name: "register_per_address",
+        max_attempts: 5,
+        window_seconds: 60 * 60,
+    };
+    /// Confirmation and password reset emails sent to one address.
+    pub const SEND_CODE_PER_ACCOUNT: Self = Self {
+        name: "send_code_per_account",
+        max_attempts: 3,
+        window_seconds: 15 * 60,
+    };
+    pub const SEND_CODE_PER_ADDRESS: Self = Self {
+        name: "send_code_per_address",
+        max_attempts: 10,
+        window_seconds: 15 * 60,
+    };
+    /// Confirmation and password reset codes entered from one address. Each account already
+    /// caps its own wrong codes, so this stops one address guessing across many accounts.
+    pub const ENTER_CODE_PER_ADDRESS: Self = Self {
+        name: "enter_code_per_address",
+        max_attempts: 20,
+        window_seconds: 15 * 60,
+    };
+    pub const REDEEM_BOOSTER_PER_ACCOUNT: Self = Self {
+        name: "redeem_booster_per_account",
+        max_attempts: 10,
+        window_seconds: 60,
+    };
+    pub const REDEEM_BOOSTER_PER_ADDRESS: Self = Self {
+        name: "redeem_booster_per_address",
+        max_attempts: 30,
+        window_seconds: 60,
+    };
+
+    /// Every limit, so expired attempts can be swept without waiting for their key to return.
+    pub const ALL: [Self; 8] = [
+        Self::LOGIN_PER_ACCOUNT,
+        Self::LOGIN_PER_ADDRESS,
+        Self::REGISTER_PER_ADDRESS,
+        Self::SEND_CODE_PER_ACCOUNT,
+        Self::SEND_CODE_PER_ADDRESS,
+        Self::ENTER_CODE_PER_ADDRESS,
+        Self::REDEEM_BOOSTER_PER_ACCOUNT,
+        Self::REDEEM_BOOSTER_PER_ADDRESS,
+    ];
+}
+
+/// The lockout after a limit was exceeded, given the key's previous lockout if it had one.
+/// Returns the new lockout count and when it ends.
+pub(super) fn next_lockout(previous: Option<(u32, i64)>, now: i64) -> (u32, i64) {
+    let lockouts = match previous {
+        Some((lockouts, locked_until)) if now - locked_until < LOCKOUT_MEMORY_SECONDS => {
+            lockouts + 1
+        }
+        _ => 1,
+    };
+    let seconds = FIRST_LOCKOUT_SECONDS
+        .saturating_mul(1 << (lockouts - 1).min(20))
+        .min(MAX_LOCKOUT_SECONDS);
+    (lockouts, now + seconds)
+}
+
+impl Repository {
+    /// Count an attempt by `key` against `limit`, refusing it while the key is locked out or
+    /// once the attempt goes over the limit.
+    pub async fn record_attempt(
+        &self,
+        limit: RateLimit,
+        key: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        let now = now.timestamp();
+        let mut transaction = self.pool.begin().await?;
+        let lockout: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT CAST(lockouts AS BIGINT), CAST(locked_until AS BIGINT)
+             FROM rate_limit_lockouts WHERE rate_limit = ?1 AND key = ?2",
+        )
+        .bind(limit.name)
+        .bind(key)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        if let Some((_, locked_until)) = lockout
+            && locked_until > now
+        {
+            return Err(UserRepositoryError::RateLimited {
+                retry_after_seconds: (locked_until - now) as u64,
+            });
+        }
+
+        sqlx::query(
+            "DELETE FROM rate_limit_attempts
+             WHERE rate_limit = ?1 AND key = ?2 AND attempted_at <= ?3",
+        )
+        .bind(limit.name)
+        .bind(key)
+        .bind(now - limit.window_seconds)
+        .execute(&mut *transaction)
+        .await?;
+        let attempts: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM rate_limit_attempts WHERE rate_limit = ?1 AND key = ?2",
+        )
+        .bind(limit.name)
+        .bind(key)
+        .fetch_one(&mut *transaction)
+        .await?;
+        if attempts < i64::from(limit.max_attempts) {
+            sqlx::query(
+                "INSERT INTO rate_limit_attempts (rate_limit, key, attempted_at)
+                 VALUES (?1, ?2, ?3)",
+            )
+            .bind(limit.name)
+            .bind(key)
+            .bind(now)
+            .execute(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            return Ok(());
+        }
+
+        // The lockout replaces the window, so the key starts afresh once it ends.
+        let (lockouts, locked_until) = next_lockout(
+            lockout.map(|(lockouts, locked_until)| (lockouts.max(1) as u32, locked_until)),
+            now,
+        );
+        sqlx::query("DELETE FROM rate_limit_attempts WHERE rate_limit = ?1 AND key = ?2")
+            .bind(limit.name)
+            .bind(key)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query(
+            "INSERT INTO rate_limit_lockouts (rate_limit, key, lockouts, locked_until)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (rate_limit, key)
+             DO UPDATE SET lockouts = excluded.lockouts, locked_until = excluded.locked_until",
+        )
+        .bind(limit.name)
+        .bind(key)
+        .bind(i64::from(lockouts))
+        .bind(locked_until)
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Err(UserRepositoryError::RateLimited {
+            retry_after_seconds: (locked_until - now) as u64,
+        })
+    }
+
+    /// Delete attempts that fell out of their window and lockouts too old to double the next
+    /// one, for every key. Returns how many rows went.
+    pub async fn prune_rate_limits(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, UserRepositoryError> {
+        let now = now.timestamp();
+        let mut transaction = self.pool.begin().await?;
+        let mut pruned = 0;
+        for limit in RateLimit::ALL {
+            pruned += sqlx::query(
+                "DELETE FROM rate_limit_attempts WHERE rate_limit = ?1 AND attempted_at <= ?2",
+            )
+            .bind(limit.name)
+            .bind(now - limit.window_seconds)
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected();
+        }
+        pruned += sqlx::query("DELETE FROM rate_limit_lockouts WHERE locked_until <= ?1")
+            .bind(now - LOCKOUT_MEMORY_SECONDS)
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected();
+        transaction.commit().await?;
+        Ok(pruned)
+    }
+
+    /// Forget `key`'s attempts and lockouts under `limit`, once it proved it isn't guessing.
+    pub async fn clear_attempts(
+        &self,
+        limit: RateLimit,
+        key: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("DELETE FROM rate_limit_attempts WHERE rate_limit = ?1 AND key = ?2")
+            .bind(limit.name)
+            .bind(key)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query("DELETE FROM rate_limit_lockouts WHERE rate_limit = ?1 AND key = ?2")
+            .bind(limit.name)
+            .bind(key)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimit, Repository, UserRepositoryError};
+    use chrono::{Duration, Utc};
+
+    fn retry_after(result: Result<(), UserRepositoryError>) -> Option<u64> {
+        match result {
+            Err(UserRepositoryError::RateLimited {
+                retry_after_seconds,
+            }) => Some(retry_after_seconds),
+            _ => None,
+        }
+    }
+
+    async fn rows(repository: &Repository, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(&repository.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn lockouts_double_until_the_key_succeeds() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let limit = RateLimit::LOGIN_PER_ACCOUNT;
+        let start = Utc::now();
+        for _ in 0..limit.max_attempts {
+            repository
+                .record_attempt(limit, "mage@example.com", start)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            retry_after(
+                repository
+                    .record_attempt(limit, "mage@example.com", start)
+                    .await
+            ),
+            Some(60)
+        );
+        assert_eq!(
+            retry_after(
+                repository
+                    .record_attempt(limit, "mage@example.com", start + Duration::seconds(30))
+                    .await
+            ),
+            Some(30)
+        );
+        // Other keys are unaffected.
+        repository
+            .record_attempt(limit, "other@example.com", start)
+            .await
+            .unwrap();
+
+        let later = start + Duration::seconds(60);
+        for _ in 0..limit.max_attempts {
+            repository
+                .record_attempt(limit, "mage@example.com", later)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            retry_after(
+                repository
+                    .record_attempt(limit, "mage@example.com", later)
+                    .await
+            ),
+            Some(120)
+        );
+
+        repository
+            .clear_attempts(limit, "mage@example.com")
+            .await
+            .unwrap();
+        repository
+            .record_attempt(limit, "mage@example.com", later)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn expired_attempts_and_lockouts_are_swept_for_every_key() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let start = Utc::now();
+        let limit = RateLimit::REDEEM_BOOSTER_PER_ACCOUNT;
+        for _ in 0..=limit.max_attempts {
+            let _ = repository.record_attempt(limit, "locked-out", start).await;
+        }
+        repository
+            .record_attempt(RateLimit::LOGIN_PER_ADDRESS, "127.0.0.1", start)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repository.prune_rate_limits(start).await.unwrap(),
+            0,
+            "nothing has expired yet"
+        );
+        let after_window = start + Duration::seconds(RateLimit::LOGIN_PER_ADDRESS.window_seconds);
+        assert_eq!(repository.prune_rate_limits(after_window).await.unwrap(), 1);
+        assert_eq!(rows(&repository, "rate_limit_attempts").await, 0);
+        assert_eq!(rows(&repository, "rate_limit_lockouts").await, 1);
+
+        let forgotten = start + Duration::days(2);
+        assert_eq!(repository.prune_rate_limits(forgotten).await.unwrap(), 1);
+        assert_eq!(rows(&repository, "rate_limit_lockouts").await, 0);
+    }
+}
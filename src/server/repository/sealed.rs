@@ -0,0 +1,137 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use sorcerers::sealed::SealedPool;
+
+use super::{Repository, RepositoryError as UserRepositoryError, TIMESTAMP_FORMAT};
+
+/// How long a player keeps a sealed pool before they can open another, so a pool can't be
+/// rerolled until it is a good one.
+pub(super) const SEALED_POOL_HOURS: i64 = 24;
+
+impl Repository {
+    /// Store the pool of a new sealed event, replacing the user's previous one once it is
+    /// [`SEALED_POOL_HOURS`] old. Sealed pools are kept apart from `user_cards` so they never
+    /// grow the permanent collection.
+    pub async fn start_sealed_event(
+        &self,
+        user_id: uuid::Uuid,
+        pool: &SealedPool,
+    ) -> Result<(), UserRepositoryError> {
+        let cards =
+            serde_json::to_string(&pool.cards).map_err(|_| UserRepositoryError::Serialization)?;
+        let replaceable_before = (Utc::now() - Duration::hours(SEALED_POOL_HOURS))
+            .format(TIMESTAMP_FORMAT)
+            .to_string();
+        let started = sqlx::query(
+            "INSERT INTO sealed_pools (user_id, id, cards) VALUES (?1, ?2, ?3)
+             ON CONFLICT (user_id)
+             DO UPDATE SET id = excluded.id, cards = excluded.cards, created_at = CURRENT_TIMESTAMP
+             WHERE sealed_pools.created_at <= ?4",
+        )
+        .bind(user_id.to_string())
+        .bind(pool.id.to_string())
+        .bind(cards)
+        .bind(replaceable_before)
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if started {
+            return Ok(());
+        }
+
+        let created_at: String = sqlx::query_scalar(
+            "SELECT CAST(created_at AS TEXT) FROM sealed_pools WHERE user_id = ?1",
+        )
+        .bind(user_id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+        let created_at = NaiveDateTime::parse_from_str(&created_at, TIMESTAMP_FORMAT)
+            .map_err(|_| UserRepositoryError::Serialization)?;
+        Err(UserRepositoryError::SealedPoolTooRecent {
+            available_at: (created_at + Duration::hours(SEALED_POOL_HOURS))
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+        })
+    }
+
+    pub async fn load_sealed_pool(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<SealedPool>, UserRepositoryError> {
+        let pool: Option<(String, String)> = sqlx::query_as(
+            "SELECT CAST(id AS TEXT), CAST(cards AS TEXT) FROM sealed_pools WHERE user_id = ?1",
+        )
+        .bind(user_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        pool.map(|(id, cards)| {
+            Ok(SealedPool {
+                id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+                cards: serde_json::from_str(&cards)
+                    .map_err(|_| UserRepositoryError::Serialization)?,
+            })
+        })
+        .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Repository, RepositoryError, confirmed_user};
+    use sorcerers::sealed::SealedPool;
+
+    #[tokio::test]
+    async fn sealed_pools_last_a_day_and_stay_out_of_the_collection() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let user = confirmed_user(&repository, "sealed_mage").await;
+        assert!(
+            repository
+                .load_sealed_pool(user.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let first = SealedPool::open();
+        repository
+            .start_sealed_event(user.id, &first)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .start_sealed_event(user.id, &SealedPool::open())
+                .await,
+            Err(RepositoryError::SealedPoolTooRecent { .. })
+        ));
+        assert_eq!(
+            repository
+                .load_sealed_pool(user.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .id,
+            first.id
+        );
+
+        sqlx::query("UPDATE sealed_pools SET created_at = datetime('now', '-25 hours')")
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        let second = SealedPool::open();
+        repository
+            .start_sealed_event(user.id, &second)
+            .await
+            .unwrap();
+
+        let stored = repository.load_sealed_pool(user.id).await.unwrap().unwrap();
+        assert_eq!(stored.id, second.id);
+        assert_eq!(stored.cards.len(), second.cards.len());
+        assert!(
+            repository
+                .load_collection(user.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
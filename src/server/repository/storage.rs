@@ -0,0 +1,778 @@
+use sorcerers::{
+    booster::{BoosterPack, UnopenedBoosterPack},
+    collection::CollectedCard,
+    deck::{CardNameWithCount, DeckList, precon::PreconDeck},
+    moderation::ReportReason,
+    quest::{QuestProgress, QuestStats},
+    sealed::SealedPool,
+    trade::{TradeOffer, TradeSide},
+};
+use std::future::Future;
+
+use super::{
+    DrainRequest, RateLimit, Repository, RepositoryError, Timings, TradeUpdate, User,
+    booster_packs::MatchReward, quests::QuestReward, users::PendingEmailConfirmation,
+};
+
+/// Everything the server keeps about its players. [`super::Repository`] stores it in SQLite; the
+/// tests use an in-memory implementation so server flows run without a database.
+///
+/// Implementations are cheap to clone and share their data between clones.
+pub trait Storage: Clone + Send + Sync + 'static {
+    /// How long calls into the database take, for storage that has one.
+    fn query_timings(&self) -> Option<&Timings> {
+        None
+    }
+
+    // Accounts
+
+    /// Create an unconfirmed account and return the code that confirms its email address.
+    fn register(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> impl Future<Output = Result<PendingEmailConfirmation, RepositoryError>> + Send;
+
+    fn verify_login(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> impl Future<Output = Result<User, RepositoryError>> + Send;
+
+    /// Replace the confirmation code of an unconfirmed account.
+    fn resend_email_confirmation(
+        &self,
+        email: &str,
+    ) -> impl Future<Output = Result<PendingEmailConfirmation, RepositoryError>> + Send;
+
+    fn confirm_email(
+        &self,
+        email: &str,
+        code: &str,
+    ) -> impl Future<Output = Result<User, RepositoryError>> + Send;
+
+    fn selected_starter_deck(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<PreconDeck>, RepositoryError>> + Send;
+
+    /// Record the starter deck, grant its cards and save it as the user's first deck, all at once.
+    fn complete_starter_selection(
+        &self,
+        user_id: uuid::Uuid,
+        starter_deck: &PreconDeck,
+        deck: &DeckList,
+        cards: &[CardNameWithCount],
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Issue a password reset code, or nothing if no account uses `email`.
+    fn request_password_reset(
+        &self,
+        email: &str,
+    ) -> impl Future<Output = Result<Option<PendingEmailConfirmation>, RepositoryError>> + Send;
+
+    /// Replace a forgotten password using a reset code, confirming the email address if needed.
+    fn reset_password(
+        &self,
+        email: &str,
+        code: &str,
+        new_password: &str,
+    ) -> impl Future<Output = Result<User, RepositoryError>> + Send;
+
+    /// Replace the password after checking the current one, returning the account's email
+    /// address so the player can be told about the change.
+    fn change_password(
+        &self,
+        user_id: uuid::Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> impl Future<Output = Result<String, RepositoryError>> + Send;
+
+    /// Move the account to a new, unconfirmed address and return the code that confirms it.
+    fn change_email(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+        new_email: &str,
+    ) -> impl Future<Output = Result<PendingEmailConfirmation, RepositoryError>> + Send;
+
+    fn change_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Delete the account and everything stored for it.
+    fn delete_account(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    // Decks and cards
+
+    fn load_decks(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<DeckList>, RepositoryError>> + Send;
+
+    fn load_collection(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<CollectedCard>, RepositoryError>> + Send;
+
+    /// Offer a trade to another player. The proposer confirms the terms by proposing them.
+    fn propose_trade(
+        &self,
+        proposer_id: uuid::Uuid,
+        recipient: &str,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send;
+
+    /// Replace the terms of an open trade. The player countering confirms the new terms, and the
+    /// other player has to confirm them again.
+    fn counter_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send;
+
+    /// Confirm the current terms of a trade. Once both players have confirmed, holdings are
+    /// checked again and everything changes hands at once.
+    fn confirm_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        unlock_decks: bool,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send;
+
+    /// Decline an incoming trade or withdraw one the player proposed.
+    fn decline_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send;
+
+    /// The open trades the player takes part in, newest activity first.
+    fn load_trades(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<TradeOffer>, RepositoryError>> + Send;
+
+    // Rewards and boosters
+
+    fn reward_points(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send;
+
+    /// Pay out `points` for a finished game. Each game only pays once per player.
+    fn award_match_points(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        points: u32,
+    ) -> impl Future<Output = Result<MatchReward, RepositoryError>> + Send;
+
+    /// Spend `cost` reward points on `pack`.
+    fn redeem_beta_booster(
+        &self,
+        user_id: uuid::Uuid,
+        pack: BoosterPack,
+        cost: u32,
+    ) -> impl Future<Output = Result<(u32, UnopenedBoosterPack), RepositoryError>> + Send;
+
+    /// Grant `packs` unless the user already claimed the week starting on `week_start`.
+    fn claim_weekly_boosters(
+        &self,
+        user_id: uuid::Uuid,
+        week_start: chrono::NaiveDate,
+        packs: &[BoosterPack],
+    ) -> impl Future<Output = Result<bool, RepositoryError>> + Send;
+
+    fn load_unopened_booster_packs(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<UnopenedBoosterPack>, RepositoryError>> + Send;
+
+    /// Open one of the user's packs and add its cards to their collection.
+    fn open_booster_pack(
+        &self,
+        user_id: uuid::Uuid,
+        pack_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<BoosterPack>, RepositoryError>> + Send;
+
+    // Sealed events
+
+    /// Store the pool of a new sealed event, replacing the user's previous one.
+    fn start_sealed_event(
+        &self,
+        user_id: uuid::Uuid,
+        pool: &SealedPool,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn load_sealed_pool(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<SealedPool>, RepositoryError>> + Send;
+
+    // Crafting
+
+    fn crafting_dust(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send;
+
+    /// Disenchant every copy beyond the deck copy limits that no saved deck relies on, and return
+    /// the new dust balance.
+    fn dust_extra_copies(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send;
+
+    /// Craft `cards` into the collection, and return the new dust balance.
+    fn craft_cards(
+        &self,
+        user_id: uuid::Uuid,
+        cards: &[CardNameWithCount],
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send;
+
+    // Quests and achievements
+
+    /// The quests active on `today`, with the player's progress on each.
+    fn load_quests(
+        &self,
+        user_id: uuid::Uuid,
+        today: chrono::NaiveDate,
+    ) -> impl Future<Output = Result<Vec<QuestProgress>, RepositoryError>> + Send;
+
+    /// Advance the active quests with what the player did in a finished game, paying out reward
+    /// points for every quest that reaches its target. Each game only counts once per player.
+    fn record_quest_progress(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        stats: &QuestStats,
+        today: chrono::NaiveDate,
+    ) -> impl Future<Output = Result<QuestReward, RepositoryError>> + Send;
+
+    /// Record the achievements earned in a game and return the ids the player did not have yet.
+    fn unlock_achievements(
+        &self,
+        user_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        achievement_ids: &[&str],
+    ) -> impl Future<Output = Result<Vec<String>, RepositoryError>> + Send;
+
+    // Rate limits
+
+    /// Count an attempt by `key` against `limit`, failing with
+    /// [`RepositoryError::RateLimited`] while the key is locked out.
+    fn record_attempt(
+        &self,
+        limit: RateLimit,
+        key: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn clear_attempts(
+        &self,
+        limit: RateLimit,
+        key: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Sweep expired attempts and lockouts for every key, returning how many were removed.
+    fn prune_rate_limits(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<u64, RepositoryError>> + Send;
+
+    // Live games
+
+    /// Note a match that just started, so operators can see it with `sorcerers-admin games`.
+    fn start_live_game(
+        &self,
+        game_id: uuid::Uuid,
+        players: [&str; 2],
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn finish_live_game(
+        &self,
+        game_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// The drain `sorcerers-admin drain` asked for, if one is in effect.
+    fn drain_request(
+        &self,
+    ) -> impl Future<Output = Result<Option<DrainRequest>, RepositoryError>> + Send;
+
+    // Moderation
+
+    /// Fail with [`RepositoryError::AccountBanned`] while the user's ban is in force.
+    fn check_ban(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn blocked_players(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<User>, RepositoryError>> + Send;
+
+    fn block_player(
+        &self,
+        user_id: uuid::Uuid,
+        blocked_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn block_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn unblock_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Report the opponent of a finished match, at most once per match.
+    fn report_player(
+        &self,
+        reporter_id: uuid::Uuid,
+        reported_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        reason: ReportReason,
+        details: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+}
+
+/// The SQLite implementation lives with each table's queries; this forwards to it and times
+/// each call.
+impl Storage for Repository {
+    fn query_timings(&self) -> Option<&Timings> {
+        Some(&self.query_timings)
+    }
+
+    fn register(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> impl Future<Output = Result<PendingEmailConfirmation, RepositoryError>> + Send {
+        self.timed(Repository::register(self, username, email, password))
+    }
+
+    fn verify_login(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> impl Future<Output = Result<User, RepositoryError>> + Send {
+        self.timed(Repository::verify_login(self, email, password))
+    }
+
+    fn resend_email_confirmation(
+        &self,
+        email: &str,
+    ) -> impl Future<Output = Result<PendingEmailConfirmation, RepositoryError>> + Send {
+        self.timed(Repository::resend_email_confirmation(self, email))
+    }
+
+    fn confirm_email(
+        &self,
+        email: &str,
+        code: &str,
+    ) -> impl Future<Output = Result<User, RepositoryError>> + Send {
+        self.timed(Repository::confirm_email(self, email, code))
+    }
+
+    fn selected_starter_deck(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<PreconDeck>, RepositoryError>> + Send {
+        self.timed(Repository::selected_starter_deck(self, user_id))
+    }
+
+    fn complete_starter_selection(
+        &self,
+        user_id: uuid::Uuid,
+        starter_deck: &PreconDeck,
+        deck: &DeckList,
+        cards: &[CardNameWithCount],
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::complete_starter_selection(
+            self,
+            user_id,
+            starter_deck,
+            deck,
+            cards,
+        ))
+    }
+
+    fn request_password_reset(
+        &self,
+        email: &str,
+    ) -> impl Future<Output = Result<Option<PendingEmailConfirmation>, RepositoryError>> + Send
+    {
+        self.timed(Repository::request_password_reset(self, email))
+    }
+
+    fn reset_password(
+        &self,
+        email: &str,
+        code: &str,
+        new_password: &str,
+    ) -> impl Future<Output = Result<User, RepositoryError>> + Send {
+        self.timed(Repository::reset_password(self, email, code, new_password))
+    }
+
+    fn change_password(
+        &self,
+        user_id: uuid::Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> impl Future<Output = Result<String, RepositoryError>> + Send {
+        self.timed(Repository::change_password(
+            self,
+            user_id,
+            current_password,
+            new_password,
+        ))
+    }
+
+    fn change_email(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+        new_email: &str,
+    ) -> impl Future<Output = Result<PendingEmailConfirmation, RepositoryError>> + Send {
+        self.timed(Repository::change_email(self, user_id, password, new_email))
+    }
+
+    fn change_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::change_username(self, user_id, username))
+    }
+
+    fn delete_account(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::delete_account(self, user_id, password))
+    }
+
+    fn load_decks(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<DeckList>, RepositoryError>> + Send {
+        self.timed(Repository::load_decks(self, user_id))
+    }
+
+    fn load_collection(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<CollectedCard>, RepositoryError>> + Send {
+        self.timed(Repository::load_collection(self, user_id))
+    }
+
+    fn propose_trade(
+        &self,
+        proposer_id: uuid::Uuid,
+        recipient: &str,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send {
+        self.timed(Repository::propose_trade(
+            self,
+            proposer_id,
+            recipient,
+            offered,
+            requested,
+            unlock_decks,
+        ))
+    }
+
+    fn counter_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        offered: &TradeSide,
+        requested: &TradeSide,
+        unlock_decks: bool,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send {
+        self.timed(Repository::counter_trade(
+            self,
+            user_id,
+            trade_id,
+            offered,
+            requested,
+            unlock_decks,
+        ))
+    }
+
+    fn confirm_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+        unlock_decks: bool,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send {
+        self.timed(Repository::confirm_trade(
+            self,
+            user_id,
+            trade_id,
+            unlock_decks,
+        ))
+    }
+
+    fn decline_trade(
+        &self,
+        user_id: uuid::Uuid,
+        trade_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<TradeUpdate, RepositoryError>> + Send {
+        self.timed(Repository::decline_trade(self, user_id, trade_id))
+    }
+
+    fn load_trades(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<TradeOffer>, RepositoryError>> + Send {
+        self.timed(Repository::load_trades(self, user_id))
+    }
+
+    fn reward_points(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send {
+        self.timed(Repository::reward_points(self, user_id))
+    }
+
+    fn award_match_points(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        points: u32,
+    ) -> impl Future<Output = Result<MatchReward, RepositoryError>> + Send {
+        self.timed(Repository::award_match_points(
+            self, game_id, user_id, points,
+        ))
+    }
+
+    fn redeem_beta_booster(
+        &self,
+        user_id: uuid::Uuid,
+        pack: BoosterPack,
+        cost: u32,
+    ) -> impl Future<Output = Result<(u32, UnopenedBoosterPack), RepositoryError>> + Send {
+        self.timed(Repository::redeem_beta_booster(self, user_id, pack, cost))
+    }
+
+    fn claim_weekly_boosters(
+        &self,
+        user_id: uuid::Uuid,
+        week_start: chrono::NaiveDate,
+        packs: &[BoosterPack],
+    ) -> impl Future<Output = Result<bool, RepositoryError>> + Send {
+        self.timed(Repository::claim_weekly_boosters(
+            self, user_id, week_start, packs,
+        ))
+    }
+
+    fn load_unopened_booster_packs(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<UnopenedBoosterPack>, RepositoryError>> + Send {
+        self.timed(Repository::load_unopened_booster_packs(self, user_id))
+    }
+
+    fn open_booster_pack(
+        &self,
+        user_id: uuid::Uuid,
+        pack_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<BoosterPack>, RepositoryError>> + Send {
+        self.timed(Repository::open_booster_pack(self, user_id, pack_id))
+    }
+
+    fn start_sealed_event(
+        &self,
+        user_id: uuid::Uuid,
+        pool: &SealedPool,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::start_sealed_event(self, user_id, pool))
+    }
+
+    fn load_sealed_pool(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<SealedPool>, RepositoryError>> + Send {
+        self.timed(Repository::load_sealed_pool(self, user_id))
+    }
+
+    fn crafting_dust(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send {
+        self.timed(Repository::crafting_dust(self, user_id))
+    }
+
+    fn dust_extra_copies(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send {
+        self.timed(Repository::dust_extra_copies(self, user_id))
+    }
+
+    fn craft_cards(
+        &self,
+        user_id: uuid::Uuid,
+        cards: &[CardNameWithCount],
+    ) -> impl Future<Output = Result<u32, RepositoryError>> + Send {
+        self.timed(Repository::craft_cards(self, user_id, cards))
+    }
+
+    fn load_quests(
+        &self,
+        user_id: uuid::Uuid,
+        today: chrono::NaiveDate,
+    ) -> impl Future<Output = Result<Vec<QuestProgress>, RepositoryError>> + Send {
+        self.timed(Repository::load_quests(self, user_id, today))
+    }
+
+    fn record_quest_progress(
+        &self,
+        game_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        stats: &QuestStats,
+        today: chrono::NaiveDate,
+    ) -> impl Future<Output = Result<QuestReward, RepositoryError>> + Send {
+        self.timed(Repository::record_quest_progress(
+            self, game_id, user_id, stats, today,
+        ))
+    }
+
+    fn unlock_achievements(
+        &self,
+        user_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        achievement_ids: &[&str],
+    ) -> impl Future<Output = Result<Vec<String>, RepositoryError>> + Send {
+        self.timed(Repository::unlock_achievements(
+            self,
+            user_id,
+            game_id,
+            achievement_ids,
+        ))
+    }
+
+    fn record_attempt(
+        &self,
+        limit: RateLimit,
+        key: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::record_attempt(self, limit, key, now))
+    }
+
+    fn clear_attempts(
+        &self,
+        limit: RateLimit,
+        key: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::clear_attempts(self, limit, key))
+    }
+
+    fn prune_rate_limits(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<u64, RepositoryError>> + Send {
+        self.timed(Repository::prune_rate_limits(self, now))
+    }
+
+    fn start_live_game(
+        &self,
+        game_id: uuid::Uuid,
+        players: [&str; 2],
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::start_live_game(self, game_id, players))
+    }
+
+    fn finish_live_game(
+        &self,
+        game_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::finish_live_game(self, game_id))
+    }
+
+    fn drain_request(
+        &self,
+    ) -> impl Future<Output = Result<Option<DrainRequest>, RepositoryError>> + Send {
+        self.timed(Repository::drain_request(self))
+    }
+
+    fn check_ban(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::check_ban(self, user_id))
+    }
+
+    fn blocked_players(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<User>, RepositoryError>> + Send {
+        self.timed(Repository::blocked_players(self, user_id))
+    }
+
+    fn block_player(
+        &self,
+        user_id: uuid::Uuid,
+        blocked_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::block_player(self, user_id, blocked_id))
+    }
+
+    fn block_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::block_username(self, user_id, username))
+    }
+
+    fn unblock_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::unblock_username(self, user_id, username))
+    }
+
+    fn report_player(
+        &self,
+        reporter_id: uuid::Uuid,
+        reported_id: uuid::Uuid,
+        game_id: uuid::Uuid,
+        reason: ReportReason,
+        details: &str,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send {
+        self.timed(Repository::report_player(
+            self,
+            reporter_id,
+            reported_id,
+            game_id,
+            reason,
+            details,
+        ))
+    }
+}
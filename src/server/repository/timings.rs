@@ -0,0 +1,74 @@
+//! Running totals of how long an operation takes, for the server's metrics.
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use super::Repository;
+
+#[derive(Debug, Default)]
+pub struct Timings {
+    count: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+/// What [`Timings`] has seen so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingSnapshot {
+    pub count: u64,
+    pub total: Duration,
+    /// The slowest run since the previous snapshot.
+    pub max: Duration,
+}
+
+impl Timings {
+    pub fn record(&self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// Read the totals and start a new window for the slowest run.
+    pub fn snapshot(&self) -> TimingSnapshot {
+        TimingSnapshot {
+            count: self.count.load(Ordering::Relaxed),
+            total: Duration::from_micros(self.total_micros.load(Ordering::Relaxed)),
+            max: Duration::from_micros(self.max_micros.swap(0, Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Repository {
+    /// Run a storage call and record how long it took.
+    pub(super) async fn timed<T>(&self, call: impl Future<Output = T>) -> T {
+        let started = Instant::now();
+        let result = call.await;
+        self.query_timings.record(started.elapsed());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_total_every_run_and_reset_the_slowest() {
+        let timings = Timings::default();
+        timings.record(Duration::from_millis(3));
+        timings.record(Duration::from_millis(5));
+
+        let snapshot = timings.snapshot();
+        assert_eq!(snapshot.count, 2);
+        assert_eq!(snapshot.total, Duration::from_millis(8));
+        assert_eq!(snapshot.max, Duration::from_millis(5));
+
+        timings.record(Duration::from_millis(1));
+        let snapshot = timings.snapshot();
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(snapshot.max, Duration::from_millis(1));
+    }
+}
@@ -6,13 +6,13 @@ use chrono::{Duration, Utc};
 use email_address::EmailAddress;
 use sorcerers::deck::{CardNameWithCount, DeckList, precon::PreconDeck};
 
-use super::{Repository, RepositoryError as UserRepositoryError};
+use super::{Repository, RepositoryError as UserRepositoryError, TIMESTAMP_FORMAT};
 
 const MIN_USERNAME_LENGTH: usize = 3;
 const MAX_USERNAME_LENGTH: usize = 32;
-const MIN_PASSWORD_LENGTH: usize = 8;
-const CONFIRMATION_CODE_LIFETIME_MINUTES: i64 = 15;
-const MAX_CONFIRMATION_ATTEMPTS: i16 = 5;
+pub(super) const MIN_PASSWORD_LENGTH: usize = 8;
+pub const CONFIRMATION_CODE_LIFETIME_MINUTES: i64 = 15;
+pub(super) const MAX_CONFIRMATION_ATTEMPTS: i16 = 5;
 
 #[derive(Clone)]
 pub struct User {
@@ -38,13 +38,9 @@ impl Repository {
             return Err(UserRepositoryError::InvalidPassword);
         }
 
-        let password_hash = Argon2::default()
-            .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
-            .map_err(|_| UserRepositoryError::Password)?
-            .to_string();
+        let password_hash = hash_password(password)?;
         let pending = new_pending_email_confirmation(email)?;
         let id = uuid::Uuid::new_v4();
-        let expires_at = Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES);
         let result = sqlx::query(
             "INSERT INTO users (id, username, email, password_hash, confirmation_code_hash, confirmation_code_expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         )
@@ -53,7 +49,7 @@ impl Repository {
             .bind(&pending.email)
             .bind(password_hash)
             .bind(hash_confirmation_code(&pending.code)?)
-            .bind(expires_at.to_rfc3339())
+            .bind(code_expires_at())
             .execute(&self.pool)
             .await;
         match result {
@@ -110,12 +106,11 @@ impl Repository {
     ) -> Result<PendingEmailConfirmation, UserRepositoryError> {
         validate_email(email)?;
         let pending = new_pending_email_confirmation(email)?;
-        let expires_at = Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES);
         let result = sqlx::query(
             "UPDATE users SET confirmation_code_hash = ?1, confirmation_code_expires_at = ?2, confirmation_attempts = 0 WHERE email = ?3 AND email_confirmed_at IS NULL",
         )
         .bind(hash_confirmation_code(&pending.code)?)
-        .bind(expires_at.to_rfc3339())
+        .bind(code_expires_at())
         .bind(&pending.email)
         .execute(&self.pool)
         .await?;
@@ -146,16 +141,7 @@ impl Repository {
         if attempts >= i64::from(MAX_CONFIRMATION_ATTEMPTS) {
             return Err(UserRepositoryError::ConfirmationAttemptsExceeded);
         }
-        let is_valid = code_not_expired
-            && code_hash
-                .as_deref()
-                .and_then(|hash| PasswordHash::new(hash).ok())
-                .is_some_and(|hash| {
-                    Argon2::default()
-                        .verify_password(code.as_bytes(), &hash)
-                        .is_ok()
-                });
-        if !is_valid {
+        if !(code_not_expired && code_matches(code_hash.as_deref(), code)) {
             sqlx::query(
                 "UPDATE users SET confirmation_attempts = confirmation_attempts + 1 WHERE id = ?1",
             )
@@ -239,9 +225,192 @@ impl Repository {
         transaction.commit().await?;
         Ok(())
     }
+
+    /// Issue a password reset code for `email`. Unknown addresses get no code; callers should
+    /// answer exactly as they would for a known one.
+    pub async fn request_password_reset(
+        &self,
+        email: &str,
+    ) -> Result<Option<PendingEmailConfirmation>, UserRepositoryError> {
+        let pending = new_pending_email_confirmation(email)?;
+        let result = sqlx::query(
+            "UPDATE users SET password_reset_code_hash = ?1, password_reset_expires_at = ?2, password_reset_attempts = 0 WHERE email = ?3",
+        )
+        .bind(hash_confirmation_code(&pending.code)?)
+        .bind(code_expires_at())
+        .bind(&pending.email)
+        .execute(&self.pool)
+        .await?;
+        Ok((result.rows_affected() > 0).then_some(pending))
+    }
+
+    /// Replace a forgotten password using a reset code. The code proves the player reads that
+    /// inbox, so an unconfirmed email address is confirmed as well.
+    pub async fn reset_password(
+        &self,
+        email: &str,
+        code: &str,
+        new_password: &str,
+    ) -> Result<User, UserRepositoryError> {
+        validate_email(email)?;
+        if new_password.len() < MIN_PASSWORD_LENGTH {
+            return Err(UserRepositoryError::InvalidPassword);
+        }
+        let row: Option<(String, String, Option<String>, bool, i64)> = sqlx::query_as(
+            "SELECT CAST(id AS TEXT), username, password_reset_code_hash,
+                    password_reset_expires_at > CURRENT_TIMESTAMP,
+                    CAST(password_reset_attempts AS BIGINT)
+             FROM users WHERE email = ?1",
+        )
+        .bind(email.trim().to_lowercase())
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((id, username, code_hash, code_not_expired, attempts)) = row else {
+            return Err(UserRepositoryError::InvalidConfirmationCode);
+        };
+        if attempts >= i64::from(MAX_CONFIRMATION_ATTEMPTS) {
+            return Err(UserRepositoryError::ConfirmationAttemptsExceeded);
+        }
+        if !(code_not_expired && code_matches(code_hash.as_deref(), code)) {
+            sqlx::query(
+                "UPDATE users SET password_reset_attempts = password_reset_attempts + 1 WHERE id = ?1",
+            )
+            .bind(&id)
+            .execute(&self.pool)
+            .await?;
+            return Err(UserRepositoryError::InvalidConfirmationCode);
+        }
+        sqlx::query(
+            "UPDATE users SET password_hash = ?1,
+                password_reset_code_hash = NULL, password_reset_expires_at = NULL,
+                password_reset_attempts = 0,
+                email_confirmed_at = COALESCE(email_confirmed_at, CURRENT_TIMESTAMP),
+                confirmation_code_hash = NULL, confirmation_code_expires_at = NULL,
+                confirmation_attempts = 0
+             WHERE id = ?2",
+        )
+        .bind(hash_password(new_password)?)
+        .bind(&id)
+        .execute(&self.pool)
+        .await?;
+        Ok(User {
+            id: id.parse().map_err(|_| UserRepositoryError::Serialization)?,
+            username,
+        })
+    }
+
+    /// Replace the password and return the account's email address.
+    pub async fn change_password(
+        &self,
+        user_id: uuid::Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<String, UserRepositoryError> {
+        if new_password.len() < MIN_PASSWORD_LENGTH {
+            return Err(UserRepositoryError::InvalidPassword);
+        }
+        self.check_password(user_id, current_password).await?;
+        let email = sqlx::query_scalar(
+            "UPDATE users SET password_hash = ?1,
+                password_reset_code_hash = NULL, password_reset_expires_at = NULL
+             WHERE id = ?2
+             RETURNING email",
+        )
+        .bind(hash_password(new_password)?)
+        .bind(user_id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(email)
+    }
+
+    /// Move the account to a new address. The new address starts unconfirmed, so the returned
+    /// code has to be entered before the next login.
+    pub async fn change_email(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+        new_email: &str,
+    ) -> Result<PendingEmailConfirmation, UserRepositoryError> {
+        validate_email(new_email)?;
+        self.check_password(user_id, password).await?;
+        let pending = new_pending_email_confirmation(new_email)?;
+        let result = sqlx::query(
+            "UPDATE users SET email = ?1, email_confirmed_at = NULL,
+                confirmation_code_hash = ?2, confirmation_code_expires_at = ?3,
+                confirmation_attempts = 0,
+                password_reset_code_hash = NULL, password_reset_expires_at = NULL
+             WHERE id = ?4",
+        )
+        .bind(&pending.email)
+        .bind(hash_confirmation_code(&pending.code)?)
+        .bind(code_expires_at())
+        .bind(user_id.to_string())
+        .execute(&self.pool)
+        .await;
+        match result {
+            Ok(_) => Ok(pending),
+            Err(sqlx::Error::Database(error)) if is_unique_violation(error.as_ref()) => {
+                Err(UserRepositoryError::EmailTaken)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn change_username(
+        &self,
+        user_id: uuid::Uuid,
+        username: &str,
+    ) -> Result<(), UserRepositoryError> {
+        validate_username(username)?;
+        let result = sqlx::query("UPDATE users SET username = ?1 WHERE id = ?2")
+            .bind(username)
+            .bind(user_id.to_string())
+            .execute(&self.pool)
+            .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if is_unique_violation(error.as_ref()) => {
+                Err(UserRepositoryError::UsernameTaken)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Delete the account along with its collection, decks, trades and rewards.
+    pub async fn delete_account(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+    ) -> Result<(), UserRepositoryError> {
+        self.check_password(user_id, password).await?;
+        // Every other table references `users` with `ON DELETE CASCADE`.
+        sqlx::query("DELETE FROM users WHERE id = ?1")
+            .bind(user_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Make sure a logged-in player knows their password before a sensitive change.
+    async fn check_password(
+        &self,
+        user_id: uuid::Uuid,
+        password: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let password_hash: String =
+            sqlx::query_scalar("SELECT password_hash FROM users WHERE id = ?1")
+                .bind(user_id.to_string())
+                .fetch_one(&self.pool)
+                .await?;
+        let parsed_hash =
+            PasswordHash::new(&password_hash).map_err(|_| UserRepositoryError::Password)?;
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .map_err(|_| UserRepositoryError::IncorrectPassword)
+    }
 }
 
-fn validate_username(username: &str) -> Result<(), UserRepositoryError> {
+pub(super) fn validate_username(username: &str) -> Result<(), UserRepositoryError> {
     if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username.len())
         || !username
             .chars()
@@ -252,13 +421,13 @@ fn validate_username(username: &str) -> Result<(), UserRepositoryError> {
     Ok(())
 }
 
-fn validate_email(email: &str) -> Result<(), UserRepositoryError> {
+pub(super) fn validate_email(email: &str) -> Result<(), UserRepositoryError> {
     EmailAddress::is_valid(email.trim())
         .then_some(())
         .ok_or(UserRepositoryError::InvalidEmail)
 }
 
-fn new_pending_email_confirmation(
+pub(super) fn new_pending_email_confirmation(
     email: &str,
 ) -> Result<PendingEmailConfirmation, UserRepositoryError> {
     let email = email.trim().to_lowercase();
@@ -274,7 +443,31 @@ fn hash_confirmation_code(code: &str) -> Result<String, UserRepositoryError> {
         .map(|hash| hash.to_string())
 }
 
-fn is_unique_violation(error: &dyn sqlx::error::DatabaseError) -> bool {
+pub(super) fn hash_password(password: &str) -> Result<String, UserRepositoryError> {
+    Argon2::default()
+        .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map_err(|_| UserRepositoryError::Password)
+        .map(|hash| hash.to_string())
+}
+
+/// When a code issued now stops working, in the stored timestamp format.
+fn code_expires_at() -> String {
+    (Utc::now() + Duration::minutes(CONFIRMATION_CODE_LIFETIME_MINUTES))
+        .format(TIMESTAMP_FORMAT)
+        .to_string()
+}
+
+fn code_matches(code_hash: Option<&str>, code: &str) -> bool {
+    code_hash
+        .and_then(|hash| PasswordHash::new(hash).ok())
+        .is_some_and(|hash| {
+            Argon2::default()
+                .verify_password(code.as_bytes(), &hash)
+                .is_ok()
+        })
+}
+
+pub(super) fn is_unique_violation(error: &dyn sqlx::error::DatabaseError) -> bool {
     matches!(
         error.code().as_deref(),
         Some("23505") | Some("2067") | Some("1555")
@@ -283,7 +476,10 @@ fn is_unique_violation(error: &dyn sqlx::error::DatabaseError) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{new_pending_email_confirmation, validate_email, validate_username};
+    use super::{
+        super::{Repository, RepositoryError},
+        new_pending_email_confirmation, validate_email, validate_username,
+    };
 
     #[test]
     fn username_validation_rejects_unsafe_names() {
@@ -309,4 +505,176 @@ mod tests {
                 .all(|character| character.is_ascii_digit())
         );
     }
+
+    #[tokio::test]
+    async fn password_resets_and_account_changes() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        assert!(
+            repository
+                .request_password_reset("nobody@example.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // A reset confirms an address that was never confirmed.
+        repository
+            .register("mage_one", "mage@example.com", "very-secret-password")
+            .await
+            .unwrap();
+        let reset = repository
+            .request_password_reset("Mage@Example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        let wrong_code = if reset.code == "000000" {
+            "111111"
+        } else {
+            "000000"
+        };
+        assert!(matches!(
+            repository
+                .reset_password("mage@example.com", wrong_code, "another-secret")
+                .await,
+            Err(RepositoryError::InvalidConfirmationCode)
+        ));
+        let user = repository
+            .reset_password("mage@example.com", &reset.code, "another-secret")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .reset_password("mage@example.com", &reset.code, "third-secret")
+                .await,
+            Err(RepositoryError::InvalidConfirmationCode)
+        ));
+        assert!(matches!(
+            repository
+                .verify_login("mage@example.com", "very-secret-password")
+                .await,
+            Err(RepositoryError::InvalidCredentials)
+        ));
+        assert_eq!(
+            repository
+                .verify_login("mage@example.com", "another-secret")
+                .await
+                .unwrap()
+                .id,
+            user.id
+        );
+
+        assert!(matches!(
+            repository
+                .change_password(user.id, "wrong-password", "third-secret")
+                .await,
+            Err(RepositoryError::IncorrectPassword)
+        ));
+        repository
+            .change_password(user.id, "another-secret", "third-secret")
+            .await
+            .unwrap();
+
+        repository
+            .register("mage_two", "other@example.com", "very-secret-password")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.change_username(user.id, "mage_two").await,
+            Err(RepositoryError::UsernameTaken)
+        ));
+        assert!(matches!(
+            repository
+                .change_email(user.id, "third-secret", "other@example.com")
+                .await,
+            Err(RepositoryError::EmailTaken)
+        ));
+        repository
+            .change_username(user.id, "archmage")
+            .await
+            .unwrap();
+        let pending = repository
+            .change_email(user.id, "third-secret", "new@example.com")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .verify_login("new@example.com", "third-secret")
+                .await,
+            Err(RepositoryError::EmailConfirmationRequired(_))
+        ));
+        let user = repository
+            .confirm_email(&pending.email, &pending.code)
+            .await
+            .unwrap();
+        assert_eq!(user.username, "archmage");
+
+        repository
+            .award_match_points(uuid::Uuid::new_v4(), user.id, 10)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.delete_account(user.id, "wrong-password").await,
+            Err(RepositoryError::IncorrectPassword)
+        ));
+        repository
+            .delete_account(user.id, "third-secret")
+            .await
+            .unwrap();
+        let rewards: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM game_rewards")
+            .fetch_one(&repository.pool)
+            .await
+            .unwrap();
+        assert_eq!(rewards, 0);
+        assert!(matches!(
+            repository
+                .verify_login("new@example.com", "third-secret")
+                .await,
+            Err(RepositoryError::InvalidCredentials)
+        ));
+    }
+
+    #[tokio::test]
+    async fn account_changes_void_outstanding_reset_codes() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        let pending = repository
+            .register("mage_one", "mage@example.com", "very-secret-password")
+            .await
+            .unwrap();
+        let user = repository
+            .confirm_email(&pending.email, &pending.code)
+            .await
+            .unwrap();
+
+        let reset = repository
+            .request_password_reset("mage@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        repository
+            .change_password(user.id, "very-secret-password", "another-secret")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .reset_password("mage@example.com", &reset.code, "third-secret")
+                .await,
+            Err(RepositoryError::InvalidConfirmationCode)
+        ));
+
+        let reset = repository
+            .request_password_reset("mage@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        repository
+            .change_email(user.id, "another-secret", "new@example.com")
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .reset_password("new@example.com", &reset.code, "third-secret")
+                .await,
+            Err(RepositoryError::InvalidConfirmationCode)
+        ));
+    }
 }